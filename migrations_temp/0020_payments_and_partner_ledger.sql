@@ -0,0 +1,128 @@
+-- Bale Backend - Payments and Partner Ledger
+-- Money received from partners and a unified ledger of what each partner owes
+
+-- =====================================================
+-- PAYMENTS TABLE
+-- =====================================================
+
+CREATE TABLE payments (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    company_id UUID NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+    partner_id UUID NOT NULL REFERENCES partners(id),
+
+    -- Payment details
+    payment_date DATE NOT NULL DEFAULT CURRENT_DATE,
+    amount DECIMAL(10,2) NOT NULL CHECK (amount > 0),
+    reference_number VARCHAR(50), -- Receipt book / transaction reference
+
+    notes TEXT,
+
+    -- Audit fields
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id),
+    modified_by UUID REFERENCES users(id),
+    deleted_at TIMESTAMPTZ
+);
+
+-- =====================================================
+-- INDEXES FOR PERFORMANCE
+-- =====================================================
+
+CREATE INDEX idx_payments_company_id ON payments(company_id);
+CREATE INDEX idx_payments_partner ON payments(company_id, partner_id);
+CREATE INDEX idx_payments_date ON payments(company_id, payment_date);
+
+-- =====================================================
+-- PARTNER LEDGER ENTRIES VIEW
+-- =====================================================
+
+-- Debits increase what the partner owes us, credits reduce it.
+-- Dispatch invoices linked to a sales order are already covered by the
+-- order total, so only stand-alone invoiced dispatches are debited.
+CREATE VIEW partner_ledger_entries AS
+-- Approved sales orders billed to the customer
+SELECT
+    so.company_id,
+    so.customer_id as partner_id,
+    'sales_order' as entry_type,
+    so.id as source_id,
+    so.order_number as reference_number,
+    so.order_date as entry_date,
+    COALESCE(so.total_amount, 0) as debit,
+    0::DECIMAL(10,2) as credit,
+    so.created_at
+FROM sales_orders so
+WHERE so.deleted_at IS NULL
+    AND so.status IN ('in_progress', 'completed')
+
+UNION ALL
+
+-- Advance collected while booking the order
+SELECT
+    so.company_id,
+    so.customer_id as partner_id,
+    'advance' as entry_type,
+    so.id as source_id,
+    so.order_number as reference_number,
+    so.order_date as entry_date,
+    0::DECIMAL(10,2) as debit,
+    so.advance_amount as credit,
+    so.created_at
+FROM sales_orders so
+WHERE so.deleted_at IS NULL
+    AND so.status IN ('in_progress', 'completed')
+    AND so.advance_amount > 0
+
+UNION ALL
+
+-- Invoiced partner dispatches not raised against a sales order
+SELECT
+    gd.company_id,
+    gd.dispatch_to_partner_id as partner_id,
+    'dispatch_invoice' as entry_type,
+    gd.id as source_id,
+    COALESCE(gd.invoice_number, gd.dispatch_number) as reference_number,
+    gd.dispatch_date as entry_date,
+    gd.invoice_amount as debit,
+    0::DECIMAL(10,2) as credit,
+    gd.created_at
+FROM goods_dispatches gd
+WHERE gd.deleted_at IS NULL
+    AND gd.is_cancelled = false
+    AND gd.dispatch_type = 'partner'
+    AND gd.sales_order_id IS NULL
+    AND gd.invoice_amount > 0
+
+UNION ALL
+
+-- Payments received from the partner
+SELECT
+    pm.company_id,
+    pm.partner_id,
+    'payment' as entry_type,
+    pm.id as source_id,
+    pm.reference_number,
+    pm.payment_date as entry_date,
+    0::DECIMAL(10,2) as debit,
+    pm.amount as credit,
+    pm.created_at
+FROM payments pm
+WHERE pm.deleted_at IS NULL;
+
+-- =====================================================
+-- TRIGGERS FOR AUTO-UPDATES
+-- =====================================================
+
+-- Auto-update timestamps
+CREATE TRIGGER update_payments_updated_at
+    BEFORE UPDATE ON payments
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- =====================================================
+-- SECURITY CONSTRAINTS
+-- =====================================================
+
+-- Ensure payments belong to a company
+ALTER TABLE payments ADD CONSTRAINT check_payment_company_not_null
+    CHECK (company_id IS NOT NULL);
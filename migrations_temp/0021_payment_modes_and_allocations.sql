@@ -0,0 +1,291 @@
+-- Bale Backend - Payment Modes, Allocations and Reversals
+-- Cash/UPI/bank/cheque receipts allocated against orders and dispatch invoices
+
+-- =====================================================
+-- PAYMENT MODE, CLEARING AND REVERSAL TRACKING
+-- =====================================================
+
+ALTER TABLE payments
+    ADD COLUMN payment_number VARCHAR(50),
+    ADD COLUMN payment_mode VARCHAR(20) NOT NULL DEFAULT 'cash'
+        CHECK (payment_mode IN ('cash', 'upi', 'bank_transfer', 'cheque')),
+
+    -- Cheque details (only for payment_mode = 'cheque')
+    ADD COLUMN cheque_number VARCHAR(20),
+    ADD COLUMN cheque_date DATE,
+    ADD COLUMN bank_name VARCHAR(100),
+
+    -- Cheques stay pending until the bank clears or bounces them
+    ADD COLUMN clearing_status VARCHAR(20) NOT NULL DEFAULT 'cleared'
+        CHECK (clearing_status IN ('pending', 'cleared', 'bounced')),
+    ADD COLUMN cleared_at TIMESTAMPTZ,
+
+    -- Reversal tracking (bounced cheques and wrongly entered payments)
+    ADD COLUMN is_reversed BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN reversed_at TIMESTAMPTZ,
+    ADD COLUMN reversed_by UUID REFERENCES users(id),
+    ADD COLUMN reversal_reason TEXT;
+
+-- Number any payments recorded before numbering existed
+UPDATE payments SET payment_number = 'PAY-' || LPAD(numbered.seq::TEXT, 6, '0')
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY company_id ORDER BY created_at) as seq
+    FROM payments
+) numbered
+WHERE payments.id = numbered.id;
+
+ALTER TABLE payments ALTER COLUMN payment_number SET NOT NULL;
+ALTER TABLE payments ADD CONSTRAINT payments_company_payment_number_key
+    UNIQUE (company_id, payment_number);
+
+-- Running payment number per company. The row lock taken by the upsert in
+-- generate_payment_number keeps concurrent payments from sharing a number.
+CREATE TABLE payment_number_series (
+    company_id UUID PRIMARY KEY REFERENCES companies(id) ON DELETE CASCADE,
+    last_number INTEGER NOT NULL DEFAULT 0 CHECK (last_number >= 0)
+);
+
+INSERT INTO payment_number_series (company_id, last_number)
+SELECT company_id, COUNT(*) FROM payments GROUP BY company_id;
+
+ALTER TABLE payments ADD CONSTRAINT check_cheque_details
+    CHECK (payment_mode != 'cheque' OR cheque_number IS NOT NULL);
+
+-- =====================================================
+-- PAYMENT ALLOCATIONS (which order or invoice a payment settles)
+-- =====================================================
+
+CREATE TABLE payment_allocations (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    company_id UUID NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+    payment_id UUID NOT NULL REFERENCES payments(id) ON DELETE CASCADE,
+
+    -- Target (mutually exclusive)
+    sales_order_id UUID REFERENCES sales_orders(id),
+    dispatch_id UUID REFERENCES goods_dispatches(id),
+
+    amount DECIMAL(10,2) NOT NULL CHECK (amount > 0),
+
+    -- Audit fields
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT check_allocation_target
+        CHECK (
+            (sales_order_id IS NOT NULL AND dispatch_id IS NULL) OR
+            (dispatch_id IS NOT NULL AND sales_order_id IS NULL)
+        )
+);
+
+-- =====================================================
+-- PAYMENT EVENTS (audit trail)
+-- =====================================================
+
+CREATE TABLE payment_events (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    company_id UUID NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+    payment_id UUID NOT NULL REFERENCES payments(id) ON DELETE CASCADE,
+
+    event_type VARCHAR(20) NOT NULL
+        CHECK (event_type IN ('recorded', 'cleared', 'bounced', 'reversed')),
+    notes TEXT,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id)
+);
+
+-- =====================================================
+-- INDEXES FOR PERFORMANCE
+-- =====================================================
+
+CREATE INDEX idx_payments_payment_number ON payments(company_id, payment_number);
+CREATE INDEX idx_payments_clearing_status ON payments(company_id, clearing_status);
+
+CREATE INDEX idx_payment_allocations_company_id ON payment_allocations(company_id);
+CREATE INDEX idx_payment_allocations_payment_id ON payment_allocations(payment_id);
+CREATE INDEX idx_payment_allocations_sales_order ON payment_allocations(sales_order_id);
+CREATE INDEX idx_payment_allocations_dispatch ON payment_allocations(dispatch_id);
+
+CREATE INDEX idx_payment_events_payment_id ON payment_events(payment_id);
+
+-- =====================================================
+-- RECEIVABLE DOCUMENTS VIEW
+-- =====================================================
+
+-- Orders and stand-alone dispatch invoices with what has been paid against them.
+-- Allocations of reversed payments no longer count as paid.
+CREATE VIEW receivable_documents AS
+SELECT
+    so.company_id,
+    so.customer_id as partner_id,
+    'sales_order' as document_type,
+    so.id as document_id,
+    so.order_number as reference_number,
+    so.order_date as document_date,
+    COALESCE(so.total_amount, 0) as document_amount,
+    COALESCE(so.advance_amount, 0) + COALESCE(paid.amount, 0) as paid_amount,
+    COALESCE(so.total_amount, 0) - COALESCE(so.advance_amount, 0) - COALESCE(paid.amount, 0) as outstanding_amount
+FROM sales_orders so
+LEFT JOIN (
+    SELECT pa.sales_order_id, SUM(pa.amount) as amount
+    FROM payment_allocations pa
+    JOIN payments pm ON pa.payment_id = pm.id
+    WHERE pm.is_reversed = false AND pm.deleted_at IS NULL
+    GROUP BY pa.sales_order_id
+) paid ON so.id = paid.sales_order_id
+WHERE so.deleted_at IS NULL
+    AND so.status IN ('in_progress', 'completed')
+
+UNION ALL
+
+SELECT
+    gd.company_id,
+    gd.dispatch_to_partner_id as partner_id,
+    'dispatch_invoice' as document_type,
+    gd.id as document_id,
+    COALESCE(gd.invoice_number, gd.dispatch_number) as reference_number,
+    gd.dispatch_date as document_date,
+    gd.invoice_amount as document_amount,
+    COALESCE(paid.amount, 0) as paid_amount,
+    gd.invoice_amount - COALESCE(paid.amount, 0) as outstanding_amount
+FROM goods_dispatches gd
+LEFT JOIN (
+    SELECT pa.dispatch_id, SUM(pa.amount) as amount
+    FROM payment_allocations pa
+    JOIN payments pm ON pa.payment_id = pm.id
+    WHERE pm.is_reversed = false AND pm.deleted_at IS NULL
+    GROUP BY pa.dispatch_id
+) paid ON gd.id = paid.dispatch_id
+WHERE gd.deleted_at IS NULL
+    AND gd.is_cancelled = false
+    AND gd.dispatch_type = 'partner'
+    AND gd.sales_order_id IS NULL
+    AND gd.invoice_amount > 0;
+
+-- =====================================================
+-- PARTNER LEDGER ENTRIES VIEW (reversals)
+-- =====================================================
+
+-- Reversed payments stay on the statement and are offset by a debit on the
+-- reversal date, so the partner sees both the receipt and the bounce.
+CREATE OR REPLACE VIEW partner_ledger_entries AS
+SELECT
+    so.company_id,
+    so.customer_id as partner_id,
+    'sales_order' as entry_type,
+    so.id as source_id,
+    so.order_number as reference_number,
+    so.order_date as entry_date,
+    COALESCE(so.total_amount, 0) as debit,
+    0::DECIMAL(10,2) as credit,
+    so.created_at
+FROM sales_orders so
+WHERE so.deleted_at IS NULL
+    AND so.status IN ('in_progress', 'completed')
+
+UNION ALL
+
+SELECT
+    so.company_id,
+    so.customer_id as partner_id,
+    'advance' as entry_type,
+    so.id as source_id,
+    so.order_number as reference_number,
+    so.order_date as entry_date,
+    0::DECIMAL(10,2) as debit,
+    so.advance_amount as credit,
+    so.created_at
+FROM sales_orders so
+WHERE so.deleted_at IS NULL
+    AND so.status IN ('in_progress', 'completed')
+    AND so.advance_amount > 0
+
+UNION ALL
+
+SELECT
+    gd.company_id,
+    gd.dispatch_to_partner_id as partner_id,
+    'dispatch_invoice' as entry_type,
+    gd.id as source_id,
+    COALESCE(gd.invoice_number, gd.dispatch_number) as reference_number,
+    gd.dispatch_date as entry_date,
+    gd.invoice_amount as debit,
+    0::DECIMAL(10,2) as credit,
+    gd.created_at
+FROM goods_dispatches gd
+WHERE gd.deleted_at IS NULL
+    AND gd.is_cancelled = false
+    AND gd.dispatch_type = 'partner'
+    AND gd.sales_order_id IS NULL
+    AND gd.invoice_amount > 0
+
+UNION ALL
+
+SELECT
+    pm.company_id,
+    pm.partner_id,
+    'payment' as entry_type,
+    pm.id as source_id,
+    pm.payment_number as reference_number,
+    pm.payment_date as entry_date,
+    0::DECIMAL(10,2) as debit,
+    pm.amount as credit,
+    pm.created_at
+FROM payments pm
+WHERE pm.deleted_at IS NULL
+
+UNION ALL
+
+SELECT
+    pm.company_id,
+    pm.partner_id,
+    'payment_reversal' as entry_type,
+    pm.id as source_id,
+    pm.payment_number as reference_number,
+    pm.reversed_at::DATE as entry_date,
+    pm.amount as debit,
+    0::DECIMAL(10,2) as credit,
+    pm.reversed_at as created_at
+FROM payments pm
+WHERE pm.deleted_at IS NULL
+    AND pm.is_reversed = true;
+
+-- =====================================================
+-- TRIGGERS FOR AUTO-UPDATES
+-- =====================================================
+
+CREATE TRIGGER update_payment_allocations_updated_at
+    BEFORE UPDATE ON payment_allocations
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Same PAY-000001 format as generate_sequence_number, but counted in
+-- payment_number_series rather than from MAX(payment_number)
+CREATE OR REPLACE FUNCTION generate_payment_number(company_uuid UUID)
+RETURNS TEXT AS $$
+DECLARE
+    next_seq INTEGER;
+BEGIN
+    INSERT INTO payment_number_series (company_id, last_number)
+    VALUES (company_uuid, 1)
+    ON CONFLICT (company_id) DO UPDATE
+    SET last_number = payment_number_series.last_number + 1
+    RETURNING last_number INTO next_seq;
+
+    RETURN 'PAY-' || LPAD(next_seq::TEXT, 6, '0');
+END;
+$$ LANGUAGE plpgsql;
+
+-- Auto-generate payment numbers
+CREATE OR REPLACE FUNCTION auto_generate_payment_number()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.payment_number IS NULL OR NEW.payment_number = '' THEN
+        NEW.payment_number := generate_payment_number(NEW.company_id);
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_auto_payment_number
+    BEFORE INSERT ON payments
+    FOR EACH ROW EXECUTE FUNCTION auto_generate_payment_number();
@@ -0,0 +1,104 @@
+-- Bale Backend - Agent Commissions
+-- Commission rules per agent and accruals earned on dispatches
+
+-- =====================================================
+-- COMMISSION RULES
+-- =====================================================
+
+CREATE TABLE commission_rules (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    company_id UUID NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+    agent_id UUID NOT NULL REFERENCES partners(id),
+
+    -- How the commission is calculated
+    rule_type VARCHAR(20) NOT NULL CHECK (rule_type IN ('percentage', 'per_unit')),
+    rate DECIMAL(10,4) NOT NULL CHECK (rate > 0), -- Percentage of value or amount per measuring unit
+
+    -- Optional scope: only products carrying this tag
+    product_tag TEXT,
+
+    -- Validity
+    effective_from DATE NOT NULL DEFAULT CURRENT_DATE,
+    effective_to DATE,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+
+    notes TEXT,
+
+    -- Audit fields
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id),
+    modified_by UUID REFERENCES users(id),
+    deleted_at TIMESTAMPTZ,
+
+    CONSTRAINT check_percentage_rate
+        CHECK (rule_type != 'percentage' OR rate <= 100),
+    CONSTRAINT check_effective_range
+        CHECK (effective_to IS NULL OR effective_to >= effective_from)
+);
+
+-- =====================================================
+-- COMMISSION ACCRUALS (earned per dispatch and product)
+-- =====================================================
+
+CREATE TABLE commission_accruals (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    company_id UUID NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+    agent_id UUID NOT NULL REFERENCES partners(id),
+    rule_id UUID NOT NULL REFERENCES commission_rules(id),
+
+    -- Source
+    dispatch_id UUID NOT NULL REFERENCES goods_dispatches(id),
+    sales_order_id UUID REFERENCES sales_orders(id),
+    product_id UUID NOT NULL REFERENCES products(id),
+
+    -- Calculation
+    quantity DECIMAL(10,3) NOT NULL,
+    base_amount DECIMAL(10,2) NOT NULL,
+    commission_amount DECIMAL(10,2) NOT NULL,
+    accrual_date DATE NOT NULL,
+
+    -- Settlement
+    status VARCHAR(20) NOT NULL DEFAULT 'accrued'
+        CHECK (status IN ('accrued', 'paid', 'cancelled')),
+    paid_at TIMESTAMPTZ,
+    paid_by UUID REFERENCES users(id),
+    payment_reference VARCHAR(50),
+
+    -- Audit fields
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    UNIQUE(dispatch_id, product_id)
+);
+
+-- =====================================================
+-- INDEXES FOR PERFORMANCE
+-- =====================================================
+
+CREATE INDEX idx_commission_rules_company_id ON commission_rules(company_id);
+CREATE INDEX idx_commission_rules_agent ON commission_rules(company_id, agent_id);
+
+CREATE INDEX idx_commission_accruals_company_id ON commission_accruals(company_id);
+CREATE INDEX idx_commission_accruals_agent ON commission_accruals(company_id, agent_id, accrual_date);
+CREATE INDEX idx_commission_accruals_dispatch ON commission_accruals(dispatch_id);
+CREATE INDEX idx_commission_accruals_status ON commission_accruals(company_id, status);
+
+-- =====================================================
+-- TRIGGERS FOR AUTO-UPDATES
+-- =====================================================
+
+CREATE TRIGGER update_commission_rules_updated_at
+    BEFORE UPDATE ON commission_rules
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TRIGGER update_commission_accruals_updated_at
+    BEFORE UPDATE ON commission_accruals
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- =====================================================
+-- SECURITY CONSTRAINTS
+-- =====================================================
+
+ALTER TABLE commission_rules ADD CONSTRAINT check_commission_rule_company_not_null
+    CHECK (company_id IS NOT NULL);
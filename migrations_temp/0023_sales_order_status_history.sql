@@ -0,0 +1,32 @@
+-- Bale Backend - Sales Order Status History
+-- Every status transition of a sales order, not only the latest one
+
+-- =====================================================
+-- SALES ORDER STATUS HISTORY
+-- =====================================================
+
+CREATE TABLE sales_order_status_history (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    company_id UUID NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+    sales_order_id UUID NOT NULL REFERENCES sales_orders(id) ON DELETE CASCADE,
+
+    -- NULL when the order was created
+    from_status VARCHAR(20),
+    to_status VARCHAR(20) NOT NULL
+        CHECK (to_status IN ('approval_pending', 'in_progress', 'completed', 'cancelled')),
+
+    -- Completion notes, cancellation reason or reopening reason
+    notes TEXT,
+    -- Completed without every item being fully dispatched
+    is_override BOOLEAN NOT NULL DEFAULT FALSE,
+
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    changed_by UUID NOT NULL REFERENCES users(id)
+);
+
+-- =====================================================
+-- INDEXES FOR PERFORMANCE
+-- =====================================================
+
+CREATE INDEX idx_sales_order_status_history_company_id ON sales_order_status_history(company_id);
+CREATE INDEX idx_sales_order_status_history_order ON sales_order_status_history(sales_order_id, changed_at);
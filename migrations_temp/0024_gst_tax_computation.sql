@@ -0,0 +1,297 @@
+-- Bale Backend - GST Tax Computation
+-- HSN-wise GST rates, place of supply and tax-inclusive sales order totals
+
+-- =====================================================
+-- GST STATE CODES (reference data)
+-- =====================================================
+
+CREATE TABLE gst_state_codes (
+    code CHAR(2) PRIMARY KEY,
+    state_name VARCHAR(100) NOT NULL UNIQUE
+);
+
+INSERT INTO gst_state_codes (code, state_name) VALUES
+    ('01', 'Jammu and Kashmir'),
+    ('02', 'Himachal Pradesh'),
+    ('03', 'Punjab'),
+    ('04', 'Chandigarh'),
+    ('05', 'Uttarakhand'),
+    ('06', 'Haryana'),
+    ('07', 'Delhi'),
+    ('08', 'Rajasthan'),
+    ('09', 'Uttar Pradesh'),
+    ('10', 'Bihar'),
+    ('11', 'Sikkim'),
+    ('12', 'Arunachal Pradesh'),
+    ('13', 'Nagaland'),
+    ('14', 'Manipur'),
+    ('15', 'Mizoram'),
+    ('16', 'Tripura'),
+    ('17', 'Meghalaya'),
+    ('18', 'Assam'),
+    ('19', 'West Bengal'),
+    ('20', 'Jharkhand'),
+    ('21', 'Odisha'),
+    ('22', 'Chhattisgarh'),
+    ('23', 'Madhya Pradesh'),
+    ('24', 'Gujarat'),
+    ('26', 'Dadra and Nagar Haveli and Daman and Diu'),
+    ('27', 'Maharashtra'),
+    ('29', 'Karnataka'),
+    ('30', 'Goa'),
+    ('31', 'Lakshadweep'),
+    ('32', 'Kerala'),
+    ('33', 'Tamil Nadu'),
+    ('34', 'Puducherry'),
+    ('35', 'Andaman and Nicobar Islands'),
+    ('36', 'Telangana'),
+    ('37', 'Andhra Pradesh'),
+    ('38', 'Ladakh');
+
+-- State code from the GSTIN prefix, falling back to the state name
+CREATE OR REPLACE FUNCTION gst_state_code(gstin TEXT, state TEXT)
+RETURNS CHAR(2) AS $$
+    SELECT COALESCE(
+        (SELECT code FROM gst_state_codes WHERE code = LEFT(NULLIF(TRIM(gstin), ''), 2)),
+        (SELECT code FROM gst_state_codes WHERE LOWER(state_name) = LOWER(TRIM(state)))
+    );
+$$ LANGUAGE sql STABLE;
+
+-- =====================================================
+-- HSN GST RATES (per company)
+-- =====================================================
+
+CREATE TABLE hsn_gst_rates (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    company_id UUID NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+
+    hsn_code VARCHAR(20) NOT NULL,
+    gst_rate DECIMAL(5,2) NOT NULL
+        CHECK (gst_rate IN (0, 0.1, 0.25, 1.5, 3, 5, 12, 18, 28)),
+    description TEXT,
+
+    -- Audit fields
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id),
+    modified_by UUID REFERENCES users(id),
+
+    UNIQUE(company_id, hsn_code)
+);
+
+CREATE INDEX idx_hsn_gst_rates_company_id ON hsn_gst_rates(company_id);
+
+CREATE TRIGGER update_hsn_gst_rates_updated_at
+    BEFORE UPDATE ON hsn_gst_rates
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- =====================================================
+-- TAX COLUMNS
+-- =====================================================
+
+-- Rate is captured when the item is added so later rate changes don't alter the order
+ALTER TABLE sales_order_items
+    ADD COLUMN hsn_code VARCHAR(20),
+    ADD COLUMN gst_rate DECIMAL(5,2) NOT NULL DEFAULT 0;
+
+ALTER TABLE sales_orders
+    ADD COLUMN is_inter_state BOOLEAN NOT NULL DEFAULT FALSE, -- IGST instead of CGST + SGST
+    ADD COLUMN subtotal DECIMAL(10,2) NOT NULL DEFAULT 0, -- Sum of line totals before discount
+    ADD COLUMN taxable_amount DECIMAL(10,2) NOT NULL DEFAULT 0,
+    ADD COLUMN cgst_amount DECIMAL(10,2) NOT NULL DEFAULT 0,
+    ADD COLUMN sgst_amount DECIMAL(10,2) NOT NULL DEFAULT 0,
+    ADD COLUMN igst_amount DECIMAL(10,2) NOT NULL DEFAULT 0,
+    ADD COLUMN round_off DECIMAL(10,2) NOT NULL DEFAULT 0; -- total_amount is rounded to the rupee
+
+-- =====================================================
+-- TAX CALCULATION
+-- =====================================================
+
+-- Tax on one line after the order discount. Each component is rounded to the
+-- paisa; CGST and SGST are each half the rate.
+CREATE OR REPLACE FUNCTION gst_line_amounts(
+    line_total DECIMAL,
+    discount_pct DECIMAL,
+    rate DECIMAL,
+    inter_state BOOLEAN,
+    OUT taxable_amount DECIMAL(10,2),
+    OUT cgst_amount DECIMAL(10,2),
+    OUT sgst_amount DECIMAL(10,2),
+    OUT igst_amount DECIMAL(10,2)
+) AS $$
+BEGIN
+    taxable_amount := ROUND(COALESCE(line_total, 0) * (1 - COALESCE(discount_pct, 0) / 100), 2);
+    IF inter_state THEN
+        cgst_amount := 0;
+        sgst_amount := 0;
+        igst_amount := ROUND(taxable_amount * rate / 100, 2);
+    ELSE
+        cgst_amount := ROUND(taxable_amount * rate / 200, 2);
+        sgst_amount := cgst_amount;
+        igst_amount := 0;
+    END IF;
+END;
+$$ LANGUAGE plpgsql IMMUTABLE;
+
+-- Order totals for the given discount and place of supply. The invoice value
+-- is rounded to the nearest rupee and the difference kept as round_off.
+CREATE OR REPLACE FUNCTION calculate_sales_order_totals(
+    order_id UUID,
+    discount_pct DECIMAL,
+    inter_state BOOLEAN,
+    OUT subtotal DECIMAL(10,2),
+    OUT taxable_amount DECIMAL(10,2),
+    OUT cgst_amount DECIMAL(10,2),
+    OUT sgst_amount DECIMAL(10,2),
+    OUT igst_amount DECIMAL(10,2),
+    OUT total_amount DECIMAL(10,2),
+    OUT round_off DECIMAL(10,2)
+) AS $$
+DECLARE
+    invoice_value DECIMAL(10,2);
+BEGIN
+    SELECT
+        COALESCE(SUM(soi.line_total), 0),
+        COALESCE(SUM(amounts.taxable_amount), 0),
+        COALESCE(SUM(amounts.cgst_amount), 0),
+        COALESCE(SUM(amounts.sgst_amount), 0),
+        COALESCE(SUM(amounts.igst_amount), 0)
+    INTO subtotal, taxable_amount, cgst_amount, sgst_amount, igst_amount
+    FROM sales_order_items soi
+    CROSS JOIN LATERAL gst_line_amounts(soi.line_total, discount_pct, soi.gst_rate, inter_state) amounts
+    WHERE soi.sales_order_id = order_id;
+
+    invoice_value := taxable_amount + cgst_amount + sgst_amount + igst_amount;
+    total_amount := ROUND(invoice_value, 0);
+    round_off := total_amount - invoice_value;
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+-- =====================================================
+-- SALES ORDER TAX LINES VIEW
+-- =====================================================
+
+CREATE VIEW sales_order_tax_lines AS
+SELECT
+    soi.company_id,
+    soi.sales_order_id,
+    soi.id as sales_order_item_id,
+    soi.product_id,
+    soi.hsn_code,
+    soi.gst_rate,
+    soi.line_total,
+    amounts.taxable_amount,
+    amounts.cgst_amount,
+    amounts.sgst_amount,
+    amounts.igst_amount,
+    amounts.taxable_amount + amounts.cgst_amount + amounts.sgst_amount + amounts.igst_amount as line_amount
+FROM sales_order_items soi
+JOIN sales_orders so ON soi.sales_order_id = so.id
+CROSS JOIN LATERAL gst_line_amounts(soi.line_total, so.discount_percentage, soi.gst_rate, so.is_inter_state) amounts;
+
+-- =====================================================
+-- TRIGGERS
+-- =====================================================
+
+-- Capture HSN code and GST rate from the product master
+CREATE OR REPLACE FUNCTION auto_populate_gst_rate()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' OR NEW.product_id IS DISTINCT FROM OLD.product_id THEN
+        SELECT p.hsn_code, COALESCE(hgr.gst_rate, 0)
+        INTO NEW.hsn_code, NEW.gst_rate
+        FROM products p
+        LEFT JOIN hsn_gst_rates hgr ON hgr.company_id = p.company_id AND hgr.hsn_code = p.hsn_code
+        WHERE p.id = NEW.product_id;
+
+        NEW.gst_rate := COALESCE(NEW.gst_rate, 0);
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_auto_populate_gst_rate
+    BEFORE INSERT OR UPDATE ON sales_order_items
+    FOR EACH ROW EXECUTE FUNCTION auto_populate_gst_rate();
+
+-- Inter-state when the customer's state differs from the company's
+CREATE OR REPLACE FUNCTION set_sales_order_place_of_supply()
+RETURNS TRIGGER AS $$
+DECLARE
+    company_code CHAR(2);
+    customer_code CHAR(2);
+BEGIN
+    IF TG_OP = 'INSERT' OR NEW.customer_id IS DISTINCT FROM OLD.customer_id THEN
+        SELECT gst_state_code(gst_number, state) INTO company_code
+        FROM companies WHERE id = NEW.company_id;
+
+        SELECT gst_state_code(gst_number, state) INTO customer_code
+        FROM partners WHERE id = NEW.customer_id;
+
+        -- Unknown states are treated as intra-state
+        NEW.is_inter_state := company_code IS NOT NULL
+            AND customer_code IS NOT NULL
+            AND company_code != customer_code;
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Named to fire before trigger_update_sales_order_total_on_discount
+CREATE TRIGGER trigger_set_sales_order_place_of_supply
+    BEFORE INSERT OR UPDATE ON sales_orders
+    FOR EACH ROW EXECUTE FUNCTION set_sales_order_place_of_supply();
+
+-- Totals now include GST (replaces the discount-only calculation)
+CREATE OR REPLACE FUNCTION update_sales_order_total()
+RETURNS TRIGGER AS $$
+DECLARE
+    order_id UUID;
+    totals RECORD;
+BEGIN
+    order_id := COALESCE(NEW.sales_order_id, OLD.sales_order_id);
+
+    SELECT t.* INTO totals
+    FROM sales_orders so
+    CROSS JOIN LATERAL calculate_sales_order_totals(so.id, so.discount_percentage, so.is_inter_state) t
+    WHERE so.id = order_id;
+
+    UPDATE sales_orders
+    SET subtotal = totals.subtotal,
+        taxable_amount = totals.taxable_amount,
+        cgst_amount = totals.cgst_amount,
+        sgst_amount = totals.sgst_amount,
+        igst_amount = totals.igst_amount,
+        total_amount = totals.total_amount,
+        round_off = totals.round_off
+    WHERE id = order_id;
+
+    RETURN COALESCE(NEW, OLD);
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION update_sales_order_total_on_discount()
+RETURNS TRIGGER AS $$
+DECLARE
+    totals RECORD;
+BEGIN
+    -- Only recalculate if the discount or place of supply changed
+    IF OLD.discount_percentage IS DISTINCT FROM NEW.discount_percentage
+        OR OLD.is_inter_state IS DISTINCT FROM NEW.is_inter_state THEN
+        SELECT * INTO totals
+        FROM calculate_sales_order_totals(NEW.id, NEW.discount_percentage, NEW.is_inter_state);
+
+        NEW.subtotal := totals.subtotal;
+        NEW.taxable_amount := totals.taxable_amount;
+        NEW.cgst_amount := totals.cgst_amount;
+        NEW.sgst_amount := totals.sgst_amount;
+        NEW.igst_amount := totals.igst_amount;
+        NEW.total_amount := totals.total_amount;
+        NEW.round_off := totals.round_off;
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
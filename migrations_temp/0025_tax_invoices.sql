@@ -0,0 +1,87 @@
+-- Bale Backend - Tax Invoices
+-- GST invoices generated from partner dispatches with a per-company number series
+
+-- =====================================================
+-- COMPANY LOGO
+-- =====================================================
+
+-- Uploaded JPEG printed on invoices; logo_url is never fetched by the server
+ALTER TABLE companies ADD COLUMN logo_jpeg BYTEA;
+
+-- =====================================================
+-- INVOICE SERIES (one running number per company and financial year)
+-- =====================================================
+
+CREATE TABLE invoice_series (
+    company_id UUID NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+    financial_year_start DATE NOT NULL,
+
+    -- PREFIX/YY-YY/NNNN has to fit the 16 characters of an invoice number
+    prefix VARCHAR(5) NOT NULL DEFAULT 'INV',
+    last_number INTEGER NOT NULL DEFAULT 0 CHECK (last_number >= 0),
+
+    PRIMARY KEY (company_id, financial_year_start)
+);
+
+-- =====================================================
+-- TAX INVOICES
+-- =====================================================
+
+-- Amounts and rates are copied at generation time so the invoice never
+-- changes when orders, products or GST rates are edited later.
+CREATE TABLE tax_invoices (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    company_id UUID NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+    dispatch_id UUID NOT NULL UNIQUE REFERENCES goods_dispatches(id),
+    sales_order_id UUID REFERENCES sales_orders(id),
+    customer_id UUID NOT NULL REFERENCES partners(id),
+
+    invoice_number VARCHAR(16) NOT NULL, -- GST allows at most 16 characters
+    invoice_date DATE NOT NULL DEFAULT CURRENT_DATE,
+
+    place_of_supply CHAR(2) REFERENCES gst_state_codes(code),
+    is_inter_state BOOLEAN NOT NULL DEFAULT FALSE,
+    discount_percentage DECIMAL(5,2) NOT NULL DEFAULT 0,
+
+    taxable_amount DECIMAL(10,2) NOT NULL,
+    cgst_amount DECIMAL(10,2) NOT NULL DEFAULT 0,
+    sgst_amount DECIMAL(10,2) NOT NULL DEFAULT 0,
+    igst_amount DECIMAL(10,2) NOT NULL DEFAULT 0,
+    round_off DECIMAL(10,2) NOT NULL DEFAULT 0,
+    total_amount DECIMAL(10,2) NOT NULL,
+
+    -- Audit fields
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id),
+
+    UNIQUE(company_id, invoice_number)
+);
+
+CREATE TABLE tax_invoice_items (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    company_id UUID NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+    invoice_id UUID NOT NULL REFERENCES tax_invoices(id) ON DELETE CASCADE,
+    product_id UUID NOT NULL REFERENCES products(id),
+
+    description VARCHAR(200) NOT NULL,
+    hsn_code VARCHAR(20),
+    quantity DECIMAL(10,3) NOT NULL,
+    measuring_unit VARCHAR(20) NOT NULL,
+    unit_rate DECIMAL(10,2) NOT NULL,
+    gst_rate DECIMAL(5,2) NOT NULL DEFAULT 0,
+
+    taxable_amount DECIMAL(10,2) NOT NULL,
+    cgst_amount DECIMAL(10,2) NOT NULL DEFAULT 0,
+    sgst_amount DECIMAL(10,2) NOT NULL DEFAULT 0,
+    igst_amount DECIMAL(10,2) NOT NULL DEFAULT 0,
+
+    UNIQUE(invoice_id, product_id)
+);
+
+-- =====================================================
+-- INDEXES FOR PERFORMANCE
+-- =====================================================
+
+CREATE INDEX idx_tax_invoices_company_id ON tax_invoices(company_id);
+CREATE INDEX idx_tax_invoices_customer ON tax_invoices(company_id, customer_id, invoice_date);
+CREATE INDEX idx_tax_invoice_items_invoice_id ON tax_invoice_items(invoice_id);
@@ -0,0 +1,13 @@
+-- Bale Backend - Dispatch Transport Details
+-- Structured transport fields required for e-way bills
+
+ALTER TABLE goods_dispatches
+    ADD COLUMN transport_mode VARCHAR(10)
+        CHECK (transport_mode IN ('road', 'rail', 'air', 'ship')),
+    ADD COLUMN transport_distance_km INTEGER
+        CHECK (transport_distance_km BETWEEN 0 AND 4000), -- 0 lets the portal compute it from PIN codes
+    ADD COLUMN transporter_name VARCHAR(100),
+    ADD COLUMN transporter_id VARCHAR(15), -- Transporter GSTIN or TRANSIN
+    ADD COLUMN vehicle_number VARCHAR(20),
+    ADD COLUMN transport_document_number VARCHAR(20), -- LR, RR, airway bill or bill of lading
+    ADD COLUMN transport_document_date DATE;
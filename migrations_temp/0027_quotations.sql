@@ -0,0 +1,259 @@
+-- Bale Backend - Quotations
+-- Priced offers sent to customers before they become sales orders
+
+-- =====================================================
+-- QUOTATIONS TABLE
+-- =====================================================
+
+CREATE TABLE quotations (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    company_id UUID NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+
+    quotation_number VARCHAR(50) NOT NULL,
+
+    customer_id UUID NOT NULL REFERENCES partners(id),
+    agent_id UUID REFERENCES partners(id),
+
+    quotation_date DATE NOT NULL DEFAULT CURRENT_DATE,
+    valid_until DATE NOT NULL, -- Open quotations past this date are expired
+
+    -- Financial (same meaning as on sales_orders, maintained by triggers)
+    discount_percentage DECIMAL(5,2) NOT NULL DEFAULT 0
+        CHECK (discount_percentage >= 0 AND discount_percentage <= 100),
+    is_inter_state BOOLEAN NOT NULL DEFAULT FALSE,
+    subtotal DECIMAL(10,2) NOT NULL DEFAULT 0,
+    taxable_amount DECIMAL(10,2) NOT NULL DEFAULT 0,
+    cgst_amount DECIMAL(10,2) NOT NULL DEFAULT 0,
+    sgst_amount DECIMAL(10,2) NOT NULL DEFAULT 0,
+    igst_amount DECIMAL(10,2) NOT NULL DEFAULT 0,
+    round_off DECIMAL(10,2) NOT NULL DEFAULT 0,
+    total_amount DECIMAL(10,2) NOT NULL DEFAULT 0,
+
+    status VARCHAR(20) NOT NULL DEFAULT 'open'
+        CHECK (status IN ('open', 'converted')),
+    converted_sales_order_id UUID REFERENCES sales_orders(id),
+    converted_at TIMESTAMPTZ,
+    converted_by UUID REFERENCES users(id),
+
+    terms TEXT, -- Payment and delivery terms printed on the proforma
+    notes TEXT,
+
+    -- Audit fields
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id),
+    modified_by UUID REFERENCES users(id),
+    deleted_at TIMESTAMPTZ,
+
+    CONSTRAINT check_quotation_validity CHECK (valid_until >= quotation_date),
+    CONSTRAINT check_quotation_conversion
+        CHECK ((status = 'converted') = (converted_sales_order_id IS NOT NULL)),
+
+    UNIQUE(company_id, quotation_number)
+);
+
+-- =====================================================
+-- QUOTATION LINE ITEMS (mirrors sales_order_items)
+-- =====================================================
+
+CREATE TABLE quotation_items (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    company_id UUID NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+    quotation_id UUID NOT NULL REFERENCES quotations(id) ON DELETE CASCADE,
+    product_id UUID NOT NULL REFERENCES products(id),
+
+    required_quantity DECIMAL(10,3) NOT NULL CHECK (required_quantity > 0),
+
+    unit_rate DECIMAL(10,2),
+    line_total DECIMAL(10,2) GENERATED ALWAYS AS (required_quantity * COALESCE(unit_rate, 0)) STORED,
+
+    hsn_code VARCHAR(20),
+    gst_rate DECIMAL(5,2) NOT NULL DEFAULT 0,
+
+    notes TEXT,
+
+    -- Audit fields
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- =====================================================
+-- INDEXES FOR PERFORMANCE
+-- =====================================================
+
+CREATE INDEX idx_quotations_company_id ON quotations(company_id);
+CREATE INDEX idx_quotations_customer ON quotations(company_id, customer_id);
+CREATE INDEX idx_quotations_status ON quotations(company_id, status, valid_until);
+CREATE INDEX idx_quotation_items_quotation_id ON quotation_items(quotation_id);
+
+-- =====================================================
+-- NUMBERING
+-- =====================================================
+
+-- Same QT-000001 format as generate_sequence_number, kept to quotations so the
+-- shared generator is not redefined here
+CREATE OR REPLACE FUNCTION generate_quotation_number(company_uuid UUID)
+RETURNS TEXT AS $$
+DECLARE
+    next_seq INTEGER;
+BEGIN
+    SELECT COALESCE(MAX(CAST(SUBSTRING(quotation_number FROM '^QT-(\d+)$') AS INTEGER)), 0) + 1
+    INTO next_seq
+    FROM quotations
+    WHERE company_id = company_uuid;
+
+    RETURN 'QT-' || LPAD(next_seq::TEXT, 6, '0');
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION auto_generate_quotation_number()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.quotation_number IS NULL OR NEW.quotation_number = '' THEN
+        NEW.quotation_number := generate_quotation_number(NEW.company_id);
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_auto_quotation_number
+    BEFORE INSERT ON quotations
+    FOR EACH ROW EXECUTE FUNCTION auto_generate_quotation_number();
+
+-- =====================================================
+-- TAX AND TOTALS
+-- =====================================================
+
+-- Same calculation as calculate_sales_order_totals over quotation items
+CREATE OR REPLACE FUNCTION calculate_quotation_totals(
+    quotation_uuid UUID,
+    discount_pct DECIMAL,
+    inter_state BOOLEAN,
+    OUT subtotal DECIMAL(10,2),
+    OUT taxable_amount DECIMAL(10,2),
+    OUT cgst_amount DECIMAL(10,2),
+    OUT sgst_amount DECIMAL(10,2),
+    OUT igst_amount DECIMAL(10,2),
+    OUT total_amount DECIMAL(10,2),
+    OUT round_off DECIMAL(10,2)
+) AS $$
+DECLARE
+    invoice_value DECIMAL(10,2);
+BEGIN
+    SELECT
+        COALESCE(SUM(qi.line_total), 0),
+        COALESCE(SUM(amounts.taxable_amount), 0),
+        COALESCE(SUM(amounts.cgst_amount), 0),
+        COALESCE(SUM(amounts.sgst_amount), 0),
+        COALESCE(SUM(amounts.igst_amount), 0)
+    INTO subtotal, taxable_amount, cgst_amount, sgst_amount, igst_amount
+    FROM quotation_items qi
+    CROSS JOIN LATERAL gst_line_amounts(qi.line_total, discount_pct, qi.gst_rate, inter_state) amounts
+    WHERE qi.quotation_id = quotation_uuid;
+
+    invoice_value := taxable_amount + cgst_amount + sgst_amount + igst_amount;
+    total_amount := ROUND(invoice_value, 0);
+    round_off := total_amount - invoice_value;
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+CREATE VIEW quotation_tax_lines AS
+SELECT
+    qi.company_id,
+    qi.quotation_id,
+    qi.id as quotation_item_id,
+    qi.product_id,
+    qi.hsn_code,
+    qi.gst_rate,
+    qi.line_total,
+    amounts.taxable_amount,
+    amounts.cgst_amount,
+    amounts.sgst_amount,
+    amounts.igst_amount
+FROM quotation_items qi
+JOIN quotations q ON qi.quotation_id = q.id
+CROSS JOIN LATERAL gst_line_amounts(qi.line_total, q.discount_percentage, qi.gst_rate, q.is_inter_state) amounts;
+
+CREATE OR REPLACE FUNCTION update_quotation_total()
+RETURNS TRIGGER AS $$
+DECLARE
+    quotation_uuid UUID;
+    totals RECORD;
+BEGIN
+    quotation_uuid := COALESCE(NEW.quotation_id, OLD.quotation_id);
+
+    SELECT t.* INTO totals
+    FROM quotations q
+    CROSS JOIN LATERAL calculate_quotation_totals(q.id, q.discount_percentage, q.is_inter_state) t
+    WHERE q.id = quotation_uuid;
+
+    UPDATE quotations
+    SET subtotal = totals.subtotal,
+        taxable_amount = totals.taxable_amount,
+        cgst_amount = totals.cgst_amount,
+        sgst_amount = totals.sgst_amount,
+        igst_amount = totals.igst_amount,
+        total_amount = totals.total_amount,
+        round_off = totals.round_off
+    WHERE id = quotation_uuid;
+
+    RETURN COALESCE(NEW, OLD);
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION update_quotation_total_on_discount()
+RETURNS TRIGGER AS $$
+DECLARE
+    totals RECORD;
+BEGIN
+    IF OLD.discount_percentage IS DISTINCT FROM NEW.discount_percentage
+        OR OLD.is_inter_state IS DISTINCT FROM NEW.is_inter_state THEN
+        SELECT * INTO totals
+        FROM calculate_quotation_totals(NEW.id, NEW.discount_percentage, NEW.is_inter_state);
+
+        NEW.subtotal := totals.subtotal;
+        NEW.taxable_amount := totals.taxable_amount;
+        NEW.cgst_amount := totals.cgst_amount;
+        NEW.sgst_amount := totals.sgst_amount;
+        NEW.igst_amount := totals.igst_amount;
+        NEW.total_amount := totals.total_amount;
+        NEW.round_off := totals.round_off;
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- =====================================================
+-- TRIGGERS
+-- =====================================================
+
+-- The sales order item and place-of-supply functions only use columns the
+-- quotation tables share, so they are reused as is.
+CREATE TRIGGER trigger_auto_populate_unit_rate
+    BEFORE INSERT OR UPDATE ON quotation_items
+    FOR EACH ROW EXECUTE FUNCTION auto_populate_unit_rate();
+
+CREATE TRIGGER trigger_auto_populate_gst_rate
+    BEFORE INSERT OR UPDATE ON quotation_items
+    FOR EACH ROW EXECUTE FUNCTION auto_populate_gst_rate();
+
+CREATE TRIGGER trigger_update_quotation_total
+    AFTER INSERT OR UPDATE OR DELETE ON quotation_items
+    FOR EACH ROW EXECUTE FUNCTION update_quotation_total();
+
+CREATE TRIGGER trigger_set_quotation_place_of_supply
+    BEFORE INSERT OR UPDATE ON quotations
+    FOR EACH ROW EXECUTE FUNCTION set_sales_order_place_of_supply();
+
+CREATE TRIGGER trigger_update_quotation_total_on_discount
+    BEFORE UPDATE ON quotations
+    FOR EACH ROW EXECUTE FUNCTION update_quotation_total_on_discount();
+
+CREATE TRIGGER update_quotations_updated_at
+    BEFORE UPDATE ON quotations
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TRIGGER update_quotation_items_updated_at
+    BEFORE UPDATE ON quotation_items
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
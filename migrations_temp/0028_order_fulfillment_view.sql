@@ -0,0 +1,85 @@
+-- Bale Backend - Order Fulfillment View
+-- Aggregates items, dispatches and job works separately so an order with
+-- several dispatches or job works no longer multiplies its item quantities
+
+DROP VIEW comprehensive_order_fulfillment;
+
+CREATE VIEW comprehensive_order_fulfillment AS
+SELECT
+    so.company_id,
+    so.id as sales_order_id,
+    so.order_number,
+    so.status as order_status,
+    so.order_date,
+    so.expected_delivery_date,
+
+    -- Customer information
+    c.first_name || ' ' || c.last_name as customer_name,
+    c.company_name as customer_company,
+    c.phone_number as customer_phone,
+
+    -- Warehouse information
+    so.fulfillment_warehouse_id,
+    w.name as fulfillment_warehouse,
+
+    -- Order financial summary
+    so.total_amount,
+    so.advance_amount,
+    so.discount_percentage,
+
+    -- Fulfillment progress
+    items.total_required_qty,
+    items.total_dispatched_qty,
+    items.total_pending_qty,
+
+    -- Completion metrics
+    CASE
+        WHEN items.total_required_qty = 0 THEN 0
+        ELSE ROUND((items.total_dispatched_qty / items.total_required_qty) * 100, 2)
+    END as fulfillment_percentage,
+
+    -- Dispatch summary
+    dispatches.total_dispatches,
+    dispatches.active_dispatches,
+
+    -- Job work integration
+    job_works.linked_job_works,
+    job_works.completed_job_works,
+
+    -- Recent activity
+    GREATEST(
+        so.updated_at,
+        items.last_updated_at,
+        dispatches.last_updated_at,
+        job_works.last_updated_at
+    ) as last_activity_at
+
+FROM sales_orders so
+JOIN partners c ON so.customer_id = c.id
+LEFT JOIN warehouses w ON so.fulfillment_warehouse_id = w.id
+CROSS JOIN LATERAL (
+    SELECT
+        COALESCE(SUM(soi.required_quantity), 0) as total_required_qty,
+        COALESCE(SUM(soi.dispatched_quantity), 0) as total_dispatched_qty,
+        COALESCE(SUM(GREATEST(soi.pending_quantity, 0)), 0) as total_pending_qty,
+        MAX(soi.updated_at) as last_updated_at
+    FROM sales_order_items soi
+    WHERE soi.sales_order_id = so.id
+) items
+CROSS JOIN LATERAL (
+    SELECT
+        COUNT(*) as total_dispatches,
+        COUNT(*) FILTER (WHERE gd.is_cancelled IS NOT TRUE) as active_dispatches,
+        MAX(gd.updated_at) as last_updated_at
+    FROM goods_dispatches gd
+    WHERE gd.sales_order_id = so.id AND gd.deleted_at IS NULL
+) dispatches
+CROSS JOIN LATERAL (
+    SELECT
+        COUNT(*) as linked_job_works,
+        COUNT(*) FILTER (WHERE jw.status = 'completed') as completed_job_works,
+        MAX(jw.updated_at) as last_updated_at
+    FROM job_works jw
+    WHERE jw.sales_order_id = so.id AND jw.deleted_at IS NULL
+) job_works
+WHERE so.deleted_at IS NULL;
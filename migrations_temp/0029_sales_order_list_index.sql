@@ -0,0 +1,6 @@
+-- Bale Backend - Sales Order List Index
+-- Serves the warehouse order book, which pages on (order_date, id)
+
+CREATE INDEX idx_sales_orders_warehouse_date
+    ON sales_orders(company_id, fulfillment_warehouse_id, order_date DESC, id DESC)
+    WHERE deleted_at IS NULL;
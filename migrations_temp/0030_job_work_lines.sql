@@ -0,0 +1,11 @@
+-- Bale Backend - Job Work Lines
+-- One line per product on each side of a job work, so dispatched and
+-- received quantities can be matched to lines by product
+
+ALTER TABLE job_work_raw_materials
+    ADD CONSTRAINT check_raw_material_required_quantity CHECK (required_quantity > 0),
+    ADD CONSTRAINT unique_job_work_raw_material_product UNIQUE (job_work_id, product_id);
+
+ALTER TABLE job_work_finished_goods
+    ADD CONSTRAINT check_finished_good_expected_quantity CHECK (expected_quantity > 0),
+    ADD CONSTRAINT unique_job_work_finished_good_product UNIQUE (job_work_id, product_id);
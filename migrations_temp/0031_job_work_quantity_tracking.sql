@@ -0,0 +1,160 @@
+-- Bale Backend - Job Work Quantity Tracking
+-- Raw materials count what has been dispatched to the vendor and finished
+-- goods what has been received back, per product, from the linked dispatches
+-- and receipts. Over-delivery is recorded as is.
+
+-- =====================================================
+-- RECOMPUTATION
+-- =====================================================
+
+CREATE OR REPLACE FUNCTION refresh_job_work_quantities(job_work_uuid UUID)
+RETURNS VOID AS $$
+BEGIN
+    IF job_work_uuid IS NULL THEN
+        RETURN;
+    END IF;
+
+    UPDATE job_work_raw_materials rm
+    SET dispatched_quantity = COALESCE((
+        SELECT SUM(su.size_quantity)
+        FROM goods_dispatches gd
+        JOIN goods_dispatch_items gdi ON gd.id = gdi.dispatch_id
+        JOIN stock_units su ON gdi.stock_unit_id = su.id
+        WHERE gd.job_work_id = rm.job_work_id
+            AND gd.link_type = 'job_work'
+            AND gd.is_cancelled IS NOT TRUE AND gd.deleted_at IS NULL
+            AND su.product_id = rm.product_id
+    ), 0)
+    WHERE rm.job_work_id = job_work_uuid;
+
+    -- Received goods become stock units created from the receipt
+    UPDATE job_work_finished_goods fg
+    SET received_quantity = COALESCE((
+        SELECT SUM(su.size_quantity)
+        FROM goods_receipts gr
+        JOIN stock_units su ON su.created_from_receipt_id = gr.id
+        WHERE gr.job_work_id = fg.job_work_id
+            AND gr.link_type = 'job_work'
+            AND gr.deleted_at IS NULL
+            AND su.deleted_at IS NULL
+            AND su.product_id = fg.product_id
+    ), 0)
+    WHERE fg.job_work_id = job_work_uuid;
+END;
+$$ LANGUAGE plpgsql;
+
+-- =====================================================
+-- TRIGGER FUNCTIONS
+-- =====================================================
+
+CREATE OR REPLACE FUNCTION refresh_job_work_on_dispatch_item()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM refresh_job_work_quantities(gd.job_work_id)
+    FROM goods_dispatches gd
+    WHERE gd.id = COALESCE(NEW.dispatch_id, OLD.dispatch_id);
+
+    RETURN COALESCE(NEW, OLD);
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION refresh_job_work_on_dispatch()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM refresh_job_work_quantities(NEW.job_work_id);
+    IF OLD.job_work_id IS DISTINCT FROM NEW.job_work_id THEN
+        PERFORM refresh_job_work_quantities(OLD.job_work_id);
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION refresh_job_work_on_receipt()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM refresh_job_work_quantities(NEW.job_work_id);
+    IF OLD.job_work_id IS DISTINCT FROM NEW.job_work_id THEN
+        PERFORM refresh_job_work_quantities(OLD.job_work_id);
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION refresh_job_work_on_received_unit()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM refresh_job_work_quantities(gr.job_work_id)
+    FROM goods_receipts gr
+    WHERE gr.id IN (NEW.created_from_receipt_id, OLD.created_from_receipt_id);
+
+    RETURN COALESCE(NEW, OLD);
+END;
+$$ LANGUAGE plpgsql;
+
+-- Lines added after goods have moved start from what already moved
+CREATE OR REPLACE FUNCTION set_new_raw_material_dispatched()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.dispatched_quantity := COALESCE((
+        SELECT SUM(su.size_quantity)
+        FROM goods_dispatches gd
+        JOIN goods_dispatch_items gdi ON gd.id = gdi.dispatch_id
+        JOIN stock_units su ON gdi.stock_unit_id = su.id
+        WHERE gd.job_work_id = NEW.job_work_id
+            AND gd.link_type = 'job_work'
+            AND gd.is_cancelled IS NOT TRUE AND gd.deleted_at IS NULL
+            AND su.product_id = NEW.product_id
+    ), 0);
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION set_new_finished_good_received()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.received_quantity := COALESCE((
+        SELECT SUM(su.size_quantity)
+        FROM goods_receipts gr
+        JOIN stock_units su ON su.created_from_receipt_id = gr.id
+        WHERE gr.job_work_id = NEW.job_work_id
+            AND gr.link_type = 'job_work'
+            AND gr.deleted_at IS NULL
+            AND su.deleted_at IS NULL
+            AND su.product_id = NEW.product_id
+    ), 0);
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- =====================================================
+-- TRIGGERS
+-- =====================================================
+
+CREATE TRIGGER trigger_refresh_job_work_on_dispatch_item
+    AFTER INSERT OR DELETE ON goods_dispatch_items
+    FOR EACH ROW EXECUTE FUNCTION refresh_job_work_on_dispatch_item();
+
+CREATE TRIGGER trigger_refresh_job_work_on_dispatch
+    AFTER UPDATE OF is_cancelled, deleted_at, job_work_id, link_type ON goods_dispatches
+    FOR EACH ROW EXECUTE FUNCTION refresh_job_work_on_dispatch();
+
+CREATE TRIGGER trigger_refresh_job_work_on_receipt
+    AFTER UPDATE OF deleted_at, job_work_id, link_type ON goods_receipts
+    FOR EACH ROW EXECUTE FUNCTION refresh_job_work_on_receipt();
+
+CREATE TRIGGER trigger_refresh_job_work_on_received_unit
+    AFTER INSERT OR DELETE OR UPDATE OF size_quantity, product_id, created_from_receipt_id, deleted_at
+    ON stock_units
+    FOR EACH ROW EXECUTE FUNCTION refresh_job_work_on_received_unit();
+
+CREATE TRIGGER trigger_set_new_raw_material_dispatched
+    BEFORE INSERT ON job_work_raw_materials
+    FOR EACH ROW EXECUTE FUNCTION set_new_raw_material_dispatched();
+
+CREATE TRIGGER trigger_set_new_finished_good_received
+    BEFORE INSERT ON job_work_finished_goods
+    FOR EACH ROW EXECUTE FUNCTION set_new_finished_good_received();
@@ -0,0 +1,31 @@
+-- Bale Backend - Job Work Yields
+-- What came back from the vendor against what was sent, compared only within
+-- the same measuring unit: 100 m grey fabric sent and 94 m dyed fabric
+-- received is a 94% yield with 6 m wasted.
+
+CREATE VIEW job_work_yields AS
+SELECT
+    jw.company_id,
+    jw.id as job_work_id,
+    raw.measuring_unit,
+    raw.dispatched_qty as raw_dispatched_qty,
+    finished.received_qty as finished_received_qty,
+    raw.dispatched_qty - finished.received_qty as wastage_qty,
+    ROUND(finished.received_qty / raw.dispatched_qty * 100, 2) as yield_percentage
+FROM job_works jw
+JOIN LATERAL (
+    SELECT p.measuring_unit, SUM(rm.dispatched_quantity) as dispatched_qty
+    FROM job_work_raw_materials rm
+    JOIN products p ON p.id = rm.product_id
+    WHERE rm.job_work_id = jw.id
+    GROUP BY p.measuring_unit
+) raw ON TRUE
+JOIN LATERAL (
+    SELECT SUM(fg.received_quantity) as received_qty
+    FROM job_work_finished_goods fg
+    JOIN products p ON p.id = fg.product_id
+    WHERE fg.job_work_id = jw.id AND p.measuring_unit = raw.measuring_unit
+    HAVING COUNT(*) > 0
+) finished ON TRUE
+WHERE jw.deleted_at IS NULL
+    AND raw.dispatched_qty > 0;
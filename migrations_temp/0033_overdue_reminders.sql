@@ -0,0 +1,26 @@
+-- Bale Backend - Overdue Reminders
+-- Reminders already sent for overdue job works, dispatches and sales orders.
+-- One reminder per record and due date, so moving the due date re-arms it.
+
+CREATE TABLE overdue_reminders (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    company_id UUID NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+
+    entity_type VARCHAR(20) NOT NULL CHECK (entity_type IN ('job_work', 'dispatch', 'sales_order')),
+    entity_id UUID NOT NULL,
+    due_date DATE NOT NULL,
+
+    notified_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    UNIQUE(entity_type, entity_id, due_date)
+);
+
+CREATE INDEX idx_overdue_reminders_company ON overdue_reminders(company_id);
+
+-- Open records with a due date, as scanned by the evaluator
+CREATE INDEX idx_job_works_open_due_date ON job_works(due_date)
+    WHERE status = 'in_progress' AND deleted_at IS NULL;
+CREATE INDEX idx_goods_dispatches_open_due_date ON goods_dispatches(due_date)
+    WHERE is_cancelled IS NOT TRUE AND deleted_at IS NULL;
+CREATE INDEX idx_sales_orders_open_delivery_date ON sales_orders(expected_delivery_date)
+    WHERE status IN ('approval_pending', 'in_progress') AND deleted_at IS NULL;
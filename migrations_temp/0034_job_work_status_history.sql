@@ -0,0 +1,46 @@
+-- Bale Backend - Job Work Status History
+-- Every status transition of a job work, not only the latest one
+
+-- =====================================================
+-- JOB WORK STATUS HISTORY
+-- =====================================================
+
+CREATE TABLE job_work_status_history (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    company_id UUID NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+    job_work_id UUID NOT NULL REFERENCES job_works(id) ON DELETE CASCADE,
+
+    -- NULL when the job work was created
+    from_status VARCHAR(20),
+    to_status VARCHAR(20) NOT NULL
+        CHECK (to_status IN ('in_progress', 'completed', 'cancelled')),
+
+    -- Completion notes or cancellation reason
+    notes TEXT,
+    -- Completed while finished goods were still pending from the vendor
+    with_pending_goods BOOLEAN NOT NULL DEFAULT FALSE,
+
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    changed_by UUID NOT NULL REFERENCES users(id)
+);
+
+-- =====================================================
+-- INDEXES FOR PERFORMANCE
+-- =====================================================
+
+CREATE INDEX idx_job_work_status_history_company_id ON job_work_status_history(company_id);
+CREATE INDEX idx_job_work_status_history_job_work ON job_work_status_history(job_work_id, changed_at);
+
+-- =====================================================
+-- BACKFILL
+-- =====================================================
+
+INSERT INTO job_work_status_history (company_id, job_work_id, from_status, to_status, changed_at, changed_by)
+SELECT company_id, id, NULL, 'in_progress', created_at, created_by
+FROM job_works;
+
+INSERT INTO job_work_status_history (company_id, job_work_id, from_status, to_status, notes,
+                                     changed_at, changed_by)
+SELECT company_id, id, 'in_progress', status, status_notes, status_changed_at, status_changed_by
+FROM job_works
+WHERE status != 'in_progress' AND status_changed_at IS NOT NULL AND status_changed_by IS NOT NULL;
@@ -0,0 +1,77 @@
+-- Bale Backend - Dispatch Scan Sessions
+-- A draft dispatch that collects scanned units one at a time, so a dropped
+-- connection does not lose what was already scanned. Finalizing it records
+-- the actual goods dispatch.
+
+-- =====================================================
+-- DISPATCH SCAN SESSIONS
+-- =====================================================
+
+CREATE TABLE dispatch_scan_sessions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    company_id UUID NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+    warehouse_id UUID NOT NULL REFERENCES warehouses(id) ON DELETE CASCADE,
+
+    -- Header of the dispatch to be recorded, as on goods_dispatches
+    dispatch_type VARCHAR(20) NOT NULL CHECK (dispatch_type IN ('partner', 'warehouse')),
+    dispatch_to_partner_id UUID REFERENCES partners(id),
+    dispatch_to_warehouse_id UUID REFERENCES warehouses(id),
+    agent_id UUID REFERENCES partners(id),
+    link_type VARCHAR(20) CHECK (link_type IN ('sales_order', 'job_work', 'other')),
+    sales_order_id UUID REFERENCES sales_orders(id),
+    job_work_id UUID REFERENCES job_works(id),
+    other_reference TEXT,
+    dispatch_date DATE,
+    due_date DATE,
+    invoice_number VARCHAR(50),
+    invoice_amount DECIMAL(10,2),
+    transport_details TEXT,
+    notes TEXT,
+
+    status VARCHAR(20) NOT NULL DEFAULT 'open'
+        CHECK (status IN ('open', 'finalized', 'discarded')),
+    -- Set once finalized
+    dispatch_id UUID REFERENCES goods_dispatches(id),
+
+    -- Audit fields
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id),
+    modified_by UUID REFERENCES users(id)
+);
+
+-- =====================================================
+-- DISPATCH SCAN ITEMS
+-- =====================================================
+
+CREATE TABLE dispatch_scan_items (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    company_id UUID NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+    session_id UUID NOT NULL REFERENCES dispatch_scan_sessions(id) ON DELETE CASCADE,
+    stock_unit_id UUID NOT NULL REFERENCES stock_units(id),
+
+    -- Unit number or QR code as scanned
+    scanned_code TEXT NOT NULL,
+
+    scanned_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    scanned_by UUID NOT NULL REFERENCES users(id),
+
+    UNIQUE(session_id, stock_unit_id)
+);
+
+-- =====================================================
+-- INDEXES FOR PERFORMANCE
+-- =====================================================
+
+CREATE INDEX idx_dispatch_scan_sessions_company_id ON dispatch_scan_sessions(company_id);
+CREATE INDEX idx_dispatch_scan_sessions_open ON dispatch_scan_sessions(warehouse_id)
+    WHERE status = 'open';
+CREATE INDEX idx_dispatch_scan_items_session ON dispatch_scan_items(session_id);
+
+-- =====================================================
+-- TRIGGERS FOR AUTO-UPDATES
+-- =====================================================
+
+CREATE TRIGGER update_dispatch_scan_sessions_updated_at
+    BEFORE UPDATE ON dispatch_scan_sessions
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,55 @@
+-- Bale Backend - Warehouse Transfers
+-- Units sent to another warehouse are in transit until the destination
+-- receives them; the receipt moves the same stock units and records any
+-- roll that did not arrive.
+
+-- =====================================================
+-- STOCK UNIT STATUS
+-- =====================================================
+
+ALTER TABLE stock_units DROP CONSTRAINT stock_units_status_check;
+ALTER TABLE stock_units ADD CONSTRAINT stock_units_status_check
+    CHECK (status IN ('pending_details', 'in_stock', 'in_transit', 'dispatched', 'removed'));
+
+-- =====================================================
+-- TRANSFER RECEIPTS
+-- =====================================================
+
+-- The warehouse dispatch a receipt takes in
+ALTER TABLE goods_receipts ADD COLUMN transfer_dispatch_id UUID REFERENCES goods_dispatches(id);
+
+ALTER TABLE goods_receipts ADD CONSTRAINT check_transfer_from_warehouse
+    CHECK (transfer_dispatch_id IS NULL OR issued_by_warehouse_id IS NOT NULL);
+
+-- A transfer is received once
+CREATE UNIQUE INDEX idx_goods_receipts_transfer_dispatch ON goods_receipts(transfer_dispatch_id)
+    WHERE transfer_dispatch_id IS NOT NULL AND deleted_at IS NULL;
+
+-- Every unit of the transfer, as received or missing at the destination
+CREATE TABLE goods_receipt_transfer_items (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    company_id UUID NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+    receipt_id UUID NOT NULL REFERENCES goods_receipts(id) ON DELETE CASCADE,
+    stock_unit_id UUID NOT NULL REFERENCES stock_units(id),
+
+    -- Dispatched but not found on arrival; the unit stays in transit until
+    -- it is found at the destination, written off or returned to the origin
+    is_missing BOOLEAN NOT NULL DEFAULT FALSE,
+    resolution VARCHAR(20) CHECK (resolution IN ('found', 'written_off', 'returned')),
+    resolved_at TIMESTAMPTZ,
+    resolved_by UUID REFERENCES users(id),
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    UNIQUE(receipt_id, stock_unit_id),
+    CONSTRAINT check_only_missing_units_resolved CHECK (resolution IS NULL OR is_missing)
+);
+
+-- =====================================================
+-- INDEXES FOR PERFORMANCE
+-- =====================================================
+
+CREATE INDEX idx_goods_receipt_transfer_items_company_id ON goods_receipt_transfer_items(company_id);
+CREATE INDEX idx_goods_receipt_transfer_items_receipt ON goods_receipt_transfer_items(receipt_id);
+CREATE INDEX idx_goods_receipt_transfer_items_missing ON goods_receipt_transfer_items(stock_unit_id)
+    WHERE is_missing AND resolution IS NULL;
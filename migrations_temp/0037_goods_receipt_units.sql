@@ -0,0 +1,23 @@
+-- Bale Backend - Goods Receipt Units
+-- Stock units of a receipt are created by the application with the real
+-- size, grade and location of each roll, instead of N placeholder units of
+-- size 1.000 per receipt item.
+
+-- =====================================================
+-- DROP PLACEHOLDER UNIT CREATION
+-- =====================================================
+
+DROP TRIGGER IF EXISTS trigger_auto_create_stock_units_from_receipt ON goods_receipt_items;
+DROP FUNCTION IF EXISTS auto_create_stock_units_from_receipt();
+
+-- =====================================================
+-- RECEIPT ITEM OF EACH UNIT
+-- =====================================================
+
+ALTER TABLE stock_units ADD COLUMN created_from_receipt_item_id UUID
+    REFERENCES goods_receipt_items(id);
+
+ALTER TABLE goods_receipt_items ADD CONSTRAINT check_quantity_received_positive
+    CHECK (quantity_received > 0);
+
+CREATE INDEX idx_stock_units_receipt_item ON stock_units(created_from_receipt_item_id);
@@ -0,0 +1,224 @@
+-- Bale Backend - Sales Returns
+-- Goods a customer sends back against a sales order: the originally
+-- dispatched units come back into stock, or quarantine, and a credit note
+-- at the order's rates is issued for them.
+
+-- =====================================================
+-- STOCK UNIT STATUS
+-- =====================================================
+
+ALTER TABLE stock_units DROP CONSTRAINT stock_units_status_check;
+ALTER TABLE stock_units ADD CONSTRAINT stock_units_status_check
+    CHECK (status IN ('pending_details', 'in_stock', 'in_transit', 'dispatched', 'quarantined',
+                      'removed'));
+
+-- =====================================================
+-- SALES RETURN ITEMS
+-- =====================================================
+
+-- Each returned unit, with the dispatch it went out on and its value
+CREATE TABLE sales_return_items (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    company_id UUID NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+    receipt_id UUID NOT NULL REFERENCES goods_receipts(id) ON DELETE CASCADE,
+    dispatch_id UUID NOT NULL REFERENCES goods_dispatches(id),
+    stock_unit_id UUID NOT NULL REFERENCES stock_units(id),
+    product_id UUID NOT NULL REFERENCES products(id),
+
+    quantity DECIMAL(10,3) NOT NULL,
+    unit_rate DECIMAL(10,2) NOT NULL,
+    amount DECIMAL(10,2) NOT NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    UNIQUE(receipt_id, stock_unit_id)
+);
+
+-- =====================================================
+-- CREDIT NOTES
+-- =====================================================
+
+CREATE TABLE credit_notes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    company_id UUID NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+    credit_note_number VARCHAR(50) NOT NULL,
+    receipt_id UUID NOT NULL UNIQUE REFERENCES goods_receipts(id),
+    sales_order_id UUID NOT NULL REFERENCES sales_orders(id),
+    customer_id UUID NOT NULL REFERENCES partners(id),
+
+    credit_note_date DATE NOT NULL DEFAULT CURRENT_DATE,
+    -- Copied from the order, so later edits do not change the note
+    discount_percentage DECIMAL(5,2) NOT NULL DEFAULT 0,
+    amount DECIMAL(10,2) NOT NULL CHECK (amount >= 0),
+
+    -- Audit fields
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id),
+    deleted_at TIMESTAMPTZ,
+
+    UNIQUE(company_id, credit_note_number)
+);
+
+-- =====================================================
+-- INDEXES FOR PERFORMANCE
+-- =====================================================
+
+CREATE INDEX idx_sales_return_items_company_id ON sales_return_items(company_id);
+CREATE INDEX idx_sales_return_items_dispatch_unit ON sales_return_items(dispatch_id, stock_unit_id);
+CREATE INDEX idx_credit_notes_company_id ON credit_notes(company_id);
+CREATE INDEX idx_credit_notes_customer ON credit_notes(company_id, customer_id);
+CREATE INDEX idx_credit_notes_sales_order ON credit_notes(sales_order_id);
+
+-- =====================================================
+-- PARTNER LEDGER ENTRIES VIEW (credit notes)
+-- =====================================================
+
+-- Credit notes reduce what the customer owes on the order
+CREATE OR REPLACE VIEW partner_ledger_entries AS
+SELECT
+    so.company_id,
+    so.customer_id as partner_id,
+    'sales_order' as entry_type,
+    so.id as source_id,
+    so.order_number as reference_number,
+    so.order_date as entry_date,
+    COALESCE(so.total_amount, 0) as debit,
+    0::DECIMAL(10,2) as credit,
+    so.created_at
+FROM sales_orders so
+WHERE so.deleted_at IS NULL
+    AND so.status IN ('in_progress', 'completed')
+
+UNION ALL
+
+SELECT
+    so.company_id,
+    so.customer_id as partner_id,
+    'advance' as entry_type,
+    so.id as source_id,
+    so.order_number as reference_number,
+    so.order_date as entry_date,
+    0::DECIMAL(10,2) as debit,
+    so.advance_amount as credit,
+    so.created_at
+FROM sales_orders so
+WHERE so.deleted_at IS NULL
+    AND so.status IN ('in_progress', 'completed')
+    AND so.advance_amount > 0
+
+UNION ALL
+
+SELECT
+    gd.company_id,
+    gd.dispatch_to_partner_id as partner_id,
+    'dispatch_invoice' as entry_type,
+    gd.id as source_id,
+    COALESCE(gd.invoice_number, gd.dispatch_number) as reference_number,
+    gd.dispatch_date as entry_date,
+    gd.invoice_amount as debit,
+    0::DECIMAL(10,2) as credit,
+    gd.created_at
+FROM goods_dispatches gd
+WHERE gd.deleted_at IS NULL
+    AND gd.is_cancelled = false
+    AND gd.dispatch_type = 'partner'
+    AND gd.sales_order_id IS NULL
+    AND gd.invoice_amount > 0
+
+UNION ALL
+
+SELECT
+    pm.company_id,
+    pm.partner_id,
+    'payment' as entry_type,
+    pm.id as source_id,
+    pm.payment_number as reference_number,
+    pm.payment_date as entry_date,
+    0::DECIMAL(10,2) as debit,
+    pm.amount as credit,
+    pm.created_at
+FROM payments pm
+WHERE pm.deleted_at IS NULL
+
+UNION ALL
+
+SELECT
+    pm.company_id,
+    pm.partner_id,
+    'payment_reversal' as entry_type,
+    pm.id as source_id,
+    pm.payment_number as reference_number,
+    pm.reversed_at::DATE as entry_date,
+    pm.amount as debit,
+    0::DECIMAL(10,2) as credit,
+    pm.reversed_at as created_at
+FROM payments pm
+WHERE pm.deleted_at IS NULL
+    AND pm.is_reversed = true
+
+UNION ALL
+
+SELECT
+    cn.company_id,
+    cn.customer_id as partner_id,
+    'credit_note' as entry_type,
+    cn.id as source_id,
+    cn.credit_note_number as reference_number,
+    cn.credit_note_date as entry_date,
+    0::DECIMAL(10,2) as debit,
+    cn.amount as credit,
+    cn.created_at
+FROM credit_notes cn
+WHERE cn.deleted_at IS NULL;
+
+-- =====================================================
+-- PRICING
+-- =====================================================
+
+-- Rate a product dispatched on an order is invoiced, commissioned and
+-- credited at: the order's rate for it, else the catalogue price
+CREATE OR REPLACE FUNCTION order_unit_rate(sales_order_uuid UUID, product_uuid UUID)
+RETURNS DECIMAL AS $$
+    SELECT COALESCE(
+        (SELECT MAX(soi.unit_rate) FROM sales_order_items soi
+         WHERE soi.sales_order_id = sales_order_uuid AND soi.product_id = product_uuid),
+        p.selling_price_per_unit,
+        0
+    )
+    FROM products p
+    WHERE p.id = product_uuid;
+$$ LANGUAGE sql STABLE;
+
+-- =====================================================
+-- NUMBERING
+-- =====================================================
+
+-- Same CN-000001 format as generate_sequence_number, kept to credit notes so the
+-- shared generator is not redefined here
+CREATE OR REPLACE FUNCTION generate_credit_note_number(company_uuid UUID)
+RETURNS TEXT AS $$
+DECLARE
+    next_seq INTEGER;
+BEGIN
+    SELECT COALESCE(MAX(CAST(SUBSTRING(credit_note_number FROM '^CN-(\d+)$') AS INTEGER)), 0) + 1
+    INTO next_seq
+    FROM credit_notes
+    WHERE company_id = company_uuid;
+
+    RETURN 'CN-' || LPAD(next_seq::TEXT, 6, '0');
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION auto_generate_credit_note_number()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.credit_note_number IS NULL OR NEW.credit_note_number = '' THEN
+        NEW.credit_note_number := generate_credit_note_number(NEW.company_id);
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_auto_credit_note_number
+    BEFORE INSERT ON credit_notes
+    FOR EACH ROW EXECUTE FUNCTION auto_generate_credit_note_number();
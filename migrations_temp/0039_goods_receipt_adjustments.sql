@@ -0,0 +1,36 @@
+-- Bale Backend - Goods Receipt Adjustments
+-- Receipts are corrected, never deleted. Changing a received quantity adds
+-- or removes the item's stock units, and every such change is kept.
+
+-- =====================================================
+-- GOODS RECEIPT ADJUSTMENTS
+-- =====================================================
+
+CREATE TABLE goods_receipt_adjustments (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    company_id UUID NOT NULL REFERENCES companies(id) ON DELETE CASCADE,
+    receipt_id UUID NOT NULL REFERENCES goods_receipts(id) ON DELETE CASCADE,
+    receipt_item_id UUID NOT NULL REFERENCES goods_receipt_items(id),
+    product_id UUID NOT NULL REFERENCES products(id),
+
+    from_quantity INTEGER NOT NULL,
+    to_quantity INTEGER NOT NULL CHECK (to_quantity > 0),
+
+    -- Units created for an increase, or marked removed for a decrease
+    added_unit_ids UUID[] NOT NULL DEFAULT '{}',
+    removed_unit_ids UUID[] NOT NULL DEFAULT '{}',
+
+    reason TEXT,
+
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    changed_by UUID NOT NULL REFERENCES users(id),
+
+    CONSTRAINT check_quantity_changed CHECK (from_quantity != to_quantity)
+);
+
+-- =====================================================
+-- INDEXES FOR PERFORMANCE
+-- =====================================================
+
+CREATE INDEX idx_goods_receipt_adjustments_company_id ON goods_receipt_adjustments(company_id);
+CREATE INDEX idx_goods_receipt_adjustments_receipt ON goods_receipt_adjustments(receipt_id, changed_at);
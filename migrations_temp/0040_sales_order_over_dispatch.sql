@@ -0,0 +1,17 @@
+-- Bale Backend - Sales Order Over-Dispatch
+-- Rolls are cut to length, so the last roll of an order often runs past the
+-- ordered quantity. Dispatching beyond the required quantity is allowed and
+-- reported as excess; only lowering the required quantity below what has
+-- already been dispatched is still refused.
+
+-- =====================================================
+-- REQUIRED QUANTITY CHECK
+-- =====================================================
+
+DROP TRIGGER trigger_validate_required_quantity ON sales_order_items;
+
+CREATE TRIGGER trigger_validate_required_quantity
+    BEFORE UPDATE OF required_quantity ON sales_order_items
+    FOR EACH ROW
+    WHEN (NEW.required_quantity < OLD.required_quantity)
+    EXECUTE FUNCTION validate_required_quantity();
@@ -1,9 +1,9 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use tokio::net::TcpListener;
 
 use axum::{
-    routing::{get, post},
+    routing::{delete, get, patch, post, put},
     serve::Serve,
     Router,
 };
@@ -11,9 +11,45 @@ use sqlx::{postgres::PgPoolOptions, PgPool};
 
 use crate::{
     config::{DatabaseSettings, Settings},
+    reminders::{run_reminder_schedule, LogNotifier},
     routes::{
-        companies::{create_company, get_company, get_company_list},
+        attention::get_attention_needed,
+        commissions::{
+            accrue_dispatch_commissions, create_commission_rule, get_commission_report,
+            get_commission_rules, pay_commissions,
+        },
+        companies::{create_company, get_company, get_company_list, upload_company_logo},
+        dispatch_sessions::{
+            create_scan_session, discard_scan_session, finalize_scan_session, get_scan_session,
+            remove_scan, scan_unit,
+        },
+        dispatches::{
+            cancel_dispatch, create_dispatch, export_e_way_bill, get_challan_pdf, get_dispatch,
+            update_transport_details,
+        },
+        gst::{get_hsn_gst_rates, set_hsn_gst_rate},
         healthcheck::health_check,
+        invoices::{generate_invoice, get_invoice, get_invoice_pdf},
+        job_work_analytics::{get_job_work_yields, get_vendor_ranking},
+        job_works::{
+            cancel_job_work, complete_job_work, create_job_work, delete_job_work, get_job_types,
+            get_job_work, get_job_work_status_history, update_job_work,
+        },
+        ledger::{get_partner_outstanding, get_partner_statement},
+        payments::{
+            bounce_payment, clear_payment, create_payment, get_partner_receivables, get_payment,
+            reverse_payment,
+        },
+        quotations::{convert_quotation, create_quotation, get_proforma_pdf, get_quotation},
+        receipts::{
+            create_receipt, create_sales_return, get_receipt, get_receipt_adjustments,
+            receive_transfer, resolve_missing_units, update_receipt,
+        },
+        sales_orders::{
+            add_sales_order_item, approve_sales_order, cancel_sales_order, complete_sales_order,
+            create_sales_order, get_sales_order, get_sales_orders, get_status_history,
+            remove_sales_order_item, reopen_sales_order, update_sales_order_item,
+        },
     },
 };
 
@@ -33,11 +69,169 @@ impl Application {
         let listener = TcpListener::bind(addr).await?;
         let port = listener.local_addr().unwrap().port();
 
+        if cfg!(feature = "header-auth") {
+            tracing::warn!("header-auth is enabled: callers are trusted as the user in x-user-id");
+        }
+
+        if let Some(minutes) = configuration.application.reminder_interval_minutes {
+            tokio::spawn(run_reminder_schedule(
+                db_pool.clone(),
+                LogNotifier,
+                Duration::from_secs(minutes.get().saturating_mul(60)),
+            ));
+        }
+
         let admin_routes = Router::new().route("/companies", get(get_company_list));
 
         let api_v1_routes = Router::new()
             .route("/companies", post(create_company))
-            .route("/companies/{company_id}", get(get_company));
+            .route("/companies/{company_id}", get(get_company))
+            .route("/companies/{company_id}/logo", put(upload_company_logo))
+            .route("/partners/{partner_id}/ledger", get(get_partner_statement))
+            .route(
+                "/partners/{partner_id}/outstanding",
+                get(get_partner_outstanding),
+            )
+            .route(
+                "/partners/{partner_id}/receivables",
+                get(get_partner_receivables),
+            )
+            .route("/payments", post(create_payment))
+            .route("/payments/{payment_id}", get(get_payment))
+            .route("/payments/{payment_id}/clear", post(clear_payment))
+            .route("/payments/{payment_id}/bounce", post(bounce_payment))
+            .route("/payments/{payment_id}/reverse", post(reverse_payment))
+            .route(
+                "/agents/{agent_id}/commission-rules",
+                get(get_commission_rules).post(create_commission_rule),
+            )
+            .route("/agents/{agent_id}/commissions", get(get_commission_report))
+            .route("/agents/{agent_id}/commissions/pay", post(pay_commissions))
+            .route("/dispatches", post(create_dispatch))
+            .route("/dispatches/{dispatch_id}", get(get_dispatch))
+            .route("/dispatches/{dispatch_id}/cancel", post(cancel_dispatch))
+            .route("/dispatches/{dispatch_id}/receive", post(receive_transfer))
+            .route("/receipts", post(create_receipt))
+            .route(
+                "/receipts/{receipt_id}",
+                get(get_receipt).patch(update_receipt),
+            )
+            .route(
+                "/receipts/{receipt_id}/adjustments",
+                get(get_receipt_adjustments),
+            )
+            .route(
+                "/receipts/{receipt_id}/missing-units",
+                post(resolve_missing_units),
+            )
+            .route(
+                "/dispatches/{dispatch_id}/commissions",
+                post(accrue_dispatch_commissions),
+            )
+            .route(
+                "/dispatches/{dispatch_id}/invoice",
+                post(generate_invoice).get(get_invoice),
+            )
+            .route(
+                "/dispatches/{dispatch_id}/invoice.pdf",
+                get(get_invoice_pdf),
+            )
+            .route(
+                "/dispatches/{dispatch_id}/challan.pdf",
+                get(get_challan_pdf),
+            )
+            .route(
+                "/dispatches/{dispatch_id}/transport",
+                patch(update_transport_details),
+            )
+            .route(
+                "/dispatches/{dispatch_id}/e-way-bill.json",
+                get(export_e_way_bill),
+            )
+            .route("/dispatch-sessions", post(create_scan_session))
+            .route("/dispatch-sessions/{session_id}", get(get_scan_session))
+            .route("/dispatch-sessions/{session_id}/scans", post(scan_unit))
+            .route(
+                "/dispatch-sessions/{session_id}/scans/{stock_unit_id}",
+                delete(remove_scan),
+            )
+            .route(
+                "/dispatch-sessions/{session_id}/finalize",
+                post(finalize_scan_session),
+            )
+            .route(
+                "/dispatch-sessions/{session_id}/discard",
+                post(discard_scan_session),
+            )
+            .route(
+                "/warehouses/{warehouse_id}/attention-needed",
+                get(get_attention_needed),
+            )
+            .route("/hsn-gst-rates", get(get_hsn_gst_rates))
+            .route("/hsn-gst-rates/{hsn_code}", put(set_hsn_gst_rate))
+            .route("/job-types", get(get_job_types))
+            .route("/job-works", post(create_job_work))
+            .route("/job-works/yields", get(get_job_work_yields))
+            .route("/job-works/vendor-ranking", get(get_vendor_ranking))
+            .route(
+                "/job-works/{job_work_id}",
+                get(get_job_work)
+                    .patch(update_job_work)
+                    .delete(delete_job_work),
+            )
+            .route("/job-works/{job_work_id}/complete", post(complete_job_work))
+            .route("/job-works/{job_work_id}/cancel", post(cancel_job_work))
+            .route(
+                "/job-works/{job_work_id}/status-history",
+                get(get_job_work_status_history),
+            )
+            .route("/quotations", post(create_quotation))
+            .route("/quotations/{quotation_id}", get(get_quotation))
+            .route(
+                "/quotations/{quotation_id}/proforma.pdf",
+                get(get_proforma_pdf),
+            )
+            .route(
+                "/quotations/{quotation_id}/convert",
+                post(convert_quotation),
+            )
+            .route(
+                "/sales-orders",
+                get(get_sales_orders).post(create_sales_order),
+            )
+            .route("/sales-orders/{sales_order_id}", get(get_sales_order))
+            .route(
+                "/sales-orders/{sales_order_id}/approve",
+                post(approve_sales_order),
+            )
+            .route(
+                "/sales-orders/{sales_order_id}/complete",
+                post(complete_sales_order),
+            )
+            .route(
+                "/sales-orders/{sales_order_id}/cancel",
+                post(cancel_sales_order),
+            )
+            .route(
+                "/sales-orders/{sales_order_id}/reopen",
+                post(reopen_sales_order),
+            )
+            .route(
+                "/sales-orders/{sales_order_id}/status-history",
+                get(get_status_history),
+            )
+            .route(
+                "/sales-orders/{sales_order_id}/items",
+                post(add_sales_order_item),
+            )
+            .route(
+                "/sales-orders/{sales_order_id}/items/{item_id}",
+                patch(update_sales_order_item).delete(remove_sales_order_item),
+            )
+            .route(
+                "/sales-orders/{sales_order_id}/returns",
+                post(create_sales_return),
+            );
 
         let app: Router = Router::new()
             .route("/health_check", get(health_check))
@@ -1,5 +1,9 @@
+use std::num::NonZeroU64;
+
 use secrecy::{ExposeSecret, SecretString};
-use serde_aux::field_attributes::deserialize_number_from_string;
+use serde_aux::field_attributes::{
+    deserialize_number_from_string, deserialize_option_number_from_string,
+};
 use sqlx::postgres::{PgConnectOptions, PgSslMode};
 
 #[derive(serde::Deserialize, Clone)]
@@ -14,6 +18,9 @@ pub struct ApplicationSettings {
     pub port: u16,
     pub host: String,
     pub base_url: String,
+    /// How often overdue reminders are sent; unset disables them and zero is refused
+    #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
+    pub reminder_interval_minutes: Option<NonZeroU64>,
 }
 
 #[derive(serde::Deserialize, Clone)]
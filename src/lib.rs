@@ -1,3 +1,6 @@
 pub mod app;
+pub mod auth;
 pub mod config;
+pub mod pdf;
+pub mod reminders;
 pub mod routes;
@@ -0,0 +1,407 @@
+//! Minimal A4 document layout for printable business documents (invoices,
+//! challans, quotations) on top of `pdf-writer`.
+//!
+//! Text is set top to bottom in the built-in Helvetica fonts, so nothing has
+//! to be embedded. Characters outside printable ASCII are replaced with `?`.
+//! Images are limited to JPEGs, which PDF readers decode themselves.
+
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref, Str};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 40.0;
+
+const REGULAR: Name = Name(b"F1");
+const BOLD: Name = Name(b"F2");
+
+/// Helvetica advance widths for ASCII 32..=126, in 1/1000 em
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// A table column, `x` and `width` in points from the left page edge
+#[derive(Debug, Clone, Copy)]
+pub struct Column {
+    pub x: f32,
+    pub width: f32,
+    pub align: Align,
+}
+
+impl Column {
+    pub const fn left(x: f32, width: f32) -> Self {
+        Self {
+            x,
+            width,
+            align: Align::Left,
+        }
+    }
+
+    pub const fn right(x: f32, width: f32) -> Self {
+        Self {
+            x,
+            width,
+            align: Align::Right,
+        }
+    }
+}
+
+/// A JPEG embedded as is
+#[derive(Debug, Clone)]
+pub struct JpegImage {
+    data: Vec<u8>,
+    width: u16,
+    height: u16,
+    components: u8,
+}
+
+impl JpegImage {
+    /// Reads the size from the frame header; `None` when `data` is not a
+    /// JPEG with 1, 3 or 4 colour components
+    pub fn parse(data: Vec<u8>) -> Option<Self> {
+        if !data.starts_with(&[0xFF, 0xD8]) {
+            return None;
+        }
+        let mut i = 2;
+        while i + 4 <= data.len() {
+            if data[i] != 0xFF {
+                return None;
+            }
+            let marker = data[i + 1];
+            let length = u16::from_be_bytes([data[i + 2], data[i + 3]]) as usize;
+            // Start of frame, other than DHT, JPG and DAC which share the range
+            if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+                let frame = data.get(i + 4..i + 10)?;
+                let height = u16::from_be_bytes([frame[1], frame[2]]);
+                let width = u16::from_be_bytes([frame[3], frame[4]]);
+                let components = frame[5];
+                if width == 0 || height == 0 || !matches!(components, 1 | 3 | 4) {
+                    return None;
+                }
+                return Some(Self {
+                    data,
+                    width,
+                    height,
+                    components,
+                });
+            }
+            i += 2 + length;
+        }
+        None
+    }
+}
+
+pub struct PdfDocument {
+    pages: Vec<Content>,
+    content: Content,
+    y: f32,
+    images: Vec<JpegImage>,
+}
+
+impl Default for PdfDocument {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PdfDocument {
+    pub const LEFT: f32 = MARGIN;
+    pub const RIGHT: f32 = PAGE_WIDTH - MARGIN;
+    pub const CONTENT_WIDTH: f32 = PAGE_WIDTH - 2.0 * MARGIN;
+
+    pub fn new() -> Self {
+        Self {
+            pages: Vec::new(),
+            content: Content::new(),
+            y: PAGE_HEIGHT - MARGIN,
+            images: Vec::new(),
+        }
+    }
+
+    /// Image at the left margin, scaled to `height` and at most a third of
+    /// the content width
+    pub fn image(&mut self, image: JpegImage, height: f32) {
+        let scale =
+            (height / image.height as f32).min(Self::CONTENT_WIDTH / 3.0 / image.width as f32);
+        let (width, height) = (image.width as f32 * scale, image.height as f32 * scale);
+        self.ensure_space(height + 4.0);
+        self.y -= height;
+        let name = format!("Im{}", self.images.len() + 1);
+        self.content
+            .save_state()
+            .transform([width, 0.0, 0.0, height, Self::LEFT, self.y])
+            .x_object(Name(name.as_bytes()))
+            .restore_state();
+        self.images.push(image);
+        self.y -= 4.0;
+    }
+
+    /// Large bold line centred on the page
+    pub fn title(&mut self, text: &str) {
+        let size = 16.0;
+        self.ensure_space(size * 1.5);
+        self.y -= size;
+        let x = (PAGE_WIDTH - text_width(text, size)) / 2.0;
+        self.show(x, self.y, text, size, true);
+        self.y -= size * 0.5;
+    }
+
+    pub fn heading(&mut self, text: &str) {
+        self.line(text, 10.0, true);
+    }
+
+    pub fn text(&mut self, text: &str) {
+        self.line(text, 9.0, false);
+    }
+
+    /// One table row; cells beyond the number of columns are ignored and text
+    /// wider than its column is cut off.
+    pub fn row(&mut self, columns: &[Column], cells: &[&str], bold: bool) {
+        let size = 8.5;
+        self.ensure_space(size * 1.6);
+        self.y -= size * 1.4;
+        for (column, cell) in columns.iter().zip(cells) {
+            let cell = fit(cell, column.width, size);
+            let x = match column.align {
+                Align::Left => column.x,
+                Align::Right => column.x + column.width - text_width(&cell, size),
+            };
+            self.show(x, self.y, &cell, size, bold);
+        }
+        self.y -= size * 0.2;
+    }
+
+    /// Horizontal line across the content area
+    pub fn rule(&mut self) {
+        self.ensure_space(6.0);
+        self.y -= 3.0;
+        self.content
+            .set_line_width(0.5)
+            .move_to(Self::LEFT, self.y)
+            .line_to(Self::RIGHT, self.y)
+            .stroke();
+        self.y -= 3.0;
+    }
+
+    pub fn space(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        self.pages.push(self.content);
+
+        let catalog_id = Ref::new(1);
+        let page_tree_id = Ref::new(2);
+        let regular_id = Ref::new(3);
+        let bold_id = Ref::new(4);
+        let page_ids: Vec<Ref> = (0..self.pages.len())
+            .map(|i| Ref::new(5 + 2 * i as i32))
+            .collect();
+
+        let mut pdf = Pdf::new();
+        pdf.catalog(catalog_id).pages(page_tree_id);
+        pdf.pages(page_tree_id)
+            .kids(page_ids.iter().copied())
+            .count(page_ids.len() as i32);
+        pdf.type1_font(regular_id).base_font(Name(b"Helvetica"));
+        pdf.type1_font(bold_id).base_font(Name(b"Helvetica-Bold"));
+
+        // Images follow the pages and are available on every page
+        let first_image_id = 5 + 2 * page_ids.len() as i32;
+        let images: Vec<(String, Ref)> = (0..self.images.len())
+            .map(|i| (format!("Im{}", i + 1), Ref::new(first_image_id + i as i32)))
+            .collect();
+        for (image, (_, image_id)) in self.images.iter().zip(&images) {
+            let mut xobject = pdf.image_xobject(*image_id, &image.data);
+            xobject.filter(Filter::DctDecode);
+            xobject.width(image.width as i32);
+            xobject.height(image.height as i32);
+            xobject.bits_per_component(8);
+            match image.components {
+                1 => xobject.color_space().device_gray(),
+                4 => xobject.color_space().device_cmyk(),
+                _ => xobject.color_space().device_rgb(),
+            }
+        }
+
+        for (content, page_id) in self.pages.into_iter().zip(page_ids) {
+            let content_id = Ref::new(page_id.get() + 1);
+            let mut page = pdf.page(page_id);
+            page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT))
+                .parent(page_tree_id)
+                .contents(content_id);
+            let mut resources = page.resources();
+            resources
+                .fonts()
+                .pair(REGULAR, regular_id)
+                .pair(BOLD, bold_id);
+            if !images.is_empty() {
+                let mut x_objects = resources.x_objects();
+                for (name, image_id) in &images {
+                    x_objects.pair(Name(name.as_bytes()), *image_id);
+                }
+            }
+            resources.finish();
+            page.finish();
+            pdf.stream(content_id, &content.finish());
+        }
+
+        pdf.finish()
+    }
+
+    fn line(&mut self, text: &str, size: f32, bold: bool) {
+        self.ensure_space(size * 1.5);
+        self.y -= size * 1.3;
+        self.show(Self::LEFT, self.y, text, size, bold);
+        self.y -= size * 0.2;
+    }
+
+    fn show(&mut self, x: f32, y: f32, text: &str, size: f32, bold: bool) {
+        let text = printable(text);
+        self.content
+            .begin_text()
+            .set_font(if bold { BOLD } else { REGULAR }, size)
+            .next_line(x, y)
+            .show(Str(text.as_bytes()))
+            .end_text();
+    }
+
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            let full = std::mem::replace(&mut self.content, Content::new());
+            self.pages.push(full);
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+    }
+}
+
+fn printable(text: &str) -> String {
+    text.chars()
+        .map(|c| if (' '..='~').contains(&c) { c } else { '?' })
+        .collect()
+}
+
+fn text_width(text: &str, size: f32) -> f32 {
+    printable(text)
+        .bytes()
+        .map(|b| HELVETICA_WIDTHS[(b - b' ') as usize] as f32)
+        .sum::<f32>()
+        * size
+        / 1000.0
+}
+
+fn fit(text: &str, width: f32, size: f32) -> String {
+    let mut fitted = printable(text);
+    while !fitted.is_empty() && text_width(&fitted, size) > width {
+        fitted.pop();
+    }
+    fitted
+}
+
+/// Amount with two decimals, as printed in money columns
+pub fn money(amount: Decimal) -> String {
+    format!("{:.2}", amount)
+}
+
+/// Short form of a product's `measuring_unit` for quantity columns
+pub fn unit_abbreviation(measuring_unit: &str) -> &str {
+    match measuring_unit {
+        "Meters" => "m",
+        "Yards" => "yd",
+        "Kg" => "kg",
+        "Pieces" => "pcs",
+        other => other,
+    }
+}
+
+/// Amount in words with Indian digit grouping, e.g.
+/// "Rupees One Lakh Twenty Thousand Five Hundred and Paise Fifty Only"
+pub fn amount_in_words(amount: Decimal) -> String {
+    let paise_total = (amount.abs() * Decimal::ONE_HUNDRED)
+        .round()
+        .to_u64()
+        .unwrap_or(0);
+    let rupees = paise_total / 100;
+    let paise = paise_total % 100;
+
+    let mut words = format!("Rupees {}", number_in_words(rupees));
+    if paise > 0 {
+        words.push_str(&format!(" and Paise {}", number_in_words(paise)));
+    }
+    words.push_str(" Only");
+    words
+}
+
+fn number_in_words(number: u64) -> String {
+    const ONES: [&str; 20] = [
+        "Zero",
+        "One",
+        "Two",
+        "Three",
+        "Four",
+        "Five",
+        "Six",
+        "Seven",
+        "Eight",
+        "Nine",
+        "Ten",
+        "Eleven",
+        "Twelve",
+        "Thirteen",
+        "Fourteen",
+        "Fifteen",
+        "Sixteen",
+        "Seventeen",
+        "Eighteen",
+        "Nineteen",
+    ];
+    const TENS: [&str; 10] = [
+        "", "", "Twenty", "Thirty", "Forty", "Fifty", "Sixty", "Seventy", "Eighty", "Ninety",
+    ];
+
+    fn below_hundred(n: u64) -> String {
+        match n {
+            0..=19 => ONES[n as usize].to_string(),
+            _ if n.is_multiple_of(10) => TENS[(n / 10) as usize].to_string(),
+            _ => format!("{} {}", TENS[(n / 10) as usize], ONES[(n % 10) as usize]),
+        }
+    }
+
+    if number < 100 {
+        return below_hundred(number);
+    }
+
+    let mut parts = Vec::new();
+    let crore = number / 10_000_000;
+    let lakh = number / 100_000 % 100;
+    let thousand = number / 1_000 % 100;
+    let hundred = number / 100 % 10;
+    let rest = number % 100;
+
+    if crore > 0 {
+        parts.push(format!("{} Crore", number_in_words(crore)));
+    }
+    if lakh > 0 {
+        parts.push(format!("{} Lakh", below_hundred(lakh)));
+    }
+    if thousand > 0 {
+        parts.push(format!("{} Thousand", below_hundred(thousand)));
+    }
+    if hundred > 0 {
+        parts.push(format!("{} Hundred", ONES[hundred as usize]));
+    }
+    if rest > 0 {
+        parts.push(below_hundred(rest));
+    }
+    parts.join(" ")
+}
@@ -0,0 +1,173 @@
+//! Overdue job works, dispatches and sales orders, and the scheduled job that
+//! sends a reminder for each through a [`Notifier`].
+
+use std::{future::Future, time::Duration};
+
+use anyhow::Context;
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+// OVERDUE
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct OverdueItem {
+    /// `job_work`, `dispatch` or `sales_order`
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    pub company_id: Uuid,
+    pub warehouse_id: Option<Uuid>,
+    pub number: String,
+    pub partner_id: Option<Uuid>,
+    pub partner_name: Option<String>,
+    pub due_date: NaiveDate,
+    pub days_overdue: i32,
+}
+
+/// Open records past their due date, most overdue first. Completed and
+/// cancelled records are skipped, as are dispatches whose sales order or job
+/// work is closed.
+pub async fn fetch_overdue_items_from_db(
+    db_pool: &PgPool,
+    company_id: Option<Uuid>,
+    warehouse_id: Option<Uuid>,
+    today: NaiveDate,
+) -> Result<Vec<OverdueItem>, sqlx::Error> {
+    sqlx::query_as!(
+        OverdueItem,
+        r#"
+        SELECT entity_type as "entity_type!", entity_id as "entity_id!", company_id as "company_id!",
+               warehouse_id, number as "number!", partner_id, partner_name,
+               due_date as "due_date!", ($3::DATE - due_date)::INTEGER as "days_overdue!"
+        FROM (
+            SELECT 'job_work' as entity_type, jw.id as entity_id, jw.company_id, jw.warehouse_id,
+                   jw.job_number as number, jw.vendor_id as partner_id, jw.due_date
+            FROM job_works jw
+            WHERE jw.status = 'in_progress' AND jw.deleted_at IS NULL AND jw.due_date < $3
+
+            UNION ALL
+
+            SELECT 'dispatch', gd.id, gd.company_id, gd.warehouse_id, gd.dispatch_number,
+                   gd.dispatch_to_partner_id, gd.due_date
+            FROM goods_dispatches gd
+            LEFT JOIN sales_orders so ON so.id = gd.sales_order_id
+            LEFT JOIN job_works jw ON jw.id = gd.job_work_id
+            WHERE gd.is_cancelled IS NOT TRUE AND gd.deleted_at IS NULL AND gd.due_date < $3
+                AND (so.id IS NULL OR so.status NOT IN ('completed', 'cancelled'))
+                AND (jw.id IS NULL OR jw.status = 'in_progress')
+
+            UNION ALL
+
+            SELECT 'sales_order', so.id, so.company_id, so.fulfillment_warehouse_id, so.order_number,
+                   so.customer_id, so.expected_delivery_date
+            FROM sales_orders so
+            WHERE so.status IN ('approval_pending', 'in_progress') AND so.deleted_at IS NULL
+                AND so.expected_delivery_date < $3
+        ) overdue
+        LEFT JOIN LATERAL (
+            SELECT COALESCE(NULLIF(p.company_name, ''), CONCAT_WS(' ', p.first_name, p.last_name)) as partner_name
+            FROM partners p
+            WHERE p.id = overdue.partner_id
+        ) partner ON TRUE
+        WHERE ($1::UUID IS NULL OR company_id = $1)
+            AND ($2::UUID IS NULL OR warehouse_id = $2)
+        ORDER BY due_date, number
+        "#,
+        company_id,
+        warehouse_id,
+        today
+    )
+    .fetch_all(db_pool)
+    .await
+}
+
+// NOTIFICATION
+// -------------------------------------------------------------------------------------
+
+/// Delivers overdue reminders, e.g. by SMS, WhatsApp or email
+pub trait Notifier: Send + Sync + 'static {
+    fn notify(&self, item: &OverdueItem) -> impl Future<Output = anyhow::Result<()>> + Send;
+}
+
+/// Writes reminders to the application log
+pub struct LogNotifier;
+
+impl Notifier for LogNotifier {
+    async fn notify(&self, item: &OverdueItem) -> anyhow::Result<()> {
+        tracing::warn!(
+            company_id = %item.company_id,
+            entity_type = %item.entity_type,
+            number = %item.number,
+            days_overdue = item.days_overdue,
+            "Overdue since {}",
+            item.due_date
+        );
+        Ok(())
+    }
+}
+
+/// Sends one reminder per overdue record and due date, returning how many
+/// were sent. A reminder that fails to send is retried on the next run.
+pub async fn send_overdue_reminders(
+    db_pool: &PgPool,
+    notifier: &impl Notifier,
+    today: NaiveDate,
+) -> anyhow::Result<usize> {
+    let items = fetch_overdue_items_from_db(db_pool, None, None, today)
+        .await
+        .context("Failed to fetch overdue items from database.")?;
+
+    let mut sent = 0;
+    for item in &items {
+        let mut transaction = db_pool
+            .begin()
+            .await
+            .context("Failed to acquire a Postgres connection from the pool.")?;
+
+        let recorded = sqlx::query_scalar!(
+            r#"
+            INSERT INTO overdue_reminders (company_id, entity_type, entity_id, due_date)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (entity_type, entity_id, due_date) DO NOTHING
+            RETURNING id
+            "#,
+            item.company_id,
+            item.entity_type,
+            item.entity_id,
+            item.due_date
+        )
+        .fetch_optional(&mut *transaction)
+        .await
+        .context("Failed to record overdue reminder.")?;
+        if recorded.is_none() {
+            continue;
+        }
+
+        if let Err(e) = notifier.notify(item).await {
+            tracing::error!(error = ?e, number = %item.number, "Failed to send overdue reminder");
+            continue;
+        }
+
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit SQL transaction to record an overdue reminder.")?;
+        sent += 1;
+    }
+
+    Ok(sent)
+}
+
+/// Sends overdue reminders every `interval`, starting immediately
+pub async fn run_reminder_schedule(db_pool: PgPool, notifier: impl Notifier, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let today = Utc::now().date_naive();
+        if let Err(e) = send_overdue_reminders(&db_pool, &notifier, today).await {
+            tracing::error!(error = ?e, "Failed to send overdue reminders");
+        }
+    }
+}
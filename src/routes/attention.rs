@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    auth::CurrentUser,
+    reminders::{fetch_overdue_items_from_db, OverdueItem},
+};
+
+// ERROR
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, thiserror::Error)]
+pub enum AttentionError {
+    #[error("Staff can only see their own warehouse")]
+    Forbidden,
+    #[error("Warehouse not found")]
+    NotFound,
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl IntoResponse for AttentionError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            Self::Forbidden => StatusCode::FORBIDDEN,
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+        .into_response()
+    }
+}
+
+// READ
+// -------------------------------------------------------------------------------------
+
+/// Overdue job works, dispatches and sales orders of a warehouse
+pub async fn get_attention_needed(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(warehouse_id): Path<Uuid>,
+) -> Result<Json<Vec<OverdueItem>>, AttentionError> {
+    if !user.is_admin() && user.warehouse_id != Some(warehouse_id) {
+        return Err(AttentionError::Forbidden);
+    }
+
+    sqlx::query_scalar!(
+        "SELECT id FROM warehouses WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL",
+        warehouse_id,
+        user.company_id
+    )
+    .fetch_optional(db_pool.as_ref())
+    .await
+    .context("Failed to fetch warehouse from database.")?
+    .ok_or(AttentionError::NotFound)?;
+
+    let items = fetch_overdue_items_from_db(
+        &db_pool,
+        Some(user.company_id),
+        Some(warehouse_id),
+        Utc::now().date_naive(),
+    )
+    .await
+    .context("Failed to fetch overdue items from database.")?;
+
+    Ok(Json(items))
+}
@@ -0,0 +1,564 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::Context;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+use crate::{auth::CurrentUser, routes::ledger::financial_year_start};
+
+// ERROR
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommissionError {
+    #[error("{0}")]
+    ValidationError(String),
+    #[error("{0}")]
+    InvalidState(String),
+    #[error("Not found")]
+    NotFound,
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl IntoResponse for CommissionError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            Self::ValidationError(_) => StatusCode::BAD_REQUEST,
+            Self::InvalidState(_) => StatusCode::CONFLICT,
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+        .into_response()
+    }
+}
+
+// CREATE
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString, Deserialize, Serialize)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum CommissionRuleType {
+    /// `rate` is a percentage of the dispatched value
+    Percentage,
+    /// `rate` is an amount per measuring unit dispatched
+    PerUnit,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewCommissionRule {
+    rule_type: CommissionRuleType,
+    rate: Decimal,
+    product_tag: Option<String>,
+    effective_from: Option<NaiveDate>,
+    effective_to: Option<NaiveDate>,
+    notes: Option<String>,
+}
+
+impl NewCommissionRule {
+    fn validate(&self) -> Result<(), String> {
+        if self.rate <= Decimal::ZERO {
+            return Err("Commission rate must be greater than zero".to_string());
+        }
+        if self.rule_type == CommissionRuleType::Percentage && self.rate > Decimal::ONE_HUNDRED {
+            return Err("Commission percentage cannot exceed 100".to_string());
+        }
+        if let (Some(from), Some(to)) = (self.effective_from, self.effective_to) {
+            if from > to {
+                return Err("`effective_from` must not be after `effective_to`".to_string());
+            }
+        }
+        if self
+            .product_tag
+            .as_deref()
+            .is_some_and(|tag| tag.trim().is_empty())
+        {
+            return Err("Product tag cannot be blank".to_string());
+        }
+        Ok(())
+    }
+}
+
+pub async fn create_commission_rule(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(agent_id): Path<Uuid>,
+    Json(new_rule): Json<NewCommissionRule>,
+) -> Result<(StatusCode, Json<Uuid>), CommissionError> {
+    new_rule
+        .validate()
+        .map_err(CommissionError::ValidationError)?;
+    ensure_agent_exists(&db_pool, user.company_id, agent_id).await?;
+
+    let rule_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO commission_rules (company_id, agent_id, rule_type, rate, product_tag,
+                                      effective_from, effective_to, notes, created_by)
+        VALUES ($1, $2, $3, $4, $5, COALESCE($6, CURRENT_DATE), $7, $8, $9)
+        RETURNING id
+        "#,
+        user.company_id,
+        agent_id,
+        new_rule.rule_type.to_string(),
+        new_rule.rate,
+        new_rule.product_tag.as_deref().map(str::trim),
+        new_rule.effective_from,
+        new_rule.effective_to,
+        new_rule.notes,
+        user.id
+    )
+    .fetch_one(db_pool.as_ref())
+    .await
+    .context("Failed to insert commission rule in the database.")?;
+
+    Ok((StatusCode::CREATED, Json(rule_id)))
+}
+
+/// Accrues commissions for a dispatch that was recorded before the agent's
+/// rules existed. Products that already have an accrual are left untouched.
+pub async fn accrue_dispatch_commissions(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(dispatch_id): Path<Uuid>,
+) -> Result<Json<Vec<CommissionAccrual>>, CommissionError> {
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    accrue_commissions_in_db(&mut transaction, user.company_id, dispatch_id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => CommissionError::NotFound,
+            e => CommissionError::UnexpectedError(
+                anyhow::Error::new(e).context("Failed to accrue commissions."),
+            ),
+        })?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to accrue commissions.")?;
+
+    let accruals = sqlx::query_as!(
+        CommissionAccrual,
+        r#"
+        SELECT id, agent_id, rule_id, dispatch_id, sales_order_id, product_id, quantity, base_amount,
+               commission_amount, accrual_date, status, paid_at, payment_reference
+        FROM commission_accruals
+        WHERE dispatch_id = $1 AND company_id = $2
+        ORDER BY created_at
+        "#,
+        dispatch_id,
+        user.company_id
+    )
+    .fetch_all(db_pool.as_ref())
+    .await
+    .context("Failed to fetch commission accruals from database.")?;
+
+    Ok(Json(accruals))
+}
+
+struct DispatchForCommission {
+    agent_id: Option<Uuid>,
+    sales_order_id: Option<Uuid>,
+    dispatch_date: NaiveDate,
+    discount_percentage: Decimal,
+    is_cancelled: bool,
+}
+
+struct DispatchedProduct {
+    product_id: Uuid,
+    tags: Vec<String>,
+    quantity: Decimal,
+    unit_rate: Decimal,
+}
+
+struct ApplicableRule {
+    id: Uuid,
+    rule_type: String,
+    rate: Decimal,
+    product_tag: Option<String>,
+}
+
+/// Records what the dispatch's agent earns on it, one accrual per product.
+///
+/// The agent is the one on the dispatch, falling back to the agent of the
+/// linked sales order. A rule scoped to one of the product's tags wins over
+/// the agent's general rule; products without a matching rule and cancelled
+/// dispatches earn nothing.
+/// Returns `RowNotFound` when the dispatch does not exist.
+pub(crate) async fn accrue_commissions_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    company_id: Uuid,
+    dispatch_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    let dispatch = sqlx::query_as!(
+        DispatchForCommission,
+        r#"
+        SELECT COALESCE(gd.agent_id, so.agent_id) as agent_id, gd.sales_order_id, gd.dispatch_date,
+               COALESCE(so.discount_percentage, 0) as "discount_percentage!",
+               COALESCE(gd.is_cancelled, false) as "is_cancelled!"
+        FROM goods_dispatches gd
+        LEFT JOIN sales_orders so ON gd.sales_order_id = so.id
+        WHERE gd.id = $1 AND gd.company_id = $2 AND gd.deleted_at IS NULL
+        "#,
+        dispatch_id,
+        company_id
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    let Some(agent_id) = dispatch.agent_id.filter(|_| !dispatch.is_cancelled) else {
+        return Ok(());
+    };
+
+    let rules = sqlx::query_as!(
+        ApplicableRule,
+        r#"
+        SELECT id, rule_type, rate, product_tag FROM commission_rules
+        WHERE company_id = $1 AND agent_id = $2 AND is_active = true AND deleted_at IS NULL
+            AND effective_from <= $3 AND (effective_to IS NULL OR effective_to >= $3)
+        ORDER BY effective_from DESC, created_at DESC
+        "#,
+        company_id,
+        agent_id,
+        dispatch.dispatch_date
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+    if rules.is_empty() {
+        return Ok(());
+    }
+
+    // Priced like the invoice and any credit note for the same units
+    let products = sqlx::query_as!(
+        DispatchedProduct,
+        r#"
+        SELECT p.id as product_id, COALESCE(p.tags, '{}') as "tags!",
+               SUM(su.size_quantity) as "quantity!",
+               order_unit_rate($2, p.id) as "unit_rate!"
+        FROM goods_dispatch_items gdi
+        JOIN stock_units su ON gdi.stock_unit_id = su.id
+        JOIN products p ON su.product_id = p.id
+        WHERE gdi.dispatch_id = $1
+        GROUP BY p.id
+        "#,
+        dispatch_id,
+        dispatch.sales_order_id
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let discount_factor = Decimal::ONE - dispatch.discount_percentage / Decimal::ONE_HUNDRED;
+    for product in products {
+        let Some(rule) = select_rule(&rules, &product.tags) else {
+            continue;
+        };
+
+        let base_amount = round_money(product.quantity * product.unit_rate * discount_factor);
+        let commission_amount = round_money(match rule.rule_type.parse() {
+            Ok(CommissionRuleType::PerUnit) => product.quantity * rule.rate,
+            _ => base_amount * rule.rate / Decimal::ONE_HUNDRED,
+        });
+
+        sqlx::query!(
+            r#"
+            INSERT INTO commission_accruals (company_id, agent_id, rule_id, dispatch_id, sales_order_id,
+                                             product_id, quantity, base_amount, commission_amount, accrual_date)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (dispatch_id, product_id) DO NOTHING
+            "#,
+            company_id,
+            agent_id,
+            rule.id,
+            dispatch_id,
+            dispatch.sales_order_id,
+            product.product_id,
+            product.quantity,
+            base_amount,
+            commission_amount,
+            dispatch.dispatch_date
+        )
+        .execute(&mut **transaction)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Drops the unpaid commissions of a cancelled dispatch. Commissions already
+/// paid out are left for the agent's next settlement to adjust.
+pub(crate) async fn cancel_commissions_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    dispatch_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE commission_accruals SET status = 'cancelled'
+        WHERE dispatch_id = $1 AND status = 'accrued'
+        "#,
+        dispatch_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+}
+
+/// Picks the newest rule scoped to one of the product's tags, else the newest
+/// general rule. `rules` must be ordered newest first.
+fn select_rule<'a>(rules: &'a [ApplicableRule], tags: &[String]) -> Option<&'a ApplicableRule> {
+    rules
+        .iter()
+        .find(|r| r.product_tag.as_ref().is_some_and(|tag| tags.contains(tag)))
+        .or_else(|| rules.iter().find(|r| r.product_tag.is_none()))
+}
+
+fn round_money(amount: Decimal) -> Decimal {
+    amount.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
+}
+
+// READ
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CommissionRule {
+    pub id: Uuid,
+    pub agent_id: Uuid,
+    pub rule_type: String,
+    pub rate: Decimal,
+    pub product_tag: Option<String>,
+    pub effective_from: NaiveDate,
+    pub effective_to: Option<NaiveDate>,
+    pub is_active: bool,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CommissionAccrual {
+    pub id: Uuid,
+    pub agent_id: Uuid,
+    pub rule_id: Uuid,
+    pub dispatch_id: Uuid,
+    pub sales_order_id: Option<Uuid>,
+    pub product_id: Uuid,
+    pub quantity: Decimal,
+    pub base_amount: Decimal,
+    pub commission_amount: Decimal,
+    pub accrual_date: NaiveDate,
+    pub status: String,
+    pub paid_at: Option<DateTime<Utc>>,
+    pub payment_reference: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CommissionReport {
+    pub agent_id: Uuid,
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    pub total_accrued: Decimal,
+    pub total_paid: Decimal,
+    pub total_payable: Decimal,
+    pub accruals: Vec<CommissionAccrual>,
+}
+
+#[derive(Deserialize)]
+pub struct CommissionReportQuery {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+}
+
+pub async fn get_commission_rules(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(agent_id): Path<Uuid>,
+) -> Result<Json<Vec<CommissionRule>>, CommissionError> {
+    ensure_agent_exists(&db_pool, user.company_id, agent_id).await?;
+
+    let rules = sqlx::query_as!(
+        CommissionRule,
+        r#"
+        SELECT id, agent_id, rule_type, rate, product_tag, effective_from, effective_to, is_active, notes
+        FROM commission_rules
+        WHERE company_id = $1 AND agent_id = $2 AND deleted_at IS NULL
+        ORDER BY effective_from DESC, created_at DESC
+        "#,
+        user.company_id,
+        agent_id
+    )
+    .fetch_all(db_pool.as_ref())
+    .await
+    .context("Failed to fetch commission rules from database.")?;
+
+    Ok(Json(rules))
+}
+
+pub async fn get_commission_report(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(agent_id): Path<Uuid>,
+    Query(query): Query<CommissionReportQuery>,
+) -> Result<Json<CommissionReport>, CommissionError> {
+    let to_date = query.to.unwrap_or_else(|| Utc::now().date_naive());
+    let from_date = query.from.unwrap_or_else(|| financial_year_start(to_date));
+    if from_date > to_date {
+        return Err(CommissionError::ValidationError(
+            "`from` must not be after `to`".to_string(),
+        ));
+    }
+
+    ensure_agent_exists(&db_pool, user.company_id, agent_id).await?;
+
+    let accruals = sqlx::query_as!(
+        CommissionAccrual,
+        r#"
+        SELECT id, agent_id, rule_id, dispatch_id, sales_order_id, product_id, quantity, base_amount,
+               commission_amount, accrual_date, status, paid_at, payment_reference
+        FROM commission_accruals
+        WHERE company_id = $1 AND agent_id = $2 AND status != 'cancelled'
+            AND accrual_date BETWEEN $3 AND $4
+        ORDER BY accrual_date, created_at
+        "#,
+        user.company_id,
+        agent_id,
+        from_date,
+        to_date
+    )
+    .fetch_all(db_pool.as_ref())
+    .await
+    .context("Failed to fetch commission accruals from database.")?;
+
+    let total_accrued: Decimal = accruals.iter().map(|a| a.commission_amount).sum();
+    let total_paid: Decimal = accruals
+        .iter()
+        .filter(|a| a.status == "paid")
+        .map(|a| a.commission_amount)
+        .sum();
+
+    Ok(Json(CommissionReport {
+        agent_id,
+        from_date,
+        to_date,
+        total_accrued,
+        total_paid,
+        total_payable: total_accrued - total_paid,
+        accruals,
+    }))
+}
+
+async fn ensure_agent_exists(
+    db_pool: &PgPool,
+    company_id: Uuid,
+    agent_id: Uuid,
+) -> Result<(), CommissionError> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT id FROM partners
+        WHERE id = $1 AND company_id = $2 AND partner_type = 'Agent' AND deleted_at IS NULL
+        "#,
+        agent_id,
+        company_id
+    )
+    .fetch_optional(db_pool)
+    .await
+    .context("Failed to fetch agent from database.")?
+    .ok_or(CommissionError::NotFound)?;
+
+    Ok(())
+}
+
+// UPDATE
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CommissionPayout {
+    accrual_ids: Vec<Uuid>,
+    payment_reference: Option<String>,
+}
+
+pub async fn pay_commissions(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(agent_id): Path<Uuid>,
+    Json(payout): Json<CommissionPayout>,
+) -> Result<StatusCode, CommissionError> {
+    if payout.accrual_ids.is_empty() {
+        return Err(CommissionError::ValidationError(
+            "At least one accrual is required".to_string(),
+        ));
+    }
+    ensure_agent_exists(&db_pool, user.company_id, agent_id).await?;
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    let statuses: HashMap<Uuid, String> = sqlx::query!(
+        r#"
+        SELECT id, status FROM commission_accruals
+        WHERE id = ANY($1) AND company_id = $2 AND agent_id = $3
+        FOR UPDATE
+        "#,
+        &payout.accrual_ids,
+        user.company_id,
+        agent_id
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to fetch commission accruals from database.")?
+    .into_iter()
+    .map(|row| (row.id, row.status))
+    .collect();
+
+    for accrual_id in &payout.accrual_ids {
+        match statuses.get(accrual_id).map(String::as_str) {
+            None => {
+                return Err(CommissionError::ValidationError(format!(
+                    "Accrual {} does not belong to this agent",
+                    accrual_id
+                )))
+            }
+            Some("accrued") => {}
+            Some(status) => {
+                return Err(CommissionError::InvalidState(format!(
+                    "Accrual {} is already {}",
+                    accrual_id, status
+                )))
+            }
+        }
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE commission_accruals
+        SET status = 'paid', paid_at = NOW(), paid_by = $2, payment_reference = $3
+        WHERE id = ANY($1)
+        "#,
+        &payout.accrual_ids,
+        user.id,
+        payout.payment_reference
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to mark commissions as paid.")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to pay commissions.")?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
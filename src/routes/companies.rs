@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use anyhow::Context;
 use axum::{
+    body::Bytes,
     extract::{Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
@@ -13,6 +14,8 @@ use sqlx::PgPool;
 use strum_macros::{Display, EnumString};
 use uuid::Uuid;
 
+use crate::{auth::CurrentUser, pdf::JpegImage, routes::listing::SortOrder};
+
 // ERROR
 // -------------------------------------------------------------------------------------
 
@@ -20,6 +23,8 @@ use uuid::Uuid;
 pub enum CompanyError {
     #[error("{0}")]
     ValidationError(String),
+    #[error("Only admins can change the company logo")]
+    Forbidden,
     #[error("Company not found")]
     NotFound,
     #[error(transparent)]
@@ -30,6 +35,7 @@ impl IntoResponse for CompanyError {
     fn into_response(self) -> axum::response::Response {
         match self {
             Self::ValidationError(_) => StatusCode::BAD_REQUEST,
+            Self::Forbidden => StatusCode::FORBIDDEN,
             Self::NotFound => StatusCode::NOT_FOUND,
             Self::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
@@ -94,6 +100,48 @@ async fn insert_company_in_db(
     Ok(id)
 }
 
+// UPDATE
+// -------------------------------------------------------------------------------------
+
+const MAX_LOGO_BYTES: usize = 1024 * 1024;
+
+/// Stores the logo printed on invoices. Only JPEGs can be embedded in the
+/// PDF, so anything else is refused here rather than dropped at render time.
+pub async fn upload_company_logo(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(company_id): Path<Uuid>,
+    logo: Bytes,
+) -> Result<StatusCode, CompanyError> {
+    if company_id != user.company_id {
+        return Err(CompanyError::NotFound);
+    }
+    if !user.is_admin() {
+        return Err(CompanyError::Forbidden);
+    }
+    if logo.len() > MAX_LOGO_BYTES || JpegImage::parse(logo.to_vec()).is_none() {
+        return Err(CompanyError::ValidationError(
+            "Logo must be a JPEG of at most 1 MB".to_string(),
+        ));
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE companies
+        SET logo_jpeg = $2, modified_by = $3
+        WHERE id = $1
+        "#,
+        company_id,
+        logo.as_ref(),
+        user.id
+    )
+    .execute(db_pool.as_ref())
+    .await
+    .context("Failed to store company logo in database.")?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
 // READ
 // -------------------------------------------------------------------------------------
 
@@ -141,22 +189,6 @@ impl SortField {
     }
 }
 
-#[allow(clippy::upper_case_acronyms)]
-#[derive(Display, EnumString)]
-#[strum(ascii_case_insensitive)]
-enum SortOrder {
-    ASC,
-    DESC,
-}
-
-impl SortOrder {
-    pub fn parse(val: Option<String>) -> Self {
-        val.as_deref()
-            .map_or(Ok(Self::ASC), |s| s.parse())
-            .unwrap_or(Self::ASC)
-    }
-}
-
 pub async fn get_company(
     State(db_pool): State<Arc<PgPool>>,
     Path(company_id): Path<Uuid>,
@@ -208,7 +240,7 @@ async fn fetch_company_list_from_db(
     let limit = query.limit.unwrap_or(20).clamp(20, 50);
     let offset = (page - 1) * limit;
     let sort = SortField::parse(query.sort);
-    let order = SortOrder::parse(query.order);
+    let order = SortOrder::parse(query.order, SortOrder::ASC);
 
     let query = format!(
         r#"
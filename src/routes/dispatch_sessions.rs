@@ -0,0 +1,621 @@
+//! Draft dispatches that collect scanned units one request at a time, so a
+//! dropped connection on the scanning phone loses nothing. Every scan is
+//! checked as it arrives and answered with running totals; finalizing the
+//! session records the actual dispatch.
+
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::{
+    auth::CurrentUser,
+    routes::{
+        dispatches::{
+            check_header_in_db, check_units_fit_link_in_db, fetch_dispatch_from_db,
+            insert_dispatch_in_db, resolve_scanned_units_in_db, Dispatch, DispatchError,
+            DispatchHeader,
+        },
+        lookups::working_warehouse,
+        sales_orders::DispatchedUnit,
+    },
+};
+
+// CREATE
+// -------------------------------------------------------------------------------------
+
+/// Opens a scan session for a dispatch. The header is checked now, so the
+/// person scanning learns of a wrong partner or closed order before the
+/// first roll.
+pub async fn create_scan_session(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Json(mut header): Json<DispatchHeader>,
+) -> Result<(StatusCode, Json<ScanSession>), DispatchError> {
+    header.warehouse_id = Some(working_warehouse(&user, header.warehouse_id)?);
+    header.validate().map_err(DispatchError::ValidationError)?;
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    check_header_in_db(&mut transaction, user.company_id, &header).await?;
+
+    let session_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO dispatch_scan_sessions (company_id, warehouse_id, dispatch_type,
+                                            dispatch_to_partner_id, dispatch_to_warehouse_id,
+                                            agent_id, link_type, sales_order_id, job_work_id,
+                                            other_reference, dispatch_date, due_date,
+                                            invoice_number, invoice_amount, transport_details,
+                                            notes, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+        RETURNING id
+        "#,
+        user.company_id,
+        header.warehouse_id,
+        header.dispatch_type.to_string(),
+        header.dispatch_to_partner_id,
+        header.dispatch_to_warehouse_id,
+        header.agent_id,
+        header.link_type.map(|l| l.to_string()),
+        header.sales_order_id,
+        header.job_work_id,
+        header.other_reference,
+        header.dispatch_date,
+        header.due_date,
+        header.invoice_number,
+        header.invoice_amount,
+        header.transport_details,
+        header.notes,
+        user.id
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to insert scan session in the database.")?;
+
+    let session = fetch_scan_session_from_db(&mut transaction, session_id)
+        .await
+        .context("Failed to fetch scan session from database.")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to store a new scan session.")?;
+
+    Ok((StatusCode::CREATED, Json(session)))
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Scan {
+    /// Unit number or QR code
+    code: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ScanResult {
+    pub stock_unit_id: Uuid,
+    pub unit_number: String,
+    /// The unit was already in the session and was not added again
+    pub duplicate: bool,
+    /// The unit's product is scanned beyond what the linked order or job work
+    /// still needs; the overshoot is dispatched as excess
+    pub excess: bool,
+    pub session: ScanSession,
+}
+
+/// Adds one scanned unit to an open session. Scanning a unit twice is not an
+/// error: the repeat is reported as a duplicate and the totals stay as they
+/// were, so a retried request after a dropped connection is harmless.
+pub async fn scan_unit(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(session_id): Path<Uuid>,
+    Json(scan): Json<Scan>,
+) -> Result<Json<ScanResult>, DispatchError> {
+    if scan.code.trim().is_empty() {
+        return Err(DispatchError::ValidationError(
+            "Scanned code cannot be empty".to_string(),
+        ));
+    }
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    let header = lock_open_session_in_db(&mut transaction, &user, session_id).await?;
+    let units = resolve_scanned_units_in_db(
+        &mut transaction,
+        user.company_id,
+        header.warehouse_id.unwrap_or_default(),
+        std::slice::from_ref(&scan.code),
+    )
+    .await?;
+    check_units_fit_link_in_db(&mut transaction, &header, &units).await?;
+    let unit = &units[0];
+
+    let other_session = sqlx::query_scalar!(
+        r#"
+        SELECT s.id FROM dispatch_scan_items dsi
+        JOIN dispatch_scan_sessions s ON s.id = dsi.session_id
+        WHERE dsi.stock_unit_id = $1 AND s.status = 'open' AND s.id != $2
+        LIMIT 1
+        "#,
+        unit.id,
+        session_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to fetch scan sessions from database.")?;
+    if other_session.is_some() {
+        return Err(DispatchError::InvalidState(format!(
+            "Unit {} is already scanned for another dispatch",
+            unit.unit_number
+        )));
+    }
+
+    let added = sqlx::query_scalar!(
+        r#"
+        INSERT INTO dispatch_scan_items (company_id, session_id, stock_unit_id, scanned_code,
+                                         scanned_by)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (session_id, stock_unit_id) DO NOTHING
+        RETURNING id
+        "#,
+        user.company_id,
+        session_id,
+        unit.id,
+        scan.code.trim(),
+        user.id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to insert scan in the database.")?;
+    if added.is_some() {
+        touch_session_in_db(&mut transaction, &user, session_id).await?;
+    }
+
+    let session = fetch_scan_session_from_db(&mut transaction, session_id)
+        .await
+        .context("Failed to fetch scan session from database.")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to store a scan.")?;
+
+    let excess = session
+        .totals
+        .iter()
+        .any(|t| t.product_id == unit.product_id && t.excess_quantity > Some(Decimal::ZERO));
+
+    Ok(Json(ScanResult {
+        stock_unit_id: unit.id,
+        unit_number: unit.unit_number.clone(),
+        duplicate: added.is_none(),
+        excess,
+        session,
+    }))
+}
+
+// UPDATE
+// -------------------------------------------------------------------------------------
+
+/// Takes a wrongly scanned unit back out of an open session
+pub async fn remove_scan(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path((session_id, stock_unit_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ScanSession>, DispatchError> {
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    lock_open_session_in_db(&mut transaction, &user, session_id).await?;
+    sqlx::query_scalar!(
+        r#"
+        DELETE FROM dispatch_scan_items
+        WHERE session_id = $1 AND stock_unit_id = $2
+        RETURNING id
+        "#,
+        session_id,
+        stock_unit_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to delete scan from the database.")?
+    .ok_or_else(|| DispatchError::ValidationError("Unit is not in this session".to_string()))?;
+    touch_session_in_db(&mut transaction, &user, session_id).await?;
+
+    let session = fetch_scan_session_from_db(&mut transaction, session_id)
+        .await
+        .context("Failed to fetch scan session from database.")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to remove a scan.")?;
+
+    Ok(Json(session))
+}
+
+/// Records the dispatch of every unit in the session. The header and units
+/// are checked again, since the order may have closed or a unit may have
+/// left the warehouse while scanning was under way.
+pub async fn finalize_scan_session(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(session_id): Path<Uuid>,
+) -> Result<(StatusCode, Json<Dispatch>), DispatchError> {
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    let header = lock_open_session_in_db(&mut transaction, &user, session_id).await?;
+    let codes = sqlx::query_scalar!(
+        r#"
+        SELECT su.unit_number FROM dispatch_scan_items dsi
+        JOIN stock_units su ON su.id = dsi.stock_unit_id
+        WHERE dsi.session_id = $1
+        ORDER BY dsi.scanned_at, dsi.id
+        "#,
+        session_id
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to fetch scanned units from database.")?;
+    if codes.is_empty() {
+        return Err(DispatchError::ValidationError(
+            "Scan at least one unit to dispatch".to_string(),
+        ));
+    }
+
+    check_header_in_db(&mut transaction, user.company_id, &header).await?;
+    let units = resolve_scanned_units_in_db(
+        &mut transaction,
+        user.company_id,
+        header.warehouse_id.unwrap_or_default(),
+        &codes,
+    )
+    .await?;
+    check_units_fit_link_in_db(&mut transaction, &header, &units).await?;
+
+    let dispatch_id = insert_dispatch_in_db(&mut transaction, &user, &header, &units).await?;
+    sqlx::query!(
+        r#"
+        UPDATE dispatch_scan_sessions
+        SET status = 'finalized', dispatch_id = $2, modified_by = $3
+        WHERE id = $1
+        "#,
+        session_id,
+        dispatch_id,
+        user.id
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to finalize scan session.")?;
+
+    let dispatch = fetch_dispatch_from_db(&mut transaction, dispatch_id)
+        .await
+        .context("Failed to fetch dispatch from database.")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to finalize a scan session.")?;
+
+    Ok((StatusCode::CREATED, Json(dispatch)))
+}
+
+/// Abandons an open session; its units stay in stock
+pub async fn discard_scan_session(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(session_id): Path<Uuid>,
+) -> Result<StatusCode, DispatchError> {
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    lock_open_session_in_db(&mut transaction, &user, session_id).await?;
+    sqlx::query!(
+        r#"
+        UPDATE dispatch_scan_sessions SET status = 'discarded', modified_by = $2
+        WHERE id = $1
+        "#,
+        session_id,
+        user.id
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to discard scan session.")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to discard a scan session.")?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Locks an open session the user can work on and returns its header.
+/// Concurrent scans into the same session queue up behind this lock.
+async fn lock_open_session_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    user: &CurrentUser,
+    session_id: Uuid,
+) -> Result<DispatchHeader, DispatchError> {
+    let session = sqlx::query!(
+        r#"
+        SELECT id, status FROM dispatch_scan_sessions
+        WHERE id = $1 AND company_id = $2
+            AND ($3 OR warehouse_id IS NOT DISTINCT FROM $4)
+        FOR UPDATE
+        "#,
+        session_id,
+        user.company_id,
+        user.is_admin(),
+        user.warehouse_id
+    )
+    .fetch_optional(&mut **transaction)
+    .await
+    .context("Failed to fetch scan session from database.")?
+    .ok_or(DispatchError::NotFound)?;
+    if session.status != "open" {
+        return Err(DispatchError::InvalidState(format!(
+            "Scan session is already {}",
+            session.status
+        )));
+    }
+
+    let header = fetch_session_header_from_db(transaction, session_id).await?;
+    Ok(header)
+}
+
+async fn touch_session_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    user: &CurrentUser,
+    session_id: Uuid,
+) -> Result<(), DispatchError> {
+    sqlx::query!(
+        "UPDATE dispatch_scan_sessions SET modified_by = $2 WHERE id = $1",
+        session_id,
+        user.id
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to update scan session.")?;
+    Ok(())
+}
+
+// READ
+// -------------------------------------------------------------------------------------
+
+/// Scanned quantity of one product against what the linked sales order or
+/// job work still needs
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ScanTotal {
+    pub product_id: Uuid,
+    pub product_name: String,
+    pub measuring_unit: String,
+    pub units: i64,
+    pub quantity: Decimal,
+    /// Still to be dispatched on the linked order; none without a link
+    pub pending_quantity: Option<Decimal>,
+    /// Pending quantity left after this session
+    pub remaining_quantity: Option<Decimal>,
+    /// Scanned beyond the pending quantity
+    pub excess_quantity: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ScanSession {
+    pub id: Uuid,
+    /// `open`, `finalized` or `discarded`
+    pub status: String,
+    #[serde(flatten)]
+    pub header: DispatchHeader,
+    /// The recorded dispatch, once finalized
+    pub dispatch_id: Option<Uuid>,
+    pub unit_count: usize,
+    pub totals: Vec<ScanTotal>,
+    /// In scan order
+    pub units: Vec<DispatchedUnit>,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+async fn fetch_session_header_from_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    session_id: Uuid,
+) -> Result<DispatchHeader, DispatchError> {
+    let row = sqlx::query!(
+        r#"
+        SELECT warehouse_id, dispatch_type, dispatch_to_partner_id, dispatch_to_warehouse_id,
+               agent_id, link_type, sales_order_id, job_work_id, other_reference, dispatch_date,
+               due_date, invoice_number, invoice_amount, transport_details, notes
+        FROM dispatch_scan_sessions
+        WHERE id = $1
+        "#,
+        session_id
+    )
+    .fetch_one(&mut **transaction)
+    .await
+    .context("Failed to fetch scan session from database.")?;
+
+    Ok(DispatchHeader {
+        dispatch_type: row
+            .dispatch_type
+            .parse()
+            .context("Unknown dispatch type on scan session.")?,
+        warehouse_id: Some(row.warehouse_id),
+        dispatch_to_partner_id: row.dispatch_to_partner_id,
+        dispatch_to_warehouse_id: row.dispatch_to_warehouse_id,
+        agent_id: row.agent_id,
+        link_type: row
+            .link_type
+            .map(|l| l.parse())
+            .transpose()
+            .context("Unknown link type on scan session.")?,
+        sales_order_id: row.sales_order_id,
+        job_work_id: row.job_work_id,
+        other_reference: row.other_reference,
+        dispatch_date: row.dispatch_date,
+        due_date: row.due_date,
+        invoice_number: row.invoice_number,
+        invoice_amount: row.invoice_amount,
+        transport_details: row.transport_details,
+        notes: row.notes,
+    })
+}
+
+async fn fetch_scan_session_from_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    session_id: Uuid,
+) -> Result<ScanSession, DispatchError> {
+    let session = sqlx::query!(
+        r#"
+        SELECT id, status, dispatch_id, created_by, created_at, updated_at
+        FROM dispatch_scan_sessions
+        WHERE id = $1
+        "#,
+        session_id
+    )
+    .fetch_one(&mut **transaction)
+    .await
+    .context("Failed to fetch scan session from database.")?;
+    let header = fetch_session_header_from_db(transaction, session_id).await?;
+
+    let units = sqlx::query_as!(
+        DispatchedUnit,
+        r#"
+        SELECT su.id as stock_unit_id, su.unit_number, su.product_id, p.name as product_name,
+               su.size_quantity, su.quality_grade
+        FROM dispatch_scan_items dsi
+        JOIN stock_units su ON su.id = dsi.stock_unit_id
+        JOIN products p ON p.id = su.product_id
+        WHERE dsi.session_id = $1
+        ORDER BY dsi.scanned_at, dsi.id
+        "#,
+        session_id
+    )
+    .fetch_all(&mut **transaction)
+    .await
+    .context("Failed to fetch scanned units from database.")?;
+
+    // Products on the linked order are listed even before their first scan,
+    // so the person scanning sees what is still to be loaded
+    let totals = sqlx::query!(
+        r#"
+        WITH scanned AS (
+            SELECT su.product_id, COUNT(*) as units, SUM(su.size_quantity) as quantity
+            FROM dispatch_scan_items dsi
+            JOIN stock_units su ON su.id = dsi.stock_unit_id
+            WHERE dsi.session_id = $1
+            GROUP BY su.product_id
+        ),
+        pending AS (
+            SELECT product_id, SUM(GREATEST(pending_quantity, 0)) as pending_quantity
+            FROM sales_order_items
+            WHERE sales_order_id = $2
+            GROUP BY product_id
+            UNION ALL
+            SELECT product_id, SUM(GREATEST(pending_quantity, 0))
+            FROM job_work_raw_materials
+            WHERE job_work_id = $3
+            GROUP BY product_id
+        )
+        SELECT p.id as "product_id!", p.name as "product_name!",
+               p.measuring_unit as "measuring_unit!",
+               COALESCE(s.units, 0) as "units!", COALESCE(s.quantity, 0) as "quantity!",
+               pe.pending_quantity
+        FROM scanned s
+        FULL JOIN pending pe ON pe.product_id = s.product_id
+        JOIN products p ON p.id = COALESCE(s.product_id, pe.product_id)
+        ORDER BY p.name
+        "#,
+        session_id,
+        header.sales_order_id,
+        header.job_work_id
+    )
+    .fetch_all(&mut **transaction)
+    .await
+    .context("Failed to fetch scan totals from database.")?
+    .into_iter()
+    .map(|t| ScanTotal {
+        product_id: t.product_id,
+        product_name: t.product_name,
+        measuring_unit: t.measuring_unit,
+        units: t.units,
+        quantity: t.quantity,
+        pending_quantity: t.pending_quantity,
+        remaining_quantity: t
+            .pending_quantity
+            .map(|p| (p - t.quantity).max(Decimal::ZERO)),
+        excess_quantity: t
+            .pending_quantity
+            .map(|p| (t.quantity - p).max(Decimal::ZERO)),
+    })
+    .collect();
+
+    Ok(ScanSession {
+        id: session.id,
+        status: session.status,
+        header,
+        dispatch_id: session.dispatch_id,
+        unit_count: units.len(),
+        totals,
+        units,
+        created_by: session.created_by,
+        created_at: session.created_at,
+        updated_at: session.updated_at,
+    })
+}
+
+/// Staff see the sessions of their own warehouse, so scanning can resume
+/// from another device
+pub async fn get_scan_session(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(session_id): Path<Uuid>,
+) -> Result<Json<ScanSession>, DispatchError> {
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    sqlx::query_scalar!(
+        r#"
+        SELECT id FROM dispatch_scan_sessions
+        WHERE id = $1 AND company_id = $2
+            AND ($3 OR warehouse_id IS NOT DISTINCT FROM $4)
+        "#,
+        session_id,
+        user.company_id,
+        user.is_admin(),
+        user.warehouse_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to fetch scan session from database.")?
+    .ok_or(DispatchError::NotFound)?;
+
+    let session = fetch_scan_session_from_db(&mut transaction, session_id).await?;
+
+    Ok(Json(session))
+}
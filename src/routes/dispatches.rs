@@ -0,0 +1,1618 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+use crate::{
+    auth::CurrentUser,
+    pdf::{Column, PdfDocument},
+    routes::{
+        commissions::{accrue_commissions_in_db, cancel_commissions_in_db},
+        lookups::{ensure_warehouses_exist, fetch_partner_type, working_warehouse, LookupError},
+        sales_orders::{refresh_dispatched_quantities_in_db, DispatchedUnit},
+    },
+};
+
+// ERROR
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, thiserror::Error)]
+pub enum DispatchError {
+    #[error("{0}")]
+    ValidationError(String),
+    #[error("E-way bill is missing mandatory fields")]
+    EWayBillIncomplete(Vec<String>),
+    #[error("{0}")]
+    InvalidState(String),
+    #[error("Staff can only dispatch from their own warehouse")]
+    Forbidden,
+    #[error("Dispatch not found")]
+    NotFound,
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+/// Body returned when a dispatch lacks details the e-way bill portal requires.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EWayBillValidationError {
+    pub error: String,
+    pub missing_fields: Vec<String>,
+}
+
+impl IntoResponse for DispatchError {
+    fn into_response(self) -> axum::response::Response {
+        if let Self::EWayBillIncomplete(missing_fields) = self {
+            let body = EWayBillValidationError {
+                error: "E-way bill is missing mandatory fields".to_string(),
+                missing_fields,
+            };
+            return (StatusCode::BAD_REQUEST, Json(body)).into_response();
+        }
+
+        match self {
+            Self::ValidationError(_) | Self::EWayBillIncomplete(_) => StatusCode::BAD_REQUEST,
+            Self::InvalidState(_) => StatusCode::CONFLICT,
+            Self::Forbidden => StatusCode::FORBIDDEN,
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+        .into_response()
+    }
+}
+
+impl From<LookupError> for DispatchError {
+    fn from(error: LookupError) -> Self {
+        match error {
+            LookupError::ValidationError(message) => Self::ValidationError(message),
+            LookupError::Forbidden => Self::Forbidden,
+            LookupError::UnexpectedError(error) => Self::UnexpectedError(error),
+        }
+    }
+}
+
+// CREATE
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString, Deserialize, Serialize)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum DispatchType {
+    Partner,
+    Warehouse,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString, Deserialize, Serialize)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum LinkType {
+    SalesOrder,
+    JobWork,
+    Other,
+}
+
+/// Who the goods go to and what they are for
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DispatchHeader {
+    pub dispatch_type: DispatchType,
+    /// Dispatching warehouse; defaults to the user's own
+    pub warehouse_id: Option<Uuid>,
+    pub dispatch_to_partner_id: Option<Uuid>,
+    pub dispatch_to_warehouse_id: Option<Uuid>,
+    pub agent_id: Option<Uuid>,
+    pub link_type: Option<LinkType>,
+    pub sales_order_id: Option<Uuid>,
+    pub job_work_id: Option<Uuid>,
+    pub other_reference: Option<String>,
+    pub dispatch_date: Option<NaiveDate>,
+    pub due_date: Option<NaiveDate>,
+    pub invoice_number: Option<String>,
+    pub invoice_amount: Option<Decimal>,
+    pub transport_details: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewDispatch {
+    #[serde(flatten)]
+    header: DispatchHeader,
+    /// Scanned unit numbers or QR codes
+    units: Vec<String>,
+}
+
+impl DispatchHeader {
+    /// Mirrors the table's constraints so callers get a readable error
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        match self.dispatch_type {
+            DispatchType::Partner => {
+                if self.dispatch_to_partner_id.is_none() || self.dispatch_to_warehouse_id.is_some()
+                {
+                    return Err("A partner dispatch needs a partner and no warehouse".to_string());
+                }
+            }
+            DispatchType::Warehouse => {
+                if self.dispatch_to_warehouse_id.is_none() || self.dispatch_to_partner_id.is_some()
+                {
+                    return Err("A warehouse dispatch needs a warehouse and no partner".to_string());
+                }
+                if self.agent_id.is_some() {
+                    return Err("Only partner dispatches can have an agent".to_string());
+                }
+                if self.dispatch_to_warehouse_id == self.warehouse_id {
+                    return Err("Cannot dispatch to the same warehouse".to_string());
+                }
+            }
+        }
+
+        match self.link_type {
+            Some(LinkType::SalesOrder) if self.sales_order_id.is_none() => {
+                return Err("A sales order dispatch needs a sales order".to_string());
+            }
+            Some(LinkType::JobWork) if self.job_work_id.is_none() => {
+                return Err("A job work dispatch needs a job work".to_string());
+            }
+            Some(LinkType::SalesOrder | LinkType::JobWork)
+                if self.dispatch_type != DispatchType::Partner =>
+            {
+                return Err(
+                    "Only partner dispatches can go to a sales order or job work".to_string(),
+                );
+            }
+            _ => {}
+        }
+        if self.sales_order_id.is_some() && self.link_type != Some(LinkType::SalesOrder) {
+            return Err("`sales_order_id` needs `link_type` sales_order".to_string());
+        }
+        if self.job_work_id.is_some() && self.link_type != Some(LinkType::JobWork) {
+            return Err("`job_work_id` needs `link_type` job_work".to_string());
+        }
+        if self.invoice_amount.is_some_and(|a| a < Decimal::ZERO) {
+            return Err("Invoice amount cannot be negative".to_string());
+        }
+        if self
+            .due_date
+            .zip(self.dispatch_date)
+            .is_some_and(|(due, dispatched)| due < dispatched)
+        {
+            return Err("Due date cannot be before the dispatch date".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Records a dispatch of scanned stock units. Every unit has to be in stock
+/// in the dispatching warehouse; the units are marked dispatched, or in
+/// transit for a warehouse transfer, and the linked sales order's dispatched
+/// quantities and the agent's commissions follow in the same transaction.
+pub async fn create_dispatch(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Json(new_dispatch): Json<NewDispatch>,
+) -> Result<(StatusCode, Json<Dispatch>), DispatchError> {
+    let mut header = new_dispatch.header;
+    header.warehouse_id = Some(working_warehouse(&user, header.warehouse_id)?);
+    header.validate().map_err(DispatchError::ValidationError)?;
+    if new_dispatch.units.is_empty() {
+        return Err(DispatchError::ValidationError(
+            "Scan at least one unit to dispatch".to_string(),
+        ));
+    }
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    check_header_in_db(&mut transaction, user.company_id, &header).await?;
+    let units = resolve_scanned_units_in_db(
+        &mut transaction,
+        user.company_id,
+        header.warehouse_id.unwrap_or_default(),
+        &new_dispatch.units,
+    )
+    .await?;
+    check_units_fit_link_in_db(&mut transaction, &header, &units).await?;
+
+    let dispatch_id = insert_dispatch_in_db(&mut transaction, &user, &header, &units).await?;
+    let dispatch = fetch_dispatch_from_db(&mut transaction, dispatch_id)
+        .await
+        .context("Failed to fetch dispatch from database.")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to store a new dispatch.")?;
+
+    Ok((StatusCode::CREATED, Json(dispatch)))
+}
+
+/// Checks that the header's warehouses, partners, sales order and job work
+/// exist and fit together
+pub(crate) async fn check_header_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    company_id: Uuid,
+    header: &DispatchHeader,
+) -> Result<(), DispatchError> {
+    let warehouse_ids: Vec<Uuid> = header
+        .warehouse_id
+        .iter()
+        .chain(header.dispatch_to_warehouse_id.iter())
+        .copied()
+        .collect();
+    ensure_warehouses_exist(transaction, company_id, &warehouse_ids).await?;
+
+    if let Some(partner_id) = header.dispatch_to_partner_id {
+        fetch_partner_type(transaction, company_id, partner_id)
+            .await?
+            .ok_or_else(|| DispatchError::ValidationError("Partner does not exist".to_string()))?;
+    }
+    if let Some(agent_id) = header.agent_id {
+        let agent_type = fetch_partner_type(transaction, company_id, agent_id)
+            .await?
+            .ok_or_else(|| DispatchError::ValidationError("Agent does not exist".to_string()))?;
+        if agent_type != "Agent" {
+            return Err(DispatchError::ValidationError(format!(
+                "Partner {} is a {}, not an Agent",
+                agent_id, agent_type
+            )));
+        }
+    }
+
+    if let Some(sales_order_id) = header.sales_order_id {
+        let order = sqlx::query!(
+            r#"
+            SELECT status, customer_id FROM sales_orders
+            WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+            "#,
+            sales_order_id,
+            company_id
+        )
+        .fetch_optional(&mut **transaction)
+        .await
+        .context("Failed to fetch sales order from database.")?
+        .ok_or_else(|| DispatchError::ValidationError("Sales order does not exist".to_string()))?;
+        if order.status != "in_progress" {
+            return Err(DispatchError::InvalidState(format!(
+                "Only approved orders in progress can be dispatched, this one is {}",
+                order.status
+            )));
+        }
+        if header.dispatch_to_partner_id != Some(order.customer_id) {
+            return Err(DispatchError::ValidationError(
+                "Goods for a sales order go to the order's customer".to_string(),
+            ));
+        }
+    }
+
+    if let Some(job_work_id) = header.job_work_id {
+        let job_work = sqlx::query!(
+            r#"
+            SELECT status, vendor_id FROM job_works
+            WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+            "#,
+            job_work_id,
+            company_id
+        )
+        .fetch_optional(&mut **transaction)
+        .await
+        .context("Failed to fetch job work from database.")?
+        .ok_or_else(|| DispatchError::ValidationError("Job work does not exist".to_string()))?;
+        if job_work.status != "in_progress" {
+            return Err(DispatchError::InvalidState(format!(
+                "Only job works in progress can be dispatched to, this one is {}",
+                job_work.status
+            )));
+        }
+        if header.dispatch_to_partner_id != Some(job_work.vendor_id) {
+            return Err(DispatchError::ValidationError(
+                "Raw materials for a job work go to its vendor".to_string(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ScannedUnit {
+    pub(crate) id: Uuid,
+    pub(crate) unit_number: String,
+    qr_code: Option<String>,
+    pub(crate) product_id: Uuid,
+    status: String,
+    warehouse_id: Uuid,
+}
+
+/// Locks the units behind scanned unit numbers or QR codes, in scan order,
+/// and checks each is in stock in the dispatching warehouse.
+pub(crate) async fn resolve_scanned_units_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    company_id: Uuid,
+    warehouse_id: Uuid,
+    codes: &[String],
+) -> Result<Vec<ScannedUnit>, DispatchError> {
+    let codes: Vec<String> = codes.iter().map(|c| c.trim().to_string()).collect();
+    let found = sqlx::query_as!(
+        ScannedUnit,
+        r#"
+        SELECT id, unit_number, qr_code, product_id, status, warehouse_id
+        FROM stock_units
+        WHERE company_id = $1 AND deleted_at IS NULL
+            AND (unit_number = ANY($2) OR qr_code = ANY($2))
+        ORDER BY id
+        FOR UPDATE
+        "#,
+        company_id,
+        &codes
+    )
+    .fetch_all(&mut **transaction)
+    .await
+    .context("Failed to fetch scanned stock units from database.")?;
+
+    let mut units: Vec<ScannedUnit> = Vec::with_capacity(codes.len());
+    for code in &codes {
+        let unit = found
+            .iter()
+            .find(|u| u.unit_number == *code || u.qr_code.as_deref() == Some(code))
+            .ok_or_else(|| DispatchError::ValidationError(format!("Unknown unit '{code}'")))?;
+        if units.iter().any(|u| u.id == unit.id) {
+            return Err(DispatchError::ValidationError(format!(
+                "Unit {} is scanned more than once",
+                unit.unit_number
+            )));
+        }
+        check_unit_dispatchable(unit, warehouse_id)?;
+        units.push(unit.clone());
+    }
+
+    Ok(units)
+}
+
+fn check_unit_dispatchable(unit: &ScannedUnit, warehouse_id: Uuid) -> Result<(), DispatchError> {
+    if unit.warehouse_id != warehouse_id {
+        return Err(DispatchError::InvalidState(format!(
+            "Unit {} is in another warehouse",
+            unit.unit_number
+        )));
+    }
+    if unit.status != "in_stock" {
+        return Err(DispatchError::InvalidState(format!(
+            "Unit {} is {}, not in stock",
+            unit.unit_number, unit.status
+        )));
+    }
+    Ok(())
+}
+
+/// Units sent against a sales order or job work must be products it lists
+pub(crate) async fn check_units_fit_link_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    header: &DispatchHeader,
+    units: &[ScannedUnit],
+) -> Result<(), DispatchError> {
+    let (allowed, what) = if let Some(sales_order_id) = header.sales_order_id {
+        let products = sqlx::query_scalar!(
+            "SELECT product_id FROM sales_order_items WHERE sales_order_id = $1",
+            sales_order_id
+        )
+        .fetch_all(&mut **transaction)
+        .await
+        .context("Failed to fetch sales order items from database.")?;
+        (products, "on the sales order")
+    } else if let Some(job_work_id) = header.job_work_id {
+        let products = sqlx::query_scalar!(
+            "SELECT product_id FROM job_work_raw_materials WHERE job_work_id = $1",
+            job_work_id
+        )
+        .fetch_all(&mut **transaction)
+        .await
+        .context("Failed to fetch job work raw materials from database.")?;
+        (products, "a raw material of the job work")
+    } else {
+        return Ok(());
+    };
+
+    if let Some(unit) = units.iter().find(|u| !allowed.contains(&u.product_id)) {
+        return Err(DispatchError::ValidationError(format!(
+            "Unit {} is not a product {}",
+            unit.unit_number, what
+        )));
+    }
+    Ok(())
+}
+
+/// Stores the dispatch and its units, marks the units as gone and brings
+/// the linked sales order and commissions up to date
+pub(crate) async fn insert_dispatch_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    user: &CurrentUser,
+    header: &DispatchHeader,
+    units: &[ScannedUnit],
+) -> Result<Uuid, DispatchError> {
+    let dispatch_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO goods_dispatches (company_id, warehouse_id, dispatch_type, dispatch_to_partner_id,
+                                      dispatch_to_warehouse_id, agent_id, link_type, sales_order_id,
+                                      job_work_id, other_reference, dispatch_date, due_date,
+                                      invoice_number, invoice_amount, transport_details, notes,
+                                      created_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, COALESCE($11, CURRENT_DATE), $12, $13, $14,
+                $15, $16, $17)
+        RETURNING id
+        "#,
+        user.company_id,
+        header.warehouse_id,
+        header.dispatch_type.to_string(),
+        header.dispatch_to_partner_id,
+        header.dispatch_to_warehouse_id,
+        header.agent_id,
+        header.link_type.map(|l| l.to_string()),
+        header.sales_order_id,
+        header.job_work_id,
+        header.other_reference,
+        header.dispatch_date,
+        header.due_date,
+        header.invoice_number,
+        header.invoice_amount,
+        header.transport_details,
+        header.notes,
+        user.id
+    )
+    .fetch_one(&mut **transaction)
+    .await
+    .context("Failed to insert dispatch in the database.")?;
+
+    let unit_ids: Vec<Uuid> = units.iter().map(|u| u.id).collect();
+    sqlx::query!(
+        r#"
+        INSERT INTO goods_dispatch_items (company_id, dispatch_id, stock_unit_id)
+        SELECT $1, $2, unnest($3::UUID[])
+        "#,
+        user.company_id,
+        dispatch_id,
+        &unit_ids
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to insert dispatch items in the database.")?;
+
+    // Transferred units stay in transit until the destination receives them
+    let status = match header.dispatch_type {
+        DispatchType::Partner => "dispatched",
+        DispatchType::Warehouse => "in_transit",
+    };
+    sqlx::query!(
+        "UPDATE stock_units SET status = $3, modified_by = $2 WHERE id = ANY($1)",
+        &unit_ids,
+        user.id,
+        status
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to mark stock units dispatched.")?;
+
+    if let Some(sales_order_id) = header.sales_order_id {
+        refresh_dispatched_quantities_in_db(transaction, sales_order_id)
+            .await
+            .context("Failed to update dispatched quantities.")?;
+    }
+    accrue_commissions_in_db(transaction, user.company_id, dispatch_id)
+        .await
+        .context("Failed to accrue commissions.")?;
+
+    Ok(dispatch_id)
+}
+
+// UPDATE
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString, Deserialize, Serialize)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum TransportMode {
+    Road,
+    Rail,
+    Air,
+    Ship,
+}
+
+/// Fields left out of the request keep their current value.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransportDetailsUpdate {
+    transport_mode: Option<TransportMode>,
+    transport_distance_km: Option<i32>,
+    transporter_name: Option<String>,
+    transporter_id: Option<String>,
+    vehicle_number: Option<String>,
+    transport_document_number: Option<String>,
+    transport_document_date: Option<NaiveDate>,
+    transport_details: Option<String>,
+}
+
+impl TransportDetailsUpdate {
+    fn validate(&self) -> Result<(), String> {
+        if self
+            .transport_distance_km
+            .is_some_and(|km| !(0..=4000).contains(&km))
+        {
+            return Err("Transport distance must be between 0 and 4000 km".to_string());
+        }
+        if self
+            .transporter_id
+            .as_deref()
+            .is_some_and(|id| id.trim().len() != 15)
+        {
+            return Err("Transporter ID must be a 15 character GSTIN or TRANSIN".to_string());
+        }
+        Ok(())
+    }
+}
+
+pub async fn update_transport_details(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(dispatch_id): Path<Uuid>,
+    Json(update): Json<TransportDetailsUpdate>,
+) -> Result<StatusCode, DispatchError> {
+    update.validate().map_err(DispatchError::ValidationError)?;
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    let is_cancelled = sqlx::query_scalar!(
+        r#"
+        SELECT COALESCE(is_cancelled, false) as "is_cancelled!"
+        FROM goods_dispatches
+        WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+            AND ($3 OR warehouse_id IS NOT DISTINCT FROM $4)
+        FOR UPDATE
+        "#,
+        dispatch_id,
+        user.company_id,
+        user.is_admin(),
+        user.warehouse_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to fetch dispatch from database.")?
+    .ok_or(DispatchError::NotFound)?;
+    if is_cancelled {
+        return Err(DispatchError::InvalidState(
+            "Cannot change transport details of a cancelled dispatch".to_string(),
+        ));
+    }
+
+    // Vehicle numbers are printed without spaces on e-way bills
+    let vehicle_number = update
+        .vehicle_number
+        .map(|v| v.replace([' ', '-'], "").to_uppercase());
+
+    sqlx::query!(
+        r#"
+        UPDATE goods_dispatches
+        SET transport_mode = COALESCE($2, transport_mode),
+            transport_distance_km = COALESCE($3, transport_distance_km),
+            transporter_name = COALESCE($4, transporter_name),
+            transporter_id = COALESCE($5, transporter_id),
+            vehicle_number = COALESCE($6, vehicle_number),
+            transport_document_number = COALESCE($7, transport_document_number),
+            transport_document_date = COALESCE($8, transport_document_date),
+            transport_details = COALESCE($9, transport_details),
+            modified_by = $10
+        WHERE id = $1
+        "#,
+        dispatch_id,
+        update.transport_mode.map(|m| m.to_string()),
+        update.transport_distance_km,
+        update.transporter_name,
+        update
+            .transporter_id
+            .as_deref()
+            .map(|id| id.trim().to_uppercase()),
+        vehicle_number,
+        update.transport_document_number,
+        update.transport_document_date,
+        update.transport_details,
+        user.id
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to update dispatch transport details.")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to update transport details.")?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DispatchCancellation {
+    reason: String,
+}
+
+/// Cancels a dispatch and reverses it in one transaction: the units go back
+/// into stock at the dispatching warehouse, and the linked sales order or job
+/// work quantities and the agent's commissions follow. Refused once any unit
+/// has moved on, e.g. been dispatched again or received elsewhere.
+pub async fn cancel_dispatch(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(dispatch_id): Path<Uuid>,
+    Json(request): Json<DispatchCancellation>,
+) -> Result<StatusCode, DispatchError> {
+    let reason = request.reason.trim();
+    if reason.is_empty() {
+        return Err(DispatchError::ValidationError(
+            "A cancellation reason is required".to_string(),
+        ));
+    }
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    let is_cancelled = sqlx::query_scalar!(
+        r#"
+        SELECT COALESCE(is_cancelled, false) as "is_cancelled!"
+        FROM goods_dispatches
+        WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+            AND ($3 OR warehouse_id IS NOT DISTINCT FROM $4)
+        FOR UPDATE
+        "#,
+        dispatch_id,
+        user.company_id,
+        user.is_admin(),
+        user.warehouse_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to fetch dispatch from database.")?
+    .ok_or(DispatchError::NotFound)?;
+    if is_cancelled {
+        return Err(DispatchError::InvalidState(
+            "Dispatch is already cancelled".to_string(),
+        ));
+    }
+
+    let received = sqlx::query_scalar!(
+        r#"
+        SELECT receipt_number FROM goods_receipts
+        WHERE transfer_dispatch_id = $1 AND deleted_at IS NULL
+        "#,
+        dispatch_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to fetch transfer receipt from database.")?;
+    if let Some(receipt_number) = received {
+        return Err(DispatchError::InvalidState(format!(
+            "Transfer is already received on {}",
+            receipt_number
+        )));
+    }
+
+    let moved = fetch_moved_units_from_db(&mut transaction, &[dispatch_id])
+        .await
+        .context("Failed to fetch dispatched units from database.")?;
+    if !moved.is_empty() {
+        return Err(DispatchError::InvalidState(format!(
+            "Units {} have moved on since this dispatch",
+            moved.join(", ")
+        )));
+    }
+
+    cancel_dispatch_in_db(&mut transaction, &user, dispatch_id, reason)
+        .await
+        .context("Failed to cancel dispatch.")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to cancel a dispatch.")?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Units of the dispatches that can no longer be put back into stock at the
+/// dispatching warehouse: dispatched again, received elsewhere or deleted
+pub(crate) async fn fetch_moved_units_from_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    dispatch_ids: &[Uuid],
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT su.unit_number
+        FROM goods_dispatch_items gdi
+        JOIN goods_dispatches gd ON gd.id = gdi.dispatch_id
+        JOIN stock_units su ON su.id = gdi.stock_unit_id
+        WHERE gdi.dispatch_id = ANY($1)
+            AND (su.status NOT IN ('dispatched', 'in_transit') OR su.warehouse_id != gd.warehouse_id
+                 OR su.deleted_at IS NOT NULL
+                 OR EXISTS (
+                     SELECT 1 FROM goods_dispatch_items later
+                     JOIN goods_dispatches lgd ON lgd.id = later.dispatch_id
+                     WHERE later.stock_unit_id = su.id AND lgd.id != gd.id
+                         AND lgd.is_cancelled IS NOT TRUE AND lgd.deleted_at IS NULL
+                         AND lgd.created_at > gd.created_at
+                 ))
+        ORDER BY su.unit_number
+        "#,
+        dispatch_ids
+    )
+    .fetch_all(&mut **transaction)
+    .await
+}
+
+/// Marks the dispatch cancelled, puts its units back into stock at the
+/// dispatching warehouse and brings the linked sales order and commissions
+/// up to date. Job work quantities follow through the dispatch triggers.
+pub(crate) async fn cancel_dispatch_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    user: &CurrentUser,
+    dispatch_id: Uuid,
+    reason: &str,
+) -> Result<(), sqlx::Error> {
+    let sales_order_id = sqlx::query_scalar!(
+        r#"
+        UPDATE goods_dispatches
+        SET is_cancelled = true, cancelled_at = NOW(), cancelled_by = $2, cancellation_reason = $3,
+            modified_by = $2
+        WHERE id = $1
+        RETURNING sales_order_id
+        "#,
+        dispatch_id,
+        user.id,
+        reason
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    // The goods are back on the shelf
+    sqlx::query!(
+        r#"
+        UPDATE stock_units su SET status = 'in_stock', warehouse_id = gd.warehouse_id,
+                                  modified_by = $2
+        FROM goods_dispatch_items gdi
+        JOIN goods_dispatches gd ON gd.id = gdi.dispatch_id
+        WHERE gdi.dispatch_id = $1 AND su.id = gdi.stock_unit_id
+            AND su.status IN ('dispatched', 'in_transit')
+        "#,
+        dispatch_id,
+        user.id
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    if let Some(sales_order_id) = sales_order_id {
+        refresh_dispatched_quantities_in_db(transaction, sales_order_id).await?;
+    }
+    cancel_commissions_in_db(transaction, dispatch_id).await?;
+
+    Ok(())
+}
+
+// READ
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Dispatch {
+    pub id: Uuid,
+    pub dispatch_number: String,
+    pub dispatch_type: String,
+    pub warehouse_id: Uuid,
+    pub dispatch_to_partner_id: Option<Uuid>,
+    pub dispatch_to_warehouse_id: Option<Uuid>,
+    pub agent_id: Option<Uuid>,
+    pub link_type: Option<String>,
+    pub sales_order_id: Option<Uuid>,
+    pub job_work_id: Option<Uuid>,
+    pub other_reference: Option<String>,
+    pub dispatch_date: NaiveDate,
+    pub due_date: Option<NaiveDate>,
+    pub invoice_number: Option<String>,
+    pub invoice_amount: Option<Decimal>,
+    pub is_cancelled: bool,
+    pub notes: Option<String>,
+    pub units: Vec<DispatchedUnit>,
+}
+
+pub(crate) async fn fetch_dispatch_from_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    dispatch_id: Uuid,
+) -> Result<Dispatch, sqlx::Error> {
+    let dispatch = sqlx::query!(
+        r#"
+        SELECT id, dispatch_number, dispatch_type, warehouse_id, dispatch_to_partner_id,
+               dispatch_to_warehouse_id, agent_id, link_type, sales_order_id, job_work_id,
+               other_reference, dispatch_date, due_date, invoice_number, invoice_amount,
+               COALESCE(is_cancelled, false) as "is_cancelled!", notes
+        FROM goods_dispatches
+        WHERE id = $1
+        "#,
+        dispatch_id
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    let units = sqlx::query_as!(
+        DispatchedUnit,
+        r#"
+        SELECT su.id as stock_unit_id, su.unit_number, su.product_id, p.name as product_name,
+               su.size_quantity, su.quality_grade
+        FROM goods_dispatch_items gdi
+        JOIN stock_units su ON su.id = gdi.stock_unit_id
+        JOIN products p ON p.id = su.product_id
+        WHERE gdi.dispatch_id = $1
+        ORDER BY p.name, su.unit_number
+        "#,
+        dispatch_id
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    Ok(Dispatch {
+        id: dispatch.id,
+        dispatch_number: dispatch.dispatch_number,
+        dispatch_type: dispatch.dispatch_type,
+        warehouse_id: dispatch.warehouse_id,
+        dispatch_to_partner_id: dispatch.dispatch_to_partner_id,
+        dispatch_to_warehouse_id: dispatch.dispatch_to_warehouse_id,
+        agent_id: dispatch.agent_id,
+        link_type: dispatch.link_type,
+        sales_order_id: dispatch.sales_order_id,
+        job_work_id: dispatch.job_work_id,
+        other_reference: dispatch.other_reference,
+        dispatch_date: dispatch.dispatch_date,
+        due_date: dispatch.due_date,
+        invoice_number: dispatch.invoice_number,
+        invoice_amount: dispatch.invoice_amount,
+        is_cancelled: dispatch.is_cancelled,
+        notes: dispatch.notes,
+        units,
+    })
+}
+
+/// Staff see dispatches leaving from or arriving at their warehouse
+pub async fn get_dispatch(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(dispatch_id): Path<Uuid>,
+) -> Result<Json<Dispatch>, DispatchError> {
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    sqlx::query_scalar!(
+        r#"
+        SELECT id FROM goods_dispatches
+        WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+            AND ($3 OR warehouse_id IS NOT DISTINCT FROM $4
+                 OR dispatch_to_warehouse_id IS NOT DISTINCT FROM $4)
+        "#,
+        dispatch_id,
+        user.company_id,
+        user.is_admin(),
+        user.warehouse_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to fetch dispatch from database.")?
+    .ok_or(DispatchError::NotFound)?;
+
+    let dispatch = fetch_dispatch_from_db(&mut transaction, dispatch_id)
+        .await
+        .context("Failed to fetch dispatch from database.")?;
+
+    Ok(Json(dispatch))
+}
+
+struct ChallanHeader {
+    dispatch_number: String,
+    dispatch_date: NaiveDate,
+    is_cancelled: bool,
+    company_name: String,
+    company_gst_number: Option<String>,
+    from_name: String,
+    from_address: String,
+    to_name: String,
+    to_address: String,
+    to_gst_number: Option<String>,
+    reference: Option<String>,
+    invoice_number: Option<String>,
+    transport_details: Option<String>,
+    notes: Option<String>,
+}
+
+struct ChallanUnit {
+    product_id: Uuid,
+    product_name: String,
+    product_number: String,
+    measuring_unit: String,
+    unit_number: String,
+    size_quantity: Decimal,
+    quality_grade: Option<String>,
+}
+
+/// Renders the delivery challan that travels with the goods: every dispatched
+/// roll grouped by product, with per-product totals. Staff can print
+/// challans for dispatches leaving from or arriving at their warehouse.
+pub async fn get_challan_pdf(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(dispatch_id): Path<Uuid>,
+) -> Result<impl IntoResponse, DispatchError> {
+    let challan = sqlx::query_as!(
+        ChallanHeader,
+        r#"
+        SELECT gd.dispatch_number, gd.dispatch_date,
+               COALESCE(gd.is_cancelled, false) as "is_cancelled!",
+               c.name as company_name, c.gst_number as company_gst_number,
+               w.name as from_name,
+               CONCAT_WS(', ', w.address_line1, w.address_line2, w.city, w.state, w.pin_code) as "from_address!",
+               CASE WHEN gd.dispatch_type = 'partner'
+                   THEN COALESCE(NULLIF(p.company_name, ''), CONCAT_WS(' ', p.first_name, p.last_name))
+                   ELSE dw.name
+               END as "to_name!",
+               CASE WHEN gd.dispatch_type = 'partner'
+                   THEN CONCAT_WS(', ', p.address_line1, p.address_line2, p.city, p.state, p.pin_code)
+                   ELSE CONCAT_WS(', ', dw.address_line1, dw.address_line2, dw.city, dw.state, dw.pin_code)
+               END as "to_address!",
+               p.gst_number as to_gst_number,
+               CASE gd.link_type
+                   WHEN 'sales_order' THEN 'Sales Order ' || so.order_number
+                   WHEN 'job_work' THEN 'Job Work ' || jw.job_number
+                   ELSE gd.other_reference
+               END as reference,
+               gd.invoice_number, gd.transport_details, gd.notes
+        FROM goods_dispatches gd
+        JOIN companies c ON c.id = gd.company_id
+        JOIN warehouses w ON w.id = gd.warehouse_id
+        LEFT JOIN partners p ON p.id = gd.dispatch_to_partner_id
+        LEFT JOIN warehouses dw ON dw.id = gd.dispatch_to_warehouse_id
+        LEFT JOIN sales_orders so ON so.id = gd.sales_order_id
+        LEFT JOIN job_works jw ON jw.id = gd.job_work_id
+        WHERE gd.id = $1 AND gd.company_id = $2 AND gd.deleted_at IS NULL
+            AND ($3 OR gd.warehouse_id IS NOT DISTINCT FROM $4
+                 OR gd.dispatch_to_warehouse_id IS NOT DISTINCT FROM $4)
+        "#,
+        dispatch_id,
+        user.company_id,
+        user.is_admin(),
+        user.warehouse_id
+    )
+    .fetch_optional(db_pool.as_ref())
+    .await
+    .context("Failed to fetch dispatch from database.")?
+    .ok_or(DispatchError::NotFound)?;
+
+    let units = sqlx::query_as!(
+        ChallanUnit,
+        r#"
+        SELECT p.id as product_id, p.name as product_name, p.product_number, p.measuring_unit,
+               su.unit_number, su.size_quantity, su.quality_grade
+        FROM goods_dispatch_items gdi
+        JOIN stock_units su ON su.id = gdi.stock_unit_id
+        JOIN products p ON p.id = su.product_id
+        WHERE gdi.dispatch_id = $1
+        ORDER BY p.name, p.id, su.unit_number
+        "#,
+        dispatch_id
+    )
+    .fetch_all(db_pool.as_ref())
+    .await
+    .context("Failed to fetch dispatched stock units from database.")?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/pdf".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("inline; filename=\"{}.pdf\"", challan.dispatch_number),
+            ),
+        ],
+        render_challan_pdf(&challan, &units),
+    ))
+}
+
+fn render_challan_pdf(challan: &ChallanHeader, units: &[ChallanUnit]) -> Vec<u8> {
+    let mut doc = PdfDocument::new();
+    doc.title(if challan.is_cancelled {
+        "DELIVERY CHALLAN (CANCELLED)"
+    } else {
+        "DELIVERY CHALLAN"
+    });
+
+    doc.heading(&challan.company_name);
+    if let Some(gstin) = &challan.company_gst_number {
+        doc.text(&format!("GSTIN: {gstin}"));
+    }
+    doc.rule();
+
+    let half = PdfDocument::CONTENT_WIDTH / 2.0;
+    let halves = [
+        Column::left(PdfDocument::LEFT, half - 10.0),
+        Column::left(PdfDocument::LEFT + half, half),
+    ];
+    doc.row(
+        &halves,
+        &[
+            &format!("Challan No: {}", challan.dispatch_number),
+            &format!("Date: {}", challan.dispatch_date.format("%d-%m-%Y")),
+        ],
+        true,
+    );
+    doc.row(
+        &halves,
+        &[
+            &format!("From: {}", challan.from_name),
+            &format!("To: {}", challan.to_name),
+        ],
+        false,
+    );
+    doc.row(
+        &halves,
+        &[&challan.from_address, &challan.to_address],
+        false,
+    );
+    if let Some(gstin) = &challan.to_gst_number {
+        doc.row(&halves, &["", &format!("GSTIN: {gstin}")], false);
+    }
+    if let Some(reference) = &challan.reference {
+        doc.text(&format!("Against: {reference}"));
+    }
+    if let Some(invoice_number) = &challan.invoice_number {
+        doc.text(&format!("Invoice No: {invoice_number}"));
+    }
+    if let Some(transport) = &challan.transport_details {
+        doc.text(&format!("Transport: {transport}"));
+    }
+    doc.rule();
+
+    let columns = [
+        Column::left(40.0, 30.0),
+        Column::left(72.0, 200.0),
+        Column::left(274.0, 140.0),
+        Column::right(416.0, 139.0),
+    ];
+    let mut total_units = 0;
+    for group in units.chunk_by(|a, b| a.product_id == b.product_id) {
+        let product = &group[0];
+        doc.heading(&format!(
+            "{} ({})",
+            product.product_name, product.product_number
+        ));
+        doc.row(&columns, &["#", "Roll No", "Quality", "Size"], true);
+        for (index, unit) in group.iter().enumerate() {
+            doc.row(
+                &columns,
+                &[
+                    &(index + 1).to_string(),
+                    &unit.unit_number,
+                    unit.quality_grade.as_deref().unwrap_or("-"),
+                    &format!("{} {}", unit.size_quantity.normalize(), unit.measuring_unit),
+                ],
+                false,
+            );
+        }
+        let quantity: Decimal = group.iter().map(|u| u.size_quantity).sum();
+        doc.row(
+            &columns,
+            &[
+                "",
+                &format!("{} rolls", group.len()),
+                "Total",
+                &format!("{} {}", quantity.normalize(), product.measuring_unit),
+            ],
+            true,
+        );
+        doc.space(6.0);
+        total_units += group.len();
+    }
+    doc.rule();
+    doc.row(
+        &[Column::left(PdfDocument::LEFT, PdfDocument::CONTENT_WIDTH)],
+        &[&format!("Total rolls dispatched: {total_units}")],
+        true,
+    );
+    if let Some(notes) = &challan.notes {
+        doc.text(&format!("Notes: {notes}"));
+    }
+
+    doc.space(40.0);
+    doc.row(
+        &halves,
+        &[
+            "Receiver's Signature",
+            &format!("For {}", challan.company_name),
+        ],
+        false,
+    );
+
+    doc.finish()
+}
+
+/// Schema version of the e-way bill bulk-upload JSON
+const E_WAY_BILL_VERSION: &str = "1.0.0621";
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EWayBillUpload {
+    pub version: String,
+    pub bill_lists: Vec<EWayBill>,
+}
+
+/// One bill in the portal's bulk-upload format. Field names and codes follow
+/// the NIC schema, which is why some are not camelCase.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EWayBill {
+    pub user_gstin: String,
+    pub supply_type: String,
+    pub sub_supply_type: u8,
+    pub sub_supply_desc: String,
+    pub doc_type: String,
+    pub doc_no: String,
+    pub doc_date: String,
+    pub trans_type: u8,
+    pub from_gstin: String,
+    pub from_trd_name: String,
+    pub from_addr1: String,
+    pub from_addr2: String,
+    pub from_place: String,
+    pub from_pincode: u32,
+    pub from_state_code: u8,
+    pub act_from_state_code: u8,
+    pub to_gstin: String,
+    pub to_trd_name: String,
+    pub to_addr1: String,
+    pub to_addr2: String,
+    pub to_place: String,
+    pub to_pincode: u32,
+    pub to_state_code: u8,
+    pub act_to_state_code: u8,
+    pub total_value: Decimal,
+    pub cgst_value: Decimal,
+    pub sgst_value: Decimal,
+    pub igst_value: Decimal,
+    pub cess_value: Decimal,
+    #[serde(rename = "TotNonAdvolVal")]
+    pub tot_non_advol_val: Decimal,
+    #[serde(rename = "OthValue")]
+    pub oth_value: Decimal,
+    pub tot_inv_value: Decimal,
+    pub trans_mode: String,
+    pub trans_distance: String,
+    pub transporter_name: String,
+    pub transporter_id: String,
+    pub trans_doc_no: String,
+    pub trans_doc_date: String,
+    pub vehicle_no: String,
+    pub vehicle_type: String,
+    pub main_hsn_code: u32,
+    pub item_list: Vec<EWayBillItem>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EWayBillItem {
+    pub item_no: u32,
+    pub product_name: String,
+    pub product_desc: String,
+    pub hsn_code: u32,
+    pub quantity: Decimal,
+    pub qty_unit: String,
+    pub taxable_amount: Decimal,
+    pub sgst_rate: Decimal,
+    pub cgst_rate: Decimal,
+    pub igst_rate: Decimal,
+    pub cess_rate: Decimal,
+    pub cess_non_advol: Decimal,
+}
+
+struct EWayBillSource {
+    dispatch_number: String,
+    dispatch_date: NaiveDate,
+    dispatch_type: String,
+    link_type: Option<String>,
+    is_cancelled: bool,
+    transport_mode: Option<String>,
+    transport_distance_km: Option<i32>,
+    transporter_name: Option<String>,
+    transporter_id: Option<String>,
+    vehicle_number: Option<String>,
+    transport_document_number: Option<String>,
+    transport_document_date: Option<NaiveDate>,
+    company_name: String,
+    company_gst_number: Option<String>,
+    from_addr1: Option<String>,
+    from_addr2: Option<String>,
+    from_place: Option<String>,
+    from_pin_code: Option<String>,
+    from_state_code: Option<String>,
+    act_from_state_code: Option<String>,
+    to_gst_number: Option<String>,
+    to_name: String,
+    to_addr1: Option<String>,
+    to_addr2: Option<String>,
+    to_place: Option<String>,
+    to_pin_code: Option<String>,
+    to_state_code: Option<String>,
+    act_to_state_code: Option<String>,
+    invoice_id: Option<Uuid>,
+    invoice_number: Option<String>,
+    invoice_date: Option<NaiveDate>,
+    invoice_round_off: Option<Decimal>,
+}
+
+struct EWayBillLine {
+    product_name: String,
+    hsn_code: Option<String>,
+    quantity: Decimal,
+    measuring_unit: String,
+    taxable_amount: Decimal,
+    cgst_rate: Decimal,
+    sgst_rate: Decimal,
+    igst_rate: Decimal,
+    cgst_amount: Decimal,
+    sgst_amount: Decimal,
+    igst_amount: Decimal,
+}
+
+/// Builds the bulk-upload JSON for filing an e-way bill on the portal.
+///
+/// Sales to partners are filed against their tax invoice, so the invoice
+/// must be generated first. Job work dispatches and transfers between the
+/// company's own warehouses move on a delivery challan, valued at selling
+/// price without tax. Every mandatory field that is missing is reported at
+/// once so staff can fix the dispatch in one go.
+pub async fn export_e_way_bill(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(dispatch_id): Path<Uuid>,
+) -> Result<impl IntoResponse, DispatchError> {
+    let source = sqlx::query_as!(
+        EWayBillSource,
+        r#"
+        SELECT gd.dispatch_number, gd.dispatch_date, gd.dispatch_type, gd.link_type,
+               COALESCE(gd.is_cancelled, false) as "is_cancelled!",
+               gd.transport_mode, gd.transport_distance_km, gd.transporter_name,
+               gd.transporter_id, gd.vehicle_number, gd.transport_document_number,
+               gd.transport_document_date,
+               c.name as company_name, c.gst_number as company_gst_number,
+               w.address_line1 as from_addr1, w.address_line2 as from_addr2,
+               w.city as from_place, w.pin_code as from_pin_code,
+               gst_state_code(c.gst_number, c.state) as from_state_code,
+               gst_state_code(NULL, COALESCE(w.state, c.state)) as act_from_state_code,
+               CASE WHEN gd.dispatch_type = 'partner' THEN p.gst_number ELSE c.gst_number END
+                   as to_gst_number,
+               CASE WHEN gd.dispatch_type = 'partner'
+                   THEN COALESCE(NULLIF(p.company_name, ''), CONCAT_WS(' ', p.first_name, p.last_name))
+                   ELSE c.name
+               END as "to_name!",
+               CASE WHEN gd.dispatch_type = 'partner' THEN p.address_line1 ELSE dw.address_line1 END
+                   as to_addr1,
+               CASE WHEN gd.dispatch_type = 'partner' THEN p.address_line2 ELSE dw.address_line2 END
+                   as to_addr2,
+               CASE WHEN gd.dispatch_type = 'partner' THEN p.city ELSE dw.city END as to_place,
+               CASE WHEN gd.dispatch_type = 'partner' THEN p.pin_code ELSE dw.pin_code END
+                   as to_pin_code,
+               CASE WHEN gd.dispatch_type = 'partner'
+                   THEN gst_state_code(p.gst_number, p.state)
+                   ELSE gst_state_code(c.gst_number, c.state)
+               END as to_state_code,
+               CASE WHEN gd.dispatch_type = 'partner'
+                   THEN gst_state_code(NULL, p.state)
+                   ELSE gst_state_code(NULL, dw.state)
+               END as act_to_state_code,
+               ti.id as "invoice_id?", ti.invoice_number as "invoice_number?",
+               ti.invoice_date as "invoice_date?", ti.round_off as "invoice_round_off?"
+        FROM goods_dispatches gd
+        JOIN companies c ON c.id = gd.company_id
+        JOIN warehouses w ON w.id = gd.warehouse_id
+        LEFT JOIN partners p ON p.id = gd.dispatch_to_partner_id
+        LEFT JOIN warehouses dw ON dw.id = gd.dispatch_to_warehouse_id
+        LEFT JOIN tax_invoices ti ON ti.dispatch_id = gd.id
+        WHERE gd.id = $1 AND gd.company_id = $2 AND gd.deleted_at IS NULL
+            AND ($3 OR gd.warehouse_id IS NOT DISTINCT FROM $4)
+        "#,
+        dispatch_id,
+        user.company_id,
+        user.is_admin(),
+        user.warehouse_id
+    )
+    .fetch_optional(db_pool.as_ref())
+    .await
+    .context("Failed to fetch dispatch from database.")?
+    .ok_or(DispatchError::NotFound)?;
+
+    if source.is_cancelled {
+        return Err(DispatchError::InvalidState(
+            "Cannot file an e-way bill for a cancelled dispatch".to_string(),
+        ));
+    }
+
+    let lines = match source.invoice_id {
+        Some(invoice_id) => sqlx::query_as!(
+            EWayBillLine,
+            r#"
+            SELECT tii.description as product_name, tii.hsn_code, tii.quantity,
+                   tii.measuring_unit, tii.taxable_amount,
+                   CASE WHEN ti.is_inter_state THEN 0 ELSE tii.gst_rate / 2 END as "cgst_rate!",
+                   CASE WHEN ti.is_inter_state THEN 0 ELSE tii.gst_rate / 2 END as "sgst_rate!",
+                   CASE WHEN ti.is_inter_state THEN tii.gst_rate ELSE 0 END as "igst_rate!",
+                   tii.cgst_amount, tii.sgst_amount, tii.igst_amount
+            FROM tax_invoice_items tii
+            JOIN tax_invoices ti ON ti.id = tii.invoice_id
+            WHERE tii.invoice_id = $1
+            ORDER BY tii.description, tii.product_id
+            "#,
+            invoice_id
+        )
+        .fetch_all(db_pool.as_ref())
+        .await
+        .context("Failed to fetch tax invoice items from database.")?,
+        None => sqlx::query_as!(
+            EWayBillLine,
+            r#"
+            SELECT p.name as product_name, p.hsn_code, SUM(su.size_quantity) as "quantity!",
+                   p.measuring_unit,
+                   ROUND(SUM(su.size_quantity) * COALESCE(p.selling_price_per_unit, 0), 2)
+                       as "taxable_amount!",
+                   0::DECIMAL as "cgst_rate!", 0::DECIMAL as "sgst_rate!",
+                   0::DECIMAL as "igst_rate!", 0::DECIMAL as "cgst_amount!",
+                   0::DECIMAL as "sgst_amount!", 0::DECIMAL as "igst_amount!"
+            FROM goods_dispatch_items gdi
+            JOIN stock_units su ON su.id = gdi.stock_unit_id
+            JOIN products p ON p.id = su.product_id
+            WHERE gdi.dispatch_id = $1
+            GROUP BY p.id
+            ORDER BY p.name, p.id
+            "#,
+            dispatch_id
+        )
+        .fetch_all(db_pool.as_ref())
+        .await
+        .context("Failed to fetch dispatched stock units from database.")?,
+    };
+
+    let bill = build_e_way_bill(&source, &lines).map_err(DispatchError::EWayBillIncomplete)?;
+    let upload = EWayBillUpload {
+        version: E_WAY_BILL_VERSION.to_string(),
+        bill_lists: vec![bill],
+    };
+
+    Ok((
+        [(
+            header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"{}-eway-bill.json\"",
+                source.dispatch_number
+            ),
+        )],
+        Json(upload),
+    ))
+}
+
+fn build_e_way_bill(
+    source: &EWayBillSource,
+    lines: &[EWayBillLine],
+) -> Result<EWayBill, Vec<String>> {
+    let mut missing = Vec::new();
+    let mut require = |value: Option<String>, field: &str| -> String {
+        value.unwrap_or_else(|| {
+            missing.push(field.to_string());
+            String::new()
+        })
+    };
+
+    let from_gstin = require(
+        source.company_gst_number.clone().filter(|g| is_gstin(g)),
+        "fromGstin (company GSTIN)",
+    );
+    let from_pincode = require(
+        source.from_pin_code.clone().filter(|p| is_pin_code(p)),
+        "fromPincode (dispatching warehouse PIN code)",
+    );
+    let from_state_code = require(
+        source.from_state_code.clone(),
+        "fromStateCode (company state)",
+    );
+    let act_from_state_code = require(
+        source.act_from_state_code.clone(),
+        "actFromStateCode (dispatching warehouse state)",
+    );
+    let to_pincode = require(
+        source.to_pin_code.clone().filter(|p| is_pin_code(p)),
+        "toPincode (recipient PIN code)",
+    );
+    let to_state_code = require(
+        source.to_state_code.clone(),
+        "toStateCode (recipient state)",
+    );
+    let act_to_state_code = require(
+        source.act_to_state_code.clone(),
+        "actToStateCode (delivery state)",
+    );
+
+    // (sub supply type, description, document type)
+    let (sub_supply_type, sub_supply_desc, doc_type) = if source.dispatch_type == "warehouse" {
+        (8, "Stock Transfer", "CHL")
+    } else if source.link_type.as_deref() == Some("job_work") {
+        (4, "", "CHL")
+    } else {
+        (1, "", "INV")
+    };
+    let (doc_no, doc_date) = if doc_type == "INV" {
+        (
+            require(
+                source.invoice_number.clone(),
+                "docNo (generate the tax invoice first)",
+            ),
+            source.invoice_date.unwrap_or(source.dispatch_date),
+        )
+    } else {
+        (source.dispatch_number.clone(), source.dispatch_date)
+    };
+
+    let trans_mode = match source.transport_mode.as_deref() {
+        Some("road") => "1",
+        Some("rail") => "2",
+        Some("air") => "3",
+        Some("ship") => "4",
+        _ => {
+            missing.push("transMode (transport mode)".to_string());
+            ""
+        }
+    };
+    let trans_distance = match source.transport_distance_km {
+        Some(km) => km.to_string(),
+        None => {
+            missing.push("transDistance (distance in km)".to_string());
+            String::new()
+        }
+    };
+    if trans_mode == "1" && source.vehicle_number.is_none() && source.transporter_id.is_none() {
+        missing.push("vehicleNo or transporterId".to_string());
+    }
+    if matches!(trans_mode, "2" | "3" | "4")
+        && (source.transport_document_number.is_none() || source.transport_document_date.is_none())
+    {
+        missing.push("transDocNo and transDocDate (transport document)".to_string());
+    }
+
+    if lines.is_empty() {
+        missing.push("itemList (no stock units dispatched)".to_string());
+    }
+    let mut item_list = Vec::with_capacity(lines.len());
+    for (index, line) in lines.iter().enumerate() {
+        let hsn_code = match line.hsn_code.as_deref().and_then(|h| h.parse::<u32>().ok()) {
+            Some(hsn_code) => hsn_code,
+            None => {
+                missing.push(format!(
+                    "itemList[{}].hsnCode (HSN code of {})",
+                    index + 1,
+                    line.product_name
+                ));
+                0
+            }
+        };
+        item_list.push(EWayBillItem {
+            item_no: index as u32 + 1,
+            product_name: line.product_name.clone(),
+            product_desc: line.product_name.clone(),
+            hsn_code,
+            quantity: line.quantity,
+            qty_unit: quantity_unit_code(&line.measuring_unit).to_string(),
+            taxable_amount: line.taxable_amount,
+            sgst_rate: line.sgst_rate.normalize(),
+            cgst_rate: line.cgst_rate.normalize(),
+            igst_rate: line.igst_rate.normalize(),
+            cess_rate: Decimal::ZERO,
+            cess_non_advol: Decimal::ZERO,
+        });
+    }
+
+    if !missing.is_empty() {
+        return Err(missing);
+    }
+
+    let total_value: Decimal = lines.iter().map(|l| l.taxable_amount).sum();
+    let cgst_value: Decimal = lines.iter().map(|l| l.cgst_amount).sum();
+    let sgst_value: Decimal = lines.iter().map(|l| l.sgst_amount).sum();
+    let igst_value: Decimal = lines.iter().map(|l| l.igst_amount).sum();
+    // Invoice rounding is the only "other" charge we carry
+    let oth_value = source.invoice_round_off.unwrap_or(Decimal::ZERO);
+    let main_hsn_code = item_list
+        .iter()
+        .max_by_key(|item| item.taxable_amount)
+        .map(|item| item.hsn_code)
+        .unwrap_or_default();
+
+    Ok(EWayBill {
+        user_gstin: from_gstin.clone(),
+        supply_type: "O".to_string(),
+        sub_supply_type,
+        sub_supply_desc: sub_supply_desc.to_string(),
+        doc_type: doc_type.to_string(),
+        doc_no,
+        doc_date: doc_date.format("%d/%m/%Y").to_string(),
+        trans_type: 1,
+        from_gstin: from_gstin.clone(),
+        from_trd_name: source.company_name.clone(),
+        from_addr1: source.from_addr1.clone().unwrap_or_default(),
+        from_addr2: source.from_addr2.clone().unwrap_or_default(),
+        from_place: source.from_place.clone().unwrap_or_default(),
+        from_pincode: from_pincode.parse().unwrap_or_default(),
+        from_state_code: from_state_code.parse().unwrap_or_default(),
+        act_from_state_code: act_from_state_code.parse().unwrap_or_default(),
+        // Unregistered recipients are filed as "URP"
+        to_gstin: source
+            .to_gst_number
+            .clone()
+            .filter(|g| is_gstin(g))
+            .unwrap_or_else(|| "URP".to_string()),
+        to_trd_name: source.to_name.clone(),
+        to_addr1: source.to_addr1.clone().unwrap_or_default(),
+        to_addr2: source.to_addr2.clone().unwrap_or_default(),
+        to_place: source.to_place.clone().unwrap_or_default(),
+        to_pincode: to_pincode.parse().unwrap_or_default(),
+        to_state_code: to_state_code.parse().unwrap_or_default(),
+        act_to_state_code: act_to_state_code.parse().unwrap_or_default(),
+        total_value,
+        cgst_value,
+        sgst_value,
+        igst_value,
+        cess_value: Decimal::ZERO,
+        tot_non_advol_val: Decimal::ZERO,
+        oth_value,
+        tot_inv_value: total_value + cgst_value + sgst_value + igst_value + oth_value,
+        trans_mode: trans_mode.to_string(),
+        trans_distance,
+        transporter_name: source.transporter_name.clone().unwrap_or_default(),
+        transporter_id: source.transporter_id.clone().unwrap_or_default(),
+        trans_doc_no: source.transport_document_number.clone().unwrap_or_default(),
+        trans_doc_date: source
+            .transport_document_date
+            .map(|d| d.format("%d/%m/%Y").to_string())
+            .unwrap_or_default(),
+        vehicle_no: source.vehicle_number.clone().unwrap_or_default(),
+        vehicle_type: if trans_mode == "1" { "R" } else { "" }.to_string(),
+        main_hsn_code,
+        item_list,
+    })
+}
+
+fn is_gstin(value: &str) -> bool {
+    value.len() == 15 && value.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn is_pin_code(value: &str) -> bool {
+    value.len() == 6 && value.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Unit quantity codes accepted by the portal
+fn quantity_unit_code(measuring_unit: &str) -> &str {
+    match measuring_unit {
+        "Meters" => "MTR",
+        "Yards" => "YDS",
+        "Kg" => "KGS",
+        "Pieces" => "PCS",
+        _ => "OTH",
+    }
+}
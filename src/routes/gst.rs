@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::auth::CurrentUser;
+
+// ERROR
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, thiserror::Error)]
+pub enum GstError {
+    #[error("{0}")]
+    ValidationError(String),
+    #[error("Only admins can manage GST rates")]
+    Forbidden,
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl IntoResponse for GstError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            Self::ValidationError(_) => StatusCode::BAD_REQUEST,
+            Self::Forbidden => StatusCode::FORBIDDEN,
+            Self::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+        .into_response()
+    }
+}
+
+// UPDATE
+// -------------------------------------------------------------------------------------
+
+/// GST slabs notified for goods, in percent
+const GST_SLABS: [(i64, u32); 9] = [
+    (0, 0),
+    (10, 2),
+    (25, 2),
+    (15, 1),
+    (3, 0),
+    (5, 0),
+    (12, 0),
+    (18, 0),
+    (28, 0),
+];
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HsnGstRateUpdate {
+    gst_rate: Decimal,
+    description: Option<String>,
+}
+
+impl HsnGstRateUpdate {
+    fn validate(&self) -> Result<(), String> {
+        let is_slab = GST_SLABS
+            .iter()
+            .any(|&(num, scale)| Decimal::new(num, scale) == self.gst_rate);
+        if !is_slab {
+            return Err(format!("{} is not a valid GST rate", self.gst_rate));
+        }
+        Ok(())
+    }
+}
+
+/// Sets the GST rate for an HSN code. Items already on sales orders keep the
+/// rate they were added with.
+pub async fn set_hsn_gst_rate(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(hsn_code): Path<String>,
+    Json(update): Json<HsnGstRateUpdate>,
+) -> Result<Json<HsnGstRate>, GstError> {
+    if !user.is_admin() {
+        return Err(GstError::Forbidden);
+    }
+    let hsn_code = hsn_code.trim();
+    if hsn_code.is_empty() || !hsn_code.chars().all(|c| c.is_ascii_digit()) {
+        return Err(GstError::ValidationError(
+            "HSN code must contain only digits".to_string(),
+        ));
+    }
+    update.validate().map_err(GstError::ValidationError)?;
+
+    let rate = sqlx::query_as!(
+        HsnGstRate,
+        r#"
+        INSERT INTO hsn_gst_rates (company_id, hsn_code, gst_rate, description, created_by)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (company_id, hsn_code) DO UPDATE
+        SET gst_rate = EXCLUDED.gst_rate,
+            description = COALESCE(EXCLUDED.description, hsn_gst_rates.description),
+            modified_by = $5
+        RETURNING hsn_code, gst_rate, description
+        "#,
+        user.company_id,
+        hsn_code,
+        update.gst_rate,
+        update.description,
+        user.id
+    )
+    .fetch_one(db_pool.as_ref())
+    .await
+    .context("Failed to upsert HSN GST rate in the database.")?;
+
+    Ok(Json(rate))
+}
+
+// READ
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HsnGstRate {
+    pub hsn_code: String,
+    pub gst_rate: Decimal,
+    pub description: Option<String>,
+}
+
+pub async fn get_hsn_gst_rates(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+) -> Result<Json<Vec<HsnGstRate>>, GstError> {
+    let rates = sqlx::query_as!(
+        HsnGstRate,
+        r#"
+        SELECT hsn_code, gst_rate, description
+        FROM hsn_gst_rates
+        WHERE company_id = $1
+        ORDER BY hsn_code
+        "#,
+        user.company_id
+    )
+    .fetch_all(db_pool.as_ref())
+    .await
+    .context("Failed to fetch HSN GST rates from database.")?;
+
+    Ok(Json(rates))
+}
@@ -0,0 +1,700 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{Datelike, NaiveDate, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::{
+    auth::CurrentUser,
+    pdf::{amount_in_words, money, unit_abbreviation, Column, JpegImage, PdfDocument},
+    routes::ledger::financial_year_start,
+};
+
+// ERROR
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, thiserror::Error)]
+pub enum InvoiceError {
+    #[error("{0}")]
+    ValidationError(String),
+    #[error("{0}")]
+    InvalidState(String),
+    #[error("Not found")]
+    NotFound,
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl IntoResponse for InvoiceError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            Self::ValidationError(_) => StatusCode::BAD_REQUEST,
+            Self::InvalidState(_) => StatusCode::CONFLICT,
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+        .into_response()
+    }
+}
+
+// CREATE
+// -------------------------------------------------------------------------------------
+
+struct InvoiceLine {
+    product_id: Uuid,
+    description: String,
+    hsn_code: Option<String>,
+    quantity: Decimal,
+    measuring_unit: String,
+    unit_rate: Decimal,
+    gst_rate: Decimal,
+    taxable_amount: Decimal,
+    cgst_amount: Decimal,
+    sgst_amount: Decimal,
+    igst_amount: Decimal,
+}
+
+/// Generates the tax invoice for a partner dispatch and derives the dispatch's
+/// `invoice_number` and `invoice_amount` from it.
+///
+/// Each product is billed at its sales order rate and captured GST rate when
+/// the dispatch is linked to an order, otherwise at the product's selling
+/// price and current HSN rate. The order discount applies before tax.
+pub async fn generate_invoice(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(dispatch_id): Path<Uuid>,
+) -> Result<(StatusCode, Json<TaxInvoice>), InvoiceError> {
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    let dispatch = sqlx::query!(
+        r#"
+        SELECT gd.dispatch_type, gd.dispatch_to_partner_id, gd.sales_order_id,
+               COALESCE(gd.is_cancelled, false) as "is_cancelled!",
+               COALESCE(so.discount_percentage, 0) as "discount_percentage!",
+               gst_state_code(p.gst_number, p.state) as place_of_supply,
+               gst_state_code(c.gst_number, c.state) as company_state_code
+        FROM goods_dispatches gd
+        JOIN companies c ON c.id = gd.company_id
+        LEFT JOIN partners p ON p.id = gd.dispatch_to_partner_id
+        LEFT JOIN sales_orders so ON so.id = gd.sales_order_id
+        WHERE gd.id = $1 AND gd.company_id = $2 AND gd.deleted_at IS NULL
+            AND ($3 OR gd.warehouse_id IS NOT DISTINCT FROM $4)
+        FOR UPDATE OF gd
+        "#,
+        dispatch_id,
+        user.company_id,
+        user.is_admin(),
+        user.warehouse_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to fetch dispatch from database.")?
+    .ok_or(InvoiceError::NotFound)?;
+
+    let customer_id = match dispatch.dispatch_to_partner_id {
+        Some(partner_id) if dispatch.dispatch_type == "partner" => partner_id,
+        _ => {
+            return Err(InvoiceError::ValidationError(
+                "Only partner dispatches can be invoiced".to_string(),
+            ))
+        }
+    };
+    if dispatch.is_cancelled {
+        return Err(InvoiceError::InvalidState(
+            "Cannot invoice a cancelled dispatch".to_string(),
+        ));
+    }
+    let already_invoiced = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM tax_invoices WHERE dispatch_id = $1) as "exists!""#,
+        dispatch_id
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to check for an existing invoice.")?;
+    if already_invoiced {
+        return Err(InvoiceError::InvalidState(
+            "Dispatch has already been invoiced".to_string(),
+        ));
+    }
+
+    // Unknown states are treated as intra-state, as on sales orders
+    let is_inter_state = matches!(
+        (&dispatch.company_state_code, &dispatch.place_of_supply),
+        (Some(company), Some(customer)) if company != customer
+    );
+    let lines = fetch_invoice_lines_from_db(
+        &mut transaction,
+        dispatch_id,
+        dispatch.sales_order_id,
+        dispatch.discount_percentage,
+        is_inter_state,
+    )
+    .await
+    .context("Failed to price dispatched products.")?;
+    if lines.is_empty() {
+        return Err(InvoiceError::ValidationError(
+            "Dispatch has no stock units to invoice".to_string(),
+        ));
+    }
+
+    let taxable_amount: Decimal = lines.iter().map(|l| l.taxable_amount).sum();
+    let cgst_amount: Decimal = lines.iter().map(|l| l.cgst_amount).sum();
+    let sgst_amount: Decimal = lines.iter().map(|l| l.sgst_amount).sum();
+    let igst_amount: Decimal = lines.iter().map(|l| l.igst_amount).sum();
+    let invoice_value = taxable_amount + cgst_amount + sgst_amount + igst_amount;
+    let total_amount =
+        invoice_value.round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero);
+
+    let invoice_date = Utc::now().date_naive();
+    let invoice_number =
+        next_invoice_number_in_db(&mut transaction, user.company_id, invoice_date).await?;
+
+    let invoice_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO tax_invoices (company_id, dispatch_id, sales_order_id, customer_id,
+                                  invoice_number, invoice_date, place_of_supply, is_inter_state,
+                                  discount_percentage, taxable_amount, cgst_amount, sgst_amount,
+                                  igst_amount, round_off, total_amount, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+        RETURNING id
+        "#,
+        user.company_id,
+        dispatch_id,
+        dispatch.sales_order_id,
+        customer_id,
+        invoice_number,
+        invoice_date,
+        dispatch.place_of_supply,
+        is_inter_state,
+        dispatch.discount_percentage,
+        taxable_amount,
+        cgst_amount,
+        sgst_amount,
+        igst_amount,
+        total_amount - invoice_value,
+        total_amount,
+        user.id
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to insert tax invoice in the database.")?;
+
+    for line in &lines {
+        sqlx::query!(
+            r#"
+            INSERT INTO tax_invoice_items (company_id, invoice_id, product_id, description,
+                                           hsn_code, quantity, measuring_unit, unit_rate, gst_rate,
+                                           taxable_amount, cgst_amount, sgst_amount, igst_amount)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            "#,
+            user.company_id,
+            invoice_id,
+            line.product_id,
+            line.description,
+            line.hsn_code,
+            line.quantity,
+            line.measuring_unit,
+            line.unit_rate,
+            line.gst_rate,
+            line.taxable_amount,
+            line.cgst_amount,
+            line.sgst_amount,
+            line.igst_amount
+        )
+        .execute(&mut *transaction)
+        .await
+        .context("Failed to insert tax invoice item in the database.")?;
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE goods_dispatches
+        SET invoice_number = $2, invoice_amount = $3, modified_by = $4
+        WHERE id = $1
+        "#,
+        dispatch_id,
+        invoice_number,
+        total_amount,
+        user.id
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to update dispatch invoice details.")?;
+
+    let invoice = fetch_invoice_from_db(&mut transaction, user.company_id, dispatch_id)
+        .await
+        .context("Failed to fetch tax invoice from database.")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to generate a tax invoice.")?;
+
+    Ok((StatusCode::CREATED, Json(invoice)))
+}
+
+async fn fetch_invoice_lines_from_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    dispatch_id: Uuid,
+    sales_order_id: Option<Uuid>,
+    discount_percentage: Decimal,
+    is_inter_state: bool,
+) -> Result<Vec<InvoiceLine>, sqlx::Error> {
+    sqlx::query_as!(
+        InvoiceLine,
+        r#"
+        WITH dispatched AS (
+            SELECT su.product_id, SUM(su.size_quantity) as quantity
+            FROM goods_dispatch_items gdi
+            JOIN stock_units su ON su.id = gdi.stock_unit_id
+            WHERE gdi.dispatch_id = $1
+            GROUP BY su.product_id
+        ),
+        priced AS (
+            SELECT d.product_id, p.name, p.measuring_unit, d.quantity,
+                   order_unit_rate($2, d.product_id) as unit_rate,
+                   COALESCE(soi.hsn_code, p.hsn_code) as hsn_code,
+                   COALESCE(soi.gst_rate, hgr.gst_rate, 0) as gst_rate
+            FROM dispatched d
+            JOIN products p ON p.id = d.product_id
+            LEFT JOIN hsn_gst_rates hgr
+                ON hgr.company_id = p.company_id AND hgr.hsn_code = p.hsn_code
+            LEFT JOIN LATERAL (
+                SELECT MAX(hsn_code) as hsn_code, MAX(gst_rate) as gst_rate
+                FROM sales_order_items
+                WHERE sales_order_id = $2 AND product_id = d.product_id
+            ) soi ON TRUE
+        )
+        SELECT pr.product_id as "product_id!",
+               pr.name as "description!",
+               pr.hsn_code,
+               pr.quantity as "quantity!",
+               pr.measuring_unit as "measuring_unit!",
+               pr.unit_rate as "unit_rate!",
+               pr.gst_rate as "gst_rate!",
+               amounts.taxable_amount as "taxable_amount!",
+               amounts.cgst_amount as "cgst_amount!",
+               amounts.sgst_amount as "sgst_amount!",
+               amounts.igst_amount as "igst_amount!"
+        FROM priced pr
+        CROSS JOIN LATERAL gst_line_amounts(
+            ROUND(pr.quantity * pr.unit_rate, 2), $3, pr.gst_rate, $4
+        ) amounts
+        ORDER BY pr.name, pr.product_id
+        "#,
+        dispatch_id,
+        sales_order_id,
+        discount_percentage,
+        is_inter_state
+    )
+    .fetch_all(&mut **transaction)
+    .await
+}
+
+/// GST allows at most 16 characters
+const MAX_INVOICE_NUMBER_LENGTH: usize = 16;
+
+/// Takes the next number of the company's series for the financial year of
+/// `invoice_date`, e.g. `INV/26-27/0001`. The series row stays locked until
+/// the transaction ends so numbers are gapless and never reused.
+async fn next_invoice_number_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    company_id: Uuid,
+    invoice_date: NaiveDate,
+) -> Result<String, InvoiceError> {
+    let year_start = financial_year_start(invoice_date);
+    let series = sqlx::query!(
+        r#"
+        INSERT INTO invoice_series (company_id, financial_year_start, last_number)
+        VALUES ($1, $2, 1)
+        ON CONFLICT (company_id, financial_year_start) DO UPDATE
+        SET last_number = invoice_series.last_number + 1
+        RETURNING prefix, last_number
+        "#,
+        company_id,
+        year_start
+    )
+    .fetch_one(&mut **transaction)
+    .await
+    .context("Failed to assign an invoice number.")?;
+
+    let invoice_number = format!(
+        "{}/{:02}-{:02}/{:04}",
+        series.prefix,
+        year_start.year() % 100,
+        (year_start.year() + 1) % 100,
+        series.last_number
+    );
+    // The transaction rolls back, so the number is not used up
+    if invoice_number.len() > MAX_INVOICE_NUMBER_LENGTH {
+        return Err(InvoiceError::InvalidState(format!(
+            "Invoice number {invoice_number} is longer than {MAX_INVOICE_NUMBER_LENGTH} characters; \
+             use a shorter series prefix"
+        )));
+    }
+    Ok(invoice_number)
+}
+
+// READ
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TaxInvoice {
+    pub id: Uuid,
+    pub dispatch_id: Uuid,
+    pub sales_order_id: Option<Uuid>,
+    pub customer_id: Uuid,
+    pub invoice_number: String,
+    pub invoice_date: NaiveDate,
+    pub place_of_supply: Option<String>,
+    pub is_inter_state: bool,
+    pub discount_percentage: Decimal,
+    pub taxable_amount: Decimal,
+    pub cgst_amount: Decimal,
+    pub sgst_amount: Decimal,
+    pub igst_amount: Decimal,
+    pub round_off: Decimal,
+    pub total_amount: Decimal,
+    pub items: Vec<TaxInvoiceItem>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TaxInvoiceItem {
+    pub product_id: Uuid,
+    pub description: String,
+    pub hsn_code: Option<String>,
+    pub quantity: Decimal,
+    pub measuring_unit: String,
+    pub unit_rate: Decimal,
+    pub gst_rate: Decimal,
+    pub taxable_amount: Decimal,
+    pub cgst_amount: Decimal,
+    pub sgst_amount: Decimal,
+    pub igst_amount: Decimal,
+}
+
+async fn fetch_invoice_from_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    company_id: Uuid,
+    dispatch_id: Uuid,
+) -> Result<TaxInvoice, sqlx::Error> {
+    let invoice = sqlx::query!(
+        r#"
+        SELECT id, dispatch_id, sales_order_id, customer_id, invoice_number, invoice_date,
+               place_of_supply, is_inter_state, discount_percentage, taxable_amount,
+               cgst_amount, sgst_amount, igst_amount, round_off, total_amount
+        FROM tax_invoices
+        WHERE dispatch_id = $1 AND company_id = $2
+        "#,
+        dispatch_id,
+        company_id
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    let items = sqlx::query_as!(
+        TaxInvoiceItem,
+        r#"
+        SELECT product_id, description, hsn_code, quantity, measuring_unit, unit_rate,
+               gst_rate, taxable_amount, cgst_amount, sgst_amount, igst_amount
+        FROM tax_invoice_items
+        WHERE invoice_id = $1
+        ORDER BY description, product_id
+        "#,
+        invoice.id
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    Ok(TaxInvoice {
+        id: invoice.id,
+        dispatch_id: invoice.dispatch_id,
+        sales_order_id: invoice.sales_order_id,
+        customer_id: invoice.customer_id,
+        invoice_number: invoice.invoice_number,
+        invoice_date: invoice.invoice_date,
+        place_of_supply: invoice.place_of_supply,
+        is_inter_state: invoice.is_inter_state,
+        discount_percentage: invoice.discount_percentage,
+        taxable_amount: invoice.taxable_amount,
+        cgst_amount: invoice.cgst_amount,
+        sgst_amount: invoice.sgst_amount,
+        igst_amount: invoice.igst_amount,
+        round_off: invoice.round_off,
+        total_amount: invoice.total_amount,
+        items,
+    })
+}
+
+/// Loads the invoice of a dispatch the user can see; staff only see
+/// dispatches from their own warehouse.
+async fn fetch_visible_invoice(
+    db_pool: &PgPool,
+    user: &CurrentUser,
+    dispatch_id: Uuid,
+) -> Result<TaxInvoice, InvoiceError> {
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    sqlx::query_scalar!(
+        r#"
+        SELECT id FROM goods_dispatches
+        WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+            AND ($3 OR warehouse_id IS NOT DISTINCT FROM $4)
+        "#,
+        dispatch_id,
+        user.company_id,
+        user.is_admin(),
+        user.warehouse_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to fetch dispatch from database.")?
+    .ok_or(InvoiceError::NotFound)?;
+
+    fetch_invoice_from_db(&mut transaction, user.company_id, dispatch_id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => InvoiceError::NotFound,
+            e => InvoiceError::UnexpectedError(
+                anyhow::Error::new(e).context("Failed to fetch tax invoice from database."),
+            ),
+        })
+}
+
+pub async fn get_invoice(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(dispatch_id): Path<Uuid>,
+) -> Result<Json<TaxInvoice>, InvoiceError> {
+    let invoice = fetch_visible_invoice(&db_pool, &user, dispatch_id).await?;
+    Ok(Json(invoice))
+}
+
+struct InvoiceParty {
+    name: String,
+    address: String,
+    gst_number: Option<String>,
+    state: Option<String>,
+}
+
+pub async fn get_invoice_pdf(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(dispatch_id): Path<Uuid>,
+) -> Result<impl IntoResponse, InvoiceError> {
+    let invoice = fetch_visible_invoice(&db_pool, &user, dispatch_id).await?;
+
+    let company = sqlx::query!(
+        r#"
+        SELECT name,
+               CONCAT_WS(', ', address_line1, address_line2, city, state, pin_code) as "address!",
+               gst_number, state, logo_jpeg
+        FROM companies
+        WHERE id = $1
+        "#,
+        user.company_id
+    )
+    .fetch_one(db_pool.as_ref())
+    .await
+    .context("Failed to fetch company from database.")?;
+    let logo = company.logo_jpeg.and_then(JpegImage::parse);
+    let seller = InvoiceParty {
+        name: company.name,
+        address: company.address,
+        gst_number: company.gst_number,
+        state: company.state,
+    };
+
+    let buyer = sqlx::query_as!(
+        InvoiceParty,
+        r#"
+        SELECT COALESCE(NULLIF(company_name, ''), CONCAT_WS(' ', first_name, last_name)) as "name!",
+               CONCAT_WS(', ', address_line1, address_line2, city, state, pin_code) as "address!",
+               gst_number, state
+        FROM partners
+        WHERE id = $1
+        "#,
+        invoice.customer_id
+    )
+    .fetch_one(db_pool.as_ref())
+    .await
+    .context("Failed to fetch customer from database.")?;
+
+    let filename = invoice.invoice_number.replace('/', "-");
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/pdf".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("inline; filename=\"{filename}.pdf\""),
+            ),
+        ],
+        render_invoice_pdf(&invoice, &seller, logo, &buyer),
+    ))
+}
+
+fn render_invoice_pdf(
+    invoice: &TaxInvoice,
+    seller: &InvoiceParty,
+    logo: Option<JpegImage>,
+    buyer: &InvoiceParty,
+) -> Vec<u8> {
+    let mut doc = PdfDocument::new();
+    if let Some(logo) = logo {
+        doc.image(logo, 40.0);
+    }
+    doc.title("TAX INVOICE");
+
+    doc.heading(&seller.name);
+    doc.text(&seller.address);
+    if let Some(gstin) = &seller.gst_number {
+        doc.text(&format!("GSTIN: {gstin}"));
+    }
+    doc.rule();
+
+    let half = PdfDocument::CONTENT_WIDTH / 2.0;
+    let halves = [
+        Column::left(PdfDocument::LEFT, half - 10.0),
+        Column::left(PdfDocument::LEFT + half, half),
+    ];
+    doc.row(
+        &halves,
+        &[
+            &format!("Invoice No: {}", invoice.invoice_number),
+            &format!("Invoice Date: {}", invoice.invoice_date.format("%d-%m-%Y")),
+        ],
+        true,
+    );
+    let place_of_supply = match (&invoice.place_of_supply, &buyer.state) {
+        (Some(code), Some(state)) => format!("{state} ({code})"),
+        (Some(code), None) => code.clone(),
+        (None, state) => state.clone().unwrap_or_default(),
+    };
+    doc.row(
+        &halves,
+        &[
+            &format!("Bill To: {}", buyer.name),
+            &format!("Place of Supply: {place_of_supply}"),
+        ],
+        false,
+    );
+    doc.row(
+        &halves,
+        &[
+            &buyer.address,
+            &format!(
+                "GSTIN: {}",
+                buyer.gst_number.as_deref().unwrap_or("Unregistered")
+            ),
+        ],
+        false,
+    );
+    doc.rule();
+
+    let columns = [
+        Column::left(40.0, 18.0),
+        Column::left(60.0, 140.0),
+        Column::left(202.0, 48.0),
+        Column::right(252.0, 62.0),
+        Column::right(316.0, 55.0),
+        Column::right(373.0, 34.0),
+        Column::right(409.0, 62.0),
+        Column::right(473.0, 82.0),
+    ];
+    doc.row(
+        &columns,
+        &[
+            "#",
+            "Description",
+            "HSN",
+            "Qty",
+            "Rate",
+            "GST %",
+            "Tax",
+            "Taxable Value",
+        ],
+        true,
+    );
+    for (index, item) in invoice.items.iter().enumerate() {
+        let tax = item.cgst_amount + item.sgst_amount + item.igst_amount;
+        doc.row(
+            &columns,
+            &[
+                &(index + 1).to_string(),
+                &item.description,
+                item.hsn_code.as_deref().unwrap_or("-"),
+                &format!(
+                    "{} {}",
+                    item.quantity.normalize(),
+                    unit_abbreviation(&item.measuring_unit)
+                ),
+                &money(item.unit_rate),
+                &item.gst_rate.normalize().to_string(),
+                &money(tax),
+                &money(item.taxable_amount),
+            ],
+            false,
+        );
+    }
+    doc.rule();
+
+    let totals = [Column::left(340.0, 120.0), Column::right(473.0, 82.0)];
+    let discount_note = if invoice.discount_percentage > Decimal::ZERO {
+        format!(
+            "Taxable Value (after {}% discount)",
+            invoice.discount_percentage.normalize()
+        )
+    } else {
+        "Taxable Value".to_string()
+    };
+    doc.row(
+        &totals,
+        &[&discount_note, &money(invoice.taxable_amount)],
+        false,
+    );
+    if invoice.is_inter_state {
+        doc.row(&totals, &["IGST", &money(invoice.igst_amount)], false);
+    } else {
+        doc.row(&totals, &["CGST", &money(invoice.cgst_amount)], false);
+        doc.row(&totals, &["SGST", &money(invoice.sgst_amount)], false);
+    }
+    doc.row(&totals, &["Round Off", &money(invoice.round_off)], false);
+    doc.row(
+        &totals,
+        &["Invoice Total", &money(invoice.total_amount)],
+        true,
+    );
+    doc.rule();
+    doc.text(&amount_in_words(invoice.total_amount));
+
+    doc.space(30.0);
+    doc.row(
+        &[Column::right(PdfDocument::LEFT, PdfDocument::CONTENT_WIDTH)],
+        &[&format!("For {}", seller.name)],
+        true,
+    );
+    doc.space(24.0);
+    doc.row(
+        &[Column::right(PdfDocument::LEFT, PdfDocument::CONTENT_WIDTH)],
+        &["Authorised Signatory"],
+        false,
+    );
+
+    doc.finish()
+}
@@ -0,0 +1,293 @@
+//! Yield, turnaround and on-time delivery of completed job works, per vendor
+//! and per job type.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use anyhow::Context;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+use crate::{
+    auth::CurrentUser,
+    routes::job_works::{JobWorkError, UnitYield},
+};
+
+// READ
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct JobWorkAnalyticsQuery {
+    warehouse_id: Option<Uuid>,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    group_by: Option<String>,
+    sort: Option<String>,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Display, EnumString)]
+enum YieldGrouping {
+    vendor,
+    job_type,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Display, EnumString)]
+enum VendorRanking {
+    yield_percentage,
+    turnaround,
+    on_time,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct YieldSummary {
+    /// Set when grouped by vendor
+    pub vendor_id: Option<Uuid>,
+    /// Vendor name or job type
+    pub name: String,
+    pub completed_job_works: i64,
+    pub yields: Vec<UnitYield>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct VendorPerformance {
+    pub vendor_id: Uuid,
+    pub vendor_name: String,
+    pub completed_job_works: i64,
+    /// Average of the job work yields in each measuring unit
+    pub yield_percentage: Option<Decimal>,
+    /// Average days from start date to completion
+    pub average_turnaround_days: Decimal,
+    /// Share of job works with a due date completed on or before it
+    pub on_time_percentage: Option<Decimal>,
+}
+
+struct CompletedJobWork {
+    id: Uuid,
+    vendor_id: Uuid,
+    vendor_name: String,
+    job_type: String,
+    start_date: NaiveDate,
+    due_date: Option<NaiveDate>,
+    completed_on: NaiveDate,
+}
+
+struct JobWorkUnitYield {
+    job_work_id: Uuid,
+    measuring_unit: String,
+    raw_dispatched: Decimal,
+    finished_received: Decimal,
+    yield_percentage: Decimal,
+}
+
+/// Yields of completed job works totalled per vendor or per job type
+pub async fn get_job_work_yields(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Query(query): Query<JobWorkAnalyticsQuery>,
+) -> Result<Json<Vec<YieldSummary>>, JobWorkError> {
+    let grouping = query
+        .group_by
+        .as_deref()
+        .map_or(Ok(YieldGrouping::vendor), |s| s.parse())
+        .map_err(|_| {
+            JobWorkError::ValidationError("`group_by` must be vendor or job_type".into())
+        })?;
+    let (job_works, yields) = fetch_completed_job_works(&db_pool, &user, &query).await?;
+
+    // Keyed by name then vendor, so vendors sharing a name stay apart
+    let mut groups: BTreeMap<(String, Option<Uuid>), Vec<Uuid>> = BTreeMap::new();
+    for job_work in &job_works {
+        let key = match grouping {
+            YieldGrouping::vendor => (job_work.vendor_name.clone(), Some(job_work.vendor_id)),
+            YieldGrouping::job_type => (job_work.job_type.clone(), None),
+        };
+        groups.entry(key).or_default().push(job_work.id);
+    }
+
+    let summaries = groups
+        .into_iter()
+        .map(|((name, vendor_id), ids)| {
+            let mut units: BTreeMap<&str, (Decimal, Decimal)> = BTreeMap::new();
+            for unit_yield in yields.iter().filter(|y| ids.contains(&y.job_work_id)) {
+                let totals = units.entry(&unit_yield.measuring_unit).or_default();
+                totals.0 += unit_yield.raw_dispatched;
+                totals.1 += unit_yield.finished_received;
+            }
+
+            YieldSummary {
+                vendor_id,
+                name,
+                completed_job_works: ids.len() as i64,
+                yields: units
+                    .into_iter()
+                    .map(|(measuring_unit, (dispatched, received))| UnitYield {
+                        measuring_unit: measuring_unit.to_string(),
+                        raw_dispatched: dispatched,
+                        finished_received: received,
+                        wastage: dispatched - received,
+                        yield_percentage: percentage(received, dispatched),
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+
+    Ok(Json(summaries))
+}
+
+/// Vendors ranked by yield (default), turnaround or on-time rate, best first
+pub async fn get_vendor_ranking(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Query(query): Query<JobWorkAnalyticsQuery>,
+) -> Result<Json<Vec<VendorPerformance>>, JobWorkError> {
+    let ranking = query
+        .sort
+        .as_deref()
+        .map_or(Ok(VendorRanking::yield_percentage), |s| s.parse())
+        .map_err(|_| {
+            JobWorkError::ValidationError(
+                "`sort` must be yield_percentage, turnaround or on_time".into(),
+            )
+        })?;
+    let (job_works, yields) = fetch_completed_job_works(&db_pool, &user, &query).await?;
+
+    let mut vendors: BTreeMap<Uuid, Vec<&CompletedJobWork>> = BTreeMap::new();
+    for job_work in &job_works {
+        vendors
+            .entry(job_work.vendor_id)
+            .or_default()
+            .push(job_work);
+    }
+
+    let mut performances: Vec<VendorPerformance> = vendors
+        .into_iter()
+        .map(|(vendor_id, completed)| {
+            let vendor_yields: Vec<Decimal> = yields
+                .iter()
+                .filter(|y| completed.iter().any(|jw| jw.id == y.job_work_id))
+                .map(|y| y.yield_percentage)
+                .collect();
+            let turnaround_days: i64 = completed
+                .iter()
+                .map(|jw| (jw.completed_on - jw.start_date).num_days())
+                .sum();
+            let with_due_date: Vec<bool> = completed
+                .iter()
+                .filter_map(|jw| jw.due_date.map(|due| jw.completed_on <= due))
+                .collect();
+            let on_time = with_due_date.iter().filter(|on_time| **on_time).count();
+
+            VendorPerformance {
+                vendor_id,
+                vendor_name: completed[0].vendor_name.clone(),
+                completed_job_works: completed.len() as i64,
+                yield_percentage: (!vendor_yields.is_empty()).then(|| {
+                    (vendor_yields.iter().sum::<Decimal>() / Decimal::from(vendor_yields.len()))
+                        .round_dp(2)
+                }),
+                average_turnaround_days: (Decimal::from(turnaround_days)
+                    / Decimal::from(completed.len()))
+                .round_dp(2),
+                on_time_percentage: (!with_due_date.is_empty()).then(|| {
+                    percentage(Decimal::from(on_time), Decimal::from(with_due_date.len()))
+                }),
+            }
+        })
+        .collect();
+
+    // Vendors without a figure for the ranking go last
+    performances.sort_by(|a, b| {
+        let order = match ranking {
+            VendorRanking::yield_percentage => {
+                rank_descending(a.yield_percentage, b.yield_percentage)
+            }
+            VendorRanking::turnaround => a.average_turnaround_days.cmp(&b.average_turnaround_days),
+            VendorRanking::on_time => rank_descending(a.on_time_percentage, b.on_time_percentage),
+        };
+        order.then_with(|| a.vendor_name.cmp(&b.vendor_name))
+    });
+
+    Ok(Json(performances))
+}
+
+fn percentage(part: Decimal, whole: Decimal) -> Decimal {
+    (part / whole * Decimal::ONE_HUNDRED).round_dp(2)
+}
+
+fn rank_descending(a: Option<Decimal>, b: Option<Decimal>) -> std::cmp::Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => b.cmp(&a),
+        (a, b) => b.is_some().cmp(&a.is_some()),
+    }
+}
+
+/// Completed job works the user can see, completed within the period, with
+/// their yields in each measuring unit
+async fn fetch_completed_job_works(
+    db_pool: &PgPool,
+    user: &CurrentUser,
+    query: &JobWorkAnalyticsQuery,
+) -> Result<(Vec<CompletedJobWork>, Vec<JobWorkUnitYield>), JobWorkError> {
+    if let (Some(from), Some(to)) = (query.from, query.to) {
+        if from > to {
+            return Err(JobWorkError::ValidationError(
+                "`from` must not be after `to`".to_string(),
+            ));
+        }
+    }
+
+    let job_works = sqlx::query_as!(
+        CompletedJobWork,
+        r#"
+        SELECT jw.id, jw.vendor_id,
+               COALESCE(NULLIF(v.company_name, ''), CONCAT_WS(' ', v.first_name, v.last_name)) as "vendor_name!",
+               jw.job_type, jw.start_date, jw.due_date,
+               jw.status_changed_at::DATE as "completed_on!"
+        FROM job_works jw
+        JOIN partners v ON v.id = jw.vendor_id
+        WHERE jw.company_id = $1 AND jw.deleted_at IS NULL
+            AND jw.status = 'completed' AND jw.status_changed_at IS NOT NULL
+            AND ($2 OR jw.warehouse_id IS NOT DISTINCT FROM $3)
+            AND ($4::UUID IS NULL OR jw.warehouse_id = $4)
+            AND ($5::DATE IS NULL OR jw.status_changed_at::DATE >= $5)
+            AND ($6::DATE IS NULL OR jw.status_changed_at::DATE <= $6)
+        "#,
+        user.company_id,
+        user.is_admin(),
+        user.warehouse_id,
+        query.warehouse_id,
+        query.from,
+        query.to
+    )
+    .fetch_all(db_pool)
+    .await
+    .context("Failed to fetch completed job works from database.")?;
+
+    let job_work_ids: Vec<Uuid> = job_works.iter().map(|jw| jw.id).collect();
+    let yields = sqlx::query_as!(
+        JobWorkUnitYield,
+        r#"
+        SELECT job_work_id as "job_work_id!", measuring_unit as "measuring_unit!",
+               raw_dispatched_qty as "raw_dispatched!", finished_received_qty as "finished_received!",
+               yield_percentage as "yield_percentage!"
+        FROM job_work_yields
+        WHERE job_work_id = ANY($1)
+        "#,
+        &job_work_ids
+    )
+    .fetch_all(db_pool)
+    .await
+    .context("Failed to fetch job work yields from database.")?;
+
+    Ok((job_works, yields))
+}
@@ -0,0 +1,1120 @@
+use std::{collections::HashSet, sync::Arc};
+
+use anyhow::Context;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+use crate::{
+    auth::CurrentUser,
+    routes::{
+        dispatches::{cancel_dispatch_in_db, fetch_moved_units_from_db},
+        lookups::{
+            ensure_products_exist, ensure_warehouses_exist, fetch_partner_type, working_warehouse,
+            LookupError,
+        },
+    },
+};
+
+// ERROR
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, thiserror::Error)]
+pub enum JobWorkError {
+    #[error("{0}")]
+    ValidationError(String),
+    #[error("{0}")]
+    InvalidState(String),
+    #[error("Staff can only manage job works of their own warehouse")]
+    Forbidden,
+    #[error("Job work not found")]
+    NotFound,
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl IntoResponse for JobWorkError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            Self::ValidationError(_) => StatusCode::BAD_REQUEST,
+            Self::InvalidState(_) => StatusCode::CONFLICT,
+            Self::Forbidden => StatusCode::FORBIDDEN,
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+        .into_response()
+    }
+}
+
+impl From<LookupError> for JobWorkError {
+    fn from(error: LookupError) -> Self {
+        match error {
+            LookupError::ValidationError(message) => Self::ValidationError(message),
+            LookupError::Forbidden => Self::Forbidden,
+            LookupError::UnexpectedError(error) => Self::UnexpectedError(error),
+        }
+    }
+}
+
+// CREATE
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString, Deserialize, Serialize)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum JobWorkStatus {
+    InProgress,
+    Completed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewJobWork {
+    job_type: String,
+    vendor_id: Uuid,
+    /// Job work normally goes to a Vendor, but any partner can take it on
+    #[serde(default)]
+    allow_any_partner: bool,
+    agent_id: Option<Uuid>,
+    /// Admins only; staff always work in their own warehouse
+    warehouse_id: Option<Uuid>,
+    sales_order_id: Option<Uuid>,
+    start_date: Option<NaiveDate>,
+    due_date: Option<NaiveDate>,
+    notes: Option<String>,
+    raw_materials: Vec<NewRawMaterial>,
+    finished_goods: Vec<NewFinishedGood>,
+}
+
+/// Material sent to the vendor
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewRawMaterial {
+    product_id: Uuid,
+    required_quantity: Decimal,
+}
+
+/// Goods expected back from the vendor
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewFinishedGood {
+    product_id: Uuid,
+    expected_quantity: Decimal,
+}
+
+impl NewJobWork {
+    fn validate(&self) -> Result<(), String> {
+        if self.job_type.trim().is_empty() {
+            return Err("Job type is required".to_string());
+        }
+        if self.raw_materials.is_empty() {
+            return Err("A job work needs at least one raw material".to_string());
+        }
+        if self.finished_goods.is_empty() {
+            return Err("A job work needs at least one finished good".to_string());
+        }
+        validate_raw_materials(&self.raw_materials)?;
+        validate_finished_goods(&self.finished_goods)?;
+        if let (Some(start_date), Some(due_date)) = (self.start_date, self.due_date) {
+            if due_date < start_date {
+                return Err("Due date cannot be before the start date".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn validate_raw_materials(lines: &[NewRawMaterial]) -> Result<(), String> {
+    validate_lines(
+        lines.iter().map(|l| (l.product_id, l.required_quantity)),
+        "raw material",
+    )
+}
+
+fn validate_finished_goods(lines: &[NewFinishedGood]) -> Result<(), String> {
+    validate_lines(
+        lines.iter().map(|l| (l.product_id, l.expected_quantity)),
+        "finished good",
+    )
+}
+
+/// Quantities are positive and each product appears at most once per side
+fn validate_lines(lines: impl Iterator<Item = (Uuid, Decimal)>, kind: &str) -> Result<(), String> {
+    let mut seen = HashSet::new();
+    for (product_id, quantity) in lines {
+        if quantity <= Decimal::ZERO {
+            return Err(format!("Each {kind} quantity must be greater than zero"));
+        }
+        if !seen.insert(product_id) {
+            return Err(format!(
+                "Product {product_id} is listed more than once as a {kind}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub async fn create_job_work(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Json(new_job_work): Json<NewJobWork>,
+) -> Result<(StatusCode, Json<JobWork>), JobWorkError> {
+    new_job_work
+        .validate()
+        .map_err(JobWorkError::ValidationError)?;
+    let warehouse_id = working_warehouse(&user, new_job_work.warehouse_id)?;
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    ensure_warehouses_exist(&mut transaction, user.company_id, &[warehouse_id]).await?;
+    let vendor_type = fetch_partner_type(&mut transaction, user.company_id, new_job_work.vendor_id)
+        .await?
+        .ok_or_else(|| JobWorkError::ValidationError("Vendor does not exist".to_string()))?;
+    if vendor_type != "Vendor" && !new_job_work.allow_any_partner {
+        return Err(JobWorkError::ValidationError(format!(
+            "Partner {} is a {}, not a Vendor; set allow_any_partner to use them anyway",
+            new_job_work.vendor_id, vendor_type
+        )));
+    }
+    if let Some(agent_id) = new_job_work.agent_id {
+        let agent_type = fetch_partner_type(&mut transaction, user.company_id, agent_id)
+            .await?
+            .ok_or_else(|| JobWorkError::ValidationError("Agent does not exist".to_string()))?;
+        if agent_type != "Agent" {
+            return Err(JobWorkError::ValidationError(format!(
+                "Partner {} is a {}, not an Agent",
+                agent_id, agent_type
+            )));
+        }
+    }
+    if let Some(sales_order_id) = new_job_work.sales_order_id {
+        ensure_sales_order_open(&mut transaction, user.company_id, sales_order_id).await?;
+    }
+    let product_ids: Vec<Uuid> = new_job_work
+        .raw_materials
+        .iter()
+        .map(|l| l.product_id)
+        .chain(new_job_work.finished_goods.iter().map(|l| l.product_id))
+        .collect();
+    ensure_products_exist(&mut transaction, user.company_id, &product_ids).await?;
+
+    let job_work_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO job_works (company_id, warehouse_id, job_type, vendor_id, agent_id, start_date,
+                               due_date, sales_order_id, notes, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        RETURNING id
+        "#,
+        user.company_id,
+        warehouse_id,
+        new_job_work.job_type.trim(),
+        new_job_work.vendor_id,
+        new_job_work.agent_id,
+        new_job_work
+            .start_date
+            .unwrap_or_else(|| Utc::now().date_naive()),
+        new_job_work.due_date,
+        new_job_work.sales_order_id,
+        new_job_work.notes,
+        user.id
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to insert job work in the database.")?;
+
+    for line in &new_job_work.raw_materials {
+        insert_raw_material_in_db(&mut transaction, user.company_id, job_work_id, line)
+            .await
+            .context("Failed to insert job work raw material in the database.")?;
+    }
+    for line in &new_job_work.finished_goods {
+        insert_finished_good_in_db(&mut transaction, user.company_id, job_work_id, line)
+            .await
+            .context("Failed to insert job work finished good in the database.")?;
+    }
+    insert_status_change_in_db(
+        &mut transaction,
+        &user,
+        job_work_id,
+        None,
+        JobWorkStatus::InProgress,
+        None,
+        false,
+    )
+    .await
+    .context("Failed to insert job work status history in the database.")?;
+
+    let job_work = fetch_job_work_from_db(&mut transaction, job_work_id)
+        .await
+        .context("Failed to fetch job work from database.")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to store a new job work.")?;
+
+    Ok((StatusCode::CREATED, Json(job_work)))
+}
+
+async fn ensure_sales_order_open(
+    transaction: &mut Transaction<'_, Postgres>,
+    company_id: Uuid,
+    sales_order_id: Uuid,
+) -> Result<(), JobWorkError> {
+    let status = sqlx::query_scalar!(
+        r#"
+        SELECT status FROM sales_orders
+        WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+        "#,
+        sales_order_id,
+        company_id
+    )
+    .fetch_optional(&mut **transaction)
+    .await
+    .context("Failed to fetch sales order from database.")?
+    .ok_or_else(|| JobWorkError::ValidationError("Sales order does not exist".to_string()))?;
+
+    if status == "cancelled" {
+        return Err(JobWorkError::ValidationError(
+            "Job work cannot be linked to a cancelled sales order".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+async fn insert_raw_material_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    company_id: Uuid,
+    job_work_id: Uuid,
+    line: &NewRawMaterial,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO job_work_raw_materials (company_id, job_work_id, product_id, required_quantity)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        company_id,
+        job_work_id,
+        line.product_id,
+        line.required_quantity
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+}
+
+async fn insert_finished_good_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    company_id: Uuid,
+    job_work_id: Uuid,
+    line: &NewFinishedGood,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO job_work_finished_goods (company_id, job_work_id, product_id, expected_quantity)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        company_id,
+        job_work_id,
+        line.product_id,
+        line.expected_quantity
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+}
+
+async fn insert_status_change_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    user: &CurrentUser,
+    job_work_id: Uuid,
+    from_status: Option<JobWorkStatus>,
+    to_status: JobWorkStatus,
+    notes: Option<&str>,
+    with_pending_goods: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO job_work_status_history (company_id, job_work_id, from_status, to_status, notes,
+                                             with_pending_goods, changed_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+        user.company_id,
+        job_work_id,
+        from_status.map(|s| s.to_string()),
+        to_status.to_string(),
+        notes,
+        with_pending_goods,
+        user.id
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+}
+
+// READ
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JobWork {
+    pub id: Uuid,
+    pub job_number: String,
+    pub job_type: String,
+    pub status: String,
+    pub warehouse_id: Uuid,
+    pub vendor_id: Uuid,
+    pub agent_id: Option<Uuid>,
+    pub sales_order_id: Option<Uuid>,
+    pub start_date: NaiveDate,
+    pub due_date: Option<NaiveDate>,
+    pub notes: Option<String>,
+    pub raw_materials: Vec<JobWorkRawMaterial>,
+    pub finished_goods: Vec<JobWorkFinishedGood>,
+    pub yields: Vec<UnitYield>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JobWorkRawMaterial {
+    pub id: Uuid,
+    pub product_id: Uuid,
+    pub product_name: String,
+    pub measuring_unit: String,
+    pub required_quantity: Decimal,
+    pub dispatched_quantity: Decimal,
+    pub pending_quantity: Decimal,
+    /// Dispatched beyond the required quantity
+    pub excess_quantity: Decimal,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JobWorkFinishedGood {
+    pub id: Uuid,
+    pub product_id: Uuid,
+    pub product_name: String,
+    pub measuring_unit: String,
+    pub expected_quantity: Decimal,
+    pub received_quantity: Decimal,
+    pub pending_quantity: Decimal,
+    /// Received beyond the expected quantity
+    pub excess_quantity: Decimal,
+}
+
+/// Finished goods received against raw materials dispatched in one measuring unit
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UnitYield {
+    pub measuring_unit: String,
+    pub raw_dispatched: Decimal,
+    pub finished_received: Decimal,
+    pub wastage: Decimal,
+    pub yield_percentage: Decimal,
+}
+
+pub(crate) async fn fetch_job_work_from_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    job_work_id: Uuid,
+) -> Result<JobWork, sqlx::Error> {
+    let job_work = sqlx::query!(
+        r#"
+        SELECT id, job_number, job_type, status, warehouse_id, vendor_id, agent_id, sales_order_id,
+               start_date, due_date, notes
+        FROM job_works
+        WHERE id = $1
+        "#,
+        job_work_id
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    let raw_materials = sqlx::query_as!(
+        JobWorkRawMaterial,
+        r#"
+        SELECT rm.id, rm.product_id, p.name as product_name, p.measuring_unit,
+               rm.required_quantity,
+               COALESCE(rm.dispatched_quantity, 0) as "dispatched_quantity!",
+               GREATEST(COALESCE(rm.pending_quantity, rm.required_quantity), 0) as "pending_quantity!",
+               GREATEST(-rm.pending_quantity, 0) as "excess_quantity!"
+        FROM job_work_raw_materials rm
+        JOIN products p ON p.id = rm.product_id
+        WHERE rm.job_work_id = $1
+        ORDER BY p.name, rm.id
+        "#,
+        job_work_id
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let finished_goods = sqlx::query_as!(
+        JobWorkFinishedGood,
+        r#"
+        SELECT fg.id, fg.product_id, p.name as product_name, p.measuring_unit,
+               fg.expected_quantity,
+               COALESCE(fg.received_quantity, 0) as "received_quantity!",
+               GREATEST(COALESCE(fg.pending_quantity, fg.expected_quantity), 0) as "pending_quantity!",
+               GREATEST(-fg.pending_quantity, 0) as "excess_quantity!"
+        FROM job_work_finished_goods fg
+        JOIN products p ON p.id = fg.product_id
+        WHERE fg.job_work_id = $1
+        ORDER BY p.name, fg.id
+        "#,
+        job_work_id
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let yields = sqlx::query_as!(
+        UnitYield,
+        r#"
+        SELECT measuring_unit as "measuring_unit!", raw_dispatched_qty as "raw_dispatched!",
+               finished_received_qty as "finished_received!", wastage_qty as "wastage!",
+               yield_percentage as "yield_percentage!"
+        FROM job_work_yields
+        WHERE job_work_id = $1
+        ORDER BY measuring_unit
+        "#,
+        job_work_id
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    Ok(JobWork {
+        id: job_work.id,
+        job_number: job_work.job_number,
+        job_type: job_work.job_type,
+        status: job_work.status,
+        warehouse_id: job_work.warehouse_id,
+        vendor_id: job_work.vendor_id,
+        agent_id: job_work.agent_id,
+        sales_order_id: job_work.sales_order_id,
+        start_date: job_work.start_date,
+        due_date: job_work.due_date,
+        notes: job_work.notes,
+        raw_materials,
+        finished_goods,
+        yields,
+    })
+}
+
+pub async fn get_job_work(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(job_work_id): Path<Uuid>,
+) -> Result<Json<JobWork>, JobWorkError> {
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    // Staff only see job works of their own warehouse
+    sqlx::query_scalar!(
+        r#"
+        SELECT id FROM job_works
+        WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+            AND ($3 OR warehouse_id IS NOT DISTINCT FROM $4)
+        "#,
+        job_work_id,
+        user.company_id,
+        user.is_admin(),
+        user.warehouse_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to fetch job work from database.")?
+    .ok_or(JobWorkError::NotFound)?;
+
+    let job_work = fetch_job_work_from_db(&mut transaction, job_work_id)
+        .await
+        .context("Failed to fetch job work from database.")?;
+
+    Ok(Json(job_work))
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JobWorkStatusChange {
+    pub from_status: Option<String>,
+    pub to_status: String,
+    pub notes: Option<String>,
+    pub with_pending_goods: bool,
+    pub changed_at: DateTime<Utc>,
+    pub changed_by: Uuid,
+}
+
+pub async fn get_job_work_status_history(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(job_work_id): Path<Uuid>,
+) -> Result<Json<Vec<JobWorkStatusChange>>, JobWorkError> {
+    // Staff only see job works of their own warehouse
+    sqlx::query_scalar!(
+        r#"
+        SELECT id FROM job_works
+        WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+            AND ($3 OR warehouse_id IS NOT DISTINCT FROM $4)
+        "#,
+        job_work_id,
+        user.company_id,
+        user.is_admin(),
+        user.warehouse_id
+    )
+    .fetch_optional(db_pool.as_ref())
+    .await
+    .context("Failed to fetch job work from database.")?
+    .ok_or(JobWorkError::NotFound)?;
+
+    let history = sqlx::query_as!(
+        JobWorkStatusChange,
+        r#"
+        SELECT from_status, to_status, notes, with_pending_goods, changed_at, changed_by
+        FROM job_work_status_history
+        WHERE job_work_id = $1
+        ORDER BY changed_at, id
+        "#,
+        job_work_id
+    )
+    .fetch_all(db_pool.as_ref())
+    .await
+    .context("Failed to fetch job work status history from database.")?;
+
+    Ok(Json(history))
+}
+
+/// Job types used before, most used first, for suggestions while typing
+pub async fn get_job_types(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+) -> Result<Json<Vec<String>>, JobWorkError> {
+    let job_types = sqlx::query_scalar!(
+        r#"
+        SELECT job_type FROM job_works
+        WHERE company_id = $1 AND deleted_at IS NULL
+        GROUP BY job_type
+        ORDER BY COUNT(*) DESC, job_type
+        "#,
+        user.company_id
+    )
+    .fetch_all(db_pool.as_ref())
+    .await
+    .context("Failed to fetch job types from database.")?;
+
+    Ok(Json(job_types))
+}
+
+// UPDATE
+// -------------------------------------------------------------------------------------
+
+/// Lines given here replace the current ones: products left out are removed,
+/// new products are added and the rest take the new quantity.
+#[derive(Debug, Clone, Deserialize)]
+pub struct JobWorkUpdate {
+    job_type: Option<String>,
+    due_date: Option<NaiveDate>,
+    notes: Option<String>,
+    raw_materials: Option<Vec<NewRawMaterial>>,
+    finished_goods: Option<Vec<NewFinishedGood>>,
+}
+
+impl JobWorkUpdate {
+    fn validate(&self) -> Result<(), String> {
+        if self
+            .job_type
+            .as_deref()
+            .is_some_and(|job_type| job_type.trim().is_empty())
+        {
+            return Err("Job type cannot be empty".to_string());
+        }
+        if let Some(lines) = &self.raw_materials {
+            if lines.is_empty() {
+                return Err("A job work needs at least one raw material".to_string());
+            }
+            validate_raw_materials(lines)?;
+        }
+        if let Some(lines) = &self.finished_goods {
+            if lines.is_empty() {
+                return Err("A job work needs at least one finished good".to_string());
+            }
+            validate_finished_goods(lines)?;
+        }
+        Ok(())
+    }
+}
+
+pub async fn update_job_work(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(job_work_id): Path<Uuid>,
+    Json(update): Json<JobWorkUpdate>,
+) -> Result<Json<JobWork>, JobWorkError> {
+    update.validate().map_err(JobWorkError::ValidationError)?;
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    let start_date = lock_in_progress_job_work(&mut transaction, &user, job_work_id).await?;
+    if update
+        .due_date
+        .is_some_and(|due_date| due_date < start_date)
+    {
+        return Err(JobWorkError::ValidationError(
+            "Due date cannot be before the start date".to_string(),
+        ));
+    }
+
+    let product_ids: Vec<Uuid> = update
+        .raw_materials
+        .iter()
+        .flatten()
+        .map(|l| l.product_id)
+        .chain(update.finished_goods.iter().flatten().map(|l| l.product_id))
+        .collect();
+    ensure_products_exist(&mut transaction, user.company_id, &product_ids).await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE job_works
+        SET job_type = COALESCE($2, job_type),
+            due_date = COALESCE($3, due_date),
+            notes = COALESCE($4, notes),
+            modified_by = $5
+        WHERE id = $1
+        "#,
+        job_work_id,
+        update.job_type.as_deref().map(str::trim),
+        update.due_date,
+        update.notes,
+        user.id
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to update job work in the database.")?;
+
+    if let Some(lines) = &update.raw_materials {
+        replace_raw_materials_in_db(&mut transaction, user.company_id, job_work_id, lines).await?;
+    }
+    if let Some(lines) = &update.finished_goods {
+        replace_finished_goods_in_db(&mut transaction, user.company_id, job_work_id, lines).await?;
+    }
+
+    let job_work = fetch_job_work_from_db(&mut transaction, job_work_id)
+        .await
+        .context("Failed to fetch job work from database.")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to update a job work.")?;
+
+    Ok(Json(job_work))
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompletionRequest {
+    notes: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CancellationRequest {
+    reason: String,
+    /// The raw materials are back from the vendor: cancel their dispatches
+    /// and return the units to stock
+    #[serde(default)]
+    return_materials: bool,
+}
+
+/// Finished goods still pending when a job work was completed, for the
+/// caller to warn about
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JobWorkCompletion {
+    pub pending_finished_goods: Vec<JobWorkFinishedGood>,
+}
+
+/// Completes a job work. Finished goods still pending from the vendor do not
+/// block completion, as the vendor may have delivered short, but they are
+/// returned and recorded in the status history.
+pub async fn complete_job_work(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(job_work_id): Path<Uuid>,
+    Json(request): Json<CompletionRequest>,
+) -> Result<Json<JobWorkCompletion>, JobWorkError> {
+    let notes = non_empty(&request.notes, "Completion notes are required")?;
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    lock_in_progress_job_work(&mut transaction, &user, job_work_id).await?;
+
+    let pending_finished_goods: Vec<JobWorkFinishedGood> =
+        fetch_job_work_from_db(&mut transaction, job_work_id)
+            .await
+            .context("Failed to fetch job work from database.")?
+            .finished_goods
+            .into_iter()
+            .filter(|fg| fg.pending_quantity > Decimal::ZERO)
+            .collect();
+
+    change_status(
+        &mut transaction,
+        &user,
+        job_work_id,
+        JobWorkStatus::Completed,
+        notes,
+        !pending_finished_goods.is_empty(),
+    )
+    .await?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to complete a job work.")?;
+
+    Ok(Json(JobWorkCompletion {
+        pending_finished_goods,
+    }))
+}
+
+/// Cancels a job work. Raw materials still out with the vendor keep it open
+/// until they are back, which `return_materials` confirms by cancelling their
+/// dispatches.
+pub async fn cancel_job_work(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(job_work_id): Path<Uuid>,
+    Json(request): Json<CancellationRequest>,
+) -> Result<StatusCode, JobWorkError> {
+    let reason = non_empty(&request.reason, "A cancellation reason is required")?;
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    lock_in_progress_job_work(&mut transaction, &user, job_work_id).await?;
+
+    let dispatch_ids = sqlx::query_scalar!(
+        r#"
+        SELECT id FROM goods_dispatches
+        WHERE job_work_id = $1 AND link_type = 'job_work'
+            AND is_cancelled IS NOT TRUE AND deleted_at IS NULL
+        FOR UPDATE
+        "#,
+        job_work_id
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to fetch raw material dispatches from database.")?;
+    if !dispatch_ids.is_empty() && !request.return_materials {
+        return Err(JobWorkError::InvalidState(format!(
+            "Job work has {} raw material dispatch(es) with the vendor; \
+             set `return_materials` once the goods are back",
+            dispatch_ids.len()
+        )));
+    }
+
+    let moved = fetch_moved_units_from_db(&mut transaction, &dispatch_ids)
+        .await
+        .context("Failed to fetch dispatched units from database.")?;
+    if !moved.is_empty() {
+        return Err(JobWorkError::InvalidState(format!(
+            "Units {} have moved on since they were dispatched",
+            moved.join(", ")
+        )));
+    }
+
+    // Quantities follow through the dispatch triggers
+    for dispatch_id in dispatch_ids {
+        cancel_dispatch_in_db(&mut transaction, &user, dispatch_id, reason)
+            .await
+            .context("Failed to cancel raw material dispatch.")?;
+    }
+
+    change_status(
+        &mut transaction,
+        &user,
+        job_work_id,
+        JobWorkStatus::Cancelled,
+        reason,
+        false,
+    )
+    .await?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to cancel a job work.")?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Soft-deletes a job work entered by mistake. Once goods have moved it has
+/// to be cancelled instead, so the movements stay traceable.
+pub async fn delete_job_work(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(job_work_id): Path<Uuid>,
+) -> Result<StatusCode, JobWorkError> {
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    lock_in_progress_job_work(&mut transaction, &user, job_work_id).await?;
+
+    let has_movements = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM goods_dispatches
+            WHERE job_work_id = $1 AND deleted_at IS NULL AND is_cancelled IS NOT TRUE
+        ) OR EXISTS (
+            SELECT 1 FROM goods_receipts
+            WHERE job_work_id = $1 AND deleted_at IS NULL
+        ) as "has_movements!"
+        "#,
+        job_work_id
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to check job work movements.")?;
+    if has_movements {
+        return Err(JobWorkError::InvalidState(
+            "Goods have already moved for this job work; cancel it instead".to_string(),
+        ));
+    }
+
+    sqlx::query!(
+        "UPDATE job_works SET deleted_at = NOW(), modified_by = $2 WHERE id = $1",
+        job_work_id,
+        user.id
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to delete job work in the database.")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to delete a job work.")?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn non_empty<'a>(value: &'a str, message: &str) -> Result<&'a str, JobWorkError> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(JobWorkError::ValidationError(message.to_string()));
+    }
+    Ok(value)
+}
+
+/// Moves an in-progress job work to `to_status`
+async fn change_status(
+    transaction: &mut Transaction<'_, Postgres>,
+    user: &CurrentUser,
+    job_work_id: Uuid,
+    to_status: JobWorkStatus,
+    notes: &str,
+    with_pending_goods: bool,
+) -> Result<(), JobWorkError> {
+    sqlx::query!(
+        r#"
+        UPDATE job_works
+        SET status = $2, status_changed_at = NOW(), status_changed_by = $3, status_notes = $4,
+            modified_by = $3
+        WHERE id = $1
+        "#,
+        job_work_id,
+        to_status.to_string(),
+        user.id,
+        notes
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to update job work status.")?;
+
+    insert_status_change_in_db(
+        transaction,
+        user,
+        job_work_id,
+        Some(JobWorkStatus::InProgress),
+        to_status,
+        Some(notes),
+        with_pending_goods,
+    )
+    .await
+    .context("Failed to insert job work status history in the database.")?;
+
+    Ok(())
+}
+
+/// Locks a job work the caller may manage and returns its start date
+async fn lock_in_progress_job_work(
+    transaction: &mut Transaction<'_, Postgres>,
+    user: &CurrentUser,
+    job_work_id: Uuid,
+) -> Result<NaiveDate, JobWorkError> {
+    let job_work = sqlx::query!(
+        r#"
+        SELECT status, start_date, warehouse_id FROM job_works
+        WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+        FOR UPDATE
+        "#,
+        job_work_id,
+        user.company_id
+    )
+    .fetch_optional(&mut **transaction)
+    .await
+    .context("Failed to fetch job work from database.")?
+    .ok_or(JobWorkError::NotFound)?;
+
+    if !user.is_admin() && user.warehouse_id != Some(job_work.warehouse_id) {
+        return Err(JobWorkError::Forbidden);
+    }
+    if job_work.status != "in_progress" {
+        return Err(JobWorkError::InvalidState(format!(
+            "Job work is {} and can no longer be changed",
+            job_work.status
+        )));
+    }
+
+    Ok(job_work.start_date)
+}
+
+async fn replace_raw_materials_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    company_id: Uuid,
+    job_work_id: Uuid,
+    lines: &[NewRawMaterial],
+) -> Result<(), JobWorkError> {
+    let existing = sqlx::query!(
+        r#"
+        SELECT id, product_id, COALESCE(dispatched_quantity, 0) as "dispatched_quantity!"
+        FROM job_work_raw_materials
+        WHERE job_work_id = $1
+        FOR UPDATE
+        "#,
+        job_work_id
+    )
+    .fetch_all(&mut **transaction)
+    .await
+    .context("Failed to fetch job work raw materials from database.")?;
+
+    for current in &existing {
+        let replacement = lines.iter().find(|l| l.product_id == current.product_id);
+        match replacement {
+            None if current.dispatched_quantity > Decimal::ZERO => {
+                return Err(JobWorkError::InvalidState(format!(
+                    "Raw material {} has {} already dispatched and cannot be removed",
+                    current.product_id, current.dispatched_quantity
+                )));
+            }
+            None => {
+                sqlx::query!(
+                    "DELETE FROM job_work_raw_materials WHERE id = $1",
+                    current.id
+                )
+                .execute(&mut **transaction)
+                .await
+                .context("Failed to delete job work raw material.")?;
+            }
+            // Going below what already moved leaves the difference as excess
+            Some(line) => {
+                sqlx::query!(
+                    "UPDATE job_work_raw_materials SET required_quantity = $2 WHERE id = $1",
+                    current.id,
+                    line.required_quantity
+                )
+                .execute(&mut **transaction)
+                .await
+                .context("Failed to update job work raw material.")?;
+            }
+        }
+    }
+
+    for line in lines {
+        if !existing.iter().any(|e| e.product_id == line.product_id) {
+            insert_raw_material_in_db(transaction, company_id, job_work_id, line)
+                .await
+                .context("Failed to insert job work raw material in the database.")?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn replace_finished_goods_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    company_id: Uuid,
+    job_work_id: Uuid,
+    lines: &[NewFinishedGood],
+) -> Result<(), JobWorkError> {
+    let existing = sqlx::query!(
+        r#"
+        SELECT id, product_id, COALESCE(received_quantity, 0) as "received_quantity!"
+        FROM job_work_finished_goods
+        WHERE job_work_id = $1
+        FOR UPDATE
+        "#,
+        job_work_id
+    )
+    .fetch_all(&mut **transaction)
+    .await
+    .context("Failed to fetch job work finished goods from database.")?;
+
+    for current in &existing {
+        let replacement = lines.iter().find(|l| l.product_id == current.product_id);
+        match replacement {
+            None if current.received_quantity > Decimal::ZERO => {
+                return Err(JobWorkError::InvalidState(format!(
+                    "Finished good {} has {} already received and cannot be removed",
+                    current.product_id, current.received_quantity
+                )));
+            }
+            None => {
+                sqlx::query!(
+                    "DELETE FROM job_work_finished_goods WHERE id = $1",
+                    current.id
+                )
+                .execute(&mut **transaction)
+                .await
+                .context("Failed to delete job work finished good.")?;
+            }
+            // Going below what already moved leaves the difference as excess
+            Some(line) => {
+                sqlx::query!(
+                    "UPDATE job_work_finished_goods SET expected_quantity = $2 WHERE id = $1",
+                    current.id,
+                    line.expected_quantity
+                )
+                .execute(&mut **transaction)
+                .await
+                .context("Failed to update job work finished good.")?;
+            }
+        }
+    }
+
+    for line in lines {
+        if !existing.iter().any(|e| e.product_id == line.product_id) {
+            insert_finished_good_in_db(transaction, company_id, job_work_id, line)
+                .await
+                .context("Failed to insert job work finished good in the database.")?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,300 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{Datelike, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::CurrentUser;
+
+// ERROR
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, thiserror::Error)]
+pub enum LedgerError {
+    #[error("{0}")]
+    ValidationError(String),
+    #[error("Partner not found")]
+    NotFound,
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl IntoResponse for LedgerError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            Self::ValidationError(_) => StatusCode::BAD_REQUEST,
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+        .into_response()
+    }
+}
+
+// READ
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LedgerEntry {
+    pub entry_date: NaiveDate,
+    pub entry_type: String,
+    pub source_id: Uuid,
+    pub reference_number: Option<String>,
+    pub debit: Decimal,
+    pub credit: Decimal,
+    pub balance: Decimal,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PartnerStatement {
+    pub partner_id: Uuid,
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    pub opening_balance: Decimal,
+    pub total_debit: Decimal,
+    pub total_credit: Decimal,
+    pub closing_balance: Decimal,
+    pub entries: Vec<LedgerEntry>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct AgeingBuckets {
+    pub days_0_30: Decimal,
+    pub days_31_60: Decimal,
+    pub days_61_90: Decimal,
+    pub days_90_plus: Decimal,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PartnerOutstanding {
+    pub partner_id: Uuid,
+    pub as_of: NaiveDate,
+    pub total_debit: Decimal,
+    pub total_credit: Decimal,
+    pub outstanding_balance: Decimal,
+    pub ageing: AgeingBuckets,
+}
+
+#[derive(Deserialize)]
+pub struct StatementQuery {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+}
+
+#[derive(Deserialize)]
+pub struct OutstandingQuery {
+    as_of: Option<NaiveDate>,
+}
+
+struct LedgerRow {
+    entry_date: NaiveDate,
+    entry_type: String,
+    source_id: Uuid,
+    reference_number: Option<String>,
+    debit: Decimal,
+    credit: Decimal,
+}
+
+pub async fn get_partner_statement(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(partner_id): Path<Uuid>,
+    Query(query): Query<StatementQuery>,
+) -> Result<Json<PartnerStatement>, LedgerError> {
+    let to_date = query.to.unwrap_or_else(|| Utc::now().date_naive());
+    let from_date = query.from.unwrap_or_else(|| financial_year_start(to_date));
+    if from_date > to_date {
+        return Err(LedgerError::ValidationError(
+            "`from` must not be after `to`".to_string(),
+        ));
+    }
+
+    ensure_partner_exists(&db_pool, user.company_id, partner_id).await?;
+
+    let opening_balance =
+        fetch_balance_before_from_db(&db_pool, user.company_id, partner_id, from_date)
+            .await
+            .context("Failed to fetch opening balance from database.")?;
+    let rows = fetch_ledger_rows_from_db(
+        &db_pool,
+        user.company_id,
+        partner_id,
+        Some(from_date),
+        to_date,
+    )
+    .await
+    .context("Failed to fetch ledger entries from database.")?;
+
+    let mut balance = opening_balance;
+    let mut total_debit = Decimal::ZERO;
+    let mut total_credit = Decimal::ZERO;
+    let entries = rows
+        .into_iter()
+        .map(|row| {
+            balance += row.debit - row.credit;
+            total_debit += row.debit;
+            total_credit += row.credit;
+            LedgerEntry {
+                entry_date: row.entry_date,
+                entry_type: row.entry_type,
+                source_id: row.source_id,
+                reference_number: row.reference_number,
+                debit: row.debit,
+                credit: row.credit,
+                balance,
+            }
+        })
+        .collect();
+
+    Ok(Json(PartnerStatement {
+        partner_id,
+        from_date,
+        to_date,
+        opening_balance,
+        total_debit,
+        total_credit,
+        closing_balance: balance,
+        entries,
+    }))
+}
+
+pub async fn get_partner_outstanding(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(partner_id): Path<Uuid>,
+    Query(query): Query<OutstandingQuery>,
+) -> Result<Json<PartnerOutstanding>, LedgerError> {
+    let as_of = query.as_of.unwrap_or_else(|| Utc::now().date_naive());
+
+    ensure_partner_exists(&db_pool, user.company_id, partner_id).await?;
+
+    let rows = fetch_ledger_rows_from_db(&db_pool, user.company_id, partner_id, None, as_of)
+        .await
+        .context("Failed to fetch ledger entries from database.")?;
+
+    let total_debit: Decimal = rows.iter().map(|r| r.debit).sum();
+    let total_credit: Decimal = rows.iter().map(|r| r.credit).sum();
+    let ageing = age_open_debits(&rows, total_credit, as_of);
+
+    Ok(Json(PartnerOutstanding {
+        partner_id,
+        as_of,
+        total_debit,
+        total_credit,
+        outstanding_balance: total_debit - total_credit,
+        ageing,
+    }))
+}
+
+/// Settles credits against the oldest debits first and buckets whatever
+/// remains unpaid by its age on `as_of`.
+fn age_open_debits(rows: &[LedgerRow], total_credit: Decimal, as_of: NaiveDate) -> AgeingBuckets {
+    let mut unapplied_credit = total_credit;
+    let mut ageing = AgeingBuckets::default();
+
+    for row in rows.iter().filter(|r| r.debit > Decimal::ZERO) {
+        let settled = row.debit.min(unapplied_credit);
+        unapplied_credit -= settled;
+        let open = row.debit - settled;
+        if open.is_zero() {
+            continue;
+        }
+
+        let bucket = match (as_of - row.entry_date).num_days() {
+            ..=30 => &mut ageing.days_0_30,
+            31..=60 => &mut ageing.days_31_60,
+            61..=90 => &mut ageing.days_61_90,
+            _ => &mut ageing.days_90_plus,
+        };
+        *bucket += open;
+    }
+
+    ageing
+}
+
+pub(crate) fn financial_year_start(date: NaiveDate) -> NaiveDate {
+    let year = if date.month() >= 4 {
+        date.year()
+    } else {
+        date.year() - 1
+    };
+    NaiveDate::from_ymd_opt(year, 4, 1).expect("1st April is always a valid date")
+}
+
+async fn ensure_partner_exists(
+    db_pool: &PgPool,
+    company_id: Uuid,
+    partner_id: Uuid,
+) -> Result<(), LedgerError> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT id FROM partners
+        WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+        "#,
+        partner_id,
+        company_id
+    )
+    .fetch_optional(db_pool)
+    .await
+    .context("Failed to fetch partner from database.")?
+    .ok_or(LedgerError::NotFound)?;
+
+    Ok(())
+}
+
+async fn fetch_balance_before_from_db(
+    db_pool: &PgPool,
+    company_id: Uuid,
+    partner_id: Uuid,
+    before: NaiveDate,
+) -> Result<Decimal, sqlx::Error> {
+    let balance = sqlx::query_scalar!(
+        r#"
+        SELECT COALESCE(SUM(debit - credit), 0) as "balance!" FROM partner_ledger_entries
+        WHERE company_id = $1 AND partner_id = $2 AND entry_date < $3
+        "#,
+        company_id,
+        partner_id,
+        before
+    )
+    .fetch_one(db_pool)
+    .await?;
+
+    Ok(balance)
+}
+
+async fn fetch_ledger_rows_from_db(
+    db_pool: &PgPool,
+    company_id: Uuid,
+    partner_id: Uuid,
+    from_date: Option<NaiveDate>,
+    to_date: NaiveDate,
+) -> Result<Vec<LedgerRow>, sqlx::Error> {
+    let rows = sqlx::query_as!(
+        LedgerRow,
+        r#"
+        SELECT entry_date as "entry_date!", entry_type as "entry_type!", source_id as "source_id!",
+               reference_number, debit as "debit!", credit as "credit!"
+        FROM partner_ledger_entries
+        WHERE company_id = $1 AND partner_id = $2
+            AND ($3::DATE IS NULL OR entry_date >= $3) AND entry_date <= $4
+        ORDER BY entry_date, created_at, debit DESC
+        "#,
+        company_id,
+        partner_id,
+        from_date,
+        to_date
+    )
+    .fetch_all(db_pool)
+    .await?;
+
+    Ok(rows)
+}
@@ -0,0 +1,59 @@
+//! Sorting and keyset pagination shared by list endpoints.
+
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(ascii_case_insensitive)]
+pub enum SortOrder {
+    ASC,
+    DESC,
+}
+
+impl SortOrder {
+    pub fn parse(val: Option<String>, default: Self) -> Self {
+        val.as_deref()
+            .map_or(Ok(default), |s| s.parse())
+            .unwrap_or(default)
+    }
+
+    /// Comparison that selects the rows after a cursor in this order
+    pub fn after(self) -> &'static str {
+        match self {
+            Self::ASC => ">",
+            Self::DESC => "<",
+        }
+    }
+}
+
+/// Position after the last row of a page: the sort column's value as text
+/// and the row id, which breaks ties between rows sharing that value.
+/// Encoded as `<value>,<id>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor {
+    pub value: String,
+    pub id: Uuid,
+}
+
+impl Cursor {
+    pub fn new(value: impl Into<String>, id: Uuid) -> Self {
+        Self {
+            value: value.into(),
+            id,
+        }
+    }
+
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid cursor '{raw}'");
+        let (value, id) = raw.rsplit_once(',').ok_or_else(invalid)?;
+        let id = id.parse().map_err(|_| invalid())?;
+        Ok(Self::new(value, id))
+    }
+}
+
+impl std::fmt::Display for Cursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{}", self.value, self.id)
+    }
+}
@@ -0,0 +1,145 @@
+//! Checks shared by handlers that move stock or take orders: which warehouse
+//! the caller works in and whether the warehouses, partners and products they
+//! name exist.
+
+use std::collections::HashSet;
+
+use anyhow::Context;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::auth::CurrentUser;
+
+// ERROR
+// -------------------------------------------------------------------------------------
+
+/// Converted into each route's own error type, which decides the message
+/// staff see when refused a warehouse.
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum LookupError {
+    #[error("{0}")]
+    ValidationError(String),
+    #[error("Staff can only work in their own warehouse")]
+    Forbidden,
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+// READ
+// -------------------------------------------------------------------------------------
+
+/// Admins work in the requested warehouse or else their current one; staff
+/// only ever in their own.
+pub(crate) fn working_warehouse(
+    user: &CurrentUser,
+    requested: Option<Uuid>,
+) -> Result<Uuid, LookupError> {
+    if user.is_admin() {
+        return requested
+            .or(user.warehouse_id)
+            .ok_or_else(|| LookupError::ValidationError("Warehouse is required".to_string()));
+    }
+    match (requested, user.warehouse_id) {
+        (_, None) => Err(LookupError::Forbidden),
+        (Some(requested), Some(own)) if requested != own => Err(LookupError::Forbidden),
+        (_, Some(own)) => Ok(own),
+    }
+}
+
+pub(crate) async fn fetch_partner_type(
+    transaction: &mut Transaction<'_, Postgres>,
+    company_id: Uuid,
+    partner_id: Uuid,
+) -> Result<Option<String>, LookupError> {
+    let partner_type = sqlx::query_scalar!(
+        r#"
+        SELECT partner_type FROM partners
+        WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+        "#,
+        partner_id,
+        company_id
+    )
+    .fetch_optional(&mut **transaction)
+    .await
+    .context("Failed to fetch partner from database.")?;
+
+    Ok(partner_type)
+}
+
+/// Refuses a partner that is missing or of another type, naming the expected
+/// type (e.g. "Customer") in the message.
+pub(crate) async fn ensure_partner_type(
+    transaction: &mut Transaction<'_, Postgres>,
+    company_id: Uuid,
+    partner_id: Uuid,
+    partner_type: &str,
+) -> Result<(), LookupError> {
+    let actual_type = fetch_partner_type(transaction, company_id, partner_id)
+        .await?
+        .ok_or_else(|| LookupError::ValidationError(format!("{} does not exist", partner_type)))?;
+
+    if actual_type != partner_type {
+        return Err(LookupError::ValidationError(format!(
+            "Partner {} is a {}, not a {}",
+            partner_id, actual_type, partner_type
+        )));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn ensure_warehouses_exist(
+    transaction: &mut Transaction<'_, Postgres>,
+    company_id: Uuid,
+    warehouse_ids: &[Uuid],
+) -> Result<(), LookupError> {
+    let found: HashSet<Uuid> = sqlx::query_scalar!(
+        r#"
+        SELECT id FROM warehouses
+        WHERE id = ANY($1) AND company_id = $2 AND deleted_at IS NULL
+        "#,
+        warehouse_ids,
+        company_id
+    )
+    .fetch_all(&mut **transaction)
+    .await
+    .context("Failed to fetch warehouses from database.")?
+    .into_iter()
+    .collect();
+
+    if warehouse_ids.iter().any(|id| !found.contains(id)) {
+        return Err(LookupError::ValidationError(
+            "Warehouse does not exist".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn ensure_products_exist(
+    transaction: &mut Transaction<'_, Postgres>,
+    company_id: Uuid,
+    product_ids: &[Uuid],
+) -> Result<(), LookupError> {
+    let found: HashSet<Uuid> = sqlx::query_scalar!(
+        r#"
+        SELECT id FROM products
+        WHERE id = ANY($1) AND company_id = $2 AND deleted_at IS NULL
+        "#,
+        product_ids,
+        company_id
+    )
+    .fetch_all(&mut **transaction)
+    .await
+    .context("Failed to fetch products from database.")?
+    .into_iter()
+    .collect();
+
+    match product_ids.iter().find(|id| !found.contains(id)) {
+        Some(missing) => Err(LookupError::ValidationError(format!(
+            "Product {} does not exist",
+            missing
+        ))),
+        None => Ok(()),
+    }
+}
@@ -1,2 +1,17 @@
+pub mod attention;
+pub mod commissions;
 pub mod companies;
+pub mod dispatch_sessions;
+pub mod dispatches;
+pub mod gst;
 pub mod healthcheck;
+pub mod invoices;
+pub mod job_work_analytics;
+pub mod job_works;
+pub mod ledger;
+pub mod listing;
+pub(crate) mod lookups;
+pub mod payments;
+pub mod quotations;
+pub mod receipts;
+pub mod sales_orders;
@@ -0,0 +1,680 @@
+use std::{collections::HashSet, sync::Arc};
+
+use anyhow::Context;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+use crate::auth::CurrentUser;
+
+// ERROR
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, thiserror::Error)]
+pub enum PaymentError {
+    #[error("{0}")]
+    ValidationError(String),
+    #[error("{0}")]
+    InvalidState(String),
+    #[error("Payment not found")]
+    NotFound,
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl IntoResponse for PaymentError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            Self::ValidationError(_) => StatusCode::BAD_REQUEST,
+            Self::InvalidState(_) => StatusCode::CONFLICT,
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+        .into_response()
+    }
+}
+
+// CREATE
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString, Deserialize, Serialize)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentMode {
+    Cash,
+    Upi,
+    BankTransfer,
+    Cheque,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString, Deserialize, Serialize)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ClearingStatus {
+    Pending,
+    Cleared,
+    Bounced,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewPayment {
+    partner_id: Uuid,
+    payment_date: Option<NaiveDate>,
+    amount: Decimal,
+    payment_mode: PaymentMode,
+    reference_number: Option<String>,
+    cheque_number: Option<String>,
+    cheque_date: Option<NaiveDate>,
+    bank_name: Option<String>,
+    notes: Option<String>,
+    #[serde(default)]
+    allocations: Vec<NewAllocation>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewAllocation {
+    sales_order_id: Option<Uuid>,
+    dispatch_id: Option<Uuid>,
+    amount: Decimal,
+}
+
+impl NewPayment {
+    fn validate(&self) -> Result<(), String> {
+        if self.amount <= Decimal::ZERO {
+            return Err("Payment amount must be greater than zero".to_string());
+        }
+        if self.payment_mode == PaymentMode::Cheque && self.cheque_number.is_none() {
+            return Err("Cheque number is required for cheque payments".to_string());
+        }
+
+        let mut targets = HashSet::new();
+        for allocation in &self.allocations {
+            let target = match (allocation.sales_order_id, allocation.dispatch_id) {
+                (Some(id), None) | (None, Some(id)) => id,
+                _ => {
+                    return Err(
+                        "Each allocation needs exactly one of sales_order_id or dispatch_id"
+                            .to_string(),
+                    )
+                }
+            };
+            if allocation.amount <= Decimal::ZERO {
+                return Err("Allocation amount must be greater than zero".to_string());
+            }
+            if !targets.insert(target) {
+                return Err("The same order or invoice is allocated more than once".to_string());
+            }
+        }
+
+        let allocated: Decimal = self.allocations.iter().map(|a| a.amount).sum();
+        if allocated > self.amount {
+            return Err("Allocated amount exceeds the payment amount".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+pub async fn create_payment(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Json(new_payment): Json<NewPayment>,
+) -> Result<(StatusCode, Json<Uuid>), PaymentError> {
+    new_payment
+        .validate()
+        .map_err(PaymentError::ValidationError)?;
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    let partner_exists = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM partners WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+        ) as "exists!"
+        "#,
+        new_payment.partner_id,
+        user.company_id
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to fetch partner from database.")?;
+    if !partner_exists {
+        return Err(PaymentError::ValidationError(
+            "Partner does not exist".to_string(),
+        ));
+    }
+
+    for allocation in &new_payment.allocations {
+        check_allocation_target(&mut transaction, &user, &new_payment, allocation).await?;
+    }
+
+    let payment_id = insert_payment_in_db(&mut transaction, &user, &new_payment)
+        .await
+        .context("Failed to insert payment in the database.")?;
+    for allocation in &new_payment.allocations {
+        insert_allocation_in_db(&mut transaction, &user, payment_id, allocation)
+            .await
+            .context("Failed to insert payment allocation in the database.")?;
+    }
+    insert_event_in_db(&mut transaction, &user, payment_id, "recorded", None)
+        .await
+        .context("Failed to insert payment event in the database.")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to store a new payment.")?;
+
+    Ok((StatusCode::CREATED, Json(payment_id)))
+}
+
+async fn check_allocation_target(
+    transaction: &mut Transaction<'_, Postgres>,
+    user: &CurrentUser,
+    new_payment: &NewPayment,
+    allocation: &NewAllocation,
+) -> Result<(), PaymentError> {
+    // Serialise allocations against the same document
+    if let Some(sales_order_id) = allocation.sales_order_id {
+        sqlx::query!(
+            "SELECT id FROM sales_orders WHERE id = $1 FOR UPDATE",
+            sales_order_id
+        )
+        .fetch_optional(&mut **transaction)
+        .await
+        .context("Failed to lock sales order.")?;
+    }
+    if let Some(dispatch_id) = allocation.dispatch_id {
+        sqlx::query!(
+            "SELECT id FROM goods_dispatches WHERE id = $1 FOR UPDATE",
+            dispatch_id
+        )
+        .fetch_optional(&mut **transaction)
+        .await
+        .context("Failed to lock dispatch.")?;
+    }
+
+    let document_id = allocation.sales_order_id.or(allocation.dispatch_id);
+    let outstanding = sqlx::query_scalar!(
+        r#"
+        SELECT outstanding_amount as "outstanding_amount!" FROM receivable_documents
+        WHERE document_id = $1 AND company_id = $2 AND partner_id = $3
+        "#,
+        document_id,
+        user.company_id,
+        new_payment.partner_id
+    )
+    .fetch_optional(&mut **transaction)
+    .await
+    .context("Failed to fetch receivable document from database.")?
+    .ok_or_else(|| {
+        PaymentError::ValidationError(
+            "Allocation target is not an open order or invoice of this partner".to_string(),
+        )
+    })?;
+
+    if allocation.amount > outstanding {
+        return Err(PaymentError::ValidationError(format!(
+            "Allocation of {} exceeds the outstanding amount of {}",
+            allocation.amount, outstanding
+        )));
+    }
+
+    Ok(())
+}
+
+async fn insert_payment_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    user: &CurrentUser,
+    new_payment: &NewPayment,
+) -> Result<Uuid, sqlx::Error> {
+    // Cheques only count as cleared once the bank confirms them
+    let (clearing_status, cleared_at) = match new_payment.payment_mode {
+        PaymentMode::Cheque => (ClearingStatus::Pending, None),
+        _ => (ClearingStatus::Cleared, Some(Utc::now())),
+    };
+
+    let id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO payments (company_id, partner_id, payment_date, amount, payment_mode, reference_number,
+                              cheque_number, cheque_date, bank_name, clearing_status, cleared_at, notes, created_by)
+        VALUES ($1, $2, COALESCE($3, CURRENT_DATE), $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+        RETURNING id
+        "#,
+        user.company_id,
+        new_payment.partner_id,
+        new_payment.payment_date,
+        new_payment.amount,
+        new_payment.payment_mode.to_string(),
+        new_payment.reference_number,
+        new_payment.cheque_number,
+        new_payment.cheque_date,
+        new_payment.bank_name,
+        clearing_status.to_string(),
+        cleared_at,
+        new_payment.notes,
+        user.id
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    Ok(id)
+}
+
+async fn insert_allocation_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    user: &CurrentUser,
+    payment_id: Uuid,
+    allocation: &NewAllocation,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO payment_allocations (company_id, payment_id, sales_order_id, dispatch_id, amount)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        user.company_id,
+        payment_id,
+        allocation.sales_order_id,
+        allocation.dispatch_id,
+        allocation.amount
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+}
+
+async fn insert_event_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    user: &CurrentUser,
+    payment_id: Uuid,
+    event_type: &str,
+    notes: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO payment_events (company_id, payment_id, event_type, notes, created_by)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        user.company_id,
+        payment_id,
+        event_type,
+        notes,
+        user.id
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+}
+
+// READ
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Payment {
+    pub id: Uuid,
+    pub payment_number: String,
+    pub partner_id: Uuid,
+    pub payment_date: NaiveDate,
+    pub amount: Decimal,
+    pub payment_mode: String,
+    pub reference_number: Option<String>,
+    pub cheque_number: Option<String>,
+    pub cheque_date: Option<NaiveDate>,
+    pub bank_name: Option<String>,
+    pub clearing_status: String,
+    pub cleared_at: Option<DateTime<Utc>>,
+    pub is_reversed: bool,
+    pub reversed_at: Option<DateTime<Utc>>,
+    pub reversed_by: Option<Uuid>,
+    pub reversal_reason: Option<String>,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub allocations: Vec<PaymentAllocation>,
+    pub events: Vec<PaymentEvent>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PaymentAllocation {
+    pub id: Uuid,
+    pub sales_order_id: Option<Uuid>,
+    pub dispatch_id: Option<Uuid>,
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PaymentEvent {
+    pub event_type: String,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReceivableDocument {
+    pub document_type: String,
+    pub document_id: Uuid,
+    pub reference_number: Option<String>,
+    pub document_date: NaiveDate,
+    pub document_amount: Decimal,
+    pub paid_amount: Decimal,
+    pub outstanding_amount: Decimal,
+}
+
+pub async fn get_payment(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(payment_id): Path<Uuid>,
+) -> Result<Json<Payment>, PaymentError> {
+    let payment = fetch_payment_from_db(&db_pool, user.company_id, payment_id)
+        .await
+        .context("Failed to fetch payment from database.")?
+        .ok_or(PaymentError::NotFound)?;
+
+    Ok(Json(payment))
+}
+
+async fn fetch_payment_from_db(
+    db_pool: &PgPool,
+    company_id: Uuid,
+    payment_id: Uuid,
+) -> Result<Option<Payment>, sqlx::Error> {
+    let Some(row) = sqlx::query!(
+        r#"
+        SELECT id, payment_number, partner_id, payment_date, amount, payment_mode, reference_number,
+               cheque_number, cheque_date, bank_name, clearing_status, cleared_at, is_reversed,
+               reversed_at, reversed_by, reversal_reason, notes, created_at, created_by
+        FROM payments
+        WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+        "#,
+        payment_id,
+        company_id
+    )
+    .fetch_optional(db_pool)
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    let allocations = sqlx::query_as!(
+        PaymentAllocation,
+        r#"
+        SELECT id, sales_order_id, dispatch_id, amount FROM payment_allocations
+        WHERE payment_id = $1
+        ORDER BY created_at
+        "#,
+        payment_id
+    )
+    .fetch_all(db_pool)
+    .await?;
+
+    let events = sqlx::query_as!(
+        PaymentEvent,
+        r#"
+        SELECT event_type, notes, created_at, created_by FROM payment_events
+        WHERE payment_id = $1
+        ORDER BY created_at
+        "#,
+        payment_id
+    )
+    .fetch_all(db_pool)
+    .await?;
+
+    Ok(Some(Payment {
+        id: row.id,
+        payment_number: row.payment_number,
+        partner_id: row.partner_id,
+        payment_date: row.payment_date,
+        amount: row.amount,
+        payment_mode: row.payment_mode,
+        reference_number: row.reference_number,
+        cheque_number: row.cheque_number,
+        cheque_date: row.cheque_date,
+        bank_name: row.bank_name,
+        clearing_status: row.clearing_status,
+        cleared_at: row.cleared_at,
+        is_reversed: row.is_reversed,
+        reversed_at: row.reversed_at,
+        reversed_by: row.reversed_by,
+        reversal_reason: row.reversal_reason,
+        notes: row.notes,
+        created_at: row.created_at,
+        created_by: row.created_by,
+        allocations,
+        events,
+    }))
+}
+
+pub async fn get_partner_receivables(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(partner_id): Path<Uuid>,
+) -> Result<Json<Vec<ReceivableDocument>>, PaymentError> {
+    let documents = sqlx::query_as!(
+        ReceivableDocument,
+        r#"
+        SELECT document_type as "document_type!", document_id as "document_id!", reference_number,
+               document_date as "document_date!", document_amount as "document_amount!",
+               paid_amount as "paid_amount!", outstanding_amount as "outstanding_amount!"
+        FROM receivable_documents
+        WHERE company_id = $1 AND partner_id = $2 AND outstanding_amount > 0
+        ORDER BY document_date, reference_number
+        "#,
+        user.company_id,
+        partner_id
+    )
+    .fetch_all(db_pool.as_ref())
+    .await
+    .context("Failed to fetch receivable documents from database.")?;
+
+    Ok(Json(documents))
+}
+
+// UPDATE
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReversalRequest {
+    reason: String,
+}
+
+struct PaymentState {
+    payment_mode: String,
+    clearing_status: String,
+    is_reversed: bool,
+}
+
+pub async fn clear_payment(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(payment_id): Path<Uuid>,
+) -> Result<StatusCode, PaymentError> {
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    let state = lock_payment(&mut transaction, user.company_id, payment_id).await?;
+    if state.is_reversed || state.clearing_status != ClearingStatus::Pending.to_string() {
+        return Err(PaymentError::InvalidState(
+            "Only pending cheques can be cleared".to_string(),
+        ));
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE payments SET clearing_status = 'cleared', cleared_at = NOW(), modified_by = $2
+        WHERE id = $1
+        "#,
+        payment_id,
+        user.id
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to clear payment.")?;
+    insert_event_in_db(&mut transaction, &user, payment_id, "cleared", None)
+        .await
+        .context("Failed to insert payment event in the database.")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to clear a payment.")?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn bounce_payment(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(payment_id): Path<Uuid>,
+    Json(request): Json<ReversalRequest>,
+) -> Result<StatusCode, PaymentError> {
+    let reason = non_empty_reason(&request)?;
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    let state = lock_payment(&mut transaction, user.company_id, payment_id).await?;
+    if state.payment_mode != PaymentMode::Cheque.to_string() {
+        return Err(PaymentError::InvalidState(
+            "Only cheque payments can bounce".to_string(),
+        ));
+    }
+    if state.is_reversed {
+        return Err(PaymentError::InvalidState(
+            "Payment is already reversed".to_string(),
+        ));
+    }
+
+    sqlx::query!(
+        "UPDATE payments SET clearing_status = 'bounced' WHERE id = $1",
+        payment_id
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to mark payment as bounced.")?;
+    reverse_payment_in_db(&mut transaction, &user, payment_id, reason)
+        .await
+        .context("Failed to reverse payment.")?;
+    insert_event_in_db(&mut transaction, &user, payment_id, "bounced", Some(reason))
+        .await
+        .context("Failed to insert payment event in the database.")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to bounce a payment.")?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn reverse_payment(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(payment_id): Path<Uuid>,
+    Json(request): Json<ReversalRequest>,
+) -> Result<StatusCode, PaymentError> {
+    let reason = non_empty_reason(&request)?;
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    let state = lock_payment(&mut transaction, user.company_id, payment_id).await?;
+    if state.is_reversed {
+        return Err(PaymentError::InvalidState(
+            "Payment is already reversed".to_string(),
+        ));
+    }
+
+    reverse_payment_in_db(&mut transaction, &user, payment_id, reason)
+        .await
+        .context("Failed to reverse payment.")?;
+    insert_event_in_db(
+        &mut transaction,
+        &user,
+        payment_id,
+        "reversed",
+        Some(reason),
+    )
+    .await
+    .context("Failed to insert payment event in the database.")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to reverse a payment.")?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn non_empty_reason(request: &ReversalRequest) -> Result<&str, PaymentError> {
+    let reason = request.reason.trim();
+    if reason.is_empty() {
+        return Err(PaymentError::ValidationError(
+            "A reason is required".to_string(),
+        ));
+    }
+    Ok(reason)
+}
+
+async fn lock_payment(
+    transaction: &mut Transaction<'_, Postgres>,
+    company_id: Uuid,
+    payment_id: Uuid,
+) -> Result<PaymentState, PaymentError> {
+    let state = sqlx::query_as!(
+        PaymentState,
+        r#"
+        SELECT payment_mode, clearing_status, is_reversed FROM payments
+        WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+        FOR UPDATE
+        "#,
+        payment_id,
+        company_id
+    )
+    .fetch_optional(&mut **transaction)
+    .await
+    .context("Failed to fetch payment from database.")?
+    .ok_or(PaymentError::NotFound)?;
+
+    Ok(state)
+}
+
+async fn reverse_payment_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    user: &CurrentUser,
+    payment_id: Uuid,
+    reason: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE payments
+        SET is_reversed = true, reversed_at = NOW(), reversed_by = $2, reversal_reason = $3, modified_by = $2
+        WHERE id = $1
+        "#,
+        payment_id,
+        user.id,
+        reason
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,606 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{Days, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::{
+    auth::CurrentUser,
+    pdf::{amount_in_words, money, unit_abbreviation, Column, PdfDocument},
+    routes::{
+        lookups::{ensure_partner_type, ensure_products_exist},
+        sales_orders::{
+            begin_admin_transaction, fetch_summary_from_db, insert_sales_order_item_in_db,
+            insert_status_change_in_db, NewSalesOrderItem, SalesOrderError, SalesOrderStatus,
+            SalesOrderSummary,
+        },
+    },
+};
+
+// ERROR
+// -------------------------------------------------------------------------------------
+
+// Quotations are the stage before a sales order and share `SalesOrderError`.
+
+// CREATE
+// -------------------------------------------------------------------------------------
+
+/// How long a quotation stays open when no `valid_until` is given
+const DEFAULT_VALIDITY_DAYS: u64 = 15;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewQuotation {
+    customer_id: Uuid,
+    agent_id: Option<Uuid>,
+    quotation_date: Option<NaiveDate>,
+    valid_until: Option<NaiveDate>,
+    discount_percentage: Option<Decimal>,
+    terms: Option<String>,
+    notes: Option<String>,
+    items: Vec<NewSalesOrderItem>,
+}
+
+impl NewQuotation {
+    fn validate(&self) -> Result<(), String> {
+        if self.items.is_empty() {
+            return Err("A quotation needs at least one item".to_string());
+        }
+        for item in &self.items {
+            item.validate()?;
+        }
+        if self
+            .discount_percentage
+            .is_some_and(|d| d < Decimal::ZERO || d > Decimal::ONE_HUNDRED)
+        {
+            return Err("Discount must be between 0 and 100 percent".to_string());
+        }
+        if let (Some(quotation_date), Some(valid_until)) = (self.quotation_date, self.valid_until) {
+            if valid_until < quotation_date {
+                return Err("Quotation cannot expire before its date".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+pub async fn create_quotation(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Json(new_quotation): Json<NewQuotation>,
+) -> Result<(StatusCode, Json<QuotationSummary>), SalesOrderError> {
+    new_quotation
+        .validate()
+        .map_err(SalesOrderError::ValidationError)?;
+    let mut transaction = begin_admin_transaction(&db_pool, &user).await?;
+
+    ensure_partner_type(
+        &mut transaction,
+        user.company_id,
+        new_quotation.customer_id,
+        "Customer",
+    )
+    .await?;
+    if let Some(agent_id) = new_quotation.agent_id {
+        ensure_partner_type(&mut transaction, user.company_id, agent_id, "Agent").await?;
+    }
+    let product_ids: Vec<Uuid> = new_quotation.items.iter().map(|i| i.product_id).collect();
+    ensure_products_exist(&mut transaction, user.company_id, &product_ids).await?;
+
+    let quotation_date = new_quotation
+        .quotation_date
+        .unwrap_or_else(|| Utc::now().date_naive());
+    let valid_until = match new_quotation.valid_until {
+        Some(valid_until) => valid_until,
+        None => quotation_date
+            .checked_add_days(Days::new(DEFAULT_VALIDITY_DAYS))
+            .ok_or_else(|| {
+                SalesOrderError::ValidationError(
+                    "Quotation date is too far in the future to default its validity".to_string(),
+                )
+            })?,
+    };
+
+    let quotation_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO quotations (company_id, customer_id, agent_id, quotation_date, valid_until,
+                                discount_percentage, terms, notes, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING id
+        "#,
+        user.company_id,
+        new_quotation.customer_id,
+        new_quotation.agent_id,
+        quotation_date,
+        valid_until,
+        new_quotation.discount_percentage.unwrap_or(Decimal::ZERO),
+        new_quotation.terms,
+        new_quotation.notes,
+        user.id
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to insert quotation in the database.")?;
+
+    for item in &new_quotation.items {
+        // A missing rate is filled from the product master by `auto_populate_unit_rate`
+        sqlx::query!(
+            r#"
+            INSERT INTO quotation_items (company_id, quotation_id, product_id, required_quantity,
+                                         unit_rate, notes)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+            user.company_id,
+            quotation_id,
+            item.product_id,
+            item.required_quantity,
+            item.unit_rate,
+            item.notes
+        )
+        .execute(&mut *transaction)
+        .await
+        .context("Failed to insert quotation item in the database.")?;
+    }
+
+    let summary = fetch_quotation_from_db(&mut transaction, user.company_id, quotation_id)
+        .await
+        .context("Failed to fetch quotation totals from database.")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to store a new quotation.")?;
+
+    Ok((StatusCode::CREATED, Json(summary)))
+}
+
+/// Turns an open quotation into a sales order awaiting approval, carrying the
+/// customer, agent, items, quoted rates and discount over. The order is
+/// fulfilled from the converting admin's warehouse.
+pub async fn convert_quotation(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(quotation_id): Path<Uuid>,
+) -> Result<(StatusCode, Json<SalesOrderSummary>), SalesOrderError> {
+    let mut transaction = begin_admin_transaction(&db_pool, &user).await?;
+
+    let quotation = sqlx::query!(
+        r#"
+        SELECT quotation_number, customer_id, agent_id, discount_percentage, notes, status,
+               valid_until, valid_until < CURRENT_DATE as "is_expired!"
+        FROM quotations
+        WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+        FOR UPDATE
+        "#,
+        quotation_id,
+        user.company_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to fetch quotation from database.")?
+    .ok_or(SalesOrderError::NotFound)?;
+
+    if quotation.status == "converted" {
+        return Err(SalesOrderError::InvalidState(
+            "Quotation has already been converted to a sales order".to_string(),
+        ));
+    }
+    if quotation.is_expired {
+        return Err(SalesOrderError::InvalidState(format!(
+            "Quotation expired on {}",
+            quotation.valid_until
+        )));
+    }
+    ensure_partner_type(
+        &mut transaction,
+        user.company_id,
+        quotation.customer_id,
+        "Customer",
+    )
+    .await?;
+
+    let sales_order_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO sales_orders (company_id, customer_id, agent_id, fulfillment_warehouse_id,
+                                  advance_amount, discount_percentage, notes, created_by)
+        VALUES ($1, $2, $3, $4, 0, $5, $6, $7)
+        RETURNING id
+        "#,
+        user.company_id,
+        quotation.customer_id,
+        quotation.agent_id,
+        user.warehouse_id,
+        quotation.discount_percentage,
+        quotation.notes,
+        user.id
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to insert sales order in the database.")?;
+
+    let items = sqlx::query_as!(
+        NewSalesOrderItem,
+        r#"
+        SELECT product_id, required_quantity, unit_rate, notes
+        FROM quotation_items
+        WHERE quotation_id = $1
+        ORDER BY created_at, id
+        "#,
+        quotation_id
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to fetch quotation items from database.")?;
+    for item in &items {
+        insert_sales_order_item_in_db(&mut transaction, user.company_id, sales_order_id, item)
+            .await
+            .context("Failed to insert sales order item in the database.")?;
+    }
+
+    insert_status_change_in_db(
+        &mut transaction,
+        &user,
+        sales_order_id,
+        None,
+        SalesOrderStatus::ApprovalPending,
+        Some(&format!(
+            "Converted from quotation {}",
+            quotation.quotation_number
+        )),
+        false,
+    )
+    .await
+    .context("Failed to insert sales order status history in the database.")?;
+
+    sqlx::query!(
+        r#"
+        UPDATE quotations
+        SET status = 'converted', converted_sales_order_id = $2, converted_at = NOW(),
+            converted_by = $3, modified_by = $3
+        WHERE id = $1
+        "#,
+        quotation_id,
+        sales_order_id,
+        user.id
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to mark quotation as converted.")?;
+
+    let summary = fetch_summary_from_db(&mut transaction, sales_order_id)
+        .await
+        .context("Failed to fetch sales order totals from database.")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to convert a quotation.")?;
+
+    Ok((StatusCode::CREATED, Json(summary)))
+}
+
+// READ
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct QuotationSummary {
+    pub id: Uuid,
+    pub quotation_number: String,
+    /// `open`, `expired` or `converted`
+    pub status: String,
+    pub customer_id: Uuid,
+    pub agent_id: Option<Uuid>,
+    pub quotation_date: NaiveDate,
+    pub valid_until: NaiveDate,
+    pub is_inter_state: bool,
+    pub subtotal: Decimal,
+    pub discount_percentage: Decimal,
+    pub discount_amount: Decimal,
+    pub taxable_amount: Decimal,
+    pub cgst_amount: Decimal,
+    pub sgst_amount: Decimal,
+    pub igst_amount: Decimal,
+    pub tax_amount: Decimal,
+    pub round_off: Decimal,
+    pub total_amount: Decimal,
+    pub converted_sales_order_id: Option<Uuid>,
+    pub terms: Option<String>,
+    pub notes: Option<String>,
+    pub items: Vec<QuotationItem>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct QuotationItem {
+    pub id: Uuid,
+    pub product_id: Uuid,
+    pub product_name: String,
+    pub measuring_unit: String,
+    pub required_quantity: Decimal,
+    pub unit_rate: Decimal,
+    pub line_total: Decimal,
+    pub hsn_code: Option<String>,
+    pub gst_rate: Decimal,
+    pub taxable_amount: Decimal,
+    pub cgst_amount: Decimal,
+    pub sgst_amount: Decimal,
+    pub igst_amount: Decimal,
+    pub notes: Option<String>,
+}
+
+async fn fetch_quotation_from_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    company_id: Uuid,
+    quotation_id: Uuid,
+) -> Result<QuotationSummary, sqlx::Error> {
+    let quotation = sqlx::query!(
+        r#"
+        SELECT id, quotation_number,
+               CASE WHEN status = 'open' AND valid_until < CURRENT_DATE THEN 'expired'
+                    ELSE status
+               END as "status!",
+               customer_id, agent_id, quotation_date, valid_until, is_inter_state, subtotal,
+               discount_percentage, taxable_amount, cgst_amount, sgst_amount, igst_amount,
+               round_off, total_amount, converted_sales_order_id, terms, notes
+        FROM quotations
+        WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+        "#,
+        quotation_id,
+        company_id
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    let items = sqlx::query_as!(
+        QuotationItem,
+        r#"
+        SELECT qi.id, qi.product_id, p.name as product_name, p.measuring_unit,
+               qi.required_quantity,
+               COALESCE(qi.unit_rate, 0) as "unit_rate!",
+               qi.line_total as "line_total!",
+               qi.hsn_code, qi.gst_rate,
+               tl.taxable_amount as "taxable_amount!",
+               tl.cgst_amount as "cgst_amount!",
+               tl.sgst_amount as "sgst_amount!",
+               tl.igst_amount as "igst_amount!",
+               qi.notes
+        FROM quotation_items qi
+        JOIN products p ON p.id = qi.product_id
+        JOIN quotation_tax_lines tl ON tl.quotation_item_id = qi.id
+        WHERE qi.quotation_id = $1
+        ORDER BY qi.created_at, qi.id
+        "#,
+        quotation_id
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    Ok(QuotationSummary {
+        id: quotation.id,
+        quotation_number: quotation.quotation_number,
+        status: quotation.status,
+        customer_id: quotation.customer_id,
+        agent_id: quotation.agent_id,
+        quotation_date: quotation.quotation_date,
+        valid_until: quotation.valid_until,
+        is_inter_state: quotation.is_inter_state,
+        subtotal: quotation.subtotal,
+        discount_percentage: quotation.discount_percentage,
+        discount_amount: quotation.subtotal - quotation.taxable_amount,
+        taxable_amount: quotation.taxable_amount,
+        cgst_amount: quotation.cgst_amount,
+        sgst_amount: quotation.sgst_amount,
+        igst_amount: quotation.igst_amount,
+        tax_amount: quotation.cgst_amount + quotation.sgst_amount + quotation.igst_amount,
+        round_off: quotation.round_off,
+        total_amount: quotation.total_amount,
+        converted_sales_order_id: quotation.converted_sales_order_id,
+        terms: quotation.terms,
+        notes: quotation.notes,
+        items,
+    })
+}
+
+async fn fetch_quotation(
+    db_pool: &PgPool,
+    company_id: Uuid,
+    quotation_id: Uuid,
+) -> Result<QuotationSummary, SalesOrderError> {
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    fetch_quotation_from_db(&mut transaction, company_id, quotation_id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => SalesOrderError::NotFound,
+            e => SalesOrderError::UnexpectedError(
+                anyhow::Error::new(e).context("Failed to fetch quotation from database."),
+            ),
+        })
+}
+
+pub async fn get_quotation(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(quotation_id): Path<Uuid>,
+) -> Result<Json<QuotationSummary>, SalesOrderError> {
+    let quotation = fetch_quotation(&db_pool, user.company_id, quotation_id).await?;
+    Ok(Json(quotation))
+}
+
+struct ProformaParties {
+    company_name: String,
+    company_address: String,
+    company_gst_number: Option<String>,
+    customer_name: String,
+    customer_address: String,
+    customer_gst_number: Option<String>,
+}
+
+/// Proforma invoice for sharing a quotation with the customer
+pub async fn get_proforma_pdf(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(quotation_id): Path<Uuid>,
+) -> Result<impl IntoResponse, SalesOrderError> {
+    let quotation = fetch_quotation(&db_pool, user.company_id, quotation_id).await?;
+
+    let parties = sqlx::query_as!(
+        ProformaParties,
+        r#"
+        SELECT c.name as company_name,
+               CONCAT_WS(', ', c.address_line1, c.address_line2, c.city, c.state, c.pin_code) as "company_address!",
+               c.gst_number as company_gst_number,
+               COALESCE(NULLIF(p.company_name, ''), CONCAT_WS(' ', p.first_name, p.last_name)) as "customer_name!",
+               CONCAT_WS(', ', p.address_line1, p.address_line2, p.city, p.state, p.pin_code) as "customer_address!",
+               p.gst_number as customer_gst_number
+        FROM companies c
+        JOIN partners p ON p.id = $2
+        WHERE c.id = $1
+        "#,
+        user.company_id,
+        quotation.customer_id
+    )
+    .fetch_one(db_pool.as_ref())
+    .await
+    .context("Failed to fetch quotation parties from database.")?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/pdf".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("inline; filename=\"{}.pdf\"", quotation.quotation_number),
+            ),
+        ],
+        render_proforma_pdf(&quotation, &parties),
+    ))
+}
+
+fn render_proforma_pdf(quotation: &QuotationSummary, parties: &ProformaParties) -> Vec<u8> {
+    let mut doc = PdfDocument::new();
+    doc.title("PROFORMA INVOICE");
+
+    doc.heading(&parties.company_name);
+    doc.text(&parties.company_address);
+    if let Some(gstin) = &parties.company_gst_number {
+        doc.text(&format!("GSTIN: {gstin}"));
+    }
+    doc.rule();
+
+    let half = PdfDocument::CONTENT_WIDTH / 2.0;
+    let halves = [
+        Column::left(PdfDocument::LEFT, half - 10.0),
+        Column::left(PdfDocument::LEFT + half, half),
+    ];
+    doc.row(
+        &halves,
+        &[
+            &format!("Quotation No: {}", quotation.quotation_number),
+            &format!("Date: {}", quotation.quotation_date.format("%d-%m-%Y")),
+        ],
+        true,
+    );
+    doc.row(
+        &halves,
+        &[
+            &format!("To: {}", parties.customer_name),
+            &format!("Valid Until: {}", quotation.valid_until.format("%d-%m-%Y")),
+        ],
+        false,
+    );
+    doc.row(
+        &halves,
+        &[
+            &parties.customer_address,
+            &format!(
+                "GSTIN: {}",
+                parties
+                    .customer_gst_number
+                    .as_deref()
+                    .unwrap_or("Unregistered")
+            ),
+        ],
+        false,
+    );
+    doc.rule();
+
+    let columns = [
+        Column::left(40.0, 18.0),
+        Column::left(60.0, 150.0),
+        Column::left(212.0, 48.0),
+        Column::right(262.0, 62.0),
+        Column::right(326.0, 60.0),
+        Column::right(388.0, 34.0),
+        Column::right(424.0, 131.0),
+    ];
+    doc.row(
+        &columns,
+        &["#", "Description", "HSN", "Qty", "Rate", "GST %", "Amount"],
+        true,
+    );
+    for (index, item) in quotation.items.iter().enumerate() {
+        doc.row(
+            &columns,
+            &[
+                &(index + 1).to_string(),
+                &item.product_name,
+                item.hsn_code.as_deref().unwrap_or("-"),
+                &format!(
+                    "{} {}",
+                    item.required_quantity.normalize(),
+                    unit_abbreviation(&item.measuring_unit)
+                ),
+                &money(item.unit_rate),
+                &item.gst_rate.normalize().to_string(),
+                &money(item.line_total),
+            ],
+            false,
+        );
+    }
+    doc.rule();
+
+    let totals = [Column::left(340.0, 120.0), Column::right(473.0, 82.0)];
+    doc.row(&totals, &["Subtotal", &money(quotation.subtotal)], false);
+    if quotation.discount_amount > Decimal::ZERO {
+        doc.row(
+            &totals,
+            &[
+                &format!("Discount ({}%)", quotation.discount_percentage.normalize()),
+                &money(-quotation.discount_amount),
+            ],
+            false,
+        );
+    }
+    if quotation.is_inter_state {
+        doc.row(&totals, &["IGST", &money(quotation.igst_amount)], false);
+    } else {
+        doc.row(&totals, &["CGST", &money(quotation.cgst_amount)], false);
+        doc.row(&totals, &["SGST", &money(quotation.sgst_amount)], false);
+    }
+    doc.row(&totals, &["Round Off", &money(quotation.round_off)], false);
+    doc.row(&totals, &["Total", &money(quotation.total_amount)], true);
+    doc.rule();
+    doc.text(&amount_in_words(quotation.total_amount));
+
+    if let Some(terms) = &quotation.terms {
+        doc.space(8.0);
+        doc.heading("Terms");
+        for line in terms.lines() {
+            doc.text(line);
+        }
+    }
+    doc.space(8.0);
+    doc.text("This is a proforma invoice and not a demand for payment.");
+
+    doc.finish()
+}
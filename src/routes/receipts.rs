@@ -0,0 +1,1525 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+use crate::{
+    auth::CurrentUser,
+    routes::{
+        dispatches::LinkType,
+        lookups::{ensure_warehouses_exist, working_warehouse, LookupError},
+        sales_orders::refresh_dispatched_quantities_in_db,
+    },
+};
+
+// ERROR
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReceiptError {
+    #[error("{0}")]
+    ValidationError(String),
+    #[error("{0}")]
+    InvalidState(String),
+    #[error("Staff can only receive into their own warehouse")]
+    Forbidden,
+    #[error("Receipt not found")]
+    NotFound,
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl IntoResponse for ReceiptError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            Self::ValidationError(_) => StatusCode::BAD_REQUEST,
+            Self::InvalidState(_) => StatusCode::CONFLICT,
+            Self::Forbidden => StatusCode::FORBIDDEN,
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+        .into_response()
+    }
+}
+
+impl From<LookupError> for ReceiptError {
+    fn from(error: LookupError) -> Self {
+        match error {
+            LookupError::ValidationError(message) => Self::ValidationError(message),
+            LookupError::Forbidden => Self::Forbidden,
+            LookupError::UnexpectedError(error) => Self::UnexpectedError(error),
+        }
+    }
+}
+
+// CREATE
+// -------------------------------------------------------------------------------------
+
+/// One received roll. Without a size the unit waits in `pending_details`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ReceivedRoll {
+    size_quantity: Option<Decimal>,
+    quality_grade: Option<String>,
+    location_description: Option<String>,
+    manufacturing_date: Option<NaiveDate>,
+    notes: Option<String>,
+}
+
+/// A product received either as a bare count of rolls, to be detailed later,
+/// or as the list of rolls with their details
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewReceiptItem {
+    product_id: Uuid,
+    quantity_received: Option<i32>,
+    rolls: Option<Vec<ReceivedRoll>>,
+    notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewReceipt {
+    /// Receiving warehouse; defaults to the user's own
+    warehouse_id: Option<Uuid>,
+    issued_by_partner_id: Uuid,
+    agent_id: Option<Uuid>,
+    link_type: Option<LinkType>,
+    job_work_id: Option<Uuid>,
+    other_reference: Option<String>,
+    receipt_date: Option<NaiveDate>,
+    invoice_number: Option<String>,
+    invoice_amount: Option<Decimal>,
+    transport_details: Option<String>,
+    notes: Option<String>,
+    items: Vec<NewReceiptItem>,
+}
+
+const MAX_ROLLS_PER_ITEM: i32 = 1000;
+
+impl NewReceiptItem {
+    /// The rolls to create, with a roll without details for each counted one
+    fn into_rolls(self) -> Result<Vec<ReceivedRoll>, String> {
+        let rolls = match (self.quantity_received, self.rolls) {
+            (Some(_), Some(_)) => {
+                return Err("Give either `quantity_received` or `rolls`, not both".to_string())
+            }
+            (None, None) => return Err("Give `quantity_received` or `rolls`".to_string()),
+            (Some(count), None) => {
+                if !(1..=MAX_ROLLS_PER_ITEM).contains(&count) {
+                    return Err(format!(
+                        "Quantity received must be between 1 and {MAX_ROLLS_PER_ITEM}"
+                    ));
+                }
+                vec![ReceivedRoll::default(); count as usize]
+            }
+            (None, Some(rolls)) => rolls,
+        };
+        if rolls.is_empty() || rolls.len() > MAX_ROLLS_PER_ITEM as usize {
+            return Err(format!(
+                "An item needs between 1 and {MAX_ROLLS_PER_ITEM} rolls"
+            ));
+        }
+        if rolls
+            .iter()
+            .any(|r| r.size_quantity.is_some_and(|q| q <= Decimal::ZERO))
+        {
+            return Err("Roll size must be positive".to_string());
+        }
+        Ok(rolls)
+    }
+}
+
+impl NewReceipt {
+    fn validate(&self) -> Result<(), String> {
+        if self.items.is_empty() {
+            return Err("A receipt needs at least one item".to_string());
+        }
+        match self.link_type {
+            Some(LinkType::SalesOrder) => {
+                return Err(
+                    "Goods returned against a sales order are received as a sales return"
+                        .to_string(),
+                );
+            }
+            Some(LinkType::JobWork) if self.job_work_id.is_none() => {
+                return Err("A job work receipt needs a job work".to_string());
+            }
+            _ => {}
+        }
+        if self.job_work_id.is_some() && self.link_type != Some(LinkType::JobWork) {
+            return Err("`job_work_id` needs `link_type` job_work".to_string());
+        }
+        if self.invoice_amount.is_some_and(|a| a < Decimal::ZERO) {
+            return Err("Invoice amount cannot be negative".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Records goods received from a partner and creates a stock unit for every
+/// roll in one go. Rolls given with a size go straight into stock; counted
+/// rolls and rolls without a size wait in `pending_details`.
+pub async fn create_receipt(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Json(mut new_receipt): Json<NewReceipt>,
+) -> Result<(StatusCode, Json<Receipt>), ReceiptError> {
+    let warehouse_id = working_warehouse(&user, new_receipt.warehouse_id)?;
+    new_receipt
+        .validate()
+        .map_err(ReceiptError::ValidationError)?;
+    let items = std::mem::take(&mut new_receipt.items)
+        .into_iter()
+        .map(|item| {
+            let (product_id, notes) = (item.product_id, item.notes.clone());
+            item.into_rolls().map(|rolls| (product_id, notes, rolls))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(ReceiptError::ValidationError)?;
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    check_receipt_in_db(
+        &mut transaction,
+        user.company_id,
+        warehouse_id,
+        &new_receipt,
+        &items.iter().map(|(p, _, _)| *p).collect::<Vec<_>>(),
+    )
+    .await?;
+
+    let receipt_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO goods_receipts (company_id, warehouse_id, issued_by_partner_id, agent_id,
+                                    link_type, job_work_id, other_reference, receipt_date,
+                                    invoice_number, invoice_amount, transport_details, notes,
+                                    created_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, COALESCE($8, CURRENT_DATE), $9, $10, $11, $12, $13)
+        RETURNING id
+        "#,
+        user.company_id,
+        warehouse_id,
+        new_receipt.issued_by_partner_id,
+        new_receipt.agent_id,
+        new_receipt.link_type.map(|l| l.to_string()),
+        new_receipt.job_work_id,
+        new_receipt.other_reference,
+        new_receipt.receipt_date,
+        new_receipt.invoice_number,
+        new_receipt.invoice_amount,
+        new_receipt.transport_details,
+        new_receipt.notes,
+        user.id
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to insert receipt in the database.")?;
+
+    for (product_id, notes, rolls) in &items {
+        let item_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO goods_receipt_items (company_id, receipt_id, product_id, quantity_received,
+                                             notes)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+            "#,
+            user.company_id,
+            receipt_id,
+            product_id,
+            rolls.len() as i32,
+            notes.as_deref()
+        )
+        .fetch_one(&mut *transaction)
+        .await
+        .context("Failed to insert receipt item in the database.")?;
+
+        insert_received_units_in_db(
+            &mut transaction,
+            &user,
+            warehouse_id,
+            receipt_id,
+            item_id,
+            *product_id,
+            rolls,
+        )
+        .await
+        .context("Failed to insert received stock units in the database.")?;
+    }
+
+    let receipt = fetch_receipt_from_db(&mut transaction, receipt_id)
+        .await
+        .context("Failed to fetch receipt from database.")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to store a new receipt.")?;
+
+    Ok((StatusCode::CREATED, Json(receipt)))
+}
+
+/// Checks that the warehouse, partners, job work and products exist and fit
+/// together
+async fn check_receipt_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    company_id: Uuid,
+    warehouse_id: Uuid,
+    receipt: &NewReceipt,
+    product_ids: &[Uuid],
+) -> Result<(), ReceiptError> {
+    ensure_warehouses_exist(transaction, company_id, &[warehouse_id]).await?;
+
+    let partners = sqlx::query!(
+        r#"
+        SELECT id, partner_type FROM partners
+        WHERE id = ANY($1) AND company_id = $2 AND deleted_at IS NULL
+        "#,
+        &[Some(receipt.issued_by_partner_id), receipt.agent_id]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<Uuid>>(),
+        company_id
+    )
+    .fetch_all(&mut **transaction)
+    .await
+    .context("Failed to fetch partners from database.")?;
+    if !partners
+        .iter()
+        .any(|p| p.id == receipt.issued_by_partner_id)
+    {
+        return Err(ReceiptError::ValidationError(
+            "Partner does not exist".to_string(),
+        ));
+    }
+    if let Some(agent_id) = receipt.agent_id {
+        let agent = partners
+            .iter()
+            .find(|p| p.id == agent_id)
+            .ok_or_else(|| ReceiptError::ValidationError("Agent does not exist".to_string()))?;
+        if agent.partner_type != "Agent" {
+            return Err(ReceiptError::ValidationError(format!(
+                "Partner {} is a {}, not an Agent",
+                agent_id, agent.partner_type
+            )));
+        }
+    }
+
+    let mut distinct_products = product_ids.to_vec();
+    distinct_products.sort();
+    distinct_products.dedup();
+    let found = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) as "count!" FROM products
+        WHERE id = ANY($1) AND company_id = $2 AND deleted_at IS NULL
+        "#,
+        &distinct_products,
+        company_id
+    )
+    .fetch_one(&mut **transaction)
+    .await
+    .context("Failed to fetch products from database.")?;
+    if found != distinct_products.len() as i64 {
+        return Err(ReceiptError::ValidationError(
+            "Product does not exist".to_string(),
+        ));
+    }
+
+    if let Some(job_work_id) = receipt.job_work_id {
+        let job_work = sqlx::query!(
+            r#"
+            SELECT status, vendor_id FROM job_works
+            WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+            "#,
+            job_work_id,
+            company_id
+        )
+        .fetch_optional(&mut **transaction)
+        .await
+        .context("Failed to fetch job work from database.")?
+        .ok_or_else(|| ReceiptError::ValidationError("Job work does not exist".to_string()))?;
+        if job_work.status != "in_progress" {
+            return Err(ReceiptError::InvalidState(format!(
+                "Only job works in progress can receive goods, this one is {}",
+                job_work.status
+            )));
+        }
+        if job_work.vendor_id != receipt.issued_by_partner_id {
+            return Err(ReceiptError::ValidationError(
+                "Finished goods of a job work come from its vendor".to_string(),
+            ));
+        }
+
+        let finished_goods = sqlx::query_scalar!(
+            "SELECT product_id FROM job_work_finished_goods WHERE job_work_id = $1",
+            job_work_id
+        )
+        .fetch_all(&mut **transaction)
+        .await
+        .context("Failed to fetch job work finished goods from database.")?;
+        if product_ids.iter().any(|p| !finished_goods.contains(p)) {
+            return Err(ReceiptError::ValidationError(
+                "Every product must be a finished good of the job work".to_string(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates a stock unit per roll, numbered by the stock unit trigger
+async fn insert_received_units_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    user: &CurrentUser,
+    warehouse_id: Uuid,
+    receipt_id: Uuid,
+    receipt_item_id: Uuid,
+    product_id: Uuid,
+    rolls: &[ReceivedRoll],
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    let mut unit_ids = Vec::with_capacity(rolls.len());
+    for roll in rolls {
+        let status = if roll.size_quantity.is_some() {
+            "in_stock"
+        } else {
+            "pending_details"
+        };
+        let unit_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO stock_units (company_id, product_id, warehouse_id, size_quantity,
+                                     quality_grade, location_description, manufacturing_date,
+                                     status, created_from_receipt_id, created_from_receipt_item_id,
+                                     notes, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            RETURNING id
+            "#,
+            user.company_id,
+            product_id,
+            warehouse_id,
+            roll.size_quantity.unwrap_or_default(),
+            roll.quality_grade,
+            roll.location_description,
+            roll.manufacturing_date,
+            status,
+            receipt_id,
+            receipt_item_id,
+            roll.notes,
+            user.id
+        )
+        .fetch_one(&mut **transaction)
+        .await?;
+        unit_ids.push(unit_id);
+    }
+
+    Ok(unit_ids)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransferReceipt {
+    /// Unit numbers or QR codes scanned on arrival
+    units: Vec<String>,
+    receipt_date: Option<NaiveDate>,
+    notes: Option<String>,
+}
+
+struct TransferUnit {
+    id: Uuid,
+    unit_number: String,
+    qr_code: Option<String>,
+    status: String,
+}
+
+/// Receives a warehouse transfer at its destination. The scanned units, the
+/// same stock units that left the origin, move into the destination's stock;
+/// units of the transfer that were not scanned are recorded as missing and
+/// stay in transit until resolved.
+pub async fn receive_transfer(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(dispatch_id): Path<Uuid>,
+    Json(request): Json<TransferReceipt>,
+) -> Result<(StatusCode, Json<Receipt>), ReceiptError> {
+    if request.units.is_empty() {
+        return Err(ReceiptError::ValidationError(
+            "Scan at least one unit to receive".to_string(),
+        ));
+    }
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    let dispatch = sqlx::query!(
+        r#"
+        SELECT dispatch_type, warehouse_id, dispatch_to_warehouse_id,
+               COALESCE(is_cancelled, false) as "is_cancelled!"
+        FROM goods_dispatches
+        WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+        FOR UPDATE
+        "#,
+        dispatch_id,
+        user.company_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to fetch dispatch from database.")?
+    .ok_or(ReceiptError::NotFound)?;
+    let Some(destination_id) = dispatch.dispatch_to_warehouse_id else {
+        return Err(ReceiptError::ValidationError(format!(
+            "Only warehouse transfers can be received, this is a {} dispatch",
+            dispatch.dispatch_type
+        )));
+    };
+    if !user.is_admin() && user.warehouse_id != Some(destination_id) {
+        return Err(ReceiptError::Forbidden);
+    }
+    if dispatch.is_cancelled {
+        return Err(ReceiptError::InvalidState(
+            "Transfer is cancelled".to_string(),
+        ));
+    }
+
+    let received = sqlx::query_scalar!(
+        r#"
+        SELECT receipt_number FROM goods_receipts
+        WHERE transfer_dispatch_id = $1 AND deleted_at IS NULL
+        "#,
+        dispatch_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to fetch transfer receipt from database.")?;
+    if let Some(receipt_number) = received {
+        return Err(ReceiptError::InvalidState(format!(
+            "Transfer is already received on {}",
+            receipt_number
+        )));
+    }
+
+    let transferred = sqlx::query_as!(
+        TransferUnit,
+        r#"
+        SELECT su.id, su.unit_number, su.qr_code, su.status
+        FROM goods_dispatch_items gdi
+        JOIN stock_units su ON su.id = gdi.stock_unit_id
+        WHERE gdi.dispatch_id = $1
+        ORDER BY su.id
+        FOR UPDATE OF su
+        "#,
+        dispatch_id
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to fetch transferred units from database.")?;
+
+    let mut arrived: Vec<Uuid> = Vec::with_capacity(request.units.len());
+    for code in request.units.iter().map(|c| c.trim()) {
+        let unit = transferred
+            .iter()
+            .find(|u| u.unit_number == code || u.qr_code.as_deref() == Some(code))
+            .ok_or_else(|| {
+                ReceiptError::ValidationError(format!("Unit '{code}' is not on this transfer"))
+            })?;
+        if arrived.contains(&unit.id) {
+            return Err(ReceiptError::ValidationError(format!(
+                "Unit {} is scanned more than once",
+                unit.unit_number
+            )));
+        }
+        if unit.status != "in_transit" {
+            return Err(ReceiptError::InvalidState(format!(
+                "Unit {} is {}, not in transit",
+                unit.unit_number, unit.status
+            )));
+        }
+        arrived.push(unit.id);
+    }
+    let missing: Vec<Uuid> = transferred
+        .iter()
+        .map(|u| u.id)
+        .filter(|id| !arrived.contains(id))
+        .collect();
+
+    let receipt_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO goods_receipts (company_id, warehouse_id, issued_by_warehouse_id,
+                                    transfer_dispatch_id, receipt_date, notes, created_by)
+        VALUES ($1, $2, $3, $4, COALESCE($5, CURRENT_DATE), $6, $7)
+        RETURNING id
+        "#,
+        user.company_id,
+        destination_id,
+        dispatch.warehouse_id,
+        dispatch_id,
+        request.receipt_date,
+        request.notes,
+        user.id
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to insert transfer receipt in the database.")?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO goods_receipt_transfer_items (company_id, receipt_id, stock_unit_id, is_missing)
+        SELECT $1, $2, unit_id, unit_id = ANY($4)
+        FROM unnest($3::UUID[]) as unit_id
+        "#,
+        user.company_id,
+        receipt_id,
+        &transferred.iter().map(|u| u.id).collect::<Vec<Uuid>>(),
+        &missing
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to insert transfer receipt items in the database.")?;
+
+    sqlx::query!(
+        r#"
+        UPDATE stock_units SET status = 'in_stock', warehouse_id = $2, modified_by = $3
+        WHERE id = ANY($1)
+        "#,
+        &arrived,
+        destination_id,
+        user.id
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to move received units into stock.")?;
+
+    let receipt = fetch_receipt_from_db(&mut transaction, receipt_id)
+        .await
+        .context("Failed to fetch receipt from database.")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to receive a transfer.")?;
+
+    Ok((StatusCode::CREATED, Json(receipt)))
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewSalesReturn {
+    /// Receiving warehouse; defaults to the user's own
+    warehouse_id: Option<Uuid>,
+    /// Unit numbers or QR codes of the returned units
+    units: Vec<String>,
+    /// Keep the units out of sellable stock until inspected
+    #[serde(default)]
+    quarantine: bool,
+    receipt_date: Option<NaiveDate>,
+    notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SalesReturn {
+    pub receipt: Receipt,
+    pub credit_note: CreditNote,
+}
+
+struct ReturnableUnit {
+    id: Uuid,
+    unit_number: String,
+    qr_code: Option<String>,
+    status: String,
+    product_id: Uuid,
+    size_quantity: Decimal,
+    dispatch_id: Uuid,
+    unit_rate: Decimal,
+}
+
+/// Takes back units a customer returns against a sales order. Only units
+/// dispatched on the order's active dispatches and not yet returned qualify;
+/// they go back into stock, or quarantine, the order's dispatched quantities
+/// drop accordingly and a credit note at the order's rates, less its
+/// discount, is issued.
+pub async fn create_sales_return(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(sales_order_id): Path<Uuid>,
+    Json(request): Json<NewSalesReturn>,
+) -> Result<(StatusCode, Json<SalesReturn>), ReceiptError> {
+    let warehouse_id = working_warehouse(&user, request.warehouse_id)?;
+    if request.units.is_empty() {
+        return Err(ReceiptError::ValidationError(
+            "Scan at least one returned unit".to_string(),
+        ));
+    }
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    let order = sqlx::query!(
+        r#"
+        SELECT status, customer_id, COALESCE(discount_percentage, 0) as "discount_percentage!"
+        FROM sales_orders
+        WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+        FOR UPDATE
+        "#,
+        sales_order_id,
+        user.company_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to fetch sales order from database.")?
+    .ok_or(ReceiptError::NotFound)?;
+    if !matches!(order.status.as_str(), "in_progress" | "completed") {
+        return Err(ReceiptError::InvalidState(format!(
+            "Goods cannot be returned against a {} order",
+            order.status
+        )));
+    }
+
+    // Units still out with the customer on the order's active dispatches
+    let returnable = sqlx::query_as!(
+        ReturnableUnit,
+        r#"
+        SELECT su.id, su.unit_number, su.qr_code, su.status, su.product_id, su.size_quantity,
+               gd.id as dispatch_id,
+               order_unit_rate(gd.sales_order_id, su.product_id) as "unit_rate!"
+        FROM goods_dispatches gd
+        JOIN goods_dispatch_items gdi ON gdi.dispatch_id = gd.id
+        JOIN stock_units su ON su.id = gdi.stock_unit_id
+        WHERE gd.sales_order_id = $1 AND gd.is_cancelled IS NOT TRUE AND gd.deleted_at IS NULL
+            AND NOT EXISTS (
+                SELECT 1 FROM sales_return_items sri
+                JOIN goods_receipts gr ON gr.id = sri.receipt_id
+                WHERE sri.dispatch_id = gd.id AND sri.stock_unit_id = su.id
+                    AND gr.deleted_at IS NULL
+            )
+        ORDER BY su.id
+        FOR UPDATE OF su
+        "#,
+        sales_order_id
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to fetch dispatched units from database.")?;
+
+    let mut returned: Vec<&ReturnableUnit> = Vec::with_capacity(request.units.len());
+    for code in request.units.iter().map(|c| c.trim()) {
+        let unit = returnable
+            .iter()
+            .find(|u| u.unit_number == code || u.qr_code.as_deref() == Some(code))
+            .ok_or_else(|| {
+                ReceiptError::ValidationError(format!(
+                    "Unit '{code}' is not out with the customer on this order"
+                ))
+            })?;
+        if returned.iter().any(|u| u.id == unit.id) {
+            return Err(ReceiptError::ValidationError(format!(
+                "Unit {} is scanned more than once",
+                unit.unit_number
+            )));
+        }
+        if unit.status != "dispatched" {
+            return Err(ReceiptError::InvalidState(format!(
+                "Unit {} is {}, not dispatched",
+                unit.unit_number, unit.status
+            )));
+        }
+        returned.push(unit);
+    }
+
+    let receipt_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO goods_receipts (company_id, warehouse_id, issued_by_partner_id, link_type,
+                                    sales_order_id, receipt_date, notes, created_by)
+        VALUES ($1, $2, $3, 'sales_order', $4, COALESCE($5, CURRENT_DATE), $6, $7)
+        RETURNING id
+        "#,
+        user.company_id,
+        warehouse_id,
+        order.customer_id,
+        sales_order_id,
+        request.receipt_date,
+        request.notes,
+        user.id
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to insert return receipt in the database.")?;
+
+    let mut gross_amount = Decimal::ZERO;
+    for unit in &returned {
+        let amount = round_money(unit.size_quantity * unit.unit_rate);
+        gross_amount += amount;
+        sqlx::query!(
+            r#"
+            INSERT INTO sales_return_items (company_id, receipt_id, dispatch_id, stock_unit_id,
+                                            product_id, quantity, unit_rate, amount)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            user.company_id,
+            receipt_id,
+            unit.dispatch_id,
+            unit.id,
+            unit.product_id,
+            unit.size_quantity,
+            unit.unit_rate,
+            amount
+        )
+        .execute(&mut *transaction)
+        .await
+        .context("Failed to insert sales return item in the database.")?;
+    }
+
+    let status = if request.quarantine {
+        "quarantined"
+    } else {
+        "in_stock"
+    };
+    sqlx::query!(
+        r#"
+        UPDATE stock_units SET status = $2, warehouse_id = $3, modified_by = $4
+        WHERE id = ANY($1)
+        "#,
+        &returned.iter().map(|u| u.id).collect::<Vec<Uuid>>(),
+        status,
+        warehouse_id,
+        user.id
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to take returned units back into stock.")?;
+
+    refresh_dispatched_quantities_in_db(&mut transaction, sales_order_id)
+        .await
+        .context("Failed to update dispatched quantities.")?;
+
+    let amount = round_money(
+        gross_amount * (Decimal::ONE_HUNDRED - order.discount_percentage) / Decimal::ONE_HUNDRED,
+    );
+    let credit_note_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO credit_notes (company_id, receipt_id, sales_order_id, customer_id,
+                                  credit_note_date, discount_percentage, amount, created_by)
+        VALUES ($1, $2, $3, $4, COALESCE($5, CURRENT_DATE), $6, $7, $8)
+        RETURNING id
+        "#,
+        user.company_id,
+        receipt_id,
+        sales_order_id,
+        order.customer_id,
+        request.receipt_date,
+        order.discount_percentage,
+        amount,
+        user.id
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to insert credit note in the database.")?;
+
+    let receipt = fetch_receipt_from_db(&mut transaction, receipt_id)
+        .await
+        .context("Failed to fetch receipt from database.")?;
+    let credit_note = fetch_credit_note_from_db(&mut transaction, credit_note_id)
+        .await
+        .context("Failed to fetch credit note from database.")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to store a sales return.")?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(SalesReturn {
+            receipt,
+            credit_note,
+        }),
+    ))
+}
+
+fn round_money(amount: Decimal) -> Decimal {
+    amount.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
+}
+
+// UPDATE
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReceiptItemUpdate {
+    item_id: Uuid,
+    quantity_received: i32,
+}
+
+/// Header fields left out keep their value. Items left out keep their
+/// quantity.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReceiptUpdate {
+    receipt_date: Option<NaiveDate>,
+    invoice_number: Option<String>,
+    invoice_amount: Option<Decimal>,
+    transport_details: Option<String>,
+    notes: Option<String>,
+    #[serde(default)]
+    items: Vec<ReceiptItemUpdate>,
+    /// Why the quantities changed, kept with the adjustment
+    reason: Option<String>,
+}
+
+impl ReceiptUpdate {
+    fn validate(&self) -> Result<(), String> {
+        if self.invoice_amount.is_some_and(|a| a < Decimal::ZERO) {
+            return Err("Invoice amount cannot be negative".to_string());
+        }
+        for (i, item) in self.items.iter().enumerate() {
+            if !(1..=MAX_ROLLS_PER_ITEM).contains(&item.quantity_received) {
+                return Err(format!(
+                    "Quantity received must be between 1 and {MAX_ROLLS_PER_ITEM}"
+                ));
+            }
+            if self.items[..i].iter().any(|o| o.item_id == item.item_id) {
+                return Err("An item can only be changed once per update".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Corrects a receipt. A higher item quantity adds units waiting for their
+/// details; a lower one removes units nobody has worked on yet, newest
+/// first. Units already in stock, dispatched or labelled are never removed,
+/// so a decrease that would need them is refused. Every quantity change is
+/// kept as an adjustment.
+pub async fn update_receipt(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(receipt_id): Path<Uuid>,
+    Json(update): Json<ReceiptUpdate>,
+) -> Result<Json<Receipt>, ReceiptError> {
+    update.validate().map_err(ReceiptError::ValidationError)?;
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    let warehouse_id = sqlx::query_scalar!(
+        r#"
+        SELECT warehouse_id FROM goods_receipts
+        WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+        FOR UPDATE
+        "#,
+        receipt_id,
+        user.company_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to fetch receipt from database.")?
+    .ok_or(ReceiptError::NotFound)?;
+    if !user.is_admin() && user.warehouse_id != Some(warehouse_id) {
+        return Err(ReceiptError::Forbidden);
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE goods_receipts
+        SET receipt_date = COALESCE($2, receipt_date),
+            invoice_number = COALESCE($3, invoice_number),
+            invoice_amount = COALESCE($4, invoice_amount),
+            transport_details = COALESCE($5, transport_details),
+            notes = COALESCE($6, notes),
+            modified_by = $7
+        WHERE id = $1
+        "#,
+        receipt_id,
+        update.receipt_date,
+        update.invoice_number,
+        update.invoice_amount,
+        update.transport_details,
+        update.notes,
+        user.id
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to update receipt in the database.")?;
+
+    for item in &update.items {
+        adjust_received_quantity_in_db(
+            &mut transaction,
+            &user,
+            warehouse_id,
+            receipt_id,
+            item,
+            update.reason.as_deref(),
+        )
+        .await?;
+    }
+
+    let receipt = fetch_receipt_from_db(&mut transaction, receipt_id)
+        .await
+        .context("Failed to fetch receipt from database.")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to update a receipt.")?;
+
+    Ok(Json(receipt))
+}
+
+/// Brings an item's units in line with its new quantity and records the
+/// adjustment
+async fn adjust_received_quantity_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    user: &CurrentUser,
+    warehouse_id: Uuid,
+    receipt_id: Uuid,
+    update: &ReceiptItemUpdate,
+    reason: Option<&str>,
+) -> Result<(), ReceiptError> {
+    let item = sqlx::query!(
+        r#"
+        SELECT gri.product_id, gri.quantity_received, p.name as product_name
+        FROM goods_receipt_items gri
+        JOIN products p ON p.id = gri.product_id
+        WHERE gri.id = $1 AND gri.receipt_id = $2
+        FOR UPDATE OF gri
+        "#,
+        update.item_id,
+        receipt_id
+    )
+    .fetch_optional(&mut **transaction)
+    .await
+    .context("Failed to fetch receipt item from database.")?
+    .ok_or_else(|| {
+        ReceiptError::ValidationError(format!("Item {} is not on this receipt", update.item_id))
+    })?;
+    if item.quantity_received == update.quantity_received {
+        return Ok(());
+    }
+
+    let mut added_unit_ids = Vec::new();
+    let mut removed_unit_ids = Vec::new();
+    if update.quantity_received > item.quantity_received {
+        let count = (update.quantity_received - item.quantity_received) as usize;
+        added_unit_ids = insert_received_units_in_db(
+            transaction,
+            user,
+            warehouse_id,
+            receipt_id,
+            update.item_id,
+            item.product_id,
+            &vec![ReceivedRoll::default(); count],
+        )
+        .await
+        .context("Failed to insert received stock units in the database.")?;
+    } else {
+        let count = i64::from(item.quantity_received - update.quantity_received);
+        // Untouched: still waiting for details, never labelled or scanned
+        removed_unit_ids = sqlx::query_scalar!(
+            r#"
+            SELECT su.id FROM stock_units su
+            WHERE su.created_from_receipt_item_id = $1 AND su.deleted_at IS NULL
+                AND su.status = 'pending_details' AND su.barcode_generated IS NOT TRUE
+                AND NOT EXISTS (SELECT 1 FROM goods_dispatch_items gdi WHERE gdi.stock_unit_id = su.id)
+                AND NOT EXISTS (
+                    SELECT 1 FROM dispatch_scan_items dsi
+                    JOIN dispatch_scan_sessions dss ON dss.id = dsi.session_id
+                    WHERE dsi.stock_unit_id = su.id AND dss.status = 'open'
+                )
+            ORDER BY su.created_at DESC, su.unit_number DESC
+            LIMIT $2
+            FOR UPDATE OF su
+            "#,
+            update.item_id,
+            count
+        )
+        .fetch_all(&mut **transaction)
+        .await
+        .context("Failed to fetch removable stock units from database.")?;
+        if (removed_unit_ids.len() as i64) < count {
+            return Err(ReceiptError::InvalidState(format!(
+                "Only {} unit(s) of {} can be removed; the rest are already in stock, labelled or dispatched",
+                removed_unit_ids.len(),
+                item.product_name
+            )));
+        }
+
+        sqlx::query!(
+            "UPDATE stock_units SET status = 'removed', modified_by = $2 WHERE id = ANY($1)",
+            &removed_unit_ids,
+            user.id
+        )
+        .execute(&mut **transaction)
+        .await
+        .context("Failed to remove stock units.")?;
+    }
+
+    sqlx::query!(
+        "UPDATE goods_receipt_items SET quantity_received = $2 WHERE id = $1",
+        update.item_id,
+        update.quantity_received
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to update receipt item in the database.")?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO goods_receipt_adjustments (company_id, receipt_id, receipt_item_id, product_id,
+                                               from_quantity, to_quantity, added_unit_ids,
+                                               removed_unit_ids, reason, changed_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        "#,
+        user.company_id,
+        receipt_id,
+        update.item_id,
+        item.product_id,
+        item.quantity_received,
+        update.quantity_received,
+        &added_unit_ids,
+        &removed_unit_ids,
+        reason,
+        user.id
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to insert receipt adjustment in the database.")?;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString, Deserialize, Serialize)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum MissingUnitResolution {
+    /// Turned up at the destination after all
+    Found,
+    /// Lost for good
+    WrittenOff,
+    /// Back in the origin's stock
+    Returned,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MissingUnitsUpdate {
+    /// Unit numbers or QR codes of units the receipt recorded as missing
+    units: Vec<String>,
+    resolution: MissingUnitResolution,
+}
+
+/// Settles units a transfer receipt recorded as missing, which stay in
+/// transit until then. Found units join the destination's stock, returned
+/// ones the origin's, and written-off ones are removed. Staff at either end
+/// of the transfer may resolve them.
+pub async fn resolve_missing_units(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(receipt_id): Path<Uuid>,
+    Json(update): Json<MissingUnitsUpdate>,
+) -> Result<Json<Receipt>, ReceiptError> {
+    if update.units.is_empty() {
+        return Err(ReceiptError::ValidationError(
+            "Pick at least one missing unit".to_string(),
+        ));
+    }
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    let receipt = sqlx::query!(
+        r#"
+        SELECT warehouse_id, issued_by_warehouse_id, transfer_dispatch_id
+        FROM goods_receipts
+        WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+        FOR UPDATE
+        "#,
+        receipt_id,
+        user.company_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to fetch receipt from database.")?
+    .ok_or(ReceiptError::NotFound)?;
+    let (Some(_), Some(origin_id)) = (receipt.transfer_dispatch_id, receipt.issued_by_warehouse_id)
+    else {
+        return Err(ReceiptError::ValidationError(
+            "Only transfer receipts have missing units".to_string(),
+        ));
+    };
+    if !user.is_admin()
+        && user.warehouse_id != Some(receipt.warehouse_id)
+        && user.warehouse_id != Some(origin_id)
+    {
+        return Err(ReceiptError::Forbidden);
+    }
+
+    let missing = sqlx::query_as!(
+        TransferUnit,
+        r#"
+        SELECT su.id, su.unit_number, su.qr_code, su.status
+        FROM goods_receipt_transfer_items gti
+        JOIN stock_units su ON su.id = gti.stock_unit_id
+        WHERE gti.receipt_id = $1 AND gti.is_missing AND gti.resolution IS NULL
+        ORDER BY su.id
+        FOR UPDATE OF gti, su
+        "#,
+        receipt_id
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to fetch missing units from database.")?;
+
+    let mut resolved: Vec<Uuid> = Vec::with_capacity(update.units.len());
+    for code in update.units.iter().map(|c| c.trim()) {
+        let unit = missing
+            .iter()
+            .find(|u| u.unit_number == code || u.qr_code.as_deref() == Some(code))
+            .ok_or_else(|| {
+                ReceiptError::ValidationError(format!(
+                    "Unit '{code}' is not an unresolved missing unit of this receipt"
+                ))
+            })?;
+        if resolved.contains(&unit.id) {
+            return Err(ReceiptError::ValidationError(format!(
+                "Unit {} is listed more than once",
+                unit.unit_number
+            )));
+        }
+        if unit.status != "in_transit" {
+            return Err(ReceiptError::InvalidState(format!(
+                "Unit {} is {}, not in transit",
+                unit.unit_number, unit.status
+            )));
+        }
+        resolved.push(unit.id);
+    }
+
+    let (status, warehouse_id) = match update.resolution {
+        MissingUnitResolution::Found => ("in_stock", Some(receipt.warehouse_id)),
+        MissingUnitResolution::Returned => ("in_stock", Some(origin_id)),
+        MissingUnitResolution::WrittenOff => ("removed", None),
+    };
+    sqlx::query!(
+        r#"
+        UPDATE stock_units
+        SET status = $2, warehouse_id = COALESCE($3, warehouse_id), modified_by = $4
+        WHERE id = ANY($1)
+        "#,
+        &resolved,
+        status,
+        warehouse_id,
+        user.id
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to update missing units in the database.")?;
+
+    sqlx::query!(
+        r#"
+        UPDATE goods_receipt_transfer_items
+        SET resolution = $3, resolved_at = NOW(), resolved_by = $4
+        WHERE receipt_id = $1 AND stock_unit_id = ANY($2)
+        "#,
+        receipt_id,
+        &resolved,
+        update.resolution.to_string(),
+        user.id
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to resolve missing units in the database.")?;
+
+    let receipt = fetch_receipt_from_db(&mut transaction, receipt_id)
+        .await
+        .context("Failed to fetch receipt from database.")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to resolve missing units.")?;
+
+    Ok(Json(receipt))
+}
+
+// READ
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CreditNoteLine {
+    pub stock_unit_id: Uuid,
+    pub unit_number: String,
+    pub product_id: Uuid,
+    pub dispatch_id: Uuid,
+    pub quantity: Decimal,
+    pub unit_rate: Decimal,
+    /// Before the order's discount
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CreditNote {
+    pub id: Uuid,
+    pub credit_note_number: String,
+    pub receipt_id: Uuid,
+    pub sales_order_id: Uuid,
+    pub customer_id: Uuid,
+    pub credit_note_date: NaiveDate,
+    pub discount_percentage: Decimal,
+    pub amount: Decimal,
+    pub lines: Vec<CreditNoteLine>,
+}
+
+async fn fetch_credit_note_from_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    credit_note_id: Uuid,
+) -> Result<CreditNote, sqlx::Error> {
+    let note = sqlx::query!(
+        r#"
+        SELECT id, credit_note_number, receipt_id, sales_order_id, customer_id, credit_note_date,
+               discount_percentage, amount
+        FROM credit_notes
+        WHERE id = $1
+        "#,
+        credit_note_id
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    let lines = sqlx::query_as!(
+        CreditNoteLine,
+        r#"
+        SELECT sri.stock_unit_id, su.unit_number, sri.product_id, sri.dispatch_id, sri.quantity,
+               sri.unit_rate, sri.amount
+        FROM sales_return_items sri
+        JOIN stock_units su ON su.id = sri.stock_unit_id
+        WHERE sri.receipt_id = $1
+        ORDER BY su.unit_number
+        "#,
+        note.receipt_id
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    Ok(CreditNote {
+        id: note.id,
+        credit_note_number: note.credit_note_number,
+        receipt_id: note.receipt_id,
+        sales_order_id: note.sales_order_id,
+        customer_id: note.customer_id,
+        credit_note_date: note.credit_note_date,
+        discount_percentage: note.discount_percentage,
+        amount: note.amount,
+        lines,
+    })
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReceivedUnit {
+    pub stock_unit_id: Uuid,
+    pub unit_number: String,
+    pub product_id: Uuid,
+    pub product_name: String,
+    pub size_quantity: Decimal,
+    pub quality_grade: Option<String>,
+    pub location_description: Option<String>,
+    /// `pending_details` until the roll's size is known
+    pub status: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Receipt {
+    pub id: Uuid,
+    pub receipt_number: String,
+    pub warehouse_id: Uuid,
+    pub issued_by_partner_id: Option<Uuid>,
+    pub issued_by_warehouse_id: Option<Uuid>,
+    pub agent_id: Option<Uuid>,
+    pub link_type: Option<String>,
+    pub sales_order_id: Option<Uuid>,
+    pub job_work_id: Option<Uuid>,
+    pub other_reference: Option<String>,
+    pub transfer_dispatch_id: Option<Uuid>,
+    pub receipt_date: NaiveDate,
+    pub invoice_number: Option<String>,
+    pub invoice_amount: Option<Decimal>,
+    pub transport_details: Option<String>,
+    pub notes: Option<String>,
+    pub units: Vec<ReceivedUnit>,
+    /// Units of a transfer that did not arrive and are not resolved yet
+    pub missing_units: Vec<ReceivedUnit>,
+}
+
+pub(crate) async fn fetch_receipt_from_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    receipt_id: Uuid,
+) -> Result<Receipt, sqlx::Error> {
+    let receipt = sqlx::query!(
+        r#"
+        SELECT id, receipt_number, warehouse_id, issued_by_partner_id, issued_by_warehouse_id,
+               agent_id, link_type, sales_order_id, job_work_id, other_reference,
+               transfer_dispatch_id, receipt_date, invoice_number, invoice_amount,
+               transport_details, notes
+        FROM goods_receipts
+        WHERE id = $1
+        "#,
+        receipt_id
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    let units = fetch_receipt_units_from_db(transaction, receipt_id, false).await?;
+    let missing_units = fetch_receipt_units_from_db(transaction, receipt_id, true).await?;
+
+    Ok(Receipt {
+        id: receipt.id,
+        receipt_number: receipt.receipt_number,
+        warehouse_id: receipt.warehouse_id,
+        issued_by_partner_id: receipt.issued_by_partner_id,
+        issued_by_warehouse_id: receipt.issued_by_warehouse_id,
+        agent_id: receipt.agent_id,
+        link_type: receipt.link_type,
+        sales_order_id: receipt.sales_order_id,
+        job_work_id: receipt.job_work_id,
+        other_reference: receipt.other_reference,
+        transfer_dispatch_id: receipt.transfer_dispatch_id,
+        receipt_date: receipt.receipt_date,
+        invoice_number: receipt.invoice_number,
+        invoice_amount: receipt.invoice_amount,
+        transport_details: receipt.transport_details,
+        notes: receipt.notes,
+        units,
+        missing_units,
+    })
+}
+
+/// Units the receipt brought in or took back, including missing units found
+/// later, or with `missing` the transferred units still unaccounted for
+async fn fetch_receipt_units_from_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    receipt_id: Uuid,
+    missing: bool,
+) -> Result<Vec<ReceivedUnit>, sqlx::Error> {
+    sqlx::query_as!(
+        ReceivedUnit,
+        r#"
+        SELECT su.id as stock_unit_id, su.unit_number, su.product_id, p.name as product_name,
+               su.size_quantity, su.quality_grade, su.location_description, su.status
+        FROM stock_units su
+        JOIN products p ON p.id = su.product_id
+        WHERE (NOT $2 AND su.created_from_receipt_id = $1 AND su.deleted_at IS NULL
+                AND su.status != 'removed')
+            OR (NOT $2 AND su.id IN (
+                SELECT stock_unit_id FROM sales_return_items WHERE receipt_id = $1
+            ))
+            OR su.id IN (
+                SELECT stock_unit_id FROM goods_receipt_transfer_items
+                WHERE receipt_id = $1
+                    AND CASE WHEN $2 THEN is_missing AND resolution IS NULL
+                             ELSE NOT is_missing OR resolution = 'found' END
+            )
+        ORDER BY p.name, su.unit_number
+        "#,
+        receipt_id,
+        missing
+    )
+    .fetch_all(&mut **transaction)
+    .await
+}
+
+/// Staff see receipts into their own warehouse
+pub async fn get_receipt(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(receipt_id): Path<Uuid>,
+) -> Result<Json<Receipt>, ReceiptError> {
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    sqlx::query_scalar!(
+        r#"
+        SELECT id FROM goods_receipts
+        WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+            AND ($3 OR warehouse_id IS NOT DISTINCT FROM $4)
+        "#,
+        receipt_id,
+        user.company_id,
+        user.is_admin(),
+        user.warehouse_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to fetch receipt from database.")?
+    .ok_or(ReceiptError::NotFound)?;
+
+    let receipt = fetch_receipt_from_db(&mut transaction, receipt_id)
+        .await
+        .context("Failed to fetch receipt from database.")?;
+
+    Ok(Json(receipt))
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReceiptAdjustment {
+    pub receipt_item_id: Uuid,
+    pub product_id: Uuid,
+    pub from_quantity: i32,
+    pub to_quantity: i32,
+    pub added_unit_ids: Vec<Uuid>,
+    pub removed_unit_ids: Vec<Uuid>,
+    pub reason: Option<String>,
+    pub changed_at: DateTime<Utc>,
+    pub changed_by: Uuid,
+}
+
+pub async fn get_receipt_adjustments(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(receipt_id): Path<Uuid>,
+) -> Result<Json<Vec<ReceiptAdjustment>>, ReceiptError> {
+    // Staff only see receipts into their own warehouse
+    sqlx::query_scalar!(
+        r#"
+        SELECT id FROM goods_receipts
+        WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+            AND ($3 OR warehouse_id IS NOT DISTINCT FROM $4)
+        "#,
+        receipt_id,
+        user.company_id,
+        user.is_admin(),
+        user.warehouse_id
+    )
+    .fetch_optional(db_pool.as_ref())
+    .await
+    .context("Failed to fetch receipt from database.")?
+    .ok_or(ReceiptError::NotFound)?;
+
+    let adjustments = sqlx::query_as!(
+        ReceiptAdjustment,
+        r#"
+        SELECT receipt_item_id, product_id, from_quantity, to_quantity, added_unit_ids,
+               removed_unit_ids, reason, changed_at, changed_by
+        FROM goods_receipt_adjustments
+        WHERE receipt_id = $1
+        ORDER BY changed_at, id
+        "#,
+        receipt_id
+    )
+    .fetch_all(db_pool.as_ref())
+    .await
+    .context("Failed to fetch receipt adjustments from database.")?;
+
+    Ok(Json(adjustments))
+}
@@ -0,0 +1,1459 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::Context;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, QueryBuilder, Transaction};
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+use crate::{
+    auth::CurrentUser,
+    routes::{
+        dispatches::{cancel_dispatch_in_db, fetch_moved_units_from_db},
+        listing::{Cursor, SortOrder},
+        lookups::{
+            ensure_partner_type, ensure_products_exist, ensure_warehouses_exist, LookupError,
+        },
+    },
+};
+
+// ERROR
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, thiserror::Error)]
+pub enum SalesOrderError {
+    #[error("{0}")]
+    ValidationError(String),
+    #[error("{0}")]
+    InvalidState(String),
+    #[error("{message}")]
+    DispatchedQuantityConflict { message: String, hint: &'static str },
+    #[error("Only admins can manage sales orders")]
+    Forbidden,
+    #[error("Sales order not found")]
+    NotFound,
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+/// Body returned when an edit would undercut what has already been dispatched.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DispatchedQuantityError {
+    pub error: String,
+    pub hint: String,
+}
+
+impl IntoResponse for SalesOrderError {
+    fn into_response(self) -> axum::response::Response {
+        if let Self::DispatchedQuantityConflict { message, hint } = self {
+            let body = DispatchedQuantityError {
+                error: message,
+                hint: hint.to_string(),
+            };
+            return (StatusCode::CONFLICT, Json(body)).into_response();
+        }
+
+        match self {
+            Self::ValidationError(_) => StatusCode::BAD_REQUEST,
+            Self::InvalidState(_) | Self::DispatchedQuantityConflict { .. } => StatusCode::CONFLICT,
+            Self::Forbidden => StatusCode::FORBIDDEN,
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+        .into_response()
+    }
+}
+
+impl From<LookupError> for SalesOrderError {
+    fn from(error: LookupError) -> Self {
+        match error {
+            LookupError::ValidationError(message) => Self::ValidationError(message),
+            LookupError::Forbidden => Self::Forbidden,
+            LookupError::UnexpectedError(error) => Self::UnexpectedError(error),
+        }
+    }
+}
+
+// CREATE
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString, Deserialize, Serialize)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum SalesOrderStatus {
+    ApprovalPending,
+    InProgress,
+    Completed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewSalesOrder {
+    customer_id: Uuid,
+    agent_id: Option<Uuid>,
+    order_date: Option<NaiveDate>,
+    expected_delivery_date: Option<NaiveDate>,
+    fulfillment_warehouse_id: Option<Uuid>,
+    advance_amount: Option<Decimal>,
+    discount_percentage: Option<Decimal>,
+    notes: Option<String>,
+    #[serde(default)]
+    attachments: Vec<String>,
+    items: Vec<NewSalesOrderItem>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewSalesOrderItem {
+    pub(crate) product_id: Uuid,
+    pub(crate) required_quantity: Decimal,
+    pub(crate) unit_rate: Option<Decimal>,
+    pub(crate) notes: Option<String>,
+}
+
+impl NewSalesOrderItem {
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if self.required_quantity <= Decimal::ZERO {
+            return Err("Required quantity must be greater than zero".to_string());
+        }
+        if self.unit_rate.is_some_and(|rate| rate < Decimal::ZERO) {
+            return Err("Unit rate cannot be negative".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl NewSalesOrder {
+    fn validate(&self) -> Result<(), String> {
+        if self.items.is_empty() {
+            return Err("A sales order needs at least one item".to_string());
+        }
+        for item in &self.items {
+            item.validate()?;
+        }
+        if self
+            .discount_percentage
+            .is_some_and(|d| d < Decimal::ZERO || d > Decimal::ONE_HUNDRED)
+        {
+            return Err("Discount must be between 0 and 100 percent".to_string());
+        }
+        if self.advance_amount.is_some_and(|a| a < Decimal::ZERO) {
+            return Err("Advance amount cannot be negative".to_string());
+        }
+        if let (Some(order_date), Some(delivery_date)) =
+            (self.order_date, self.expected_delivery_date)
+        {
+            if delivery_date < order_date {
+                return Err("Expected delivery date cannot be before the order date".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Creates an order placed from the inventory app. Orders are fulfilled from
+/// the admin's current warehouse unless another one is picked explicitly.
+pub async fn create_sales_order(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Json(new_order): Json<NewSalesOrder>,
+) -> Result<(StatusCode, Json<SalesOrderSummary>), SalesOrderError> {
+    if !user.is_admin() {
+        return Err(SalesOrderError::Forbidden);
+    }
+    new_order
+        .validate()
+        .map_err(SalesOrderError::ValidationError)?;
+
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    ensure_partner_type(
+        &mut transaction,
+        user.company_id,
+        new_order.customer_id,
+        "Customer",
+    )
+    .await?;
+    if let Some(agent_id) = new_order.agent_id {
+        ensure_partner_type(&mut transaction, user.company_id, agent_id, "Agent").await?;
+    }
+    let product_ids: Vec<Uuid> = new_order.items.iter().map(|i| i.product_id).collect();
+    ensure_products_exist(&mut transaction, user.company_id, &product_ids).await?;
+
+    let fulfillment_warehouse_id = new_order.fulfillment_warehouse_id.or(user.warehouse_id);
+    if let Some(warehouse_id) = new_order.fulfillment_warehouse_id {
+        ensure_warehouses_exist(&mut transaction, user.company_id, &[warehouse_id]).await?;
+    }
+
+    let order_id = insert_sales_order_in_db(
+        &mut transaction,
+        &user,
+        &new_order,
+        fulfillment_warehouse_id,
+    )
+    .await
+    .context("Failed to insert sales order in the database.")?;
+    for item in &new_order.items {
+        insert_sales_order_item_in_db(&mut transaction, user.company_id, order_id, item)
+            .await
+            .context("Failed to insert sales order item in the database.")?;
+    }
+    insert_status_change_in_db(
+        &mut transaction,
+        &user,
+        order_id,
+        None,
+        SalesOrderStatus::ApprovalPending,
+        None,
+        false,
+    )
+    .await
+    .context("Failed to insert sales order status history in the database.")?;
+
+    // Totals are maintained by triggers, so read them back before committing
+    let summary = fetch_summary_from_db(&mut transaction, order_id)
+        .await
+        .context("Failed to fetch sales order totals from database.")?;
+    if summary.advance_amount > summary.total_amount {
+        return Err(SalesOrderError::ValidationError(format!(
+            "Advance of {} exceeds the order total of {}",
+            summary.advance_amount, summary.total_amount
+        )));
+    }
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to store a new sales order.")?;
+
+    Ok((StatusCode::CREATED, Json(summary)))
+}
+
+async fn insert_sales_order_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    user: &CurrentUser,
+    new_order: &NewSalesOrder,
+    fulfillment_warehouse_id: Option<Uuid>,
+) -> Result<Uuid, sqlx::Error> {
+    let id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO sales_orders (company_id, customer_id, agent_id, order_date, expected_delivery_date,
+                                  fulfillment_warehouse_id, advance_amount, discount_percentage, notes,
+                                  attachments, created_by)
+        VALUES ($1, $2, $3, COALESCE($4, CURRENT_DATE), $5, $6, $7, $8, $9, $10, $11)
+        RETURNING id
+        "#,
+        user.company_id,
+        new_order.customer_id,
+        new_order.agent_id,
+        new_order.order_date,
+        new_order.expected_delivery_date,
+        fulfillment_warehouse_id,
+        new_order.advance_amount.unwrap_or(Decimal::ZERO),
+        new_order.discount_percentage.unwrap_or(Decimal::ZERO),
+        new_order.notes,
+        &new_order.attachments,
+        user.id
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    Ok(id)
+}
+
+pub(crate) async fn insert_sales_order_item_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    company_id: Uuid,
+    sales_order_id: Uuid,
+    item: &NewSalesOrderItem,
+) -> Result<(), sqlx::Error> {
+    // A missing rate is filled from the product master by `auto_populate_unit_rate`
+    sqlx::query!(
+        r#"
+        INSERT INTO sales_order_items (company_id, sales_order_id, product_id, required_quantity, unit_rate, notes)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+        company_id,
+        sales_order_id,
+        item.product_id,
+        item.required_quantity,
+        item.unit_rate,
+        item.notes
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+}
+
+pub(crate) async fn insert_status_change_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    user: &CurrentUser,
+    sales_order_id: Uuid,
+    from_status: Option<SalesOrderStatus>,
+    to_status: SalesOrderStatus,
+    notes: Option<&str>,
+    is_override: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO sales_order_status_history (company_id, sales_order_id, from_status, to_status,
+                                                notes, is_override, changed_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+        user.company_id,
+        sales_order_id,
+        from_status.map(|s| s.to_string()),
+        to_status.to_string(),
+        notes,
+        is_override,
+        user.id
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+}
+
+// READ
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SalesOrderSummary {
+    pub id: Uuid,
+    pub order_number: String,
+    pub status: String,
+    pub customer_id: Uuid,
+    pub agent_id: Option<Uuid>,
+    pub fulfillment_warehouse_id: Option<Uuid>,
+    pub is_inter_state: bool,
+    pub subtotal: Decimal,
+    pub discount_percentage: Decimal,
+    pub discount_amount: Decimal,
+    pub taxable_amount: Decimal,
+    pub cgst_amount: Decimal,
+    pub sgst_amount: Decimal,
+    pub igst_amount: Decimal,
+    pub tax_amount: Decimal,
+    pub round_off: Decimal,
+    pub total_amount: Decimal,
+    pub advance_amount: Decimal,
+    /// Allocated from payments that have not been reversed
+    pub paid_amount: Decimal,
+    pub balance_due: Decimal,
+    pub items: Vec<SalesOrderItem>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StatusChange {
+    pub from_status: Option<String>,
+    pub to_status: String,
+    pub notes: Option<String>,
+    pub is_override: bool,
+    pub changed_at: DateTime<Utc>,
+    pub changed_by: Uuid,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SalesOrderItem {
+    pub id: Uuid,
+    pub product_id: Uuid,
+    pub required_quantity: Decimal,
+    pub dispatched_quantity: Decimal,
+    pub pending_quantity: Decimal,
+    /// Dispatched beyond the required quantity
+    pub excess_quantity: Decimal,
+    pub unit_rate: Decimal,
+    pub line_total: Decimal,
+    pub hsn_code: Option<String>,
+    pub gst_rate: Decimal,
+    /// Line total after the order discount
+    pub taxable_amount: Decimal,
+    pub cgst_amount: Decimal,
+    pub sgst_amount: Decimal,
+    pub igst_amount: Decimal,
+    pub notes: Option<String>,
+}
+
+pub(crate) async fn fetch_summary_from_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    sales_order_id: Uuid,
+) -> Result<SalesOrderSummary, sqlx::Error> {
+    let order = sqlx::query!(
+        r#"
+        SELECT id, order_number, status, customer_id, agent_id, fulfillment_warehouse_id,
+               is_inter_state, subtotal, taxable_amount,
+               cgst_amount, sgst_amount, igst_amount, round_off,
+               COALESCE(discount_percentage, 0) as "discount_percentage!",
+               COALESCE(total_amount, 0) as "total_amount!",
+               COALESCE(advance_amount, 0) as "advance_amount!",
+               (
+                   SELECT COALESCE(SUM(pa.amount), 0) FROM payment_allocations pa
+                   JOIN payments pm ON pa.payment_id = pm.id
+                   WHERE pa.sales_order_id = sales_orders.id
+                       AND pm.is_reversed = false AND pm.deleted_at IS NULL
+               ) as "paid_amount!"
+        FROM sales_orders
+        WHERE id = $1
+        "#,
+        sales_order_id
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+
+    let items = sqlx::query_as!(
+        SalesOrderItem,
+        r#"
+        SELECT soi.id, soi.product_id, soi.required_quantity,
+               COALESCE(soi.dispatched_quantity, 0) as "dispatched_quantity!",
+               GREATEST(soi.pending_quantity, 0) as "pending_quantity!",
+               GREATEST(-soi.pending_quantity, 0) as "excess_quantity!",
+               COALESCE(soi.unit_rate, 0) as "unit_rate!",
+               soi.line_total as "line_total!",
+               soi.hsn_code,
+               soi.gst_rate,
+               tl.taxable_amount as "taxable_amount!",
+               tl.cgst_amount as "cgst_amount!",
+               tl.sgst_amount as "sgst_amount!",
+               tl.igst_amount as "igst_amount!",
+               soi.notes
+        FROM sales_order_items soi
+        JOIN sales_order_tax_lines tl ON tl.sales_order_item_id = soi.id
+        WHERE soi.sales_order_id = $1
+        ORDER BY soi.created_at, soi.id
+        "#,
+        sales_order_id
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    Ok(SalesOrderSummary {
+        id: order.id,
+        order_number: order.order_number,
+        status: order.status,
+        customer_id: order.customer_id,
+        agent_id: order.agent_id,
+        fulfillment_warehouse_id: order.fulfillment_warehouse_id,
+        is_inter_state: order.is_inter_state,
+        subtotal: order.subtotal,
+        discount_percentage: order.discount_percentage,
+        discount_amount: order.subtotal - order.taxable_amount,
+        taxable_amount: order.taxable_amount,
+        cgst_amount: order.cgst_amount,
+        sgst_amount: order.sgst_amount,
+        igst_amount: order.igst_amount,
+        tax_amount: order.cgst_amount + order.sgst_amount + order.igst_amount,
+        round_off: order.round_off,
+        total_amount: order.total_amount,
+        advance_amount: order.advance_amount,
+        paid_amount: order.paid_amount,
+        balance_due: order.total_amount - order.advance_amount - order.paid_amount,
+        items,
+    })
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SalesOrderDetail {
+    pub order: SalesOrderSummary,
+    pub order_date: NaiveDate,
+    pub expected_delivery_date: Option<NaiveDate>,
+    pub customer_name: Option<String>,
+    pub customer_company: Option<String>,
+    pub fulfillment_warehouse: Option<String>,
+    pub fulfillment: FulfillmentProgress,
+    pub dispatches: Vec<LinkedDispatch>,
+    pub job_works: Vec<LinkedJobWork>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FulfillmentProgress {
+    pub total_required_quantity: Decimal,
+    pub total_dispatched_quantity: Decimal,
+    pub total_pending_quantity: Decimal,
+    pub completion_percentage: Decimal,
+    pub total_dispatches: i64,
+    pub active_dispatches: i64,
+    pub linked_job_works: i64,
+    pub completed_job_works: i64,
+    pub last_activity_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LinkedDispatch {
+    pub id: Uuid,
+    pub dispatch_number: String,
+    pub dispatch_date: NaiveDate,
+    pub warehouse_id: Uuid,
+    pub is_cancelled: bool,
+    pub invoice_number: Option<String>,
+    pub units: Vec<DispatchedUnit>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DispatchedUnit {
+    pub stock_unit_id: Uuid,
+    pub unit_number: String,
+    pub product_id: Uuid,
+    pub product_name: String,
+    pub size_quantity: Decimal,
+    pub quality_grade: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LinkedJobWork {
+    pub id: Uuid,
+    pub job_number: String,
+    pub job_type: String,
+    pub vendor_id: Uuid,
+    pub status: String,
+    pub start_date: NaiveDate,
+    pub due_date: Option<NaiveDate>,
+}
+
+/// Order with its fulfillment progress from `comprehensive_order_fulfillment`,
+/// every linked dispatch with the units it carried, and linked job works
+pub async fn get_sales_order(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(sales_order_id): Path<Uuid>,
+) -> Result<Json<SalesOrderDetail>, SalesOrderError> {
+    let mut transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+
+    // Staff only see orders fulfilled from their own warehouse
+    let progress = sqlx::query!(
+        r#"
+        SELECT order_date as "order_date!", expected_delivery_date, customer_name,
+               customer_company, fulfillment_warehouse,
+               total_required_qty as "total_required_qty!",
+               total_dispatched_qty as "total_dispatched_qty!",
+               total_pending_qty as "total_pending_qty!",
+               fulfillment_percentage as "fulfillment_percentage!",
+               total_dispatches as "total_dispatches!",
+               active_dispatches as "active_dispatches!",
+               linked_job_works as "linked_job_works!",
+               completed_job_works as "completed_job_works!",
+               last_activity_at
+        FROM comprehensive_order_fulfillment
+        WHERE sales_order_id = $1 AND company_id = $2
+            AND ($3 OR fulfillment_warehouse_id IS NOT DISTINCT FROM $4)
+        "#,
+        sales_order_id,
+        user.company_id,
+        user.is_admin(),
+        user.warehouse_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to fetch sales order fulfillment from database.")?
+    .ok_or(SalesOrderError::NotFound)?;
+
+    let order = fetch_summary_from_db(&mut transaction, sales_order_id)
+        .await
+        .context("Failed to fetch sales order from database.")?;
+
+    let dispatches = sqlx::query!(
+        r#"
+        SELECT id, dispatch_number, dispatch_date, warehouse_id,
+               COALESCE(is_cancelled, false) as "is_cancelled!", invoice_number
+        FROM goods_dispatches
+        WHERE sales_order_id = $1 AND deleted_at IS NULL
+        ORDER BY dispatch_date, created_at
+        "#,
+        sales_order_id
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to fetch linked dispatches from database.")?;
+
+    let dispatch_ids: Vec<Uuid> = dispatches.iter().map(|d| d.id).collect();
+    let units = sqlx::query!(
+        r#"
+        SELECT gdi.dispatch_id, su.id as stock_unit_id, su.unit_number, su.product_id,
+               p.name as product_name, su.size_quantity, su.quality_grade
+        FROM goods_dispatch_items gdi
+        JOIN stock_units su ON su.id = gdi.stock_unit_id
+        JOIN products p ON p.id = su.product_id
+        WHERE gdi.dispatch_id = ANY($1)
+        ORDER BY su.unit_number
+        "#,
+        &dispatch_ids
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to fetch dispatched units from database.")?;
+
+    let mut units_by_dispatch: HashMap<Uuid, Vec<DispatchedUnit>> = HashMap::new();
+    for unit in units {
+        units_by_dispatch
+            .entry(unit.dispatch_id)
+            .or_default()
+            .push(DispatchedUnit {
+                stock_unit_id: unit.stock_unit_id,
+                unit_number: unit.unit_number,
+                product_id: unit.product_id,
+                product_name: unit.product_name,
+                size_quantity: unit.size_quantity,
+                quality_grade: unit.quality_grade,
+            });
+    }
+    let dispatches = dispatches
+        .into_iter()
+        .map(|dispatch| LinkedDispatch {
+            units: units_by_dispatch.remove(&dispatch.id).unwrap_or_default(),
+            id: dispatch.id,
+            dispatch_number: dispatch.dispatch_number,
+            dispatch_date: dispatch.dispatch_date,
+            warehouse_id: dispatch.warehouse_id,
+            is_cancelled: dispatch.is_cancelled,
+            invoice_number: dispatch.invoice_number,
+        })
+        .collect();
+
+    let job_works = sqlx::query_as!(
+        LinkedJobWork,
+        r#"
+        SELECT id, job_number, job_type, vendor_id, status, start_date, due_date
+        FROM job_works
+        WHERE sales_order_id = $1 AND deleted_at IS NULL
+        ORDER BY start_date, created_at
+        "#,
+        sales_order_id
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to fetch linked job works from database.")?;
+
+    Ok(Json(SalesOrderDetail {
+        order,
+        order_date: progress.order_date,
+        expected_delivery_date: progress.expected_delivery_date,
+        customer_name: progress.customer_name,
+        customer_company: progress.customer_company,
+        fulfillment_warehouse: progress.fulfillment_warehouse,
+        fulfillment: FulfillmentProgress {
+            total_required_quantity: progress.total_required_qty,
+            total_dispatched_quantity: progress.total_dispatched_qty,
+            total_pending_quantity: progress.total_pending_qty,
+            completion_percentage: progress.fulfillment_percentage,
+            total_dispatches: progress.total_dispatches,
+            active_dispatches: progress.active_dispatches,
+            linked_job_works: progress.linked_job_works,
+            completed_job_works: progress.completed_job_works,
+            last_activity_at: progress.last_activity_at,
+        },
+        dispatches,
+        job_works,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct SalesOrderQuery {
+    status: Option<String>,
+    customer_id: Option<Uuid>,
+    agent_id: Option<Uuid>,
+    product_id: Option<Uuid>,
+    /// Admins only; staff always see their own warehouse
+    warehouse_id: Option<Uuid>,
+    from_date: Option<NaiveDate>,
+    to_date: Option<NaiveDate>,
+    sort: Option<String>,
+    order: Option<String>,
+    cursor: Option<String>,
+    limit: Option<i64>,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Display, EnumString)]
+enum SortField {
+    order_date,
+    order_number,
+    created_at,
+}
+
+impl SortField {
+    pub fn parse(val: Option<String>) -> Self {
+        val.as_deref()
+            .map_or(Ok(Self::order_date), |s| s.parse())
+            .unwrap_or(Self::order_date)
+    }
+
+    /// Binds a cursor value as the column's own type so it compares like
+    /// the column; a value that does not parse as that type is refused
+    fn push_cursor_value(
+        self,
+        builder: &mut QueryBuilder<'_, Postgres>,
+        value: &str,
+    ) -> Result<(), String> {
+        let invalid = || format!("Invalid cursor value '{value}' for sort {self}");
+        match self {
+            Self::order_date => {
+                builder.push_bind(value.parse::<NaiveDate>().map_err(|_| invalid())?);
+            }
+            Self::order_number => {
+                builder.push_bind(value.to_string());
+            }
+            Self::created_at => {
+                builder.push_bind(value.parse::<DateTime<Utc>>().map_err(|_| invalid())?);
+            }
+        }
+        Ok(())
+    }
+
+    fn cursor_value(self, order: &SalesOrderListItem) -> String {
+        match self {
+            Self::order_date => order.order_date.to_string(),
+            Self::order_number => order.order_number.clone(),
+            Self::created_at => order
+                .created_at
+                .to_rfc3339_opts(SecondsFormat::Micros, true),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, sqlx::FromRow)]
+pub struct SalesOrderListItem {
+    pub id: Uuid,
+    pub order_number: String,
+    pub status: String,
+    pub order_date: NaiveDate,
+    pub expected_delivery_date: Option<NaiveDate>,
+    pub customer_id: Uuid,
+    pub customer_name: String,
+    pub agent_id: Option<Uuid>,
+    pub fulfillment_warehouse_id: Option<Uuid>,
+    pub total_amount: Decimal,
+    pub pending_quantity: Decimal,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SalesOrderPage {
+    pub orders: Vec<SalesOrderListItem>,
+    /// Pass back as `cursor` to fetch the next page; absent on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// Order book of the caller's warehouse, newest orders first by default.
+/// Pages are keyed on the sort column and id rather than offsets, so deep
+/// pages cost the same as the first one.
+pub async fn get_sales_orders(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Query(query): Query<SalesOrderQuery>,
+) -> Result<Json<SalesOrderPage>, SalesOrderError> {
+    let status = query
+        .status
+        .as_deref()
+        .map(|s| {
+            s.parse::<SalesOrderStatus>()
+                .map_err(|_| SalesOrderError::ValidationError(format!("Unknown status '{s}'")))
+        })
+        .transpose()?;
+    if let (Some(from_date), Some(to_date)) = (query.from_date, query.to_date) {
+        if to_date < from_date {
+            return Err(SalesOrderError::ValidationError(
+                "to_date cannot be before from_date".to_string(),
+            ));
+        }
+    }
+    let cursor = query
+        .cursor
+        .as_deref()
+        .map(Cursor::parse)
+        .transpose()
+        .map_err(SalesOrderError::ValidationError)?;
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    let sort = SortField::parse(query.sort);
+    let order = SortOrder::parse(query.order, SortOrder::DESC);
+
+    let mut builder = QueryBuilder::<Postgres>::new(
+        r#"
+        SELECT so.id, so.order_number, so.status, so.order_date, so.expected_delivery_date,
+               so.customer_id,
+               COALESCE(NULLIF(p.company_name, ''), CONCAT_WS(' ', p.first_name, p.last_name)) as customer_name,
+               so.agent_id, so.fulfillment_warehouse_id,
+               COALESCE(so.total_amount, 0) as total_amount,
+               COALESCE((SELECT SUM(GREATEST(soi.pending_quantity, 0)) FROM sales_order_items soi
+                         WHERE soi.sales_order_id = so.id), 0) as pending_quantity,
+               so.created_at
+        FROM sales_orders so
+        JOIN partners p ON p.id = so.customer_id
+        WHERE so.deleted_at IS NULL AND so.company_id = "#,
+    );
+    builder.push_bind(user.company_id);
+
+    // Admins work in their current warehouse unless they pick another one
+    if user.is_admin() {
+        if let Some(warehouse_id) = query.warehouse_id.or(user.warehouse_id) {
+            builder
+                .push(" AND so.fulfillment_warehouse_id = ")
+                .push_bind(warehouse_id);
+        }
+    } else {
+        builder
+            .push(" AND so.fulfillment_warehouse_id IS NOT DISTINCT FROM ")
+            .push_bind(user.warehouse_id);
+    }
+    if let Some(status) = status {
+        builder
+            .push(" AND so.status = ")
+            .push_bind(status.to_string());
+    }
+    if let Some(customer_id) = query.customer_id {
+        builder
+            .push(" AND so.customer_id = ")
+            .push_bind(customer_id);
+    }
+    if let Some(agent_id) = query.agent_id {
+        builder.push(" AND so.agent_id = ").push_bind(agent_id);
+    }
+    if let Some(product_id) = query.product_id {
+        builder
+            .push(" AND EXISTS (SELECT 1 FROM sales_order_items soi WHERE soi.sales_order_id = so.id AND soi.product_id = ")
+            .push_bind(product_id)
+            .push(")");
+    }
+    if let Some(from_date) = query.from_date {
+        builder.push(" AND so.order_date >= ").push_bind(from_date);
+    }
+    if let Some(to_date) = query.to_date {
+        builder.push(" AND so.order_date <= ").push_bind(to_date);
+    }
+    if let Some(cursor) = cursor {
+        builder.push(format!(" AND (so.{sort}, so.id) {} (", order.after()));
+        sort.push_cursor_value(&mut builder, &cursor.value)
+            .map_err(SalesOrderError::ValidationError)?;
+        builder.push(", ").push_bind(cursor.id).push(")");
+    }
+    // One extra row tells whether another page follows
+    builder
+        .push(format!(" ORDER BY so.{sort} {order}, so.id {order} LIMIT "))
+        .push_bind(limit + 1);
+
+    let mut orders = builder
+        .build_query_as::<SalesOrderListItem>()
+        .fetch_all(db_pool.as_ref())
+        .await
+        .context("Failed to fetch sales orders from database.")?;
+
+    let next_cursor = if orders.len() as i64 > limit {
+        orders.truncate(limit as usize);
+        orders
+            .last()
+            .map(|last| Cursor::new(sort.cursor_value(last), last.id).to_string())
+    } else {
+        None
+    };
+
+    Ok(Json(SalesOrderPage {
+        orders,
+        next_cursor,
+    }))
+}
+
+pub async fn get_status_history(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(sales_order_id): Path<Uuid>,
+) -> Result<Json<Vec<StatusChange>>, SalesOrderError> {
+    // Staff only see orders fulfilled from their own warehouse
+    sqlx::query_scalar!(
+        r#"
+        SELECT id FROM sales_orders
+        WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+            AND ($3 OR fulfillment_warehouse_id IS NOT DISTINCT FROM $4)
+        "#,
+        sales_order_id,
+        user.company_id,
+        user.is_admin(),
+        user.warehouse_id
+    )
+    .fetch_optional(db_pool.as_ref())
+    .await
+    .context("Failed to fetch sales order from database.")?
+    .ok_or(SalesOrderError::NotFound)?;
+
+    let history = sqlx::query_as!(
+        StatusChange,
+        r#"
+        SELECT from_status, to_status, notes, is_override, changed_at, changed_by
+        FROM sales_order_status_history
+        WHERE sales_order_id = $1
+        ORDER BY changed_at, id
+        "#,
+        sales_order_id
+    )
+    .fetch_all(db_pool.as_ref())
+    .await
+    .context("Failed to fetch sales order status history from database.")?;
+
+    Ok(Json(history))
+}
+
+// UPDATE
+// -------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompletionRequest {
+    notes: String,
+    /// Complete even though some items are not fully dispatched
+    #[serde(default)]
+    force: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CancellationRequest {
+    reason: String,
+    /// Cancel the order's active dispatches along with it
+    #[serde(default)]
+    cancel_dispatches: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReopenRequest {
+    reason: String,
+}
+
+pub async fn approve_sales_order(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(sales_order_id): Path<Uuid>,
+) -> Result<StatusCode, SalesOrderError> {
+    let mut transaction = begin_admin_transaction(&db_pool, &user).await?;
+
+    let status = lock_sales_order(&mut transaction, user.company_id, sales_order_id).await?;
+    if status != SalesOrderStatus::ApprovalPending {
+        return Err(SalesOrderError::InvalidState(format!(
+            "Only orders awaiting approval can be approved, this one is {}",
+            status
+        )));
+    }
+
+    change_status(
+        &mut transaction,
+        &user,
+        sales_order_id,
+        status,
+        SalesOrderStatus::InProgress,
+        None,
+        false,
+    )
+    .await?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to approve a sales order.")?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Completes an order once every item is fully dispatched. `force` completes
+/// it anyway, e.g. when the customer accepts a short delivery.
+pub async fn complete_sales_order(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(sales_order_id): Path<Uuid>,
+    Json(request): Json<CompletionRequest>,
+) -> Result<StatusCode, SalesOrderError> {
+    let notes = non_empty(&request.notes, "Completion notes are required")?;
+    let mut transaction = begin_admin_transaction(&db_pool, &user).await?;
+
+    let status = lock_sales_order(&mut transaction, user.company_id, sales_order_id).await?;
+    if status != SalesOrderStatus::InProgress {
+        return Err(SalesOrderError::InvalidState(format!(
+            "Only orders in progress can be completed, this one is {}",
+            status
+        )));
+    }
+
+    let fully_dispatched = sqlx::query_scalar!(
+        r#"
+        SELECT COALESCE(bool_and(pending_quantity <= 0), false) as "fully_dispatched!"
+        FROM sales_order_items
+        WHERE sales_order_id = $1
+        "#,
+        sales_order_id
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to fetch sales order dispatch progress from database.")?;
+    if !fully_dispatched && !request.force {
+        return Err(SalesOrderError::InvalidState(
+            "Order is not fully dispatched; set `force` to complete it anyway".to_string(),
+        ));
+    }
+
+    change_status(
+        &mut transaction,
+        &user,
+        sales_order_id,
+        status,
+        SalesOrderStatus::Completed,
+        Some(notes),
+        !fully_dispatched,
+    )
+    .await?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to complete a sales order.")?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Cancels an order that is awaiting approval or in progress. Orders with
+/// active dispatches are only cancelled when `cancel_dispatches` is set, in
+/// which case the dispatched units go back into stock.
+pub async fn cancel_sales_order(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(sales_order_id): Path<Uuid>,
+    Json(request): Json<CancellationRequest>,
+) -> Result<StatusCode, SalesOrderError> {
+    let reason = non_empty(&request.reason, "A cancellation reason is required")?;
+    let mut transaction = begin_admin_transaction(&db_pool, &user).await?;
+
+    let status = lock_sales_order(&mut transaction, user.company_id, sales_order_id).await?;
+    if !matches!(
+        status,
+        SalesOrderStatus::ApprovalPending | SalesOrderStatus::InProgress
+    ) {
+        return Err(SalesOrderError::InvalidState(format!(
+            "A {} order cannot be cancelled",
+            status
+        )));
+    }
+
+    let dispatch_ids = sqlx::query_scalar!(
+        r#"
+        SELECT id FROM goods_dispatches
+        WHERE sales_order_id = $1 AND is_cancelled IS NOT TRUE AND deleted_at IS NULL
+        FOR UPDATE
+        "#,
+        sales_order_id
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to fetch linked dispatches from database.")?;
+    if !dispatch_ids.is_empty() && !request.cancel_dispatches {
+        return Err(SalesOrderError::InvalidState(format!(
+            "Order has {} active dispatch(es); set `cancel_dispatches` to cancel them too",
+            dispatch_ids.len()
+        )));
+    }
+
+    let moved = fetch_moved_units_from_db(&mut transaction, &dispatch_ids)
+        .await
+        .context("Failed to fetch dispatched units from database.")?;
+    if !moved.is_empty() {
+        return Err(SalesOrderError::InvalidState(format!(
+            "Units {} have moved on since they were dispatched",
+            moved.join(", ")
+        )));
+    }
+
+    for dispatch_id in dispatch_ids {
+        cancel_dispatch_in_db(&mut transaction, &user, dispatch_id, reason)
+            .await
+            .context("Failed to cancel linked dispatch.")?;
+    }
+
+    change_status(
+        &mut transaction,
+        &user,
+        sales_order_id,
+        status,
+        SalesOrderStatus::Cancelled,
+        Some(reason),
+        false,
+    )
+    .await?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to cancel a sales order.")?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Moves a completed order back into progress, or a cancelled one back to
+/// approval.
+pub async fn reopen_sales_order(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(sales_order_id): Path<Uuid>,
+    Json(request): Json<ReopenRequest>,
+) -> Result<StatusCode, SalesOrderError> {
+    let reason = non_empty(&request.reason, "A reason for reopening is required")?;
+    let mut transaction = begin_admin_transaction(&db_pool, &user).await?;
+
+    let status = lock_sales_order(&mut transaction, user.company_id, sales_order_id).await?;
+    let to_status = match status {
+        SalesOrderStatus::Completed => SalesOrderStatus::InProgress,
+        SalesOrderStatus::Cancelled => SalesOrderStatus::ApprovalPending,
+        _ => {
+            return Err(SalesOrderError::InvalidState(format!(
+                "Only completed or cancelled orders can be reopened, this one is {}",
+                status
+            )))
+        }
+    };
+
+    change_status(
+        &mut transaction,
+        &user,
+        sales_order_id,
+        status,
+        to_status,
+        Some(reason),
+        false,
+    )
+    .await?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to reopen a sales order.")?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Same guidance as the `validate_required_quantity` trigger
+const REDUCE_QUANTITY_HINT: &str = "To reduce quantity: 1) Cancel existing dispatches, \
+     2) Update required quantity, 3) Create new dispatches if needed";
+const REMOVE_ITEM_HINT: &str = "To remove this item: 1) Cancel existing dispatches, \
+     2) Remove the item, 3) Create new dispatches for the remaining items if needed";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SalesOrderItemUpdate {
+    required_quantity: Option<Decimal>,
+    unit_rate: Option<Decimal>,
+    notes: Option<String>,
+}
+
+struct LockedItem {
+    required_quantity: Decimal,
+    dispatched_quantity: Decimal,
+}
+
+pub async fn add_sales_order_item(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path(sales_order_id): Path<Uuid>,
+    Json(item): Json<NewSalesOrderItem>,
+) -> Result<(StatusCode, Json<SalesOrderSummary>), SalesOrderError> {
+    item.validate().map_err(SalesOrderError::ValidationError)?;
+    let mut transaction = begin_admin_transaction(&db_pool, &user).await?;
+
+    lock_editable_order(&mut transaction, user.company_id, sales_order_id).await?;
+    ensure_products_exist(&mut transaction, user.company_id, &[item.product_id]).await?;
+    insert_sales_order_item_in_db(&mut transaction, user.company_id, sales_order_id, &item)
+        .await
+        .context("Failed to insert sales order item in the database.")?;
+
+    let summary = finish_item_edit(transaction, sales_order_id).await?;
+
+    Ok((StatusCode::CREATED, Json(summary)))
+}
+
+pub async fn update_sales_order_item(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path((sales_order_id, item_id)): Path<(Uuid, Uuid)>,
+    Json(update): Json<SalesOrderItemUpdate>,
+) -> Result<Json<SalesOrderSummary>, SalesOrderError> {
+    if update.required_quantity.is_some_and(|q| q <= Decimal::ZERO) {
+        return Err(SalesOrderError::ValidationError(
+            "Required quantity must be greater than zero".to_string(),
+        ));
+    }
+    if update.unit_rate.is_some_and(|rate| rate < Decimal::ZERO) {
+        return Err(SalesOrderError::ValidationError(
+            "Unit rate cannot be negative".to_string(),
+        ));
+    }
+    let mut transaction = begin_admin_transaction(&db_pool, &user).await?;
+
+    lock_editable_order(&mut transaction, user.company_id, sales_order_id).await?;
+    let item = lock_sales_order_item(&mut transaction, sales_order_id, item_id).await?;
+
+    // Checked here so callers get guidance instead of the trigger's raw exception.
+    // Raising the quantity of an over-dispatched line is fine.
+    if let Some(required_quantity) = update.required_quantity {
+        if required_quantity < item.required_quantity
+            && required_quantity < item.dispatched_quantity
+        {
+            return Err(SalesOrderError::DispatchedQuantityConflict {
+                message: format!(
+                    "Cannot reduce required quantity ({}) below dispatched quantity ({})",
+                    required_quantity, item.dispatched_quantity
+                ),
+                hint: REDUCE_QUANTITY_HINT,
+            });
+        }
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE sales_order_items
+        SET required_quantity = $2, unit_rate = COALESCE($3, unit_rate), notes = COALESCE($4, notes)
+        WHERE id = $1
+        "#,
+        item_id,
+        update.required_quantity.unwrap_or(item.required_quantity),
+        update.unit_rate,
+        update.notes
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to update sales order item.")?;
+
+    let summary = finish_item_edit(transaction, sales_order_id).await?;
+
+    Ok(Json(summary))
+}
+
+pub async fn remove_sales_order_item(
+    State(db_pool): State<Arc<PgPool>>,
+    user: CurrentUser,
+    Path((sales_order_id, item_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<SalesOrderSummary>, SalesOrderError> {
+    let mut transaction = begin_admin_transaction(&db_pool, &user).await?;
+
+    lock_editable_order(&mut transaction, user.company_id, sales_order_id).await?;
+    let item = lock_sales_order_item(&mut transaction, sales_order_id, item_id).await?;
+    if item.dispatched_quantity > Decimal::ZERO {
+        return Err(SalesOrderError::DispatchedQuantityConflict {
+            message: format!(
+                "Cannot remove an item with dispatched quantity ({})",
+                item.dispatched_quantity
+            ),
+            hint: REMOVE_ITEM_HINT,
+        });
+    }
+
+    let remaining_items = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM sales_order_items WHERE sales_order_id = $1"#,
+        sales_order_id
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to count sales order items.")?;
+    if remaining_items <= 1 {
+        return Err(SalesOrderError::ValidationError(
+            "A sales order needs at least one item; cancel the order instead".to_string(),
+        ));
+    }
+
+    sqlx::query!("DELETE FROM sales_order_items WHERE id = $1", item_id)
+        .execute(&mut *transaction)
+        .await
+        .context("Failed to delete sales order item.")?;
+
+    let summary = finish_item_edit(transaction, sales_order_id).await?;
+
+    Ok(Json(summary))
+}
+
+async fn lock_editable_order(
+    transaction: &mut Transaction<'_, Postgres>,
+    company_id: Uuid,
+    sales_order_id: Uuid,
+) -> Result<(), SalesOrderError> {
+    let status = lock_sales_order(transaction, company_id, sales_order_id).await?;
+    if !matches!(
+        status,
+        SalesOrderStatus::ApprovalPending | SalesOrderStatus::InProgress
+    ) {
+        return Err(SalesOrderError::InvalidState(format!(
+            "Items of a {} order cannot be changed",
+            status
+        )));
+    }
+    Ok(())
+}
+
+async fn lock_sales_order_item(
+    transaction: &mut Transaction<'_, Postgres>,
+    sales_order_id: Uuid,
+    item_id: Uuid,
+) -> Result<LockedItem, SalesOrderError> {
+    let item = sqlx::query_as!(
+        LockedItem,
+        r#"
+        SELECT required_quantity, COALESCE(dispatched_quantity, 0) as "dispatched_quantity!"
+        FROM sales_order_items
+        WHERE id = $1 AND sales_order_id = $2
+        FOR UPDATE
+        "#,
+        item_id,
+        sales_order_id
+    )
+    .fetch_optional(&mut **transaction)
+    .await
+    .context("Failed to fetch sales order item from database.")?
+    .ok_or(SalesOrderError::NotFound)?;
+
+    Ok(item)
+}
+
+/// Reads back the trigger-maintained totals, rejects the edit if the advance
+/// no longer fits and commits otherwise.
+async fn finish_item_edit(
+    mut transaction: Transaction<'_, Postgres>,
+    sales_order_id: Uuid,
+) -> Result<SalesOrderSummary, SalesOrderError> {
+    let summary = fetch_summary_from_db(&mut transaction, sales_order_id)
+        .await
+        .context("Failed to fetch sales order totals from database.")?;
+    if summary.advance_amount > summary.total_amount {
+        return Err(SalesOrderError::ValidationError(format!(
+            "Advance of {} would exceed the new order total of {}",
+            summary.advance_amount, summary.total_amount
+        )));
+    }
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to update sales order items.")?;
+
+    Ok(summary)
+}
+
+fn non_empty<'a>(value: &'a str, message: &str) -> Result<&'a str, SalesOrderError> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(SalesOrderError::ValidationError(message.to_string()));
+    }
+    Ok(value)
+}
+
+pub(crate) async fn begin_admin_transaction<'a>(
+    db_pool: &'a PgPool,
+    user: &CurrentUser,
+) -> Result<Transaction<'a, Postgres>, SalesOrderError> {
+    if !user.is_admin() {
+        return Err(SalesOrderError::Forbidden);
+    }
+    let transaction = db_pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool.")?;
+    Ok(transaction)
+}
+
+async fn lock_sales_order(
+    transaction: &mut Transaction<'_, Postgres>,
+    company_id: Uuid,
+    sales_order_id: Uuid,
+) -> Result<SalesOrderStatus, SalesOrderError> {
+    let status = sqlx::query_scalar!(
+        r#"
+        SELECT status FROM sales_orders
+        WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL
+        FOR UPDATE
+        "#,
+        sales_order_id,
+        company_id
+    )
+    .fetch_optional(&mut **transaction)
+    .await
+    .context("Failed to fetch sales order from database.")?
+    .ok_or(SalesOrderError::NotFound)?;
+
+    let status = status
+        .parse()
+        .context("Failed to parse sales order status.")?;
+    Ok(status)
+}
+
+async fn change_status(
+    transaction: &mut Transaction<'_, Postgres>,
+    user: &CurrentUser,
+    sales_order_id: Uuid,
+    from_status: SalesOrderStatus,
+    to_status: SalesOrderStatus,
+    notes: Option<&str>,
+    is_override: bool,
+) -> Result<(), SalesOrderError> {
+    sqlx::query!(
+        r#"
+        UPDATE sales_orders
+        SET status = $2, status_changed_at = NOW(), status_changed_by = $3, status_notes = $4,
+            modified_by = $3
+        WHERE id = $1
+        "#,
+        sales_order_id,
+        to_status.to_string(),
+        user.id,
+        notes
+    )
+    .execute(&mut **transaction)
+    .await
+    .context("Failed to update sales order status.")?;
+
+    insert_status_change_in_db(
+        transaction,
+        user,
+        sales_order_id,
+        Some(from_status),
+        to_status,
+        notes,
+        is_override,
+    )
+    .await
+    .context("Failed to insert sales order status history in the database.")?;
+
+    Ok(())
+}
+
+/// Recomputes each item's dispatched quantity from the order's active
+/// dispatches, less the units the customer has returned.
+pub(crate) async fn refresh_dispatched_quantities_in_db(
+    transaction: &mut Transaction<'_, Postgres>,
+    sales_order_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE sales_order_items soi
+        SET dispatched_quantity = COALESCE((
+            SELECT SUM(su.size_quantity)
+            FROM goods_dispatches gd
+            JOIN goods_dispatch_items gdi ON gd.id = gdi.dispatch_id
+            JOIN stock_units su ON gdi.stock_unit_id = su.id
+            WHERE gd.sales_order_id = soi.sales_order_id
+                AND gd.is_cancelled IS NOT TRUE AND gd.deleted_at IS NULL
+                AND su.product_id = soi.product_id
+                AND NOT EXISTS (
+                    SELECT 1 FROM sales_return_items sri
+                    JOIN goods_receipts gr ON gr.id = sri.receipt_id
+                    WHERE sri.dispatch_id = gd.id AND sri.stock_unit_id = su.id
+                        AND gr.deleted_at IS NULL
+                )
+        ), 0)
+        WHERE soi.sales_order_id = $1
+        "#,
+        sales_order_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+}
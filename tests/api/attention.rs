@@ -0,0 +1,247 @@
+use std::sync::Mutex;
+
+use bale_backend::{
+    auth::USER_ID_HEADER,
+    config::ApplicationSettings,
+    reminders::{send_overdue_reminders, Notifier, OverdueItem},
+};
+use chrono::{Days, NaiveDate, Utc};
+use reqwest::StatusCode;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::test_app::{TestApp, TestTenant};
+
+// HELPERS
+// -------------------------------------------------------------------------------------
+
+#[derive(Default)]
+struct RecordingNotifier {
+    sent: Mutex<Vec<OverdueItem>>,
+}
+
+impl Notifier for RecordingNotifier {
+    async fn notify(&self, item: &OverdueItem) -> anyhow::Result<()> {
+        self.sent.lock().unwrap().push(item.clone());
+        Ok(())
+    }
+}
+
+fn days_ago(days: u64) -> NaiveDate {
+    Utc::now().date_naive() - Days::new(days)
+}
+
+async fn seed_job_work(
+    app: &TestApp,
+    tenant: &TestTenant,
+    warehouse_id: Uuid,
+    due_date: NaiveDate,
+    status: &str,
+) -> Uuid {
+    let vendor_id = app.seed_partner(tenant, "Vendor").await;
+    sqlx::query_scalar!(
+        r#"
+        INSERT INTO job_works (company_id, warehouse_id, job_type, vendor_id, start_date, due_date,
+                               status, created_by)
+        VALUES ($1, $2, 'Dyeing', $3, $4, $5, $6, $7)
+        RETURNING id
+        "#,
+        tenant.company_id,
+        warehouse_id,
+        vendor_id,
+        due_date - Days::new(10),
+        due_date,
+        status,
+        tenant.admin_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap()
+}
+
+async fn seed_delivery(
+    app: &TestApp,
+    tenant: &TestTenant,
+    expected_delivery_date: NaiveDate,
+    status: &str,
+) -> Uuid {
+    let customer_id = app.seed_partner(tenant, "Customer").await;
+    let order_id = app
+        .seed_sales_order(
+            tenant,
+            customer_id,
+            expected_delivery_date - Days::new(10),
+            1000,
+            0,
+            status,
+        )
+        .await;
+    sqlx::query!(
+        r#"
+        UPDATE sales_orders SET expected_delivery_date = $2, fulfillment_warehouse_id = $3
+        WHERE id = $1
+        "#,
+        order_id,
+        expected_delivery_date,
+        tenant.warehouse_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    order_id
+}
+
+async fn get_attention_needed(
+    app: &TestApp,
+    user_id: Uuid,
+    warehouse_id: Uuid,
+) -> reqwest::Response {
+    app.api_client
+        .get(format!(
+            "{}/api/v1/warehouses/{}/attention-needed",
+            app.address, warehouse_id
+        ))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .send()
+        .await
+        .unwrap()
+}
+
+// READ
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn attention_needed_lists_open_overdue_records_of_the_warehouse() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let other_warehouse = app.seed_warehouse(&tenant, "Surat Branch").await;
+    let late_job_work = seed_job_work(
+        &app,
+        &tenant,
+        tenant.warehouse_id,
+        days_ago(3),
+        "in_progress",
+    )
+    .await;
+    seed_job_work(&app, &tenant, tenant.warehouse_id, days_ago(3), "completed").await;
+    seed_job_work(
+        &app,
+        &tenant,
+        tenant.warehouse_id,
+        days_ago(0),
+        "in_progress",
+    )
+    .await;
+    seed_job_work(&app, &tenant, other_warehouse, days_ago(3), "in_progress").await;
+    let late_order = seed_delivery(&app, &tenant, days_ago(8), "in_progress").await;
+    seed_delivery(&app, &tenant, days_ago(8), "cancelled").await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let dispatch_id = app
+        .seed_dispatch(&tenant, customer_id, None, Some(late_order), &[])
+        .await;
+    sqlx::query!(
+        "UPDATE goods_dispatches SET due_date = $2 WHERE id = $1",
+        dispatch_id,
+        days_ago(5)
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let res = get_attention_needed(&app, tenant.admin_id, tenant.warehouse_id).await;
+    let status = res.status();
+    let items: Vec<OverdueItem> = res.json().await.unwrap();
+    let found: Vec<(&str, Uuid, i32)> = items
+        .iter()
+        .map(|i| (i.entity_type.as_str(), i.entity_id, i.days_overdue))
+        .collect();
+
+    assert_eq!(StatusCode::OK, status);
+    assert_eq!(
+        vec![
+            ("sales_order", late_order, 8),
+            ("dispatch", dispatch_id, 5),
+            ("job_work", late_job_work, 3),
+        ],
+        found
+    );
+}
+
+#[tokio::test]
+async fn staff_only_see_attention_needed_for_their_own_warehouse() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let other_warehouse = app.seed_warehouse(&tenant, "Surat Branch").await;
+    let staff_id = app.seed_staff(&tenant, other_warehouse).await;
+
+    let own = get_attention_needed(&app, staff_id, other_warehouse).await;
+    let elsewhere = get_attention_needed(&app, staff_id, tenant.warehouse_id).await;
+
+    assert_eq!(StatusCode::OK, own.status());
+    assert_eq!(StatusCode::FORBIDDEN, elsewhere.status());
+}
+
+// REMINDERS
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn reminders_are_sent_once_per_due_date() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let job_work_id = seed_job_work(
+        &app,
+        &tenant,
+        tenant.warehouse_id,
+        days_ago(2),
+        "in_progress",
+    )
+    .await;
+    seed_delivery(&app, &tenant, days_ago(1), "approval_pending").await;
+    let notifier = RecordingNotifier::default();
+    let today = Utc::now().date_naive();
+
+    let first_run = send_overdue_reminders(&app.db_pool, &notifier, today)
+        .await
+        .unwrap();
+    let second_run = send_overdue_reminders(&app.db_pool, &notifier, today)
+        .await
+        .unwrap();
+    sqlx::query!(
+        "UPDATE job_works SET due_date = $2 WHERE id = $1",
+        job_work_id,
+        days_ago(1)
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    let after_due_date_moved = send_overdue_reminders(&app.db_pool, &notifier, today)
+        .await
+        .unwrap();
+
+    assert_eq!(2, first_run);
+    assert_eq!(0, second_run);
+    assert_eq!(1, after_due_date_moved);
+    assert_eq!(job_work_id, notifier.sent.lock().unwrap()[2].entity_id);
+}
+
+#[test]
+fn zero_reminder_interval_is_refused() {
+    let settings = |minutes: &str| {
+        serde_json::from_value::<ApplicationSettings>(json!({
+            "port": 8000,
+            "host": "127.0.0.1",
+            "base_url": "http://127.0.0.1",
+            "reminder_interval_minutes": minutes,
+        }))
+    };
+
+    assert!(settings("0").is_err());
+    assert_eq!(
+        60,
+        settings("60")
+            .unwrap()
+            .reminder_interval_minutes
+            .unwrap()
+            .get()
+    );
+}
@@ -0,0 +1,302 @@
+use bale_backend::{
+    auth::USER_ID_HEADER,
+    routes::commissions::{CommissionAccrual, CommissionReport},
+};
+use reqwest::StatusCode;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::test_app::{TestApp, TestTenant};
+
+// HELPERS
+// -------------------------------------------------------------------------------------
+
+async fn post_rule(
+    app: &TestApp,
+    tenant: &TestTenant,
+    agent_id: Uuid,
+    body: &serde_json::Value,
+) -> reqwest::Response {
+    app.api_client
+        .post(format!(
+            "{}/api/v1/agents/{}/commission-rules",
+            app.address, agent_id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .json(body)
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn accrue(app: &TestApp, tenant: &TestTenant, dispatch_id: Uuid) -> Vec<CommissionAccrual> {
+    app.api_client
+        .post(format!(
+            "{}/api/v1/dispatches/{}/commissions",
+            app.address, dispatch_id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+async fn get_report(app: &TestApp, tenant: &TestTenant, agent_id: Uuid) -> CommissionReport {
+    app.api_client
+        .get(format!(
+            "{}/api/v1/agents/{}/commissions",
+            app.address, agent_id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+async fn pay(
+    app: &TestApp,
+    tenant: &TestTenant,
+    agent_id: Uuid,
+    accrual_ids: &[Uuid],
+) -> reqwest::Response {
+    app.api_client
+        .post(format!(
+            "{}/api/v1/agents/{}/commissions/pay",
+            app.address, agent_id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .json(&serde_json::json!({
+            "accrual_ids": accrual_ids,
+            "payment_reference": "NEFT-55120",
+        }))
+        .send()
+        .await
+        .unwrap()
+}
+
+// RULES
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn create_rule_rejects_partner_that_is_not_an_agent() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+
+    let body = serde_json::json!({ "rule_type": "percentage", "rate": "2" });
+    let res = post_rule(&app, &tenant, customer_id, &body).await;
+
+    assert_eq!(StatusCode::NOT_FOUND, res.status());
+}
+
+#[tokio::test]
+async fn create_rule_rejects_percentage_above_hundred() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let agent_id = app.seed_partner(&tenant, "Agent").await;
+
+    let body = serde_json::json!({ "rule_type": "percentage", "rate": "120" });
+    let res = post_rule(&app, &tenant, agent_id, &body).await;
+
+    assert_eq!(StatusCode::BAD_REQUEST, res.status());
+}
+
+// ACCRUAL
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn percentage_rule_accrues_on_dispatched_value() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let agent_id = app.seed_partner(&tenant, "Agent").await;
+    let product_id = app.seed_product(&tenant, 200, &[]).await;
+    let units = vec![
+        app.seed_stock_unit(&tenant, product_id, 10).await,
+        app.seed_stock_unit(&tenant, product_id, 5).await,
+    ];
+    let dispatch_id = app
+        .seed_dispatch(&tenant, customer_id, Some(agent_id), None, &units)
+        .await;
+    let body = serde_json::json!({
+        "rule_type": "percentage",
+        "rate": "2.5",
+        "effective_from": "2000-01-01",
+    });
+    post_rule(&app, &tenant, agent_id, &body).await;
+
+    let accruals = accrue(&app, &tenant, dispatch_id).await;
+
+    assert_eq!(1, accruals.len());
+    assert_eq!(Decimal::from(15), accruals[0].quantity);
+    assert_eq!(Decimal::from(3_000), accruals[0].base_amount);
+    assert_eq!(Decimal::from(75), accruals[0].commission_amount);
+    assert_eq!("accrued", accruals[0].status);
+}
+
+#[tokio::test]
+async fn tag_rule_takes_precedence_over_general_rule() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let agent_id = app.seed_partner(&tenant, "Agent").await;
+    let silk_id = app.seed_product(&tenant, 500, &["silk"]).await;
+    let cotton_id = app.seed_product(&tenant, 100, &["cotton"]).await;
+    let units = vec![
+        app.seed_stock_unit(&tenant, silk_id, 20).await,
+        app.seed_stock_unit(&tenant, cotton_id, 30).await,
+    ];
+    let dispatch_id = app
+        .seed_dispatch(&tenant, customer_id, Some(agent_id), None, &units)
+        .await;
+    for body in [
+        serde_json::json!({ "rule_type": "percentage", "rate": "2", "effective_from": "2000-01-01" }),
+        serde_json::json!({
+            "rule_type": "per_unit",
+            "rate": "4",
+            "product_tag": "silk",
+            "effective_from": "2000-01-01",
+        }),
+    ] {
+        post_rule(&app, &tenant, agent_id, &body).await;
+    }
+
+    let accruals = accrue(&app, &tenant, dispatch_id).await;
+    let commission_for = |product_id: Uuid| {
+        accruals
+            .iter()
+            .find(|a| a.product_id == product_id)
+            .map(|a| a.commission_amount)
+    };
+
+    assert_eq!(2, accruals.len());
+    assert_eq!(Some(Decimal::from(80)), commission_for(silk_id));
+    assert_eq!(Some(Decimal::from(60)), commission_for(cotton_id));
+}
+
+#[tokio::test]
+async fn accrual_uses_order_agent_rate_and_discount() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let agent_id = app.seed_partner(&tenant, "Agent").await;
+    let product_id = app.seed_product(&tenant, 200, &[]).await;
+    let order_id = app
+        .seed_sales_order(
+            &tenant,
+            customer_id,
+            chrono::Utc::now().date_naive(),
+            0,
+            0,
+            "in_progress",
+        )
+        .await;
+    sqlx::query!(
+        "UPDATE sales_orders SET agent_id = $2, discount_percentage = 10 WHERE id = $1",
+        order_id,
+        agent_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        r#"
+        INSERT INTO sales_order_items (company_id, sales_order_id, product_id, required_quantity, unit_rate)
+        VALUES ($1, $2, $3, 10, 300)
+        "#,
+        tenant.company_id,
+        order_id,
+        product_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    let units = vec![app.seed_stock_unit(&tenant, product_id, 10).await];
+    let dispatch_id = app
+        .seed_dispatch(&tenant, customer_id, None, Some(order_id), &units)
+        .await;
+    let body = serde_json::json!({
+        "rule_type": "percentage",
+        "rate": "5",
+        "effective_from": "2000-01-01",
+    });
+    post_rule(&app, &tenant, agent_id, &body).await;
+
+    let accruals = accrue(&app, &tenant, dispatch_id).await;
+
+    assert_eq!(1, accruals.len());
+    assert_eq!(agent_id, accruals[0].agent_id);
+    assert_eq!(Some(order_id), accruals[0].sales_order_id);
+    assert_eq!(Decimal::from(2_700), accruals[0].base_amount);
+    assert_eq!(Decimal::from(135), accruals[0].commission_amount);
+}
+
+#[tokio::test]
+async fn accruing_twice_does_not_duplicate_commissions() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let agent_id = app.seed_partner(&tenant, "Agent").await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let units = vec![app.seed_stock_unit(&tenant, product_id, 10).await];
+    let dispatch_id = app
+        .seed_dispatch(&tenant, customer_id, Some(agent_id), None, &units)
+        .await;
+    let body = serde_json::json!({
+        "rule_type": "per_unit",
+        "rate": "1.5",
+        "effective_from": "2000-01-01",
+    });
+    post_rule(&app, &tenant, agent_id, &body).await;
+
+    accrue(&app, &tenant, dispatch_id).await;
+    let accruals = accrue(&app, &tenant, dispatch_id).await;
+
+    assert_eq!(1, accruals.len());
+    assert_eq!(Decimal::from(15), accruals[0].commission_amount);
+}
+
+// PAYABLE REPORT
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn paying_commissions_moves_them_out_of_payable() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let agent_id = app.seed_partner(&tenant, "Agent").await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let body = serde_json::json!({
+        "rule_type": "percentage",
+        "rate": "10",
+        "effective_from": "2000-01-01",
+    });
+    post_rule(&app, &tenant, agent_id, &body).await;
+
+    let mut accrual_ids = Vec::new();
+    for size in [10, 20] {
+        let units = vec![app.seed_stock_unit(&tenant, product_id, size).await];
+        let dispatch_id = app
+            .seed_dispatch(&tenant, customer_id, Some(agent_id), None, &units)
+            .await;
+        accrual_ids.push(accrue(&app, &tenant, dispatch_id).await[0].id);
+    }
+    let before = get_report(&app, &tenant, agent_id).await;
+
+    let first = pay(&app, &tenant, agent_id, &accrual_ids[..1]).await;
+    let second = pay(&app, &tenant, agent_id, &accrual_ids[..1]).await;
+    let after = get_report(&app, &tenant, agent_id).await;
+
+    assert_eq!(Decimal::from(300), before.total_payable);
+    assert_eq!(StatusCode::NO_CONTENT, first.status());
+    assert_eq!(StatusCode::CONFLICT, second.status());
+    assert_eq!(Decimal::from(300), after.total_accrued);
+    assert_eq!(Decimal::from(100), after.total_paid);
+    assert_eq!(Decimal::from(200), after.total_payable);
+}
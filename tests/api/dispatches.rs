@@ -0,0 +1,1135 @@
+use bale_backend::{
+    auth::USER_ID_HEADER,
+    routes::{
+        dispatch_sessions::{ScanResult, ScanSession},
+        dispatches::{Dispatch, EWayBillUpload, EWayBillValidationError},
+        sales_orders::SalesOrderDetail,
+    },
+};
+use reqwest::{header, StatusCode};
+use rust_decimal::Decimal;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::test_app::{TestApp, TestTenant};
+
+// HELPERS
+// -------------------------------------------------------------------------------------
+
+async fn get_challan(app: &TestApp, user_id: Uuid, dispatch_id: Uuid) -> reqwest::Response {
+    app.api_client
+        .get(format!(
+            "{}/api/v1/dispatches/{}/challan.pdf",
+            app.address, dispatch_id
+        ))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn patch_transport(
+    app: &TestApp,
+    user_id: Uuid,
+    dispatch_id: Uuid,
+    body: &serde_json::Value,
+) -> reqwest::Response {
+    app.api_client
+        .patch(format!(
+            "{}/api/v1/dispatches/{}/transport",
+            app.address, dispatch_id
+        ))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .json(body)
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn get_e_way_bill(app: &TestApp, user_id: Uuid, dispatch_id: Uuid) -> reqwest::Response {
+    app.api_client
+        .get(format!(
+            "{}/api/v1/dispatches/{}/e-way-bill.json",
+            app.address, dispatch_id
+        ))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .send()
+        .await
+        .unwrap()
+}
+
+/// Registers the company and gives its warehouses and partners full addresses
+async fn complete_gst_registration(app: &TestApp, tenant: &TestTenant) {
+    sqlx::query!(
+        "UPDATE companies SET gst_number = '27AAPFU0939F1ZV' WHERE id = $1",
+        tenant.company_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        r#"
+        UPDATE warehouses SET address_line1 = 'Plot 12, MIDC', city = 'Bhiwandi', pin_code = '421302',
+                              state = COALESCE(state, 'Maharashtra')
+        WHERE company_id = $1
+        "#,
+        tenant.company_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        r#"
+        UPDATE partners SET address_line1 = '4 Ring Road', city = 'Surat', state = 'Gujarat',
+                            pin_code = '395002', gst_number = '24AAACR5055K1Z7'
+        WHERE company_id = $1
+        "#,
+        tenant.company_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+}
+
+/// A 40 m roll of a product under HSN 5208 taxed at 5%
+async fn seed_taxed_roll(app: &TestApp, tenant: &TestTenant) -> Uuid {
+    let product_id = app.seed_taxed_product(tenant, 1_500, 5).await;
+    app.seed_stock_unit(tenant, product_id, 40).await
+}
+
+async fn post_dispatch(
+    app: &TestApp,
+    user_id: Uuid,
+    body: &serde_json::Value,
+) -> reqwest::Response {
+    app.api_client
+        .post(format!("{}/api/v1/dispatches", app.address))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .json(body)
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn unit_number(app: &TestApp, stock_unit_id: Uuid) -> String {
+    sqlx::query_scalar!(
+        "SELECT unit_number FROM stock_units WHERE id = $1",
+        stock_unit_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap()
+}
+
+async fn unit_status(app: &TestApp, stock_unit_id: Uuid) -> String {
+    sqlx::query_scalar!(
+        "SELECT status FROM stock_units WHERE id = $1",
+        stock_unit_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap()
+}
+
+async fn post_scan_session(
+    app: &TestApp,
+    user_id: Uuid,
+    body: &serde_json::Value,
+) -> reqwest::Response {
+    app.api_client
+        .post(format!("{}/api/v1/dispatch-sessions", app.address))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .json(body)
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn post_scan(
+    app: &TestApp,
+    user_id: Uuid,
+    session_id: Uuid,
+    code: &str,
+) -> reqwest::Response {
+    app.api_client
+        .post(format!(
+            "{}/api/v1/dispatch-sessions/{}/scans",
+            app.address, session_id
+        ))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .json(&json!({ "code": code }))
+        .send()
+        .await
+        .unwrap()
+}
+
+/// `finalize` or `discard`
+async fn post_session_action(
+    app: &TestApp,
+    user_id: Uuid,
+    session_id: Uuid,
+    action: &str,
+) -> reqwest::Response {
+    app.api_client
+        .post(format!(
+            "{}/api/v1/dispatch-sessions/{}/{}",
+            app.address, session_id, action
+        ))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn post_cancel(
+    app: &TestApp,
+    user_id: Uuid,
+    dispatch_id: Uuid,
+    reason: &str,
+) -> reqwest::Response {
+    app.api_client
+        .post(format!(
+            "{}/api/v1/dispatches/{}/cancel",
+            app.address, dispatch_id
+        ))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .json(&json!({ "reason": reason }))
+        .send()
+        .await
+        .unwrap()
+}
+
+/// An approved order for `quantity` of the product, returning the customer
+/// and the order
+async fn seed_open_order(
+    app: &TestApp,
+    tenant: &TestTenant,
+    product_id: Uuid,
+    quantity: i64,
+) -> (Uuid, Uuid) {
+    let customer_id = app.seed_partner(tenant, "Customer").await;
+    let order_id = app
+        .seed_sales_order(
+            tenant,
+            customer_id,
+            chrono::Utc::now().date_naive(),
+            0,
+            0,
+            "in_progress",
+        )
+        .await;
+    sqlx::query!(
+        r#"
+        INSERT INTO sales_order_items (company_id, sales_order_id, product_id, required_quantity, unit_rate)
+        VALUES ($1, $2, $3, $4, 100)
+        "#,
+        tenant.company_id,
+        order_id,
+        product_id,
+        Decimal::from(quantity)
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    (customer_id, order_id)
+}
+
+// CREATE
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn scanned_units_are_dispatched_against_the_sales_order() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let (customer_id, order_id) = seed_open_order(&app, &tenant, product_id, 50).await;
+    let first = app.seed_stock_unit(&tenant, product_id, 20).await;
+    let second = app.seed_stock_unit(&tenant, product_id, 15).await;
+    sqlx::query!(
+        "UPDATE stock_units SET qr_code = 'QR-ROLL-2' WHERE id = $1",
+        second
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let res = post_dispatch(
+        &app,
+        tenant.admin_id,
+        &json!({
+            "dispatch_type": "partner",
+            "dispatch_to_partner_id": customer_id,
+            "link_type": "sales_order",
+            "sales_order_id": order_id,
+            "units": [unit_number(&app, first).await, "QR-ROLL-2"],
+        }),
+    )
+    .await;
+    let status = res.status();
+    let dispatch: Dispatch = res.json().await.unwrap();
+    let dispatched_quantity = sqlx::query_scalar!(
+        r#"SELECT dispatched_quantity as "dispatched_quantity!" FROM sales_order_items WHERE sales_order_id = $1"#,
+        order_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(StatusCode::CREATED, status);
+    assert_eq!(tenant.warehouse_id, dispatch.warehouse_id);
+    assert_eq!(2, dispatch.units.len());
+    assert_eq!("dispatched", unit_status(&app, first).await);
+    assert_eq!("dispatched", unit_status(&app, second).await);
+    assert_eq!(Decimal::from(35), dispatched_quantity);
+}
+
+#[tokio::test]
+async fn rolls_past_the_ordered_quantity_are_dispatched_as_excess() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let (customer_id, order_id) = seed_open_order(&app, &tenant, product_id, 50).await;
+    let first = app.seed_stock_unit(&tenant, product_id, 30).await;
+    let second = app.seed_stock_unit(&tenant, product_id, 25).await;
+
+    let res = post_dispatch(
+        &app,
+        tenant.admin_id,
+        &json!({
+            "dispatch_type": "partner",
+            "dispatch_to_partner_id": customer_id,
+            "link_type": "sales_order",
+            "sales_order_id": order_id,
+            "units": [unit_number(&app, first).await, unit_number(&app, second).await],
+        }),
+    )
+    .await;
+    let status = res.status();
+    let detail: SalesOrderDetail = app
+        .api_client
+        .get(format!("{}/api/v1/sales-orders/{}", app.address, order_id))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let item = &detail.order.items[0];
+
+    assert_eq!(StatusCode::CREATED, status);
+    assert_eq!(Decimal::from(55), item.dispatched_quantity);
+    assert_eq!(Decimal::ZERO, item.pending_quantity);
+    assert_eq!(Decimal::from(5), item.excess_quantity);
+    assert_eq!(Decimal::ZERO, detail.fulfillment.total_pending_quantity);
+}
+
+#[tokio::test]
+async fn only_units_in_stock_in_the_dispatching_warehouse_can_be_scanned() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let available = app.seed_stock_unit(&tenant, product_id, 20).await;
+    let gone = app.seed_stock_unit(&tenant, product_id, 20).await;
+    let elsewhere = app.seed_stock_unit(&tenant, product_id, 20).await;
+    let other_warehouse = app.seed_warehouse(&tenant, "Surat Branch").await;
+    sqlx::query!(
+        "UPDATE stock_units SET status = 'dispatched' WHERE id = $1",
+        gone
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        "UPDATE stock_units SET warehouse_id = $2 WHERE id = $1",
+        elsewhere,
+        other_warehouse
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    let available_number = unit_number(&app, available).await;
+    let body = |units: Vec<String>| {
+        json!({
+            "dispatch_type": "partner",
+            "dispatch_to_partner_id": customer_id,
+            "units": units,
+        })
+    };
+
+    let already_dispatched = post_dispatch(
+        &app,
+        tenant.admin_id,
+        &body(vec![
+            available_number.clone(),
+            unit_number(&app, gone).await,
+        ]),
+    )
+    .await;
+    let other_warehouse = post_dispatch(
+        &app,
+        tenant.admin_id,
+        &body(vec![unit_number(&app, elsewhere).await]),
+    )
+    .await;
+    let unknown = post_dispatch(&app, tenant.admin_id, &body(vec!["NO-SUCH-ROLL".into()])).await;
+    let scanned_twice = post_dispatch(
+        &app,
+        tenant.admin_id,
+        &body(vec![available_number.clone(), available_number]),
+    )
+    .await;
+    let dispatches = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM goods_dispatches WHERE company_id = $1"#,
+        tenant.company_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(StatusCode::CONFLICT, already_dispatched.status());
+    assert_eq!(StatusCode::CONFLICT, other_warehouse.status());
+    assert_eq!(StatusCode::BAD_REQUEST, unknown.status());
+    assert_eq!(StatusCode::BAD_REQUEST, scanned_twice.status());
+    assert_eq!(0, dispatches);
+    assert_eq!("in_stock", unit_status(&app, available).await);
+}
+
+#[tokio::test]
+async fn dispatch_type_rules_are_enforced() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let agent_id = app.seed_partner(&tenant, "Agent").await;
+    let other_warehouse = app.seed_warehouse(&tenant, "Surat Branch").await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let unit = app.seed_stock_unit(&tenant, product_id, 20).await;
+    let units = vec![unit_number(&app, unit).await];
+
+    let to_same_warehouse = post_dispatch(
+        &app,
+        tenant.admin_id,
+        &json!({
+            "dispatch_type": "warehouse",
+            "dispatch_to_warehouse_id": tenant.warehouse_id,
+            "units": units,
+        }),
+    )
+    .await;
+    let transfer_with_agent = post_dispatch(
+        &app,
+        tenant.admin_id,
+        &json!({
+            "dispatch_type": "warehouse",
+            "dispatch_to_warehouse_id": other_warehouse,
+            "agent_id": agent_id,
+            "units": units,
+        }),
+    )
+    .await;
+    let partner_and_warehouse = post_dispatch(
+        &app,
+        tenant.admin_id,
+        &json!({
+            "dispatch_type": "partner",
+            "dispatch_to_partner_id": customer_id,
+            "dispatch_to_warehouse_id": other_warehouse,
+            "units": units,
+        }),
+    )
+    .await;
+    let customer_as_agent = post_dispatch(
+        &app,
+        tenant.admin_id,
+        &json!({
+            "dispatch_type": "partner",
+            "dispatch_to_partner_id": customer_id,
+            "agent_id": customer_id,
+            "units": units,
+        }),
+    )
+    .await;
+    let transfer = post_dispatch(
+        &app,
+        tenant.admin_id,
+        &json!({
+            "dispatch_type": "warehouse",
+            "dispatch_to_warehouse_id": other_warehouse,
+            "units": units,
+        }),
+    )
+    .await;
+
+    assert_eq!(StatusCode::BAD_REQUEST, to_same_warehouse.status());
+    assert_eq!(StatusCode::BAD_REQUEST, transfer_with_agent.status());
+    assert_eq!(StatusCode::BAD_REQUEST, partner_and_warehouse.status());
+    assert_eq!(StatusCode::BAD_REQUEST, customer_as_agent.status());
+    assert_eq!(StatusCode::CREATED, transfer.status());
+}
+
+#[tokio::test]
+async fn staff_dispatch_only_from_their_own_warehouse() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let other_warehouse = app.seed_warehouse(&tenant, "Surat Branch").await;
+    let staff_id = app.seed_staff(&tenant, other_warehouse).await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let unit = app.seed_stock_unit(&tenant, product_id, 20).await;
+
+    let res = post_dispatch(
+        &app,
+        staff_id,
+        &json!({
+            "dispatch_type": "partner",
+            "warehouse_id": tenant.warehouse_id,
+            "dispatch_to_partner_id": customer_id,
+            "units": [unit_number(&app, unit).await],
+        }),
+    )
+    .await;
+
+    assert_eq!(StatusCode::FORBIDDEN, res.status());
+}
+
+// SCAN SESSIONS
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn scan_session_keeps_running_totals_against_the_order_until_finalized() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let (customer_id, order_id) = seed_open_order(&app, &tenant, product_id, 50).await;
+    let first = app.seed_stock_unit(&tenant, product_id, 20).await;
+    let second = app.seed_stock_unit(&tenant, product_id, 15).await;
+    let first_number = unit_number(&app, first).await;
+    let session: ScanSession = post_scan_session(
+        &app,
+        tenant.admin_id,
+        &json!({
+            "dispatch_type": "partner",
+            "dispatch_to_partner_id": customer_id,
+            "link_type": "sales_order",
+            "sales_order_id": order_id,
+        }),
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+
+    let before_scanning = &session.totals[0];
+    post_scan(&app, tenant.admin_id, session.id, &first_number).await;
+    let scanned: ScanResult = post_scan(
+        &app,
+        tenant.admin_id,
+        session.id,
+        &unit_number(&app, second).await,
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+    let repeated: ScanResult = post_scan(&app, tenant.admin_id, session.id, &first_number)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let totals = &repeated.session.totals[0];
+    let res = post_session_action(&app, tenant.admin_id, session.id, "finalize").await;
+    let status = res.status();
+    let dispatch: Dispatch = res.json().await.unwrap();
+    let finalized: ScanSession = app
+        .api_client
+        .get(format!(
+            "{}/api/v1/dispatch-sessions/{}",
+            app.address, session.id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(0, before_scanning.units);
+    assert_eq!(Some(Decimal::from(50)), before_scanning.remaining_quantity);
+    assert!(!scanned.duplicate);
+    assert!(repeated.duplicate);
+    assert_eq!(2, repeated.session.unit_count);
+    assert_eq!(2, totals.units);
+    assert_eq!(Decimal::from(35), totals.quantity);
+    assert_eq!(Some(Decimal::from(50)), totals.pending_quantity);
+    assert_eq!(Some(Decimal::from(15)), totals.remaining_quantity);
+    assert_eq!(StatusCode::CREATED, status);
+    assert_eq!(Some(order_id), dispatch.sales_order_id);
+    assert_eq!(2, dispatch.units.len());
+    assert_eq!("dispatched", unit_status(&app, second).await);
+    assert_eq!("finalized", finalized.status);
+    assert_eq!(Some(dispatch.id), finalized.dispatch_id);
+}
+
+#[tokio::test]
+async fn scans_past_the_pending_quantity_are_flagged_and_still_finalize() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let (customer_id, order_id) = seed_open_order(&app, &tenant, product_id, 50).await;
+    let first = app.seed_stock_unit(&tenant, product_id, 30).await;
+    let second = app.seed_stock_unit(&tenant, product_id, 25).await;
+    let session: ScanSession = post_scan_session(
+        &app,
+        tenant.admin_id,
+        &json!({
+            "dispatch_type": "partner",
+            "dispatch_to_partner_id": customer_id,
+            "link_type": "sales_order",
+            "sales_order_id": order_id,
+        }),
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+
+    let within: ScanResult = post_scan(
+        &app,
+        tenant.admin_id,
+        session.id,
+        &unit_number(&app, first).await,
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+    let past: ScanResult = post_scan(
+        &app,
+        tenant.admin_id,
+        session.id,
+        &unit_number(&app, second).await,
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+    let totals = &past.session.totals[0];
+    let finalized = post_session_action(&app, tenant.admin_id, session.id, "finalize").await;
+    let dispatched_quantity = sqlx::query_scalar!(
+        r#"SELECT dispatched_quantity as "dispatched_quantity!" FROM sales_order_items WHERE sales_order_id = $1"#,
+        order_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert!(!within.excess);
+    assert!(past.excess);
+    assert_eq!(Some(Decimal::ZERO), totals.remaining_quantity);
+    assert_eq!(Some(Decimal::from(5)), totals.excess_quantity);
+    assert_eq!(StatusCode::CREATED, finalized.status());
+    assert_eq!(Decimal::from(55), dispatched_quantity);
+}
+
+#[tokio::test]
+async fn each_scan_is_validated_as_it_arrives() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let other_product = app.seed_product(&tenant, 100, &[]).await;
+    let (customer_id, order_id) = seed_open_order(&app, &tenant, product_id, 50).await;
+    let gone = app.seed_stock_unit(&tenant, product_id, 20).await;
+    let off_order = app.seed_stock_unit(&tenant, other_product, 20).await;
+    let claimed = app.seed_stock_unit(&tenant, product_id, 20).await;
+    sqlx::query!(
+        "UPDATE stock_units SET status = 'dispatched' WHERE id = $1",
+        gone
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    let header = json!({
+        "dispatch_type": "partner",
+        "dispatch_to_partner_id": customer_id,
+        "link_type": "sales_order",
+        "sales_order_id": order_id,
+    });
+    let session: ScanSession = post_scan_session(&app, tenant.admin_id, &header)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let other_session: ScanSession = post_scan_session(&app, tenant.admin_id, &header)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let claimed_number = unit_number(&app, claimed).await;
+    post_scan(&app, tenant.admin_id, other_session.id, &claimed_number).await;
+
+    let unknown = post_scan(&app, tenant.admin_id, session.id, "NO-SUCH-ROLL").await;
+    let not_on_order = post_scan(
+        &app,
+        tenant.admin_id,
+        session.id,
+        &unit_number(&app, off_order).await,
+    )
+    .await;
+    let not_in_stock = post_scan(
+        &app,
+        tenant.admin_id,
+        session.id,
+        &unit_number(&app, gone).await,
+    )
+    .await;
+    let in_other_session = post_scan(&app, tenant.admin_id, session.id, &claimed_number).await;
+    let empty_finalize = post_session_action(&app, tenant.admin_id, session.id, "finalize").await;
+
+    assert_eq!(StatusCode::BAD_REQUEST, unknown.status());
+    assert_eq!(StatusCode::BAD_REQUEST, not_on_order.status());
+    assert_eq!(StatusCode::CONFLICT, not_in_stock.status());
+    assert_eq!(StatusCode::CONFLICT, in_other_session.status());
+    assert_eq!(StatusCode::BAD_REQUEST, empty_finalize.status());
+}
+
+#[tokio::test]
+async fn discarded_session_leaves_units_in_stock() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let unit = app.seed_stock_unit(&tenant, product_id, 20).await;
+    let number = unit_number(&app, unit).await;
+    let session: ScanSession = post_scan_session(
+        &app,
+        tenant.admin_id,
+        &json!({
+            "dispatch_type": "partner",
+            "dispatch_to_partner_id": customer_id,
+        }),
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+    let scanned: ScanResult = post_scan(&app, tenant.admin_id, session.id, &number)
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    let discarded = post_session_action(&app, tenant.admin_id, session.id, "discard").await;
+    let finalize = post_session_action(&app, tenant.admin_id, session.id, "finalize").await;
+    let scan_after = post_scan(&app, tenant.admin_id, session.id, &number).await;
+
+    assert!(scanned.session.totals[0].pending_quantity.is_none());
+    assert_eq!(StatusCode::NO_CONTENT, discarded.status());
+    assert_eq!(StatusCode::CONFLICT, finalize.status());
+    assert_eq!(StatusCode::CONFLICT, scan_after.status());
+    assert_eq!("in_stock", unit_status(&app, unit).await);
+}
+
+// CANCEL
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn cancelled_dispatch_returns_units_to_stock_and_reverses_the_order() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let (customer_id, order_id) = seed_open_order(&app, &tenant, product_id, 50).await;
+    let first = app.seed_stock_unit(&tenant, product_id, 20).await;
+    let second = app.seed_stock_unit(&tenant, product_id, 15).await;
+    let dispatch: Dispatch = post_dispatch(
+        &app,
+        tenant.admin_id,
+        &json!({
+            "dispatch_type": "partner",
+            "dispatch_to_partner_id": customer_id,
+            "link_type": "sales_order",
+            "sales_order_id": order_id,
+            "units": [unit_number(&app, first).await, unit_number(&app, second).await],
+        }),
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+
+    let without_reason = post_cancel(&app, tenant.admin_id, dispatch.id, " ").await;
+    let cancelled = post_cancel(&app, tenant.admin_id, dispatch.id, "Customer refused").await;
+    let again = post_cancel(&app, tenant.admin_id, dispatch.id, "Customer refused").await;
+    let dispatched_quantity = sqlx::query_scalar!(
+        r#"SELECT dispatched_quantity as "dispatched_quantity!" FROM sales_order_items WHERE sales_order_id = $1"#,
+        order_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    let reason = sqlx::query_scalar!(
+        "SELECT cancellation_reason FROM goods_dispatches WHERE id = $1",
+        dispatch.id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(StatusCode::BAD_REQUEST, without_reason.status());
+    assert_eq!(StatusCode::NO_CONTENT, cancelled.status());
+    assert_eq!(StatusCode::CONFLICT, again.status());
+    assert_eq!("in_stock", unit_status(&app, first).await);
+    assert_eq!("in_stock", unit_status(&app, second).await);
+    assert_eq!(Decimal::ZERO, dispatched_quantity);
+    assert_eq!(Some("Customer refused".to_string()), reason);
+}
+
+#[tokio::test]
+async fn dispatch_cannot_be_cancelled_once_its_units_moved_on() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let unit = app.seed_stock_unit(&tenant, product_id, 20).await;
+    let body = json!({
+        "dispatch_type": "partner",
+        "dispatch_to_partner_id": customer_id,
+        "units": [unit_number(&app, unit).await],
+    });
+    let first: Dispatch = post_dispatch(&app, tenant.admin_id, &body)
+        .await
+        .json()
+        .await
+        .unwrap();
+    // Returned by the customer and sent out again
+    sqlx::query!(
+        "UPDATE stock_units SET status = 'in_stock' WHERE id = $1",
+        unit
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    let second: Dispatch = post_dispatch(&app, tenant.admin_id, &body)
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    let earlier = post_cancel(&app, tenant.admin_id, first.id, "Wrong customer").await;
+    let latest = post_cancel(&app, tenant.admin_id, second.id, "Wrong customer").await;
+
+    assert_eq!(StatusCode::CONFLICT, earlier.status());
+    assert_eq!(StatusCode::NO_CONTENT, latest.status());
+    assert_eq!("in_stock", unit_status(&app, unit).await);
+}
+
+// CHALLAN
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn challan_lists_rolls_grouped_by_product_with_totals() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let poplin_id = app.seed_product(&tenant, 150, &[]).await;
+    let silk_id = app.seed_product(&tenant, 900, &[]).await;
+    let units = [
+        app.seed_stock_unit(&tenant, poplin_id, 40).await,
+        app.seed_stock_unit(&tenant, poplin_id, 35).await,
+        app.seed_stock_unit(&tenant, silk_id, 12).await,
+    ];
+    let dispatch_id = app
+        .seed_dispatch(&tenant, customer_id, None, None, &units)
+        .await;
+    sqlx::query!(
+        "UPDATE goods_dispatches SET transport_details = 'VRL Logistics LR 4471' WHERE id = $1",
+        dispatch_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    let unit_numbers = sqlx::query_scalar!(
+        "SELECT unit_number FROM stock_units WHERE id = ANY($1)",
+        &units
+    )
+    .fetch_all(&app.db_pool)
+    .await
+    .unwrap();
+
+    let res = get_challan(&app, tenant.admin_id, dispatch_id).await;
+    let status = res.status();
+    let content_type = res.headers()[header::CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let body = res.bytes().await.unwrap();
+    let text = String::from_utf8_lossy(&body);
+
+    assert_eq!(StatusCode::OK, status);
+    assert_eq!("application/pdf", content_type);
+    assert!(body.starts_with(b"%PDF-"));
+    assert!(text.contains("DELIVERY CHALLAN"));
+    assert!(text.contains("VRL Logistics LR 4471"));
+    for unit_number in unit_numbers {
+        assert!(text.contains(&unit_number));
+    }
+    assert!(text.contains("75 Meters"));
+    assert!(text.contains("12 Meters"));
+    assert!(text.contains("Total rolls dispatched: 3"));
+}
+
+#[tokio::test]
+async fn challan_is_visible_to_staff_at_either_end_of_a_transfer() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let product_id = app.seed_product(&tenant, 150, &[]).await;
+    let unit_id = app.seed_stock_unit(&tenant, product_id, 40).await;
+    let branch_id = app.seed_warehouse(&tenant, "Surat Branch").await;
+    let outsider_warehouse_id = app.seed_warehouse(&tenant, "Bhiwandi Godown").await;
+    let branch_staff_id = app.seed_staff(&tenant, branch_id).await;
+    let outsider_id = app.seed_staff(&tenant, outsider_warehouse_id).await;
+    let dispatch_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO goods_dispatches (company_id, warehouse_id, dispatch_type,
+                                      dispatch_to_warehouse_id, created_by)
+        VALUES ($1, $2, 'warehouse', $3, $4)
+        RETURNING id
+        "#,
+        tenant.company_id,
+        tenant.warehouse_id,
+        branch_id,
+        tenant.admin_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        "INSERT INTO goods_dispatch_items (company_id, dispatch_id, stock_unit_id) VALUES ($1, $2, $3)",
+        tenant.company_id,
+        dispatch_id,
+        unit_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let branch_res = get_challan(&app, branch_staff_id, dispatch_id).await;
+    let outsider_res = get_challan(&app, outsider_id, dispatch_id).await;
+
+    assert_eq!(StatusCode::OK, branch_res.status());
+    let text = String::from_utf8_lossy(&branch_res.bytes().await.unwrap()).to_string();
+    assert!(text.contains("To: Surat Branch"));
+    assert_eq!(StatusCode::NOT_FOUND, outsider_res.status());
+}
+
+#[tokio::test]
+async fn challan_of_another_company_is_not_found() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let other_tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let dispatch_id = app
+        .seed_dispatch(&tenant, customer_id, None, None, &[])
+        .await;
+
+    let res = get_challan(&app, other_tenant.admin_id, dispatch_id).await;
+
+    assert_eq!(StatusCode::NOT_FOUND, res.status());
+}
+
+// E-WAY BILL
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn e_way_bill_is_built_from_the_tax_invoice_and_transport_details() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let unit_id = seed_taxed_roll(&app, &tenant).await;
+    complete_gst_registration(&app, &tenant).await;
+    let dispatch_id = app
+        .seed_dispatch(&tenant, customer_id, None, None, &[unit_id])
+        .await;
+    app.api_client
+        .post(format!(
+            "{}/api/v1/dispatches/{}/invoice",
+            app.address, dispatch_id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .send()
+        .await
+        .unwrap();
+
+    let patch_res = patch_transport(
+        &app,
+        tenant.admin_id,
+        dispatch_id,
+        &json!({
+            "transport_mode": "road",
+            "transport_distance_km": 270,
+            "transporter_name": "VRL Logistics",
+            "vehicle_number": "mh 04 ab-1234",
+        }),
+    )
+    .await;
+    let res = get_e_way_bill(&app, tenant.admin_id, dispatch_id).await;
+    let status = res.status();
+    let upload: EWayBillUpload = res.json().await.unwrap();
+    let bill = &upload.bill_lists[0];
+    let invoice_number = sqlx::query_scalar!(
+        "SELECT invoice_number FROM goods_dispatches WHERE id = $1",
+        dispatch_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(StatusCode::NO_CONTENT, patch_res.status());
+    assert_eq!(StatusCode::OK, status);
+    assert_eq!("INV", bill.doc_type);
+    assert_eq!(invoice_number, Some(bill.doc_no.clone()));
+    assert_eq!(1, bill.sub_supply_type);
+    assert_eq!("27AAPFU0939F1ZV", bill.from_gstin);
+    assert_eq!("24AAACR5055K1Z7", bill.to_gstin);
+    assert_eq!(27, bill.from_state_code);
+    assert_eq!(24, bill.to_state_code);
+    assert_eq!(421_302, bill.from_pincode);
+    assert_eq!(Decimal::from(60_000), bill.total_value);
+    assert_eq!(Decimal::from(3_000), bill.igst_value);
+    assert_eq!(Decimal::from(63_000), bill.tot_inv_value);
+    assert_eq!("1", bill.trans_mode);
+    assert_eq!("270", bill.trans_distance);
+    assert_eq!("MH04AB1234", bill.vehicle_no);
+    assert_eq!(5208, bill.main_hsn_code);
+    assert_eq!("MTR", bill.item_list[0].qty_unit);
+    assert_eq!(Decimal::from(5), bill.item_list[0].igst_rate);
+}
+
+#[tokio::test]
+async fn e_way_bill_reports_every_missing_mandatory_field() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let product_id = app.seed_product(&tenant, 1_500, &[]).await;
+    let unit_id = app.seed_stock_unit(&tenant, product_id, 40).await;
+    let dispatch_id = app
+        .seed_dispatch(&tenant, customer_id, None, None, &[unit_id])
+        .await;
+
+    let res = get_e_way_bill(&app, tenant.admin_id, dispatch_id).await;
+    let status = res.status();
+    let error: EWayBillValidationError = res.json().await.unwrap();
+    let missing = |prefix: &str| error.missing_fields.iter().any(|f| f.starts_with(prefix));
+
+    assert_eq!(StatusCode::BAD_REQUEST, status);
+    assert!(missing("fromGstin"));
+    assert!(missing("fromPincode"));
+    assert!(missing("toPincode"));
+    assert!(missing("docNo"));
+    assert!(missing("transMode"));
+    assert!(missing("transDistance"));
+    assert!(missing("itemList[1].hsnCode"));
+}
+
+#[tokio::test]
+async fn warehouse_transfer_is_filed_on_a_challan_to_own_gstin() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let unit_id = seed_taxed_roll(&app, &tenant).await;
+    let branch_id = app.seed_warehouse(&tenant, "Surat Branch").await;
+    complete_gst_registration(&app, &tenant).await;
+    let dispatch_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO goods_dispatches (company_id, warehouse_id, dispatch_type,
+                                      dispatch_to_warehouse_id, created_by)
+        VALUES ($1, $2, 'warehouse', $3, $4)
+        RETURNING id
+        "#,
+        tenant.company_id,
+        tenant.warehouse_id,
+        branch_id,
+        tenant.admin_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        "INSERT INTO goods_dispatch_items (company_id, dispatch_id, stock_unit_id) VALUES ($1, $2, $3)",
+        tenant.company_id,
+        dispatch_id,
+        unit_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    patch_transport(
+        &app,
+        tenant.admin_id,
+        dispatch_id,
+        &json!({
+            "transport_mode": "rail",
+            "transport_distance_km": 300,
+            "transport_document_number": "RR-99812",
+            "transport_document_date": "2026-04-02",
+        }),
+    )
+    .await;
+
+    let upload: EWayBillUpload = get_e_way_bill(&app, tenant.admin_id, dispatch_id)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let bill = &upload.bill_lists[0];
+
+    assert_eq!("CHL", bill.doc_type);
+    assert_eq!(8, bill.sub_supply_type);
+    assert_eq!(bill.from_gstin, bill.to_gstin);
+    assert_eq!(Decimal::from(60_000), bill.tot_inv_value);
+    assert_eq!(Decimal::ZERO, bill.cgst_value);
+    assert_eq!("02/04/2026", bill.trans_doc_date);
+}
+
+#[tokio::test]
+async fn transport_details_are_validated() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let dispatch_id = app
+        .seed_dispatch(&tenant, customer_id, None, None, &[])
+        .await;
+
+    let too_far = patch_transport(
+        &app,
+        tenant.admin_id,
+        dispatch_id,
+        &json!({ "transport_distance_km": 4500 }),
+    )
+    .await;
+    let bad_transporter = patch_transport(
+        &app,
+        tenant.admin_id,
+        dispatch_id,
+        &json!({ "transporter_id": "ABC" }),
+    )
+    .await;
+    sqlx::query!(
+        "UPDATE goods_dispatches SET is_cancelled = TRUE WHERE id = $1",
+        dispatch_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    let cancelled = patch_transport(
+        &app,
+        tenant.admin_id,
+        dispatch_id,
+        &json!({ "transport_mode": "road" }),
+    )
+    .await;
+
+    assert_eq!(StatusCode::BAD_REQUEST, too_far.status());
+    assert_eq!(StatusCode::BAD_REQUEST, bad_transporter.status());
+    assert_eq!(StatusCode::CONFLICT, cancelled.status());
+}
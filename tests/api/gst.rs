@@ -0,0 +1,221 @@
+use bale_backend::{
+    auth::USER_ID_HEADER,
+    routes::{gst::HsnGstRate, sales_orders::SalesOrderSummary},
+};
+use reqwest::StatusCode;
+use rust_decimal::Decimal;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::test_app::{TestApp, TestTenant};
+
+// HELPERS
+// -------------------------------------------------------------------------------------
+
+async fn put_hsn_rate(
+    app: &TestApp,
+    user_id: Uuid,
+    hsn_code: &str,
+    body: &serde_json::Value,
+) -> reqwest::Response {
+    app.api_client
+        .put(format!("{}/api/v1/hsn-gst-rates/{}", app.address, hsn_code))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .json(body)
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn create_order(
+    app: &TestApp,
+    tenant: &TestTenant,
+    customer_id: Uuid,
+    body: serde_json::Value,
+) -> SalesOrderSummary {
+    let mut body = body;
+    body["customer_id"] = json!(customer_id);
+    let res = app
+        .api_client
+        .post(format!("{}/api/v1/sales-orders", app.address))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::CREATED, res.status());
+    res.json().await.unwrap()
+}
+
+// TAX COMPUTATION
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn intra_state_order_splits_tax_into_cgst_and_sgst() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let product_id = app.seed_taxed_product(&tenant, 150, 5).await;
+
+    let order = create_order(
+        &app,
+        &tenant,
+        customer_id,
+        json!({
+            "discount_percentage": "10",
+            "items": [{ "product_id": product_id, "required_quantity": "20" }],
+        }),
+    )
+    .await;
+    let item = &order.items[0];
+
+    assert!(!order.is_inter_state);
+    assert_eq!(Some("5208"), item.hsn_code.as_deref());
+    assert_eq!(Decimal::from(5), item.gst_rate);
+    assert_eq!(Decimal::from(3_000), order.subtotal);
+    assert_eq!(Decimal::from(2_700), order.taxable_amount);
+    assert_eq!(Decimal::new(6_750, 2), order.cgst_amount);
+    assert_eq!(Decimal::new(6_750, 2), order.sgst_amount);
+    assert_eq!(Decimal::ZERO, order.igst_amount);
+    assert_eq!(Decimal::from(135), order.tax_amount);
+    assert_eq!(Decimal::from(2_835), order.total_amount);
+    assert_eq!(Decimal::new(6_750, 2), item.cgst_amount);
+}
+
+#[tokio::test]
+async fn inter_state_order_charges_igst() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    sqlx::query!(
+        "UPDATE partners SET state = 'Gujarat' WHERE id = $1",
+        customer_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    let product_id = app.seed_taxed_product(&tenant, 150, 5).await;
+
+    let order = create_order(
+        &app,
+        &tenant,
+        customer_id,
+        json!({ "items": [{ "product_id": product_id, "required_quantity": "20" }] }),
+    )
+    .await;
+
+    assert!(order.is_inter_state);
+    assert_eq!(Decimal::ZERO, order.cgst_amount);
+    assert_eq!(Decimal::ZERO, order.sgst_amount);
+    assert_eq!(Decimal::from(150), order.igst_amount);
+    assert_eq!(Decimal::from(3_150), order.total_amount);
+}
+
+#[tokio::test]
+async fn gstin_state_code_takes_precedence_over_state_name() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    // Registered in Karnataka though the address says Maharashtra
+    sqlx::query!(
+        "UPDATE partners SET gst_number = '29ABCDE1234F1Z5' WHERE id = $1",
+        customer_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    let product_id = app.seed_taxed_product(&tenant, 150, 5).await;
+
+    let order = create_order(
+        &app,
+        &tenant,
+        customer_id,
+        json!({ "items": [{ "product_id": product_id, "required_quantity": "20" }] }),
+    )
+    .await;
+
+    assert!(order.is_inter_state);
+    assert_eq!(Decimal::from(150), order.igst_amount);
+}
+
+#[tokio::test]
+async fn total_is_rounded_to_the_nearest_rupee() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let product_id = app.seed_taxed_product(&tenant, 100, 12).await;
+
+    let order = create_order(
+        &app,
+        &tenant,
+        customer_id,
+        json!({
+            "items": [{ "product_id": product_id, "required_quantity": "3", "unit_rate": "99.99" }],
+        }),
+    )
+    .await;
+
+    // 299.97 taxable, 6% of it is 17.9982 which rounds to 18.00 per component
+    assert_eq!(Decimal::new(29_997, 2), order.taxable_amount);
+    assert_eq!(Decimal::from(18), order.cgst_amount);
+    assert_eq!(Decimal::from(18), order.sgst_amount);
+    assert_eq!(Decimal::new(3, 2), order.round_off);
+    assert_eq!(Decimal::from(336), order.total_amount);
+}
+
+// RATES
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn rate_change_does_not_alter_existing_order_items() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let product_id = app.seed_taxed_product(&tenant, 150, 5).await;
+    let order = create_order(
+        &app,
+        &tenant,
+        customer_id,
+        json!({ "items": [{ "product_id": product_id, "required_quantity": "20" }] }),
+    )
+    .await;
+
+    let res = put_hsn_rate(&app, tenant.admin_id, "5208", &json!({ "gst_rate": "12" })).await;
+    let rates: Vec<HsnGstRate> = app
+        .api_client
+        .get(format!("{}/api/v1/hsn-gst-rates", app.address))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let gst_rate = sqlx::query_scalar!(
+        "SELECT gst_rate FROM sales_order_items WHERE sales_order_id = $1",
+        order.id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(StatusCode::OK, res.status());
+    assert_eq!(1, rates.len());
+    assert_eq!(Decimal::from(12), rates[0].gst_rate);
+    assert_eq!(Decimal::from(5), gst_rate);
+}
+
+#[tokio::test]
+async fn set_hsn_gst_rate_rejects_invalid_input() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let staff_id = app.seed_staff(&tenant, tenant.warehouse_id).await;
+
+    let off_slab = put_hsn_rate(&app, tenant.admin_id, "5208", &json!({ "gst_rate": "7" })).await;
+    let bad_code = put_hsn_rate(&app, tenant.admin_id, "52AB", &json!({ "gst_rate": "5" })).await;
+    let by_staff = put_hsn_rate(&app, staff_id, "5208", &json!({ "gst_rate": "5" })).await;
+
+    assert_eq!(StatusCode::BAD_REQUEST, off_slab.status());
+    assert_eq!(StatusCode::BAD_REQUEST, bad_code.status());
+    assert_eq!(StatusCode::FORBIDDEN, by_staff.status());
+}
@@ -1,6 +1,7 @@
 use crate::test_app::TestApp;
 
 #[tokio::test]
+#[allow(clippy::needless_borrows_for_generic_args)]
 async fn health_check_works() {
     let app = TestApp::build().await;
 
@@ -0,0 +1,399 @@
+use bale_backend::{auth::USER_ID_HEADER, routes::invoices::TaxInvoice};
+use chrono::{Datelike, Utc};
+use reqwest::{header, StatusCode};
+use rust_decimal::Decimal;
+use serde_json::json;
+use uuid::Uuid;
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use crate::test_app::{TestApp, TestTenant};
+
+// HELPERS
+// -------------------------------------------------------------------------------------
+
+async fn post_invoice(app: &TestApp, user_id: Uuid, dispatch_id: Uuid) -> reqwest::Response {
+    app.api_client
+        .post(format!(
+            "{}/api/v1/dispatches/{}/invoice",
+            app.address, dispatch_id
+        ))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn put_logo(app: &TestApp, user_id: Uuid, company_id: Uuid, logo: Vec<u8>) -> StatusCode {
+    app.api_client
+        .put(format!(
+            "{}/api/v1/companies/{}/logo",
+            app.address, company_id
+        ))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .header(header::CONTENT_TYPE, "image/jpeg")
+        .body(logo)
+        .send()
+        .await
+        .unwrap()
+        .status()
+}
+
+/// Dispatches two 10 m rolls of the product to the customer
+async fn seed_rolls_dispatch(
+    app: &TestApp,
+    tenant: &TestTenant,
+    customer_id: Uuid,
+    product_id: Uuid,
+    sales_order_id: Option<Uuid>,
+) -> Uuid {
+    let first = app.seed_stock_unit(tenant, product_id, 10).await;
+    let second = app.seed_stock_unit(tenant, product_id, 10).await;
+    app.seed_dispatch(tenant, customer_id, None, sales_order_id, &[first, second])
+        .await
+}
+
+fn expected_number(serial: u32) -> String {
+    let today = Utc::now().date_naive();
+    let start = if today.month() >= 4 {
+        today.year()
+    } else {
+        today.year() - 1
+    };
+    format!(
+        "INV/{:02}-{:02}/{:04}",
+        start % 100,
+        (start + 1) % 100,
+        serial
+    )
+}
+
+// GENERATION
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn invoice_uses_order_rates_discount_and_derives_dispatch_amount() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let product_id = app.seed_taxed_product(&tenant, 200, 5).await;
+    let order_id = app
+        .api_client
+        .post(format!("{}/api/v1/sales-orders", app.address))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .json(&json!({
+            "customer_id": customer_id,
+            "discount_percentage": "10",
+            "items": [{ "product_id": product_id, "required_quantity": "20", "unit_rate": "149.99" }],
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .parse::<Uuid>()
+        .unwrap();
+    let dispatch_id =
+        seed_rolls_dispatch(&app, &tenant, customer_id, product_id, Some(order_id)).await;
+
+    let res = post_invoice(&app, tenant.admin_id, dispatch_id).await;
+    let status = res.status();
+    let invoice: TaxInvoice = res.json().await.unwrap();
+    let dispatch = sqlx::query!(
+        "SELECT invoice_number, invoice_amount FROM goods_dispatches WHERE id = $1",
+        dispatch_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    let item = &invoice.items[0];
+
+    assert_eq!(StatusCode::CREATED, status);
+    assert_eq!(expected_number(1), invoice.invoice_number);
+    assert_eq!(Some("27"), invoice.place_of_supply.as_deref());
+    assert!(!invoice.is_inter_state);
+    assert_eq!(Decimal::from(20), item.quantity);
+    assert_eq!(Decimal::new(14_999, 2), item.unit_rate);
+    assert_eq!(Some("5208"), item.hsn_code.as_deref());
+    // 2,999.80 less 10% is 2,699.82; 2.5% of it is 67.4955 per component
+    assert_eq!(Decimal::new(269_982, 2), invoice.taxable_amount);
+    assert_eq!(Decimal::new(6_750, 2), invoice.cgst_amount);
+    assert_eq!(Decimal::new(6_750, 2), invoice.sgst_amount);
+    assert_eq!(Decimal::new(18, 2), invoice.round_off);
+    assert_eq!(Decimal::from(2_835), invoice.total_amount);
+    assert_eq!(Some(invoice.invoice_number), dispatch.invoice_number);
+    assert_eq!(Some(Decimal::from(2_835)), dispatch.invoice_amount);
+}
+
+#[tokio::test]
+async fn stand_alone_dispatch_is_billed_at_selling_price_with_igst() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    sqlx::query!(
+        "UPDATE partners SET state = 'Gujarat' WHERE id = $1",
+        customer_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    let product_id = app.seed_taxed_product(&tenant, 200, 5).await;
+    let dispatch_id = seed_rolls_dispatch(&app, &tenant, customer_id, product_id, None).await;
+
+    let invoice: TaxInvoice = post_invoice(&app, tenant.admin_id, dispatch_id)
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    assert!(invoice.is_inter_state);
+    assert_eq!(Some("24"), invoice.place_of_supply.as_deref());
+    assert_eq!(Decimal::from(4_000), invoice.taxable_amount);
+    assert_eq!(Decimal::ZERO, invoice.cgst_amount);
+    assert_eq!(Decimal::from(200), invoice.igst_amount);
+    assert_eq!(Decimal::from(4_200), invoice.total_amount);
+}
+
+#[tokio::test]
+async fn invoice_numbers_follow_the_company_series() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let other_tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let other_customer_id = app.seed_partner(&other_tenant, "Customer").await;
+    let product_id = app.seed_taxed_product(&tenant, 200, 5).await;
+    let other_product_id = app.seed_taxed_product(&other_tenant, 200, 5).await;
+    let first = seed_rolls_dispatch(&app, &tenant, customer_id, product_id, None).await;
+    let second = seed_rolls_dispatch(&app, &tenant, customer_id, product_id, None).await;
+    let other = seed_rolls_dispatch(
+        &app,
+        &other_tenant,
+        other_customer_id,
+        other_product_id,
+        None,
+    )
+    .await;
+
+    let first: TaxInvoice = post_invoice(&app, tenant.admin_id, first)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let second: TaxInvoice = post_invoice(&app, tenant.admin_id, second)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let other: TaxInvoice = post_invoice(&app, other_tenant.admin_id, other)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let again = post_invoice(&app, tenant.admin_id, first.dispatch_id).await;
+
+    assert_eq!(expected_number(1), first.invoice_number);
+    assert_eq!(expected_number(2), second.invoice_number);
+    assert_eq!(expected_number(1), other.invoice_number);
+    assert_eq!(StatusCode::CONFLICT, again.status());
+}
+
+#[tokio::test]
+async fn invoice_numbers_longer_than_gst_allows_are_refused() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let product_id = app.seed_taxed_product(&tenant, 200, 5).await;
+    let dispatch_id = seed_rolls_dispatch(&app, &tenant, customer_id, product_id, None).await;
+    let today = Utc::now().date_naive();
+    let year_start = if today.month() >= 4 {
+        today.year()
+    } else {
+        today.year() - 1
+    };
+    let series = |prefix: &'static str| {
+        sqlx::query!(
+            r#"
+            INSERT INTO invoice_series (company_id, financial_year_start, prefix, last_number)
+            VALUES ($1, make_date($2, 4, 1), $3, 9999)
+            "#,
+            tenant.company_id,
+            year_start,
+            prefix
+        )
+    };
+
+    let long_prefix = series("TAXINV").execute(&app.db_pool).await;
+    series("TAXIN").execute(&app.db_pool).await.unwrap();
+    // TAXIN/YY-YY/10000 is 17 characters
+    let res = post_invoice(&app, tenant.admin_id, dispatch_id).await;
+    let last_number = sqlx::query_scalar!(
+        "SELECT last_number FROM invoice_series WHERE company_id = $1",
+        tenant.company_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert!(long_prefix.is_err());
+    assert_eq!(StatusCode::CONFLICT, res.status());
+    assert_eq!(9999, last_number);
+}
+
+#[tokio::test]
+async fn cancelled_or_foreign_dispatches_are_not_invoiced() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let product_id = app.seed_taxed_product(&tenant, 200, 5).await;
+    let cancelled = seed_rolls_dispatch(&app, &tenant, customer_id, product_id, None).await;
+    let open = seed_rolls_dispatch(&app, &tenant, customer_id, product_id, None).await;
+    sqlx::query!(
+        "UPDATE goods_dispatches SET is_cancelled = TRUE WHERE id = $1",
+        cancelled
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    let other_warehouse_id = app.seed_warehouse(&tenant, "Surat Godown").await;
+    let staff_id = app.seed_staff(&tenant, other_warehouse_id).await;
+
+    let cancelled_res = post_invoice(&app, tenant.admin_id, cancelled).await;
+    let staff_res = post_invoice(&app, staff_id, open).await;
+
+    assert_eq!(StatusCode::CONFLICT, cancelled_res.status());
+    assert_eq!(StatusCode::NOT_FOUND, staff_res.status());
+}
+
+// PDF
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn invoice_pdf_is_rendered_once_generated() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let product_id = app.seed_taxed_product(&tenant, 200, 5).await;
+    let dispatch_id = seed_rolls_dispatch(&app, &tenant, customer_id, product_id, None).await;
+    let pdf_url = format!(
+        "{}/api/v1/dispatches/{}/invoice.pdf",
+        app.address, dispatch_id
+    );
+
+    let before = app
+        .api_client
+        .get(&pdf_url)
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .send()
+        .await
+        .unwrap();
+    post_invoice(&app, tenant.admin_id, dispatch_id).await;
+    let after = app
+        .api_client
+        .get(&pdf_url)
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(StatusCode::NOT_FOUND, before.status());
+    assert_eq!(StatusCode::OK, after.status());
+    assert_eq!(
+        "application/pdf",
+        after.headers()[header::CONTENT_TYPE].to_str().unwrap()
+    );
+    let body = after.bytes().await.unwrap();
+    assert!(body.starts_with(b"%PDF-"));
+    let text = String::from_utf8_lossy(&body);
+    assert!(text.contains("TAX INVOICE"));
+    assert!(text.contains("Rupees Four Thousand Two Hundred Only"));
+}
+
+#[tokio::test]
+async fn invoice_pdf_carries_the_uploaded_company_logo() {
+    let app = TestApp::build().await;
+    // A logo_url is only a reference; the server never fetches it
+    let logo_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/logo.jpg"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_bytes(include_bytes!("fixtures/logo.jpg").to_vec()),
+        )
+        .expect(0)
+        .mount(&logo_server)
+        .await;
+    let mut pdfs = Vec::new();
+    for upload in [true, false] {
+        let tenant = app.seed_tenant().await;
+        sqlx::query!(
+            "UPDATE companies SET logo_url = $2 WHERE id = $1",
+            tenant.company_id,
+            format!("{}/logo.jpg", logo_server.uri())
+        )
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+        if upload {
+            let status = put_logo(
+                &app,
+                tenant.admin_id,
+                tenant.company_id,
+                include_bytes!("fixtures/logo.jpg").to_vec(),
+            )
+            .await;
+            assert_eq!(StatusCode::NO_CONTENT, status);
+        }
+        let customer_id = app.seed_partner(&tenant, "Customer").await;
+        let product_id = app.seed_taxed_product(&tenant, 200, 5).await;
+        let dispatch_id = seed_rolls_dispatch(&app, &tenant, customer_id, product_id, None).await;
+        post_invoice(&app, tenant.admin_id, dispatch_id).await;
+        let res = app
+            .api_client
+            .get(format!(
+                "{}/api/v1/dispatches/{}/invoice.pdf",
+                app.address, dispatch_id
+            ))
+            .header(USER_ID_HEADER, tenant.admin_id.to_string())
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        pdfs.push(String::from_utf8_lossy(&res.bytes().await.unwrap()).to_string());
+    }
+
+    assert!(pdfs[0].contains("/Subtype /Image"));
+    assert!(pdfs[0].contains("/DCTDecode"));
+    assert!(pdfs[0].contains("/Width 16"));
+    // Without an uploaded logo the header falls back to the company details
+    assert!(!pdfs[1].contains("/Subtype /Image"));
+    assert!(pdfs[1].contains("TAX INVOICE"));
+}
+
+#[tokio::test]
+async fn only_admins_can_upload_a_jpeg_logo_for_their_own_company() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let other = app.seed_tenant().await;
+    let staff_id = app.seed_staff(&tenant, tenant.warehouse_id).await;
+    let jpeg = include_bytes!("fixtures/logo.jpg").to_vec();
+
+    let not_jpeg = put_logo(&app, tenant.admin_id, tenant.company_id, b"<svg/>".to_vec()).await;
+    let staff = put_logo(&app, staff_id, tenant.company_id, jpeg.clone()).await;
+    let other_company = put_logo(&app, tenant.admin_id, other.company_id, jpeg).await;
+
+    assert_eq!(StatusCode::BAD_REQUEST, not_jpeg);
+    assert_eq!(StatusCode::FORBIDDEN, staff);
+    assert_eq!(StatusCode::NOT_FOUND, other_company);
+    let stored = sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM companies WHERE id = ANY($1) AND logo_jpeg IS NOT NULL",
+        &[tenant.company_id, other.company_id][..]
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(Some(0), stored);
+}
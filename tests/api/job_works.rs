@@ -0,0 +1,716 @@
+use bale_backend::{
+    auth::USER_ID_HEADER,
+    routes::{
+        job_work_analytics::{VendorPerformance, YieldSummary},
+        job_works::{JobWork, JobWorkCompletion, JobWorkStatusChange},
+    },
+};
+use reqwest::StatusCode;
+use rust_decimal::Decimal;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::test_app::{TestApp, TestTenant};
+
+// HELPERS
+// -------------------------------------------------------------------------------------
+
+async fn post_job_work(
+    app: &TestApp,
+    user_id: Uuid,
+    body: &serde_json::Value,
+) -> reqwest::Response {
+    app.api_client
+        .post(format!("{}/api/v1/job-works", app.address))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .json(body)
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn patch_job_work(
+    app: &TestApp,
+    user_id: Uuid,
+    job_work_id: Uuid,
+    body: &serde_json::Value,
+) -> reqwest::Response {
+    app.api_client
+        .patch(format!("{}/api/v1/job-works/{}", app.address, job_work_id))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .json(body)
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn post_transition(
+    app: &TestApp,
+    user_id: Uuid,
+    job_work_id: Uuid,
+    transition: &str,
+    body: &serde_json::Value,
+) -> reqwest::Response {
+    app.api_client
+        .post(format!(
+            "{}/api/v1/job-works/{}/{}",
+            app.address, job_work_id, transition
+        ))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .json(body)
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn get_status_history(
+    app: &TestApp,
+    user_id: Uuid,
+    job_work_id: Uuid,
+) -> Vec<JobWorkStatusChange> {
+    app.api_client
+        .get(format!(
+            "{}/api/v1/job-works/{}/status-history",
+            app.address, job_work_id
+        ))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+async fn get_job_work(app: &TestApp, user_id: Uuid, job_work_id: Uuid) -> reqwest::Response {
+    app.api_client
+        .get(format!("{}/api/v1/job-works/{}", app.address, job_work_id))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .send()
+        .await
+        .unwrap()
+}
+
+/// Grey fabric sent for dyeing, dyed fabric expected back
+struct DyeingSetup {
+    vendor_id: Uuid,
+    grey_id: Uuid,
+    dyed_id: Uuid,
+}
+
+async fn seed_dyeing(app: &TestApp, tenant: &TestTenant) -> DyeingSetup {
+    DyeingSetup {
+        vendor_id: app.seed_partner(tenant, "Vendor").await,
+        grey_id: app.seed_product(tenant, 80, &[]).await,
+        dyed_id: app.seed_product(tenant, 120, &[]).await,
+    }
+}
+
+fn dyeing_body(setup: &DyeingSetup) -> serde_json::Value {
+    json!({
+        "job_type": "Dyeing",
+        "vendor_id": setup.vendor_id,
+        "start_date": "2026-03-01",
+        "due_date": "2026-03-15",
+        "raw_materials": [{ "product_id": setup.grey_id, "required_quantity": "100" }],
+        "finished_goods": [{ "product_id": setup.dyed_id, "expected_quantity": "95" }],
+    })
+}
+
+async fn seed_job_work_dispatch(
+    app: &TestApp,
+    tenant: &TestTenant,
+    job_work: &JobWork,
+    stock_unit_ids: &[Uuid],
+) -> Uuid {
+    let dispatch_id = app
+        .seed_dispatch(tenant, job_work.vendor_id, None, None, stock_unit_ids)
+        .await;
+    sqlx::query!(
+        "UPDATE goods_dispatches SET link_type = 'job_work', job_work_id = $2 WHERE id = $1",
+        dispatch_id,
+        job_work.id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    dispatch_id
+}
+
+/// Receives finished goods back from the vendor as new stock units
+async fn seed_job_work_receipt(
+    app: &TestApp,
+    tenant: &TestTenant,
+    job_work: &JobWork,
+    product_id: Uuid,
+    sizes: &[i64],
+) {
+    let receipt_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO goods_receipts (company_id, warehouse_id, issued_by_partner_id, link_type,
+                                    job_work_id, created_by)
+        VALUES ($1, $2, $3, 'job_work', $4, $5)
+        RETURNING id
+        "#,
+        tenant.company_id,
+        tenant.warehouse_id,
+        job_work.vendor_id,
+        job_work.id,
+        tenant.admin_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+
+    for size in sizes {
+        let stock_unit_id = app.seed_stock_unit(tenant, product_id, *size).await;
+        sqlx::query!(
+            "UPDATE stock_units SET created_from_receipt_id = $2 WHERE id = $1",
+            stock_unit_id,
+            receipt_id
+        )
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+    }
+}
+
+/// Sends `sent` of grey fabric, receives `received` of dyed fabric back and
+/// completes the job work on `completed_on`
+async fn seed_completed_dyeing(
+    app: &TestApp,
+    tenant: &TestTenant,
+    setup: &DyeingSetup,
+    sent: i64,
+    received: i64,
+    completed_on: &str,
+) -> JobWork {
+    let job_work: JobWork = post_job_work(app, tenant.admin_id, &dyeing_body(setup))
+        .await
+        .json()
+        .await
+        .unwrap();
+    let unit = app.seed_stock_unit(tenant, setup.grey_id, sent).await;
+    seed_job_work_dispatch(app, tenant, &job_work, &[unit]).await;
+    seed_job_work_receipt(app, tenant, &job_work, setup.dyed_id, &[received]).await;
+    sqlx::query!(
+        r#"
+        UPDATE job_works SET status = 'completed', status_changed_at = $2::TEXT::TIMESTAMPTZ
+        WHERE id = $1
+        "#,
+        job_work.id,
+        completed_on
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    job_work
+}
+
+async fn get_analytics(app: &TestApp, user_id: Uuid, path: &str) -> reqwest::Response {
+    app.api_client
+        .get(format!("{}/api/v1/job-works/{}", app.address, path))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .send()
+        .await
+        .unwrap()
+}
+
+// CREATE
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn create_job_work_stores_raw_materials_and_finished_goods() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let setup = seed_dyeing(&app, &tenant).await;
+
+    let res = post_job_work(&app, tenant.admin_id, &dyeing_body(&setup)).await;
+    let status = res.status();
+    let job_work: JobWork = res.json().await.unwrap();
+
+    assert_eq!(StatusCode::CREATED, status);
+    assert_eq!("JW-000001", job_work.job_number);
+    assert_eq!("in_progress", job_work.status);
+    assert_eq!(tenant.warehouse_id, job_work.warehouse_id);
+    assert_eq!(setup.grey_id, job_work.raw_materials[0].product_id);
+    assert_eq!(
+        Decimal::from(100),
+        job_work.raw_materials[0].pending_quantity
+    );
+    assert_eq!(
+        Decimal::from(95),
+        job_work.finished_goods[0].pending_quantity
+    );
+}
+
+#[tokio::test]
+async fn non_vendor_partner_needs_explicit_override() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let mut setup = seed_dyeing(&app, &tenant).await;
+    setup.vendor_id = app.seed_partner(&tenant, "Supplier").await;
+    let mut body = dyeing_body(&setup);
+
+    let refused = post_job_work(&app, tenant.admin_id, &body).await;
+    body["allow_any_partner"] = json!(true);
+    let accepted = post_job_work(&app, tenant.admin_id, &body).await;
+
+    assert_eq!(StatusCode::BAD_REQUEST, refused.status());
+    assert_eq!(StatusCode::CREATED, accepted.status());
+}
+
+#[tokio::test]
+async fn job_work_with_unknown_product_is_not_stored() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let other_tenant = app.seed_tenant().await;
+    let mut setup = seed_dyeing(&app, &tenant).await;
+    setup.dyed_id = app.seed_product(&other_tenant, 120, &[]).await;
+
+    let res = post_job_work(&app, tenant.admin_id, &dyeing_body(&setup)).await;
+    let count = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM job_works WHERE company_id = $1"#,
+        tenant.company_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(StatusCode::BAD_REQUEST, res.status());
+    assert_eq!(0, count);
+}
+
+#[tokio::test]
+async fn duplicate_products_on_one_side_are_rejected() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let setup = seed_dyeing(&app, &tenant).await;
+    let mut body = dyeing_body(&setup);
+    body["raw_materials"] = json!([
+        { "product_id": setup.grey_id, "required_quantity": "60" },
+        { "product_id": setup.grey_id, "required_quantity": "40" },
+    ]);
+
+    let res = post_job_work(&app, tenant.admin_id, &body).await;
+
+    assert_eq!(StatusCode::BAD_REQUEST, res.status());
+}
+
+#[tokio::test]
+async fn staff_work_only_in_their_own_warehouse() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let setup = seed_dyeing(&app, &tenant).await;
+    let other_warehouse = app.seed_warehouse(&tenant, "Surat Branch").await;
+    let staff_id = app.seed_staff(&tenant, other_warehouse).await;
+    let job_work: JobWork = post_job_work(&app, tenant.admin_id, &dyeing_body(&setup))
+        .await
+        .json()
+        .await
+        .unwrap();
+    let mut body = dyeing_body(&setup);
+    body["warehouse_id"] = json!(tenant.warehouse_id);
+
+    let create_elsewhere = post_job_work(&app, staff_id, &body).await;
+    let read_elsewhere = get_job_work(&app, staff_id, job_work.id).await;
+    let edit_elsewhere =
+        patch_job_work(&app, staff_id, job_work.id, &json!({ "notes": "Rush" })).await;
+
+    assert_eq!(StatusCode::FORBIDDEN, create_elsewhere.status());
+    assert_eq!(StatusCode::NOT_FOUND, read_elsewhere.status());
+    assert_eq!(StatusCode::FORBIDDEN, edit_elsewhere.status());
+}
+
+#[tokio::test]
+async fn partial_dispatches_and_receipts_update_pending_quantities() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let setup = seed_dyeing(&app, &tenant).await;
+    let job_work: JobWork = post_job_work(&app, tenant.admin_id, &dyeing_body(&setup))
+        .await
+        .json()
+        .await
+        .unwrap();
+    let first = app.seed_stock_unit(&tenant, setup.grey_id, 30).await;
+    let second = app.seed_stock_unit(&tenant, setup.grey_id, 25).await;
+
+    seed_job_work_dispatch(&app, &tenant, &job_work, &[first]).await;
+    seed_job_work_dispatch(&app, &tenant, &job_work, &[second]).await;
+    seed_job_work_receipt(&app, &tenant, &job_work, setup.dyed_id, &[20, 15]).await;
+    let updated: JobWork = get_job_work(&app, tenant.admin_id, job_work.id)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let raw = &updated.raw_materials[0];
+    let finished = &updated.finished_goods[0];
+
+    assert_eq!(Decimal::from(55), raw.dispatched_quantity);
+    assert_eq!(Decimal::from(45), raw.pending_quantity);
+    assert_eq!(Decimal::from(35), finished.received_quantity);
+    assert_eq!(Decimal::from(60), finished.pending_quantity);
+    assert_eq!(Decimal::ZERO, finished.excess_quantity);
+}
+
+#[tokio::test]
+async fn over_delivery_is_recorded_as_excess() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let setup = seed_dyeing(&app, &tenant).await;
+    let job_work: JobWork = post_job_work(&app, tenant.admin_id, &dyeing_body(&setup))
+        .await
+        .json()
+        .await
+        .unwrap();
+    let unit = app.seed_stock_unit(&tenant, setup.grey_id, 110).await;
+
+    seed_job_work_dispatch(&app, &tenant, &job_work, &[unit]).await;
+    seed_job_work_receipt(&app, &tenant, &job_work, setup.dyed_id, &[60, 40]).await;
+    let updated: JobWork = get_job_work(&app, tenant.admin_id, job_work.id)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let raw = &updated.raw_materials[0];
+    let finished = &updated.finished_goods[0];
+
+    assert_eq!(Decimal::from(110), raw.dispatched_quantity);
+    assert_eq!(Decimal::ZERO, raw.pending_quantity);
+    assert_eq!(Decimal::from(10), raw.excess_quantity);
+    assert_eq!(Decimal::from(100), finished.received_quantity);
+    assert_eq!(Decimal::ZERO, finished.pending_quantity);
+    assert_eq!(Decimal::from(5), finished.excess_quantity);
+}
+
+#[tokio::test]
+async fn cancelled_dispatch_no_longer_counts_as_dispatched() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let setup = seed_dyeing(&app, &tenant).await;
+    let job_work: JobWork = post_job_work(&app, tenant.admin_id, &dyeing_body(&setup))
+        .await
+        .json()
+        .await
+        .unwrap();
+    let kept = app.seed_stock_unit(&tenant, setup.grey_id, 30).await;
+    let returned = app.seed_stock_unit(&tenant, setup.grey_id, 40).await;
+    seed_job_work_dispatch(&app, &tenant, &job_work, &[kept]).await;
+    let dispatch_id = seed_job_work_dispatch(&app, &tenant, &job_work, &[returned]).await;
+
+    sqlx::query!(
+        "UPDATE goods_dispatches SET is_cancelled = TRUE WHERE id = $1",
+        dispatch_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    let updated: JobWork = get_job_work(&app, tenant.admin_id, job_work.id)
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        Decimal::from(30),
+        updated.raw_materials[0].dispatched_quantity
+    );
+    assert_eq!(Decimal::from(70), updated.raw_materials[0].pending_quantity);
+}
+
+// READ
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn yield_and_wastage_are_reported_per_job_work_and_job_type() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let setup = seed_dyeing(&app, &tenant).await;
+    let job_work = seed_completed_dyeing(&app, &tenant, &setup, 100, 94, "2026-03-12").await;
+    seed_completed_dyeing(&app, &tenant, &setup, 50, 48, "2026-03-14").await;
+
+    let detail: JobWork = get_job_work(&app, tenant.admin_id, job_work.id)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let res = get_analytics(&app, tenant.admin_id, "yields?group_by=job_type").await;
+    let status = res.status();
+    let summaries: Vec<YieldSummary> = res.json().await.unwrap();
+    let invalid = get_analytics(&app, tenant.admin_id, "yields?group_by=colour").await;
+
+    assert_eq!("Meters", detail.yields[0].measuring_unit);
+    assert_eq!(Decimal::from(6), detail.yields[0].wastage);
+    assert_eq!(Decimal::from(94), detail.yields[0].yield_percentage);
+    assert_eq!(StatusCode::OK, status);
+    assert_eq!(1, summaries.len());
+    assert_eq!("Dyeing", summaries[0].name);
+    assert_eq!(2, summaries[0].completed_job_works);
+    assert_eq!(Decimal::from(150), summaries[0].yields[0].raw_dispatched);
+    assert_eq!(Decimal::from(8), summaries[0].yields[0].wastage);
+    assert_eq!(
+        Decimal::new(9467, 2),
+        summaries[0].yields[0].yield_percentage
+    );
+    assert_eq!(StatusCode::BAD_REQUEST, invalid.status());
+}
+
+#[tokio::test]
+async fn vendors_are_ranked_by_yield_turnaround_and_on_time_rate() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let careful = seed_dyeing(&app, &tenant).await;
+    let mut quick = seed_dyeing(&app, &tenant).await;
+    quick.vendor_id = app.seed_partner(&tenant, "Vendor").await;
+    // Due on 15 March: the careful vendor is late, the quick one on time
+    seed_completed_dyeing(&app, &tenant, &careful, 100, 96, "2026-03-21").await;
+    seed_completed_dyeing(&app, &tenant, &quick, 100, 90, "2026-03-11").await;
+
+    let by_yield: Vec<VendorPerformance> = get_analytics(&app, tenant.admin_id, "vendor-ranking")
+        .await
+        .json()
+        .await
+        .unwrap();
+    let by_turnaround: Vec<VendorPerformance> =
+        get_analytics(&app, tenant.admin_id, "vendor-ranking?sort=turnaround")
+            .await
+            .json()
+            .await
+            .unwrap();
+    let by_on_time: Vec<VendorPerformance> =
+        get_analytics(&app, tenant.admin_id, "vendor-ranking?sort=on_time")
+            .await
+            .json()
+            .await
+            .unwrap();
+
+    assert_eq!(careful.vendor_id, by_yield[0].vendor_id);
+    assert_eq!(Some(Decimal::from(96)), by_yield[0].yield_percentage);
+    assert_eq!(Decimal::from(20), by_yield[0].average_turnaround_days);
+    assert_eq!(Some(Decimal::ZERO), by_yield[0].on_time_percentage);
+    assert_eq!(quick.vendor_id, by_turnaround[0].vendor_id);
+    assert_eq!(Decimal::from(10), by_turnaround[0].average_turnaround_days);
+    assert_eq!(quick.vendor_id, by_on_time[0].vendor_id);
+    assert_eq!(Some(Decimal::from(100)), by_on_time[0].on_time_percentage);
+}
+
+// UPDATE
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn line_updates_keep_dispatched_lines_and_report_the_excess() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let setup = seed_dyeing(&app, &tenant).await;
+    let thread_id = app.seed_product(&tenant, 10, &[]).await;
+    let job_work: JobWork = post_job_work(&app, tenant.admin_id, &dyeing_body(&setup))
+        .await
+        .json()
+        .await
+        .unwrap();
+    sqlx::query!(
+        "UPDATE job_work_raw_materials SET dispatched_quantity = 40 WHERE job_work_id = $1",
+        job_work.id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let below_dispatched: JobWork = patch_job_work(
+        &app,
+        tenant.admin_id,
+        job_work.id,
+        &json!({ "raw_materials": [{ "product_id": setup.grey_id, "required_quantity": "30" }] }),
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+    let removing_dispatched = patch_job_work(
+        &app,
+        tenant.admin_id,
+        job_work.id,
+        &json!({ "raw_materials": [{ "product_id": thread_id, "required_quantity": "5" }] }),
+    )
+    .await;
+    let res = patch_job_work(
+        &app,
+        tenant.admin_id,
+        job_work.id,
+        &json!({
+            "due_date": "2026-03-20",
+            "raw_materials": [
+                { "product_id": setup.grey_id, "required_quantity": "50" },
+                { "product_id": thread_id, "required_quantity": "5" },
+            ],
+        }),
+    )
+    .await;
+    let status = res.status();
+    let updated: JobWork = res.json().await.unwrap();
+    let grey = updated
+        .raw_materials
+        .iter()
+        .find(|l| l.product_id == setup.grey_id)
+        .unwrap();
+
+    assert_eq!(
+        Decimal::ZERO,
+        below_dispatched.raw_materials[0].pending_quantity
+    );
+    assert_eq!(
+        Decimal::from(10),
+        below_dispatched.raw_materials[0].excess_quantity
+    );
+    assert_eq!(StatusCode::CONFLICT, removing_dispatched.status());
+    assert_eq!(StatusCode::OK, status);
+    assert_eq!(2, updated.raw_materials.len());
+    assert_eq!(Decimal::from(10), grey.pending_quantity);
+    assert_eq!("2026-03-20", updated.due_date.unwrap().to_string());
+    assert_eq!(1, updated.finished_goods.len());
+}
+
+#[tokio::test]
+async fn unused_job_work_can_be_deleted() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let setup = seed_dyeing(&app, &tenant).await;
+    let job_work: JobWork = post_job_work(&app, tenant.admin_id, &dyeing_body(&setup))
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    let res = app
+        .api_client
+        .delete(format!("{}/api/v1/job-works/{}", app.address, job_work.id))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .send()
+        .await
+        .unwrap();
+    let after = get_job_work(&app, tenant.admin_id, job_work.id).await;
+
+    assert_eq!(StatusCode::NO_CONTENT, res.status());
+    assert_eq!(StatusCode::NOT_FOUND, after.status());
+}
+
+#[tokio::test]
+async fn completion_needs_notes_and_reports_pending_finished_goods() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let setup = seed_dyeing(&app, &tenant).await;
+    let job_work: JobWork = post_job_work(&app, tenant.admin_id, &dyeing_body(&setup))
+        .await
+        .json()
+        .await
+        .unwrap();
+    seed_job_work_receipt(&app, &tenant, &job_work, setup.dyed_id, &[35]).await;
+
+    let without_notes = post_transition(
+        &app,
+        tenant.admin_id,
+        job_work.id,
+        "complete",
+        &json!({ "notes": " " }),
+    )
+    .await;
+    let res = post_transition(
+        &app,
+        tenant.admin_id,
+        job_work.id,
+        "complete",
+        &json!({ "notes": "Vendor delivered short" }),
+    )
+    .await;
+    let status = res.status();
+    let completion: JobWorkCompletion = res.json().await.unwrap();
+    let again = post_transition(
+        &app,
+        tenant.admin_id,
+        job_work.id,
+        "complete",
+        &json!({ "notes": "Twice" }),
+    )
+    .await;
+    let history = get_status_history(&app, tenant.admin_id, job_work.id).await;
+
+    assert_eq!(StatusCode::BAD_REQUEST, without_notes.status());
+    assert_eq!(StatusCode::OK, status);
+    assert_eq!(
+        Decimal::from(60),
+        completion.pending_finished_goods[0].pending_quantity
+    );
+    assert_eq!(StatusCode::CONFLICT, again.status());
+    assert_eq!(2, history.len());
+    assert_eq!(None, history[0].from_status);
+    assert_eq!("completed", history[1].to_status);
+    assert_eq!(Some("Vendor delivered short"), history[1].notes.as_deref());
+    assert!(history[1].with_pending_goods);
+}
+
+#[tokio::test]
+async fn cancellation_needs_dispatched_materials_returned() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let setup = seed_dyeing(&app, &tenant).await;
+    let job_work: JobWork = post_job_work(&app, tenant.admin_id, &dyeing_body(&setup))
+        .await
+        .json()
+        .await
+        .unwrap();
+    let unit = app.seed_stock_unit(&tenant, setup.grey_id, 40).await;
+    sqlx::query!(
+        "UPDATE stock_units SET status = 'dispatched' WHERE id = $1",
+        unit
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    seed_job_work_dispatch(&app, &tenant, &job_work, &[unit]).await;
+
+    let without_reason = post_transition(
+        &app,
+        tenant.admin_id,
+        job_work.id,
+        "cancel",
+        &json!({ "reason": "" }),
+    )
+    .await;
+    let materials_out = post_transition(
+        &app,
+        tenant.admin_id,
+        job_work.id,
+        "cancel",
+        &json!({ "reason": "Vendor shut down" }),
+    )
+    .await;
+    let returned = post_transition(
+        &app,
+        tenant.admin_id,
+        job_work.id,
+        "cancel",
+        &json!({ "reason": "Vendor shut down", "return_materials": true }),
+    )
+    .await;
+    let cancelled: JobWork = get_job_work(&app, tenant.admin_id, job_work.id)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let unit_status = sqlx::query_scalar!("SELECT status FROM stock_units WHERE id = $1", unit)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    let history = get_status_history(&app, tenant.admin_id, job_work.id).await;
+
+    assert_eq!(StatusCode::BAD_REQUEST, without_reason.status());
+    assert_eq!(StatusCode::CONFLICT, materials_out.status());
+    assert_eq!(StatusCode::NO_CONTENT, returned.status());
+    assert_eq!("cancelled", cancelled.status);
+    assert_eq!(
+        Decimal::ZERO,
+        cancelled.raw_materials[0].dispatched_quantity
+    );
+    assert_eq!("in_stock", unit_status);
+    assert_eq!("cancelled", history[1].to_status);
+}
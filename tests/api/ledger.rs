@@ -0,0 +1,303 @@
+use bale_backend::{
+    auth::USER_ID_HEADER,
+    routes::ledger::{PartnerOutstanding, PartnerStatement},
+};
+use chrono::{Duration, NaiveDate, Utc};
+use reqwest::StatusCode;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::test_app::{TestApp, TestTenant};
+
+// HELPERS
+// -------------------------------------------------------------------------------------
+
+async fn insert_payment(
+    app: &TestApp,
+    tenant: &TestTenant,
+    partner_id: Uuid,
+    payment_date: NaiveDate,
+    amount: i64,
+) {
+    sqlx::query!(
+        r#"
+        INSERT INTO payments (company_id, partner_id, payment_date, amount, created_by)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        tenant.company_id,
+        partner_id,
+        payment_date,
+        Decimal::from(amount),
+        tenant.admin_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .expect("Failed to insert payment.");
+}
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+// STATEMENT
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn statement_returns_running_balance_for_orders_advances_and_payments() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+
+    app.seed_sales_order(
+        &tenant,
+        customer_id,
+        date(2025, 5, 1),
+        10_000,
+        2_000,
+        "in_progress",
+    )
+    .await;
+    insert_payment(&app, &tenant, customer_id, date(2025, 5, 10), 3_000).await;
+    app.seed_sales_order(
+        &tenant,
+        customer_id,
+        date(2025, 5, 20),
+        5_000,
+        0,
+        "completed",
+    )
+    .await;
+
+    let res = app
+        .api_client
+        .get(format!(
+            "{}/api/v1/partners/{}/ledger?from=2025-05-01&to=2025-05-31",
+            app.address, customer_id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .send()
+        .await
+        .unwrap();
+
+    let status = res.status();
+    let statement: PartnerStatement = res.json().await.unwrap();
+    let balances: Vec<Decimal> = statement.entries.iter().map(|e| e.balance).collect();
+
+    assert_eq!(StatusCode::OK, status);
+    assert_eq!(Decimal::ZERO, statement.opening_balance);
+    assert_eq!(
+        balances,
+        vec![
+            Decimal::from(10_000),
+            Decimal::from(8_000),
+            Decimal::from(5_000),
+            Decimal::from(10_000)
+        ]
+    );
+    assert_eq!(Decimal::from(15_000), statement.total_debit);
+    assert_eq!(Decimal::from(5_000), statement.total_credit);
+    assert_eq!(Decimal::from(10_000), statement.closing_balance);
+}
+
+#[tokio::test]
+async fn statement_opening_balance_includes_entries_before_range() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+
+    app.seed_sales_order(
+        &tenant,
+        customer_id,
+        date(2025, 4, 2),
+        8_000,
+        0,
+        "in_progress",
+    )
+    .await;
+    insert_payment(&app, &tenant, customer_id, date(2025, 4, 15), 6_000).await;
+    insert_payment(&app, &tenant, customer_id, date(2025, 6, 5), 1_000).await;
+
+    let res = app
+        .api_client
+        .get(format!(
+            "{}/api/v1/partners/{}/ledger?from=2025-06-01&to=2025-06-30",
+            app.address, customer_id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .send()
+        .await
+        .unwrap();
+
+    let statement: PartnerStatement = res.json().await.unwrap();
+
+    assert_eq!(Decimal::from(2_000), statement.opening_balance);
+    assert_eq!(1, statement.entries.len());
+    assert_eq!(Decimal::from(1_000), statement.closing_balance);
+}
+
+#[tokio::test]
+async fn statement_ignores_unapproved_and_cancelled_orders() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+
+    app.seed_sales_order(
+        &tenant,
+        customer_id,
+        date(2025, 5, 1),
+        4_000,
+        500,
+        "approval_pending",
+    )
+    .await;
+    app.seed_sales_order(
+        &tenant,
+        customer_id,
+        date(2025, 5, 2),
+        7_000,
+        0,
+        "cancelled",
+    )
+    .await;
+
+    let res = app
+        .api_client
+        .get(format!(
+            "{}/api/v1/partners/{}/ledger?from=2025-05-01&to=2025-05-31",
+            app.address, customer_id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .send()
+        .await
+        .unwrap();
+
+    let statement: PartnerStatement = res.json().await.unwrap();
+
+    assert!(statement.entries.is_empty());
+    assert_eq!(Decimal::ZERO, statement.closing_balance);
+}
+
+#[tokio::test]
+async fn statement_rejects_inverted_date_range() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+
+    let res = app
+        .api_client
+        .get(format!(
+            "{}/api/v1/partners/{}/ledger?from=2025-06-01&to=2025-05-01",
+            app.address, customer_id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(StatusCode::BAD_REQUEST, res.status());
+}
+
+// OUTSTANDING
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn outstanding_buckets_unpaid_debits_by_age() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let today = Utc::now().date_naive();
+
+    app.seed_sales_order(
+        &tenant,
+        customer_id,
+        today - Duration::days(120),
+        5_000,
+        0,
+        "completed",
+    )
+    .await;
+    app.seed_sales_order(
+        &tenant,
+        customer_id,
+        today - Duration::days(45),
+        3_000,
+        0,
+        "in_progress",
+    )
+    .await;
+    app.seed_sales_order(
+        &tenant,
+        customer_id,
+        today - Duration::days(5),
+        2_000,
+        0,
+        "in_progress",
+    )
+    .await;
+    // Settles the oldest order in full and part of the next one
+    insert_payment(&app, &tenant, customer_id, today - Duration::days(1), 6_000).await;
+
+    let res = app
+        .api_client
+        .get(format!(
+            "{}/api/v1/partners/{}/outstanding",
+            app.address, customer_id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .send()
+        .await
+        .unwrap();
+
+    let status = res.status();
+    let outstanding: PartnerOutstanding = res.json().await.unwrap();
+
+    assert_eq!(StatusCode::OK, status);
+    assert_eq!(Decimal::from(4_000), outstanding.outstanding_balance);
+    assert_eq!(Decimal::from(2_000), outstanding.ageing.days_0_30);
+    assert_eq!(Decimal::from(2_000), outstanding.ageing.days_31_60);
+    assert_eq!(Decimal::ZERO, outstanding.ageing.days_61_90);
+    assert_eq!(Decimal::ZERO, outstanding.ageing.days_90_plus);
+}
+
+// ACCESS
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn ledger_returns_not_found_for_partner_of_another_company() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let other_tenant = app.seed_tenant().await;
+    let other_customer_id = app.seed_partner(&other_tenant, "Customer").await;
+
+    let res = app
+        .api_client
+        .get(format!(
+            "{}/api/v1/partners/{}/ledger",
+            app.address, other_customer_id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(StatusCode::NOT_FOUND, res.status());
+}
+
+#[tokio::test]
+async fn ledger_returns_unauthorized_without_user() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+
+    let res = app
+        .api_client
+        .get(format!(
+            "{}/api/v1/partners/{}/ledger",
+            app.address, customer_id
+        ))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(StatusCode::UNAUTHORIZED, res.status());
+}
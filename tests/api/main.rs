@@ -2,8 +2,19 @@ use ctor::{ctor, dtor};
 use once_cell::sync::Lazy;
 use std::{process::Command, sync::Mutex};
 
+mod attention;
+mod commissions;
 mod companies;
+mod dispatches;
+mod gst;
 mod healthcheck;
+mod invoices;
+mod job_works;
+mod ledger;
+mod payments;
+mod quotations;
+mod receipts;
+mod sales_orders;
 mod test_app;
 
 static DATABASE_CONTAINER_ID: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
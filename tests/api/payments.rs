@@ -0,0 +1,404 @@
+use bale_backend::{
+    auth::USER_ID_HEADER,
+    routes::{
+        ledger::PartnerStatement,
+        payments::{Payment, ReceivableDocument},
+    },
+};
+use chrono::{NaiveDate, Utc};
+use reqwest::StatusCode;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::test_app::{TestApp, TestTenant};
+
+// HELPERS
+// -------------------------------------------------------------------------------------
+
+async fn post_payment(
+    app: &TestApp,
+    tenant: &TestTenant,
+    body: &serde_json::Value,
+) -> reqwest::Response {
+    app.api_client
+        .post(format!("{}/api/v1/payments", app.address))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .json(body)
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn get_payment(app: &TestApp, tenant: &TestTenant, payment_id: Uuid) -> Payment {
+    app.api_client
+        .get(format!("{}/api/v1/payments/{}", app.address, payment_id))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+async fn get_receivables(
+    app: &TestApp,
+    tenant: &TestTenant,
+    partner_id: Uuid,
+) -> Vec<ReceivableDocument> {
+    app.api_client
+        .get(format!(
+            "{}/api/v1/partners/{}/receivables",
+            app.address, partner_id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+fn today() -> NaiveDate {
+    Utc::now().date_naive()
+}
+
+// CREATE
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn create_payment_allocates_across_orders() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let order1 = app
+        .seed_sales_order(&tenant, customer_id, today(), 10_000, 1_000, "in_progress")
+        .await;
+    let order2 = app
+        .seed_sales_order(&tenant, customer_id, today(), 4_000, 0, "in_progress")
+        .await;
+
+    let body = serde_json::json!({
+        "partner_id": customer_id,
+        "amount": "12000",
+        "payment_mode": "upi",
+        "reference_number": "UPI-778812",
+        "allocations": [
+            { "sales_order_id": order1, "amount": "9000" },
+            { "sales_order_id": order2, "amount": "3000" },
+        ],
+    });
+    let res = post_payment(&app, &tenant, &body).await;
+    let status = res.status();
+    let payment_id: Uuid = res.json().await.unwrap();
+
+    let payment = get_payment(&app, &tenant, payment_id).await;
+    let receivables = get_receivables(&app, &tenant, customer_id).await;
+
+    assert_eq!(StatusCode::CREATED, status);
+    assert_eq!("cleared", payment.clearing_status);
+    assert!(payment.payment_number.starts_with("PAY-"));
+    assert_eq!(2, payment.allocations.len());
+    assert_eq!(1, receivables.len());
+    assert_eq!(order2, receivables[0].document_id);
+    assert_eq!(Decimal::from(1_000), receivables[0].outstanding_amount);
+}
+
+#[tokio::test]
+async fn create_payment_rejects_allocation_exceeding_outstanding() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let order_id = app
+        .seed_sales_order(&tenant, customer_id, today(), 5_000, 2_000, "in_progress")
+        .await;
+
+    let body = serde_json::json!({
+        "partner_id": customer_id,
+        "amount": "4000",
+        "payment_mode": "cash",
+        "allocations": [{ "sales_order_id": order_id, "amount": "4000" }],
+    });
+    let res = post_payment(&app, &tenant, &body).await;
+
+    let saved = sqlx::query_scalar!(r#"SELECT COUNT(*) as "count!" FROM payments"#)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+
+    assert_eq!(StatusCode::BAD_REQUEST, res.status());
+    assert_eq!(0, saved);
+}
+
+#[tokio::test]
+async fn create_payment_rejects_allocation_to_unapproved_order() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let order_id = app
+        .seed_sales_order(&tenant, customer_id, today(), 5_000, 0, "approval_pending")
+        .await;
+
+    let body = serde_json::json!({
+        "partner_id": customer_id,
+        "amount": "1000",
+        "payment_mode": "cash",
+        "allocations": [{ "sales_order_id": order_id, "amount": "1000" }],
+    });
+    let res = post_payment(&app, &tenant, &body).await;
+
+    assert_eq!(StatusCode::BAD_REQUEST, res.status());
+}
+
+#[tokio::test]
+async fn create_cheque_payment_requires_cheque_number() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+
+    let body = serde_json::json!({
+        "partner_id": customer_id,
+        "amount": "1000",
+        "payment_mode": "cheque",
+    });
+    let res = post_payment(&app, &tenant, &body).await;
+
+    assert_eq!(StatusCode::BAD_REQUEST, res.status());
+}
+
+#[tokio::test]
+async fn concurrent_payments_get_distinct_numbers() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let body = serde_json::json!({
+        "partner_id": customer_id,
+        "amount": "100",
+        "payment_mode": "cash",
+    });
+
+    let responses = tokio::join!(
+        post_payment(&app, &tenant, &body),
+        post_payment(&app, &tenant, &body),
+        post_payment(&app, &tenant, &body),
+        post_payment(&app, &tenant, &body),
+        post_payment(&app, &tenant, &body),
+    );
+
+    let mut numbers = Vec::new();
+    for res in [
+        responses.0,
+        responses.1,
+        responses.2,
+        responses.3,
+        responses.4,
+    ] {
+        assert_eq!(StatusCode::CREATED, res.status());
+        let payment_id: Uuid = res.json().await.unwrap();
+        numbers.push(get_payment(&app, &tenant, payment_id).await.payment_number);
+    }
+    numbers.sort();
+    assert_eq!(
+        vec![
+            "PAY-000001",
+            "PAY-000002",
+            "PAY-000003",
+            "PAY-000004",
+            "PAY-000005"
+        ],
+        numbers
+    );
+}
+
+// CHEQUE CLEARING
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn cheque_payment_stays_pending_until_cleared() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+
+    let body = serde_json::json!({
+        "partner_id": customer_id,
+        "amount": "2500",
+        "payment_mode": "cheque",
+        "cheque_number": "004512",
+        "bank_name": "State Bank of India",
+    });
+    let payment_id: Uuid = post_payment(&app, &tenant, &body)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let pending = get_payment(&app, &tenant, payment_id).await;
+
+    let res = app
+        .api_client
+        .post(format!(
+            "{}/api/v1/payments/{}/clear",
+            app.address, payment_id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .send()
+        .await
+        .unwrap();
+    let cleared = get_payment(&app, &tenant, payment_id).await;
+    let events: Vec<&str> = cleared
+        .events
+        .iter()
+        .map(|e| e.event_type.as_str())
+        .collect();
+
+    assert_eq!("pending", pending.clearing_status);
+    assert_eq!(StatusCode::NO_CONTENT, res.status());
+    assert_eq!("cleared", cleared.clearing_status);
+    assert!(cleared.cleared_at.is_some());
+    assert_eq!(events, vec!["recorded", "cleared"]);
+}
+
+#[tokio::test]
+async fn bounced_cheque_releases_allocation_and_debits_ledger() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let order_id = app
+        .seed_sales_order(&tenant, customer_id, today(), 6_000, 0, "in_progress")
+        .await;
+
+    let body = serde_json::json!({
+        "partner_id": customer_id,
+        "amount": "6000",
+        "payment_mode": "cheque",
+        "cheque_number": "118822",
+        "allocations": [{ "sales_order_id": order_id, "amount": "6000" }],
+    });
+    let payment_id: Uuid = post_payment(&app, &tenant, &body)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let receivables_before = get_receivables(&app, &tenant, customer_id).await;
+
+    let res = app
+        .api_client
+        .post(format!(
+            "{}/api/v1/payments/{}/bounce",
+            app.address, payment_id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .json(&serde_json::json!({ "reason": "Insufficient funds" }))
+        .send()
+        .await
+        .unwrap();
+
+    let payment = get_payment(&app, &tenant, payment_id).await;
+    let receivables_after = get_receivables(&app, &tenant, customer_id).await;
+    let statement: PartnerStatement = app
+        .api_client
+        .get(format!(
+            "{}/api/v1/partners/{}/ledger",
+            app.address, customer_id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let entry_types: Vec<&str> = statement
+        .entries
+        .iter()
+        .map(|e| e.entry_type.as_str())
+        .collect();
+
+    assert_eq!(StatusCode::NO_CONTENT, res.status());
+    assert!(receivables_before.is_empty());
+    assert_eq!("bounced", payment.clearing_status);
+    assert!(payment.is_reversed);
+    assert_eq!(
+        Some("Insufficient funds".to_string()),
+        payment.reversal_reason
+    );
+    assert_eq!(1, receivables_after.len());
+    assert_eq!(
+        Decimal::from(6_000),
+        receivables_after[0].outstanding_amount
+    );
+    assert_eq!(
+        entry_types,
+        vec!["sales_order", "payment", "payment_reversal"]
+    );
+    assert_eq!(Decimal::from(6_000), statement.closing_balance);
+}
+
+#[tokio::test]
+async fn bounce_is_rejected_for_non_cheque_payments() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+
+    let body = serde_json::json!({
+        "partner_id": customer_id,
+        "amount": "500",
+        "payment_mode": "cash",
+    });
+    let payment_id: Uuid = post_payment(&app, &tenant, &body)
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    let res = app
+        .api_client
+        .post(format!(
+            "{}/api/v1/payments/{}/bounce",
+            app.address, payment_id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .json(&serde_json::json!({ "reason": "Wrong entry" }))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(StatusCode::CONFLICT, res.status());
+}
+
+// REVERSAL
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn reversing_a_payment_twice_returns_conflict() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+
+    let body = serde_json::json!({
+        "partner_id": customer_id,
+        "amount": "500",
+        "payment_mode": "bank_transfer",
+    });
+    let payment_id: Uuid = post_payment(&app, &tenant, &body)
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    let reverse = || {
+        app.api_client
+            .post(format!(
+                "{}/api/v1/payments/{}/reverse",
+                app.address, payment_id
+            ))
+            .header(USER_ID_HEADER, tenant.admin_id.to_string())
+            .json(&serde_json::json!({ "reason": "Entered against wrong party" }))
+            .send()
+    };
+    let first = reverse().await.unwrap();
+    let second = reverse().await.unwrap();
+
+    assert_eq!(StatusCode::NO_CONTENT, first.status());
+    assert_eq!(StatusCode::CONFLICT, second.status());
+}
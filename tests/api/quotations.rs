@@ -0,0 +1,308 @@
+use bale_backend::{
+    auth::USER_ID_HEADER,
+    routes::{quotations::QuotationSummary, sales_orders::SalesOrderSummary},
+};
+use chrono::{Days, NaiveDate, Utc};
+use reqwest::{header, StatusCode};
+use rust_decimal::Decimal;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::test_app::{TestApp, TestTenant};
+
+// HELPERS
+// -------------------------------------------------------------------------------------
+
+async fn post_quotation(
+    app: &TestApp,
+    user_id: Uuid,
+    body: &serde_json::Value,
+) -> reqwest::Response {
+    app.api_client
+        .post(format!("{}/api/v1/quotations", app.address))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .json(body)
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn post_convert(app: &TestApp, user_id: Uuid, quotation_id: Uuid) -> reqwest::Response {
+    app.api_client
+        .post(format!(
+            "{}/api/v1/quotations/{}/convert",
+            app.address, quotation_id
+        ))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .send()
+        .await
+        .unwrap()
+}
+
+/// 20 m at 149.99 and 5 m at the product's selling price, both under HSN 5208 at 5%
+async fn seed_quotation(app: &TestApp, tenant: &TestTenant) -> QuotationSummary {
+    sqlx::query!(
+        r#"
+        INSERT INTO hsn_gst_rates (company_id, hsn_code, gst_rate, created_by)
+        VALUES ($1, '5208', 5, $2)
+        "#,
+        tenant.company_id,
+        tenant.admin_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    let customer_id = app.seed_partner(tenant, "Customer").await;
+    let product_id = app.seed_product(tenant, 200, &[]).await;
+    sqlx::query!(
+        "UPDATE products SET hsn_code = '5208' WHERE id = $1",
+        product_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    post_quotation(
+        app,
+        tenant.admin_id,
+        &json!({
+            "customer_id": customer_id,
+            "discount_percentage": "10",
+            "terms": "50% advance, balance on delivery",
+            "items": [
+                { "product_id": product_id, "required_quantity": "20", "unit_rate": "149.99" },
+                { "product_id": product_id, "required_quantity": "5" },
+            ],
+        }),
+    )
+    .await
+    .json()
+    .await
+    .unwrap()
+}
+
+// CREATE
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn create_quotation_prices_items_and_computes_tax() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+
+    let quotation = seed_quotation(&app, &tenant).await;
+
+    let today = Utc::now().date_naive();
+    assert_eq!("QT-000001", quotation.quotation_number);
+    assert_eq!("open", quotation.status);
+    assert_eq!(today, quotation.quotation_date);
+    assert_eq!(
+        today.checked_add_days(Days::new(15)),
+        Some(quotation.valid_until)
+    );
+    assert!(quotation
+        .items
+        .iter()
+        .any(|item| item.unit_rate == Decimal::from(200)));
+    assert!(quotation
+        .items
+        .iter()
+        .all(|item| item.hsn_code.as_deref() == Some("5208")));
+    // 2,999.80 + 1,000 less 10% is 3,599.82, taxed at 5% line by line
+    assert_eq!(Decimal::new(399_980, 2), quotation.subtotal);
+    assert_eq!(Decimal::new(359_982, 2), quotation.taxable_amount);
+    assert_eq!(Decimal::from(180), quotation.tax_amount);
+    assert_eq!(Decimal::new(18, 2), quotation.round_off);
+    assert_eq!(Decimal::from(3_780), quotation.total_amount);
+}
+
+#[tokio::test]
+async fn quotation_cannot_expire_before_its_date() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let product_id = app.seed_product(&tenant, 200, &[]).await;
+
+    let res = post_quotation(
+        &app,
+        tenant.admin_id,
+        &json!({
+            "customer_id": customer_id,
+            "quotation_date": "2026-03-10",
+            "valid_until": "2026-03-09",
+            "items": [{ "product_id": product_id, "required_quantity": "5" }],
+        }),
+    )
+    .await;
+
+    assert_eq!(StatusCode::BAD_REQUEST, res.status());
+}
+
+#[tokio::test]
+async fn quotation_at_the_end_of_the_calendar_is_refused_a_default_validity() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let product_id = app.seed_product(&tenant, 200, &[]).await;
+
+    let res = post_quotation(
+        &app,
+        tenant.admin_id,
+        &json!({
+            "customer_id": customer_id,
+            "quotation_date": NaiveDate::MAX,
+            "items": [{ "product_id": product_id, "required_quantity": "5" }],
+        }),
+    )
+    .await;
+
+    assert_eq!(StatusCode::BAD_REQUEST, res.status());
+}
+
+#[tokio::test]
+async fn staff_cannot_create_quotations() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let staff_id = app.seed_staff(&tenant, tenant.warehouse_id).await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let product_id = app.seed_product(&tenant, 200, &[]).await;
+
+    let res = post_quotation(
+        &app,
+        staff_id,
+        &json!({
+            "customer_id": customer_id,
+            "items": [{ "product_id": product_id, "required_quantity": "5" }],
+        }),
+    )
+    .await;
+
+    assert_eq!(StatusCode::FORBIDDEN, res.status());
+}
+
+// CONVERSION
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn conversion_carries_items_rates_and_discount_into_a_pending_order() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let quotation = seed_quotation(&app, &tenant).await;
+
+    let res = post_convert(&app, tenant.admin_id, quotation.id).await;
+    let status = res.status();
+    let order: SalesOrderSummary = res.json().await.unwrap();
+    let converted: QuotationSummary = app
+        .api_client
+        .get(format!(
+            "{}/api/v1/quotations/{}",
+            app.address, quotation.id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(StatusCode::CREATED, status);
+    assert_eq!("approval_pending", order.status);
+    assert_eq!(quotation.customer_id, order.customer_id);
+    assert_eq!(Some(tenant.warehouse_id), order.fulfillment_warehouse_id);
+    assert_eq!(Decimal::from(10), order.discount_percentage);
+    assert_eq!(2, order.items.len());
+    let mut rates: Vec<Decimal> = order.items.iter().map(|item| item.unit_rate).collect();
+    rates.sort();
+    assert_eq!(vec![Decimal::new(14_999, 2), Decimal::from(200)], rates);
+    assert_eq!(quotation.total_amount, order.total_amount);
+    assert_eq!("converted", converted.status);
+    assert_eq!(Some(order.id), converted.converted_sales_order_id);
+}
+
+#[tokio::test]
+async fn quotation_converts_only_once() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let quotation = seed_quotation(&app, &tenant).await;
+
+    post_convert(&app, tenant.admin_id, quotation.id).await;
+    let res = post_convert(&app, tenant.admin_id, quotation.id).await;
+    let orders = sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM sales_orders WHERE company_id = $1",
+        tenant.company_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(StatusCode::CONFLICT, res.status());
+    assert_eq!(Some(1), orders);
+}
+
+#[tokio::test]
+async fn expired_quotation_cannot_be_converted() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let quotation = seed_quotation(&app, &tenant).await;
+    sqlx::query!(
+        r#"
+        UPDATE quotations
+        SET quotation_date = CURRENT_DATE - 30, valid_until = CURRENT_DATE - 1
+        WHERE id = $1
+        "#,
+        quotation.id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let res = post_convert(&app, tenant.admin_id, quotation.id).await;
+    let expired: QuotationSummary = app
+        .api_client
+        .get(format!(
+            "{}/api/v1/quotations/{}",
+            app.address, quotation.id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(StatusCode::CONFLICT, res.status());
+    assert_eq!("expired", expired.status);
+}
+
+// PROFORMA
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn proforma_pdf_is_served_for_the_quotation() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let quotation = seed_quotation(&app, &tenant).await;
+
+    let res = app
+        .api_client
+        .get(format!(
+            "{}/api/v1/quotations/{}/proforma.pdf",
+            app.address, quotation.id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .send()
+        .await
+        .unwrap();
+    let status = res.status();
+    let content_type = res.headers()[header::CONTENT_TYPE].clone();
+    let body = res.bytes().await.unwrap();
+    let text = String::from_utf8_lossy(&body);
+
+    assert_eq!(StatusCode::OK, status);
+    assert_eq!("application/pdf", content_type);
+    assert!(body.starts_with(b"%PDF-"));
+    assert!(text.contains("PROFORMA INVOICE"));
+    assert!(text.contains("QT-000001"));
+    assert!(text.contains("Rupees Three Thousand Seven Hundred Eighty Only"));
+}
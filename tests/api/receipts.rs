@@ -0,0 +1,905 @@
+use bale_backend::{
+    auth::USER_ID_HEADER,
+    routes::{
+        dispatches::Dispatch,
+        receipts::{Receipt, ReceiptAdjustment, SalesReturn},
+    },
+};
+use reqwest::StatusCode;
+use rust_decimal::Decimal;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::test_app::{TestApp, TestTenant};
+
+// HELPERS
+// -------------------------------------------------------------------------------------
+
+async fn unit_number(app: &TestApp, stock_unit_id: Uuid) -> String {
+    sqlx::query_scalar!(
+        "SELECT unit_number FROM stock_units WHERE id = $1",
+        stock_unit_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap()
+}
+
+/// Status and warehouse of a stock unit
+async fn unit_location(app: &TestApp, stock_unit_id: Uuid) -> (String, Uuid) {
+    let unit = sqlx::query!(
+        "SELECT status, warehouse_id FROM stock_units WHERE id = $1",
+        stock_unit_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    (unit.status, unit.warehouse_id)
+}
+
+async fn post_dispatch(
+    app: &TestApp,
+    user_id: Uuid,
+    body: &serde_json::Value,
+) -> reqwest::Response {
+    app.api_client
+        .post(format!("{}/api/v1/dispatches", app.address))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .json(body)
+        .send()
+        .await
+        .unwrap()
+}
+
+/// Sends the units from the main warehouse to `destination`
+async fn seed_transfer(
+    app: &TestApp,
+    tenant: &TestTenant,
+    destination: Uuid,
+    unit_ids: &[Uuid],
+) -> Dispatch {
+    let mut units = Vec::new();
+    for unit_id in unit_ids {
+        units.push(unit_number(app, *unit_id).await);
+    }
+    post_dispatch(
+        app,
+        tenant.admin_id,
+        &json!({
+            "dispatch_type": "warehouse",
+            "dispatch_to_warehouse_id": destination,
+            "units": units,
+        }),
+    )
+    .await
+    .json()
+    .await
+    .unwrap()
+}
+
+async fn post_receive(
+    app: &TestApp,
+    user_id: Uuid,
+    dispatch_id: Uuid,
+    units: &[String],
+) -> reqwest::Response {
+    app.api_client
+        .post(format!(
+            "{}/api/v1/dispatches/{}/receive",
+            app.address, dispatch_id
+        ))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .json(&json!({ "units": units }))
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn post_receipt(app: &TestApp, user_id: Uuid, body: &serde_json::Value) -> reqwest::Response {
+    app.api_client
+        .post(format!("{}/api/v1/receipts", app.address))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .json(body)
+        .send()
+        .await
+        .unwrap()
+}
+
+/// A dyeing job work in progress with the vendor, expecting `product_id` back
+async fn seed_job_work(
+    app: &TestApp,
+    tenant: &TestTenant,
+    vendor_id: Uuid,
+    product_id: Uuid,
+) -> Uuid {
+    let job_work_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO job_works (company_id, warehouse_id, job_type, vendor_id, start_date, created_by)
+        VALUES ($1, $2, 'Dyeing', $3, CURRENT_DATE, $4)
+        RETURNING id
+        "#,
+        tenant.company_id,
+        tenant.warehouse_id,
+        vendor_id,
+        tenant.admin_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        r#"
+        INSERT INTO job_work_finished_goods (company_id, job_work_id, product_id, expected_quantity)
+        VALUES ($1, $2, $3, 100)
+        "#,
+        tenant.company_id,
+        job_work_id,
+        product_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    job_work_id
+}
+
+/// A sales order at 100 per unit and 10% discount, with `unit_ids`
+/// dispatched to its customer
+async fn seed_dispatched_order(
+    app: &TestApp,
+    tenant: &TestTenant,
+    product_id: Uuid,
+    unit_ids: &[Uuid],
+) -> (Uuid, Dispatch) {
+    let customer_id = app.seed_partner(tenant, "Customer").await;
+    let order_id = app
+        .seed_sales_order(
+            tenant,
+            customer_id,
+            chrono::Utc::now().date_naive(),
+            0,
+            0,
+            "in_progress",
+        )
+        .await;
+    sqlx::query!(
+        r#"
+        UPDATE sales_orders SET discount_percentage = 10 WHERE id = $1
+        "#,
+        order_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        r#"
+        INSERT INTO sales_order_items (company_id, sales_order_id, product_id, required_quantity, unit_rate)
+        VALUES ($1, $2, $3, 100, 100)
+        "#,
+        tenant.company_id,
+        order_id,
+        product_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    let mut units = Vec::new();
+    for unit_id in unit_ids {
+        units.push(unit_number(app, *unit_id).await);
+    }
+    let dispatch = post_dispatch(
+        app,
+        tenant.admin_id,
+        &json!({
+            "dispatch_type": "partner",
+            "dispatch_to_partner_id": customer_id,
+            "link_type": "sales_order",
+            "sales_order_id": order_id,
+            "units": units,
+        }),
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+    (order_id, dispatch)
+}
+
+async fn post_sales_return(
+    app: &TestApp,
+    user_id: Uuid,
+    sales_order_id: Uuid,
+    body: &serde_json::Value,
+) -> reqwest::Response {
+    app.api_client
+        .post(format!(
+            "{}/api/v1/sales-orders/{}/returns",
+            app.address, sales_order_id
+        ))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .json(body)
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn dispatched_quantity(app: &TestApp, sales_order_id: Uuid) -> Decimal {
+    sqlx::query_scalar!(
+        r#"SELECT dispatched_quantity as "dispatched_quantity!" FROM sales_order_items WHERE sales_order_id = $1"#,
+        sales_order_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap()
+}
+
+async fn patch_receipt(
+    app: &TestApp,
+    user_id: Uuid,
+    receipt_id: Uuid,
+    body: &serde_json::Value,
+) -> reqwest::Response {
+    app.api_client
+        .patch(format!("{}/api/v1/receipts/{}", app.address, receipt_id))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .json(body)
+        .send()
+        .await
+        .unwrap()
+}
+
+/// The only item of a receipt
+async fn receipt_item(app: &TestApp, receipt_id: Uuid) -> Uuid {
+    sqlx::query_scalar!(
+        "SELECT id FROM goods_receipt_items WHERE receipt_id = $1",
+        receipt_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap()
+}
+
+// CREATE
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn receipt_creates_a_unit_per_roll_with_its_details() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let supplier_id = app.seed_partner(&tenant, "Supplier").await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+
+    let res = post_receipt(
+        &app,
+        tenant.admin_id,
+        &json!({
+            "issued_by_partner_id": supplier_id,
+            "invoice_number": "SUP-881",
+            "items": [
+                {
+                    "product_id": product_id,
+                    "rolls": [
+                        { "size_quantity": "42.5", "quality_grade": "A", "location_description": "Rack 3" },
+                        { "quality_grade": "B" },
+                    ],
+                },
+                { "product_id": product_id, "quantity_received": 3 },
+            ],
+        }),
+    )
+    .await;
+    let status = res.status();
+    let receipt: Receipt = res.json().await.unwrap();
+    let detailed: Vec<_> = receipt
+        .units
+        .iter()
+        .filter(|u| u.status == "in_stock")
+        .collect();
+    let quantities = sqlx::query_scalar!(
+        "SELECT quantity_received FROM goods_receipt_items WHERE receipt_id = $1 ORDER BY quantity_received",
+        receipt.id
+    )
+    .fetch_all(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(StatusCode::CREATED, status);
+    assert_eq!(tenant.warehouse_id, receipt.warehouse_id);
+    assert_eq!(Some(supplier_id), receipt.issued_by_partner_id);
+    assert_eq!(5, receipt.units.len());
+    assert_eq!(1, detailed.len());
+    assert_eq!(Decimal::new(425, 1), detailed[0].size_quantity);
+    assert_eq!(Some("A".to_string()), detailed[0].quality_grade);
+    assert_eq!(Some("Rack 3".to_string()), detailed[0].location_description);
+    assert_eq!(vec![2, 3], quantities);
+}
+
+#[tokio::test]
+async fn job_work_receipt_counts_towards_the_finished_goods() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let vendor_id = app.seed_partner(&tenant, "Vendor").await;
+    let supplier_id = app.seed_partner(&tenant, "Supplier").await;
+    let dyed = app.seed_product(&tenant, 100, &[]).await;
+    let grey = app.seed_product(&tenant, 100, &[]).await;
+    let job_work_id = seed_job_work(&app, &tenant, vendor_id, dyed).await;
+    let body = |partner_id: Uuid, product_id: Uuid| {
+        json!({
+            "issued_by_partner_id": partner_id,
+            "link_type": "job_work",
+            "job_work_id": job_work_id,
+            "items": [{ "product_id": product_id, "rolls": [{ "size_quantity": 30 }, { "size_quantity": 25 }] }],
+        })
+    };
+
+    let wrong_partner = post_receipt(&app, tenant.admin_id, &body(supplier_id, dyed)).await;
+    let wrong_product = post_receipt(&app, tenant.admin_id, &body(vendor_id, grey)).await;
+    let received = post_receipt(&app, tenant.admin_id, &body(vendor_id, dyed)).await;
+    let received_quantity = sqlx::query_scalar!(
+        r#"SELECT received_quantity as "received_quantity!" FROM job_work_finished_goods WHERE job_work_id = $1"#,
+        job_work_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(StatusCode::BAD_REQUEST, wrong_partner.status());
+    assert_eq!(StatusCode::BAD_REQUEST, wrong_product.status());
+    assert_eq!(StatusCode::CREATED, received.status());
+    assert_eq!(Decimal::from(55), received_quantity);
+}
+
+#[tokio::test]
+async fn receipt_items_need_either_a_count_or_rolls() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let branch = app.seed_warehouse(&tenant, "Surat Branch").await;
+    let branch_staff = app.seed_staff(&tenant, branch).await;
+    let supplier_id = app.seed_partner(&tenant, "Supplier").await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let body =
+        |item: serde_json::Value| json!({ "issued_by_partner_id": supplier_id, "items": [item] });
+
+    let both = post_receipt(
+        &app,
+        tenant.admin_id,
+        &body(json!({ "product_id": product_id, "quantity_received": 2, "rolls": [{}] })),
+    )
+    .await;
+    let neither = post_receipt(
+        &app,
+        tenant.admin_id,
+        &body(json!({ "product_id": product_id })),
+    )
+    .await;
+    let zero_size = post_receipt(
+        &app,
+        tenant.admin_id,
+        &body(json!({ "product_id": product_id, "rolls": [{ "size_quantity": 0 }] })),
+    )
+    .await;
+    let other_warehouse = post_receipt(
+        &app,
+        branch_staff,
+        &json!({
+            "warehouse_id": tenant.warehouse_id,
+            "issued_by_partner_id": supplier_id,
+            "items": [{ "product_id": product_id, "quantity_received": 1 }],
+        }),
+    )
+    .await;
+
+    assert_eq!(StatusCode::BAD_REQUEST, both.status());
+    assert_eq!(StatusCode::BAD_REQUEST, neither.status());
+    assert_eq!(StatusCode::BAD_REQUEST, zero_size.status());
+    assert_eq!(StatusCode::FORBIDDEN, other_warehouse.status());
+}
+
+// TRANSFERS
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn received_transfer_moves_the_same_units_and_flags_missing_rolls() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let branch = app.seed_warehouse(&tenant, "Surat Branch").await;
+    let branch_staff = app.seed_staff(&tenant, branch).await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let first = app.seed_stock_unit(&tenant, product_id, 20).await;
+    let second = app.seed_stock_unit(&tenant, product_id, 15).await;
+    let lost = app.seed_stock_unit(&tenant, product_id, 10).await;
+    let transfer = seed_transfer(&app, &tenant, branch, &[first, second, lost]).await;
+    let in_transit = unit_location(&app, lost).await;
+    let arrived = vec![
+        unit_number(&app, first).await,
+        unit_number(&app, second).await,
+    ];
+
+    let res = post_receive(&app, branch_staff, transfer.id, &arrived).await;
+    let status = res.status();
+    let receipt: Receipt = res.json().await.unwrap();
+    let again = post_receive(&app, branch_staff, transfer.id, &arrived).await;
+    let units_in_company = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM stock_units WHERE company_id = $1"#,
+        tenant.company_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    let cancel = app
+        .api_client
+        .post(format!(
+            "{}/api/v1/dispatches/{}/cancel",
+            app.address, transfer.id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .json(&json!({ "reason": "Sent by mistake" }))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(("in_transit".to_string(), tenant.warehouse_id), in_transit);
+    assert_eq!(StatusCode::CREATED, status);
+    assert_eq!(branch, receipt.warehouse_id);
+    assert_eq!(Some(tenant.warehouse_id), receipt.issued_by_warehouse_id);
+    assert_eq!(Some(transfer.id), receipt.transfer_dispatch_id);
+    assert_eq!(2, receipt.units.len());
+    assert_eq!(
+        vec![lost],
+        receipt
+            .missing_units
+            .iter()
+            .map(|u| u.stock_unit_id)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        ("in_stock".to_string(), branch),
+        unit_location(&app, first).await
+    );
+    assert_eq!(
+        ("in_stock".to_string(), branch),
+        unit_location(&app, second).await
+    );
+    assert_eq!(in_transit, unit_location(&app, lost).await);
+    assert_eq!(3, units_in_company);
+    assert_eq!(StatusCode::CONFLICT, again.status());
+    assert_eq!(StatusCode::CONFLICT, cancel.status());
+}
+
+#[tokio::test]
+async fn only_the_destination_receives_a_transfer_of_its_own_units() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let branch = app.seed_warehouse(&tenant, "Surat Branch").await;
+    let origin_staff = app.seed_staff(&tenant, tenant.warehouse_id).await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let transferred = app.seed_stock_unit(&tenant, product_id, 20).await;
+    let sold = app.seed_stock_unit(&tenant, product_id, 20).await;
+    let other = app.seed_stock_unit(&tenant, product_id, 20).await;
+    let transfer = seed_transfer(&app, &tenant, branch, &[transferred]).await;
+    let transferred_number = unit_number(&app, transferred).await;
+    let sale: Dispatch = post_dispatch(
+        &app,
+        tenant.admin_id,
+        &json!({
+            "dispatch_type": "partner",
+            "dispatch_to_partner_id": customer_id,
+            "units": [unit_number(&app, sold).await],
+        }),
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+
+    let by_origin = post_receive(
+        &app,
+        origin_staff,
+        transfer.id,
+        std::slice::from_ref(&transferred_number),
+    )
+    .await;
+    let not_a_transfer = post_receive(
+        &app,
+        tenant.admin_id,
+        sale.id,
+        &[unit_number(&app, sold).await],
+    )
+    .await;
+    let not_on_transfer = post_receive(
+        &app,
+        tenant.admin_id,
+        transfer.id,
+        &[unit_number(&app, other).await],
+    )
+    .await;
+    let dispatched_in_transit = post_dispatch(
+        &app,
+        tenant.admin_id,
+        &json!({
+            "dispatch_type": "partner",
+            "dispatch_to_partner_id": customer_id,
+            "units": [transferred_number],
+        }),
+    )
+    .await;
+
+    assert_eq!(StatusCode::FORBIDDEN, by_origin.status());
+    assert_eq!(StatusCode::BAD_REQUEST, not_a_transfer.status());
+    assert_eq!(StatusCode::BAD_REQUEST, not_on_transfer.status());
+    assert_eq!(StatusCode::CONFLICT, dispatched_in_transit.status());
+}
+
+async fn post_missing_resolution(
+    app: &TestApp,
+    user_id: Uuid,
+    receipt_id: Uuid,
+    stock_unit_id: Uuid,
+    resolution: &str,
+) -> reqwest::Response {
+    app.api_client
+        .post(format!(
+            "{}/api/v1/receipts/{}/missing-units",
+            app.address, receipt_id
+        ))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .json(&json!({
+            "units": [unit_number(app, stock_unit_id).await],
+            "resolution": resolution,
+        }))
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn missing_transfer_units_are_found_returned_or_written_off() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let branch = app.seed_warehouse(&tenant, "Surat Branch").await;
+    let elsewhere = app.seed_warehouse(&tenant, "Vapi Godown").await;
+    let branch_staff = app.seed_staff(&tenant, branch).await;
+    let origin_staff = app.seed_staff(&tenant, tenant.warehouse_id).await;
+    let other_staff = app.seed_staff(&tenant, elsewhere).await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let arrived = app.seed_stock_unit(&tenant, product_id, 20).await;
+    let late = app.seed_stock_unit(&tenant, product_id, 15).await;
+    let left_behind = app.seed_stock_unit(&tenant, product_id, 12).await;
+    let lost = app.seed_stock_unit(&tenant, product_id, 10).await;
+    let transfer = seed_transfer(&app, &tenant, branch, &[arrived, late, left_behind, lost]).await;
+    let receipt: Receipt = post_receive(
+        &app,
+        branch_staff,
+        transfer.id,
+        &[unit_number(&app, arrived).await],
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+
+    let by_other = post_missing_resolution(&app, other_staff, receipt.id, late, "found").await;
+    let found = post_missing_resolution(&app, branch_staff, receipt.id, late, "found").await;
+    let found_again = post_missing_resolution(&app, branch_staff, receipt.id, late, "found").await;
+    let returned =
+        post_missing_resolution(&app, origin_staff, receipt.id, left_behind, "returned").await;
+    let res = post_missing_resolution(&app, tenant.admin_id, receipt.id, lost, "written_off").await;
+    let status = res.status();
+    let settled: Receipt = res.json().await.unwrap();
+
+    assert_eq!(StatusCode::FORBIDDEN, by_other.status());
+    assert_eq!(StatusCode::OK, found.status());
+    assert_eq!(StatusCode::BAD_REQUEST, found_again.status());
+    assert_eq!(StatusCode::OK, returned.status());
+    assert_eq!(StatusCode::OK, status);
+    assert!(settled.missing_units.is_empty());
+    // A unit found later counts as received; returned and written-off ones do not
+    assert_eq!(2, settled.units.len());
+    assert!(settled.units.iter().any(|u| u.stock_unit_id == late));
+    assert_eq!(
+        ("in_stock".to_string(), branch),
+        unit_location(&app, late).await
+    );
+    assert_eq!(
+        ("in_stock".to_string(), tenant.warehouse_id),
+        unit_location(&app, left_behind).await
+    );
+    assert_eq!("removed", unit_location(&app, lost).await.0);
+}
+
+// UPDATE
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn changing_a_received_quantity_adds_or_removes_pending_units() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let supplier_id = app.seed_partner(&tenant, "Supplier").await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let receipt: Receipt = post_receipt(
+        &app,
+        tenant.admin_id,
+        &json!({
+            "issued_by_partner_id": supplier_id,
+            "items": [{ "product_id": product_id, "quantity_received": 3 }],
+        }),
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+    let item_id = receipt_item(&app, receipt.id).await;
+
+    let res = patch_receipt(
+        &app,
+        tenant.admin_id,
+        receipt.id,
+        &json!({ "invoice_number": "SUP-902", "items": [{ "item_id": item_id, "quantity_received": 5 }] }),
+    )
+    .await;
+    let status = res.status();
+    let increased: Receipt = res.json().await.unwrap();
+    let decreased: Receipt = patch_receipt(
+        &app,
+        tenant.admin_id,
+        receipt.id,
+        &json!({
+            "items": [{ "item_id": item_id, "quantity_received": 2 }],
+            "reason": "Counted twice at the gate",
+        }),
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+    let adjustments: Vec<ReceiptAdjustment> = app
+        .api_client
+        .get(format!(
+            "{}/api/v1/receipts/{}/adjustments",
+            app.address, receipt.id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(StatusCode::OK, status);
+    assert_eq!(Some("SUP-902".to_string()), increased.invoice_number);
+    assert_eq!(5, increased.units.len());
+    assert_eq!(2, decreased.units.len());
+    assert_eq!(2, adjustments.len());
+    assert_eq!(
+        (3, 5),
+        (adjustments[0].from_quantity, adjustments[0].to_quantity)
+    );
+    assert_eq!(2, adjustments[0].added_unit_ids.len());
+    assert_eq!(3, adjustments[1].removed_unit_ids.len());
+    assert_eq!(
+        Some("Counted twice at the gate".to_string()),
+        adjustments[1].reason
+    );
+    for unit_id in &adjustments[1].removed_unit_ids {
+        assert_eq!("removed", unit_location(&app, *unit_id).await.0);
+    }
+}
+
+#[tokio::test]
+async fn units_already_in_stock_are_never_removed_by_an_edit() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let supplier_id = app.seed_partner(&tenant, "Supplier").await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let receipt: Receipt = post_receipt(
+        &app,
+        tenant.admin_id,
+        &json!({
+            "issued_by_partner_id": supplier_id,
+            "items": [{
+                "product_id": product_id,
+                "rolls": [{ "size_quantity": "20" }, { "size_quantity": "25" }, {}],
+            }],
+        }),
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+    let item_id = receipt_item(&app, receipt.id).await;
+    let branch = app.seed_warehouse(&tenant, "Surat Branch").await;
+    let branch_staff = app.seed_staff(&tenant, branch).await;
+
+    let too_few = patch_receipt(
+        &app,
+        tenant.admin_id,
+        receipt.id,
+        &json!({ "items": [{ "item_id": item_id, "quantity_received": 1 }] }),
+    )
+    .await;
+    let other_warehouse = patch_receipt(
+        &app,
+        branch_staff,
+        receipt.id,
+        &json!({ "notes": "Checked" }),
+    )
+    .await;
+    let quantity = sqlx::query_scalar!(
+        "SELECT quantity_received FROM goods_receipt_items WHERE id = $1",
+        item_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    let pending_only = patch_receipt(
+        &app,
+        tenant.admin_id,
+        receipt.id,
+        &json!({ "items": [{ "item_id": item_id, "quantity_received": 2 }] }),
+    )
+    .await;
+    let remaining: Receipt = pending_only.json().await.unwrap();
+
+    assert_eq!(StatusCode::CONFLICT, too_few.status());
+    assert_eq!(StatusCode::FORBIDDEN, other_warehouse.status());
+    assert_eq!(3, quantity);
+    assert!(remaining.units.iter().all(|u| u.status == "in_stock"));
+    assert_eq!(2, remaining.units.len());
+}
+
+// RETURNS
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn returned_units_come_back_into_stock_with_a_credit_note() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let kept = app.seed_stock_unit(&tenant, product_id, 20).await;
+    let returned = app.seed_stock_unit(&tenant, product_id, 15).await;
+    let damaged = app.seed_stock_unit(&tenant, product_id, 5).await;
+    let (order_id, dispatch) =
+        seed_dispatched_order(&app, &tenant, product_id, &[kept, returned, damaged]).await;
+
+    let res = post_sales_return(
+        &app,
+        tenant.admin_id,
+        order_id,
+        &json!({ "units": [unit_number(&app, returned).await] }),
+    )
+    .await;
+    let status = res.status();
+    let sales_return: SalesReturn = res.json().await.unwrap();
+    let quarantined = post_sales_return(
+        &app,
+        tenant.admin_id,
+        order_id,
+        &json!({ "units": [unit_number(&app, damaged).await], "quarantine": true }),
+    )
+    .await;
+
+    assert_eq!(StatusCode::CREATED, status);
+    assert_eq!(Some(order_id), sales_return.receipt.sales_order_id);
+    assert_eq!(
+        vec![returned],
+        sales_return
+            .receipt
+            .units
+            .iter()
+            .map(|u| u.stock_unit_id)
+            .collect::<Vec<_>>()
+    );
+    assert!(sales_return
+        .credit_note
+        .credit_note_number
+        .starts_with("CN"));
+    assert_eq!(dispatch.id, sales_return.credit_note.lines[0].dispatch_id);
+    assert_eq!(
+        Decimal::from(1500),
+        sales_return.credit_note.lines[0].amount
+    );
+    assert_eq!(Decimal::from(1350), sales_return.credit_note.amount);
+    assert_eq!(StatusCode::CREATED, quarantined.status());
+    assert_eq!(
+        ("in_stock".to_string(), tenant.warehouse_id),
+        unit_location(&app, returned).await
+    );
+    assert_eq!("quarantined", unit_location(&app, damaged).await.0);
+    assert_eq!("dispatched", unit_location(&app, kept).await.0);
+    assert_eq!(Decimal::from(20), dispatched_quantity(&app, order_id).await);
+}
+
+#[tokio::test]
+async fn credit_note_prices_units_like_the_invoice_and_commission() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let returned = app.seed_stock_unit(&tenant, product_id, 10).await;
+    let (order_id, _) = seed_dispatched_order(&app, &tenant, product_id, &[returned]).await;
+    // A later line for the same product at a higher rate
+    sqlx::query!(
+        r#"
+        INSERT INTO sales_order_items (company_id, sales_order_id, product_id, required_quantity, unit_rate)
+        VALUES ($1, $2, $3, 10, 120)
+        "#,
+        tenant.company_id,
+        order_id,
+        product_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    let order_rate = sqlx::query_scalar!(
+        r#"SELECT order_unit_rate($1, $2) as "rate!""#,
+        order_id,
+        product_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+
+    let sales_return: SalesReturn = post_sales_return(
+        &app,
+        tenant.admin_id,
+        order_id,
+        &json!({ "units": [unit_number(&app, returned).await] }),
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+
+    assert_eq!(Decimal::from(120), order_rate);
+    assert_eq!(order_rate, sales_return.credit_note.lines[0].unit_rate);
+}
+
+#[tokio::test]
+async fn only_units_out_with_the_customer_on_the_order_can_be_returned() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let dispatched = app.seed_stock_unit(&tenant, product_id, 20).await;
+    let never_sent = app.seed_stock_unit(&tenant, product_id, 20).await;
+    let (order_id, dispatch) =
+        seed_dispatched_order(&app, &tenant, product_id, &[dispatched]).await;
+    let dispatched_number = unit_number(&app, dispatched).await;
+
+    let not_dispatched = post_sales_return(
+        &app,
+        tenant.admin_id,
+        order_id,
+        &json!({ "units": [unit_number(&app, never_sent).await] }),
+    )
+    .await;
+    let first = post_sales_return(
+        &app,
+        tenant.admin_id,
+        order_id,
+        &json!({ "units": [&dispatched_number] }),
+    )
+    .await;
+    let again = post_sales_return(
+        &app,
+        tenant.admin_id,
+        order_id,
+        &json!({ "units": [&dispatched_number] }),
+    )
+    .await;
+    let cancel = app
+        .api_client
+        .post(format!(
+            "{}/api/v1/dispatches/{}/cancel",
+            app.address, dispatch.id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .json(&json!({ "reason": "Wrong customer" }))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(StatusCode::BAD_REQUEST, not_dispatched.status());
+    assert_eq!(StatusCode::CREATED, first.status());
+    assert_eq!(StatusCode::BAD_REQUEST, again.status());
+    // Returned units have moved on from the dispatch
+    assert_eq!(StatusCode::CONFLICT, cancel.status());
+}
@@ -0,0 +1,961 @@
+use bale_backend::{
+    auth::USER_ID_HEADER,
+    routes::sales_orders::{
+        DispatchedQuantityError, SalesOrderDetail, SalesOrderPage, SalesOrderSummary, StatusChange,
+    },
+};
+use reqwest::StatusCode;
+use rust_decimal::Decimal;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::test_app::{TestApp, TestTenant};
+
+// HELPERS
+// -------------------------------------------------------------------------------------
+
+async fn post_sales_order(
+    app: &TestApp,
+    user_id: Uuid,
+    body: &serde_json::Value,
+) -> reqwest::Response {
+    app.api_client
+        .post(format!("{}/api/v1/sales-orders", app.address))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .json(body)
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn count_orders(app: &TestApp, tenant: &TestTenant) -> i64 {
+    sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM sales_orders WHERE company_id = $1"#,
+        tenant.company_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap()
+}
+
+// CREATE
+// -------------------------------------------------------------------------------------
+
+#[tokio::test]
+async fn create_sales_order_returns_computed_totals() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let agent_id = app.seed_partner(&tenant, "Agent").await;
+    let poplin_id = app.seed_product(&tenant, 150, &[]).await;
+    let silk_id = app.seed_product(&tenant, 900, &[]).await;
+
+    let body = serde_json::json!({
+        "customer_id": customer_id,
+        "agent_id": agent_id,
+        "discount_percentage": "10",
+        "advance_amount": "1000",
+        "items": [
+            { "product_id": poplin_id, "required_quantity": "20" },
+            { "product_id": silk_id, "required_quantity": "5", "unit_rate": "800" },
+        ],
+    });
+    let res = post_sales_order(&app, tenant.admin_id, &body).await;
+    let status = res.status();
+    let order: SalesOrderSummary = res.json().await.unwrap();
+    let poplin = order
+        .items
+        .iter()
+        .find(|i| i.product_id == poplin_id)
+        .unwrap();
+
+    assert_eq!(StatusCode::CREATED, status);
+    assert!(order.order_number.starts_with("SO-"));
+    assert_eq!("approval_pending", order.status);
+    assert_eq!(Some(tenant.warehouse_id), order.fulfillment_warehouse_id);
+    assert_eq!(Decimal::from(150), poplin.unit_rate);
+    assert_eq!(Decimal::from(7_000), order.subtotal);
+    assert_eq!(Decimal::from(700), order.discount_amount);
+    assert_eq!(Decimal::from(6_300), order.total_amount);
+    assert_eq!(Decimal::from(5_300), order.balance_due);
+}
+
+#[tokio::test]
+async fn create_sales_order_rejects_non_customer_partner() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let supplier_id = app.seed_partner(&tenant, "Supplier").await;
+    let product_id = app.seed_product(&tenant, 150, &[]).await;
+
+    let body = serde_json::json!({
+        "customer_id": supplier_id,
+        "items": [{ "product_id": product_id, "required_quantity": "1" }],
+    });
+    let res = post_sales_order(&app, tenant.admin_id, &body).await;
+
+    assert_eq!(StatusCode::BAD_REQUEST, res.status());
+}
+
+#[tokio::test]
+async fn create_sales_order_rejects_agent_that_is_not_an_agent() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let other_customer_id = app.seed_partner(&tenant, "Customer").await;
+    let product_id = app.seed_product(&tenant, 150, &[]).await;
+
+    let body = serde_json::json!({
+        "customer_id": customer_id,
+        "agent_id": other_customer_id,
+        "items": [{ "product_id": product_id, "required_quantity": "1" }],
+    });
+    let res = post_sales_order(&app, tenant.admin_id, &body).await;
+
+    assert_eq!(StatusCode::BAD_REQUEST, res.status());
+}
+
+#[tokio::test]
+async fn create_sales_order_rolls_back_when_advance_exceeds_total() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+
+    let body = serde_json::json!({
+        "customer_id": customer_id,
+        "advance_amount": "5000",
+        "items": [{ "product_id": product_id, "required_quantity": "10" }],
+    });
+    let res = post_sales_order(&app, tenant.admin_id, &body).await;
+
+    assert_eq!(StatusCode::BAD_REQUEST, res.status());
+    assert_eq!(0, count_orders(&app, &tenant).await);
+}
+
+#[tokio::test]
+async fn create_sales_order_rejects_product_of_another_company() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let other_tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let foreign_product_id = app.seed_product(&other_tenant, 100, &[]).await;
+
+    let body = serde_json::json!({
+        "customer_id": customer_id,
+        "items": [{ "product_id": foreign_product_id, "required_quantity": "1" }],
+    });
+    let res = post_sales_order(&app, tenant.admin_id, &body).await;
+
+    assert_eq!(StatusCode::BAD_REQUEST, res.status());
+    assert_eq!(0, count_orders(&app, &tenant).await);
+}
+
+#[tokio::test]
+async fn create_sales_order_is_forbidden_for_staff() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let staff_id = app.seed_staff(&tenant, tenant.warehouse_id).await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+
+    let body = serde_json::json!({
+        "customer_id": customer_id,
+        "items": [{ "product_id": product_id, "required_quantity": "1" }],
+    });
+    let res = post_sales_order(&app, staff_id, &body).await;
+
+    assert_eq!(StatusCode::FORBIDDEN, res.status());
+}
+
+// STATUS WORKFLOW
+// -------------------------------------------------------------------------------------
+
+async fn create_order(app: &TestApp, tenant: &TestTenant) -> (SalesOrderSummary, Uuid) {
+    let customer_id = app.seed_partner(tenant, "Customer").await;
+    let product_id = app.seed_product(tenant, 100, &[]).await;
+    let body = serde_json::json!({
+        "customer_id": customer_id,
+        "items": [{ "product_id": product_id, "required_quantity": "10" }],
+    });
+    let order = post_sales_order(app, tenant.admin_id, &body)
+        .await
+        .json()
+        .await
+        .unwrap();
+    (order, product_id)
+}
+
+async fn transition(
+    app: &TestApp,
+    user_id: Uuid,
+    order_id: Uuid,
+    action: &str,
+    body: serde_json::Value,
+) -> reqwest::Response {
+    app.api_client
+        .post(format!(
+            "{}/api/v1/sales-orders/{}/{}",
+            app.address, order_id, action
+        ))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .json(&body)
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn get_history(app: &TestApp, tenant: &TestTenant, order_id: Uuid) -> Vec<StatusChange> {
+    app.api_client
+        .get(format!(
+            "{}/api/v1/sales-orders/{}/status-history",
+            app.address, order_id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+async fn order_status(app: &TestApp, order_id: Uuid) -> String {
+    sqlx::query_scalar!("SELECT status FROM sales_orders WHERE id = $1", order_id)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn approving_twice_returns_conflict() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let (order, _) = create_order(&app, &tenant).await;
+
+    let first = transition(&app, tenant.admin_id, order.id, "approve", json!({})).await;
+    let second = transition(&app, tenant.admin_id, order.id, "approve", json!({})).await;
+
+    assert_eq!(StatusCode::NO_CONTENT, first.status());
+    assert_eq!(StatusCode::CONFLICT, second.status());
+    assert_eq!("in_progress", order_status(&app, order.id).await);
+}
+
+#[tokio::test]
+async fn completion_requires_notes_and_full_dispatch_unless_forced() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let (order, _) = create_order(&app, &tenant).await;
+    transition(&app, tenant.admin_id, order.id, "approve", json!({})).await;
+
+    let without_notes = transition(
+        &app,
+        tenant.admin_id,
+        order.id,
+        "complete",
+        json!({ "notes": " " }),
+    )
+    .await;
+    let partial = transition(
+        &app,
+        tenant.admin_id,
+        order.id,
+        "complete",
+        json!({ "notes": "Delivered" }),
+    )
+    .await;
+    let forced = transition(
+        &app,
+        tenant.admin_id,
+        order.id,
+        "complete",
+        json!({ "notes": "Customer accepted short delivery", "force": true }),
+    )
+    .await;
+    let history = get_history(&app, &tenant, order.id).await;
+
+    assert_eq!(StatusCode::BAD_REQUEST, without_notes.status());
+    assert_eq!(StatusCode::CONFLICT, partial.status());
+    assert_eq!(StatusCode::NO_CONTENT, forced.status());
+    assert_eq!("completed", order_status(&app, order.id).await);
+    assert!(history.last().unwrap().is_override);
+}
+
+#[tokio::test]
+async fn fully_dispatched_order_completes_without_override() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let (order, _) = create_order(&app, &tenant).await;
+    transition(&app, tenant.admin_id, order.id, "approve", json!({})).await;
+    sqlx::query!(
+        "UPDATE sales_order_items SET dispatched_quantity = required_quantity WHERE sales_order_id = $1",
+        order.id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let res = transition(
+        &app,
+        tenant.admin_id,
+        order.id,
+        "complete",
+        json!({ "notes": "All rolls delivered" }),
+    )
+    .await;
+    let history = get_history(&app, &tenant, order.id).await;
+
+    assert_eq!(StatusCode::NO_CONTENT, res.status());
+    assert!(!history.last().unwrap().is_override);
+}
+
+#[tokio::test]
+async fn cancelling_with_active_dispatches_requires_cancelling_them() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let (order, product_id) = create_order(&app, &tenant).await;
+    transition(&app, tenant.admin_id, order.id, "approve", json!({})).await;
+    let unit_id = app.seed_stock_unit(&tenant, product_id, 4).await;
+    sqlx::query!(
+        "UPDATE stock_units SET status = 'dispatched' WHERE id = $1",
+        unit_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    let dispatch_id = app
+        .seed_dispatch(&tenant, order.customer_id, None, Some(order.id), &[unit_id])
+        .await;
+
+    let refused = transition(
+        &app,
+        tenant.admin_id,
+        order.id,
+        "cancel",
+        json!({ "reason": "Customer backed out" }),
+    )
+    .await;
+    let cancelled = transition(
+        &app,
+        tenant.admin_id,
+        order.id,
+        "cancel",
+        json!({ "reason": "Customer backed out", "cancel_dispatches": true }),
+    )
+    .await;
+
+    let dispatch_cancelled = sqlx::query_scalar!(
+        r#"SELECT is_cancelled as "is_cancelled!" FROM goods_dispatches WHERE id = $1"#,
+        dispatch_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    let unit_status = sqlx::query_scalar!("SELECT status FROM stock_units WHERE id = $1", unit_id)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+
+    assert_eq!(StatusCode::CONFLICT, refused.status());
+    assert_eq!(StatusCode::NO_CONTENT, cancelled.status());
+    assert_eq!("cancelled", order_status(&app, order.id).await);
+    assert!(dispatch_cancelled);
+    assert_eq!("in_stock", unit_status);
+}
+
+#[tokio::test]
+async fn every_transition_is_kept_in_status_history() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let (order, _) = create_order(&app, &tenant).await;
+
+    transition(&app, tenant.admin_id, order.id, "approve", json!({})).await;
+    transition(
+        &app,
+        tenant.admin_id,
+        order.id,
+        "cancel",
+        json!({ "reason": "Duplicate order" }),
+    )
+    .await;
+    let reopened = transition(
+        &app,
+        tenant.admin_id,
+        order.id,
+        "reopen",
+        json!({ "reason": "Not a duplicate after all" }),
+    )
+    .await;
+    let history = get_history(&app, &tenant, order.id).await;
+    let steps: Vec<(Option<&str>, &str)> = history
+        .iter()
+        .map(|h| (h.from_status.as_deref(), h.to_status.as_str()))
+        .collect();
+
+    assert_eq!(StatusCode::NO_CONTENT, reopened.status());
+    assert_eq!(
+        steps,
+        vec![
+            (None, "approval_pending"),
+            (Some("approval_pending"), "in_progress"),
+            (Some("in_progress"), "cancelled"),
+            (Some("cancelled"), "approval_pending"),
+        ]
+    );
+    assert_eq!(Some("Duplicate order"), history[2].notes.as_deref());
+}
+
+#[tokio::test]
+async fn status_transitions_are_forbidden_for_staff() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let staff_id = app.seed_staff(&tenant, tenant.warehouse_id).await;
+    let (order, _) = create_order(&app, &tenant).await;
+
+    let res = transition(&app, staff_id, order.id, "approve", json!({})).await;
+
+    assert_eq!(StatusCode::FORBIDDEN, res.status());
+}
+
+// LINE ITEMS
+// -------------------------------------------------------------------------------------
+
+async fn edit_item(
+    app: &TestApp,
+    tenant: &TestTenant,
+    method: reqwest::Method,
+    path: String,
+    body: Option<serde_json::Value>,
+) -> reqwest::Response {
+    let mut request = app
+        .api_client
+        .request(
+            method,
+            format!("{}/api/v1/sales-orders/{}", app.address, path),
+        )
+        .header(USER_ID_HEADER, tenant.admin_id.to_string());
+    if let Some(body) = body {
+        request = request.json(&body);
+    }
+    request.send().await.unwrap()
+}
+
+async fn mark_dispatched(app: &TestApp, item_id: Uuid, quantity: i64) {
+    sqlx::query!(
+        "UPDATE sales_order_items SET dispatched_quantity = $2 WHERE id = $1",
+        item_id,
+        Decimal::from(quantity)
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn editing_items_keeps_discounted_total_consistent() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let other_product_id = app.seed_product(&tenant, 50, &[]).await;
+    let body = json!({
+        "customer_id": customer_id,
+        "discount_percentage": "20",
+        "items": [{ "product_id": product_id, "required_quantity": "10" }],
+    });
+    let order: SalesOrderSummary = post_sales_order(&app, tenant.admin_id, &body)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let item_id = order.items[0].id;
+
+    let updated: SalesOrderSummary = edit_item(
+        &app,
+        &tenant,
+        reqwest::Method::PATCH,
+        format!("{}/items/{}", order.id, item_id),
+        Some(json!({ "required_quantity": "15", "unit_rate": "120" })),
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+    let added = edit_item(
+        &app,
+        &tenant,
+        reqwest::Method::POST,
+        format!("{}/items", order.id),
+        Some(json!({ "product_id": other_product_id, "required_quantity": "20" })),
+    )
+    .await;
+    let added_status = added.status();
+    let added: SalesOrderSummary = added.json().await.unwrap();
+
+    assert_eq!(Decimal::from(1_800), updated.subtotal);
+    assert_eq!(Decimal::from(1_440), updated.total_amount);
+    assert_eq!(StatusCode::CREATED, added_status);
+    assert_eq!(2, added.items.len());
+    assert_eq!(Decimal::from(2_240), added.total_amount);
+}
+
+#[tokio::test]
+async fn reducing_quantity_below_dispatched_returns_hint() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let (order, _) = create_order(&app, &tenant).await;
+    let item_id = order.items[0].id;
+    mark_dispatched(&app, item_id, 6).await;
+
+    let res = edit_item(
+        &app,
+        &tenant,
+        reqwest::Method::PATCH,
+        format!("{}/items/{}", order.id, item_id),
+        Some(json!({ "required_quantity": "5" })),
+    )
+    .await;
+    let status = res.status();
+    let error: DispatchedQuantityError = res.json().await.unwrap();
+
+    assert_eq!(StatusCode::CONFLICT, status);
+    assert!(error.error.contains("below dispatched quantity"));
+    assert!(error.hint.contains("Cancel existing dispatches"));
+}
+
+#[tokio::test]
+async fn removing_a_dispatched_item_is_blocked() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let (order, _) = create_order(&app, &tenant).await;
+    let other_product_id = app.seed_product(&tenant, 80, &[]).await;
+    edit_item(
+        &app,
+        &tenant,
+        reqwest::Method::POST,
+        format!("{}/items", order.id),
+        Some(json!({ "product_id": other_product_id, "required_quantity": "3" })),
+    )
+    .await;
+    let item_id = order.items[0].id;
+    mark_dispatched(&app, item_id, 2).await;
+
+    let res = edit_item(
+        &app,
+        &tenant,
+        reqwest::Method::DELETE,
+        format!("{}/items/{}", order.id, item_id),
+        None,
+    )
+    .await;
+    let status = res.status();
+    let error: DispatchedQuantityError = res.json().await.unwrap();
+
+    assert_eq!(StatusCode::CONFLICT, status);
+    assert!(error.hint.contains("Cancel existing dispatches"));
+}
+
+#[tokio::test]
+async fn items_of_completed_orders_cannot_be_changed() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let (order, _) = create_order(&app, &tenant).await;
+    transition(&app, tenant.admin_id, order.id, "approve", json!({})).await;
+    transition(
+        &app,
+        tenant.admin_id,
+        order.id,
+        "complete",
+        json!({ "notes": "Closed", "force": true }),
+    )
+    .await;
+
+    let res = edit_item(
+        &app,
+        &tenant,
+        reqwest::Method::PATCH,
+        format!("{}/items/{}", order.id, order.items[0].id),
+        Some(json!({ "required_quantity": "12" })),
+    )
+    .await;
+
+    assert_eq!(StatusCode::CONFLICT, res.status());
+}
+
+// FULFILLMENT DETAIL
+// -------------------------------------------------------------------------------------
+
+async fn get_detail(app: &TestApp, user_id: Uuid, order_id: Uuid) -> reqwest::Response {
+    app.api_client
+        .get(format!("{}/api/v1/sales-orders/{}", app.address, order_id))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn detail_combines_progress_dispatches_and_job_works() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let (order, product_id) = create_order(&app, &tenant).await;
+    transition(&app, tenant.admin_id, order.id, "approve", json!({})).await;
+    let first_unit = app.seed_stock_unit(&tenant, product_id, 4).await;
+    let second_unit = app.seed_stock_unit(&tenant, product_id, 2).await;
+    let first = app
+        .seed_dispatch(
+            &tenant,
+            order.customer_id,
+            None,
+            Some(order.id),
+            &[first_unit],
+        )
+        .await;
+    let second = app
+        .seed_dispatch(
+            &tenant,
+            order.customer_id,
+            None,
+            Some(order.id),
+            &[second_unit],
+        )
+        .await;
+    // A dispatch never flagged either way is still active
+    sqlx::query!(
+        "UPDATE goods_dispatches SET is_cancelled = NULL WHERE id = $1",
+        second
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    mark_dispatched(&app, order.items[0].id, 6).await;
+    let vendor_id = app.seed_partner(&tenant, "Vendor").await;
+    sqlx::query!(
+        r#"
+        INSERT INTO job_works (company_id, warehouse_id, job_type, vendor_id, start_date,
+                               sales_order_id, created_by)
+        VALUES ($1, $2, 'Dyeing', $3, CURRENT_DATE, $4, $5)
+        "#,
+        tenant.company_id,
+        tenant.warehouse_id,
+        vendor_id,
+        order.id,
+        tenant.admin_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let res = get_detail(&app, tenant.admin_id, order.id).await;
+    let status = res.status();
+    let detail: SalesOrderDetail = res.json().await.unwrap();
+    let first_dispatch = detail.dispatches.iter().find(|d| d.id == first).unwrap();
+
+    assert_eq!(StatusCode::OK, status);
+    assert_eq!(order.id, detail.order.id);
+    assert_eq!(Decimal::from(4), detail.order.items[0].pending_quantity);
+    // Quantities are counted once however many dispatches and job works link to the order
+    assert_eq!(
+        Decimal::from(10),
+        detail.fulfillment.total_required_quantity
+    );
+    assert_eq!(
+        Decimal::from(6),
+        detail.fulfillment.total_dispatched_quantity
+    );
+    assert_eq!(Decimal::from(60), detail.fulfillment.completion_percentage);
+    assert_eq!(2, detail.fulfillment.active_dispatches);
+    assert_eq!(2, detail.dispatches.len());
+    assert_eq!(1, first_dispatch.units.len());
+    assert_eq!(first_unit, first_dispatch.units[0].stock_unit_id);
+    assert_eq!(Decimal::from(4), first_dispatch.units[0].size_quantity);
+    assert_eq!(1, detail.job_works.len());
+    assert_eq!("Dyeing", detail.job_works[0].job_type);
+}
+
+#[tokio::test]
+async fn staff_only_see_orders_fulfilled_from_their_warehouse() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let (order, _) = create_order(&app, &tenant).await;
+    let other_warehouse = app.seed_warehouse(&tenant, "Surat Branch").await;
+    let local_staff = app.seed_staff(&tenant, tenant.warehouse_id).await;
+    let other_staff = app.seed_staff(&tenant, other_warehouse).await;
+
+    let local = get_detail(&app, local_staff, order.id).await;
+    let other = get_detail(&app, other_staff, order.id).await;
+
+    assert_eq!(StatusCode::OK, local.status());
+    assert_eq!(StatusCode::NOT_FOUND, other.status());
+}
+
+#[tokio::test]
+async fn balance_due_deducts_payments_until_they_are_reversed() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let (order, _) = create_order(&app, &tenant).await;
+    transition(&app, tenant.admin_id, order.id, "approve", json!({})).await;
+    let payment_id: Uuid = app
+        .api_client
+        .post(format!("{}/api/v1/payments", app.address))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .json(&json!({
+            "partner_id": order.customer_id,
+            "amount": "400",
+            "payment_mode": "upi",
+            "allocations": [{ "sales_order_id": order.id, "amount": "400" }],
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    let paid: SalesOrderDetail = get_detail(&app, tenant.admin_id, order.id)
+        .await
+        .json()
+        .await
+        .unwrap();
+    app.api_client
+        .post(format!(
+            "{}/api/v1/payments/{}/reverse",
+            app.address, payment_id
+        ))
+        .header(USER_ID_HEADER, tenant.admin_id.to_string())
+        .json(&json!({ "reason": "Duplicate entry" }))
+        .send()
+        .await
+        .unwrap();
+    let reversed: SalesOrderDetail = get_detail(&app, tenant.admin_id, order.id)
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(Decimal::from(400), paid.order.paid_amount);
+    assert_eq!(
+        order.total_amount - Decimal::from(400),
+        paid.order.balance_due
+    );
+    assert_eq!(Decimal::ZERO, reversed.order.paid_amount);
+    assert_eq!(order.total_amount, reversed.order.balance_due);
+}
+
+// ORDER LIST
+// -------------------------------------------------------------------------------------
+
+async fn create_dated_order(
+    app: &TestApp,
+    tenant: &TestTenant,
+    customer_id: Uuid,
+    product_id: Uuid,
+    order_date: &str,
+    warehouse_id: Option<Uuid>,
+) -> Uuid {
+    let order: SalesOrderSummary = post_sales_order(
+        app,
+        tenant.admin_id,
+        &json!({
+            "customer_id": customer_id,
+            "order_date": order_date,
+            "fulfillment_warehouse_id": warehouse_id,
+            "items": [{ "product_id": product_id, "required_quantity": "10" }],
+        }),
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+    order.id
+}
+
+async fn list_orders(app: &TestApp, user_id: Uuid, query: &str) -> reqwest::Response {
+    app.api_client
+        .get(format!("{}/api/v1/sales-orders?{}", app.address, query))
+        .header(USER_ID_HEADER, user_id.to_string())
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn list_order_ids(app: &TestApp, user_id: Uuid, query: &str) -> Vec<Uuid> {
+    let page: SalesOrderPage = list_orders(app, user_id, query).await.json().await.unwrap();
+    page.orders.iter().map(|o| o.id).collect()
+}
+
+#[tokio::test]
+async fn order_list_pages_newest_first_within_the_current_warehouse() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let other_warehouse = app.seed_warehouse(&tenant, "Surat Branch").await;
+    let oldest =
+        create_dated_order(&app, &tenant, customer_id, product_id, "2026-01-05", None).await;
+    let middle =
+        create_dated_order(&app, &tenant, customer_id, product_id, "2026-02-05", None).await;
+    let newest =
+        create_dated_order(&app, &tenant, customer_id, product_id, "2026-03-05", None).await;
+    let elsewhere = create_dated_order(
+        &app,
+        &tenant,
+        customer_id,
+        product_id,
+        "2026-03-10",
+        Some(other_warehouse),
+    )
+    .await;
+
+    let first: SalesOrderPage = list_orders(&app, tenant.admin_id, "limit=2")
+        .await
+        .json()
+        .await
+        .unwrap();
+    let cursor = first.next_cursor.clone().unwrap();
+    let second: SalesOrderPage =
+        list_orders(&app, tenant.admin_id, &format!("limit=2&cursor={cursor}"))
+            .await
+            .json()
+            .await
+            .unwrap();
+    let other = list_order_ids(
+        &app,
+        tenant.admin_id,
+        &format!("warehouse_id={other_warehouse}"),
+    )
+    .await;
+    let ascending = list_order_ids(&app, tenant.admin_id, "order=asc").await;
+
+    assert_eq!(
+        vec![newest, middle],
+        first.orders.iter().map(|o| o.id).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![oldest],
+        second.orders.iter().map(|o| o.id).collect::<Vec<_>>()
+    );
+    assert!(second.next_cursor.is_none());
+    assert_eq!(vec![elsewhere], other);
+    assert_eq!(vec![oldest, middle, newest], ascending);
+}
+
+#[tokio::test]
+async fn order_list_filters_by_status_customer_product_and_date() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let other_customer_id = app.seed_partner(&tenant, "Customer").await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let other_product_id = app.seed_product(&tenant, 100, &[]).await;
+    let approved =
+        create_dated_order(&app, &tenant, customer_id, product_id, "2026-01-05", None).await;
+    let pending =
+        create_dated_order(&app, &tenant, customer_id, product_id, "2026-02-05", None).await;
+    let other_customer = create_dated_order(
+        &app,
+        &tenant,
+        other_customer_id,
+        other_product_id,
+        "2026-03-05",
+        None,
+    )
+    .await;
+    transition(&app, tenant.admin_id, approved, "approve", json!({})).await;
+
+    let by_status = list_order_ids(&app, tenant.admin_id, "status=in_progress").await;
+    let by_customer = list_order_ids(
+        &app,
+        tenant.admin_id,
+        &format!("customer_id={other_customer_id}"),
+    )
+    .await;
+    let by_product =
+        list_order_ids(&app, tenant.admin_id, &format!("product_id={product_id}")).await;
+    let by_date = list_order_ids(
+        &app,
+        tenant.admin_id,
+        "from_date=2026-02-01&to_date=2026-02-28",
+    )
+    .await;
+    let unknown_status = list_orders(&app, tenant.admin_id, "status=shipped").await;
+
+    assert_eq!(vec![approved], by_status);
+    assert_eq!(vec![other_customer], by_customer);
+    assert_eq!(vec![pending, approved], by_product);
+    assert_eq!(vec![pending], by_date);
+    assert_eq!(StatusCode::BAD_REQUEST, unknown_status.status());
+}
+
+#[tokio::test]
+async fn staff_order_list_is_limited_to_their_warehouse() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let other_warehouse = app.seed_warehouse(&tenant, "Surat Branch").await;
+    let local =
+        create_dated_order(&app, &tenant, customer_id, product_id, "2026-01-05", None).await;
+    create_dated_order(
+        &app,
+        &tenant,
+        customer_id,
+        product_id,
+        "2026-01-06",
+        Some(other_warehouse),
+    )
+    .await;
+    let staff_id = app.seed_staff(&tenant, tenant.warehouse_id).await;
+
+    let own = list_order_ids(&app, staff_id, "").await;
+    let other = list_order_ids(&app, staff_id, &format!("warehouse_id={other_warehouse}")).await;
+    let bad_cursor = list_orders(&app, staff_id, "cursor=yesterday").await;
+
+    assert_eq!(vec![local], own);
+    assert_eq!(vec![local], other);
+    assert_eq!(StatusCode::BAD_REQUEST, bad_cursor.status());
+}
+
+#[tokio::test]
+async fn cursor_values_must_match_the_sort_column() {
+    let app = TestApp::build().await;
+    let tenant = app.seed_tenant().await;
+    let customer_id = app.seed_partner(&tenant, "Customer").await;
+    let product_id = app.seed_product(&tenant, 100, &[]).await;
+    let first =
+        create_dated_order(&app, &tenant, customer_id, product_id, "2026-01-05", None).await;
+    let second =
+        create_dated_order(&app, &tenant, customer_id, product_id, "2026-01-06", None).await;
+
+    let page: SalesOrderPage = list_orders(&app, tenant.admin_id, "sort=created_at&limit=1")
+        .await
+        .json()
+        .await
+        .unwrap();
+    let cursor = page.next_cursor.unwrap();
+    let next = list_order_ids(
+        &app,
+        tenant.admin_id,
+        &format!("sort=created_at&limit=1&cursor={cursor}"),
+    )
+    .await;
+    let garbage_date = list_orders(
+        &app,
+        tenant.admin_id,
+        &format!("cursor=garbage,{}", Uuid::new_v4()),
+    )
+    .await;
+    let date_for_timestamp = list_orders(
+        &app,
+        tenant.admin_id,
+        &format!("sort=created_at&cursor=2026-01-05,{}", Uuid::new_v4()),
+    )
+    .await;
+
+    assert_eq!(
+        vec![second],
+        page.orders.iter().map(|o| o.id).collect::<Vec<_>>()
+    );
+    assert_eq!(vec![first], next);
+    assert_eq!(StatusCode::BAD_REQUEST, garbage_date.status());
+    assert_eq!(StatusCode::BAD_REQUEST, date_for_timestamp.status());
+}
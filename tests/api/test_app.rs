@@ -1,3 +1,5 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
 use secrecy::SecretString;
 use sqlx::{Connection, PgConnection, PgPool};
 use uuid::Uuid;
@@ -10,6 +12,7 @@ use bale_backend::{
 // TEST APP
 // -------------------------------------------------------------------------------------
 
+#[allow(dead_code)]
 pub struct TestApp {
     pub address: String,
     pub port: u16,
@@ -23,6 +26,8 @@ impl TestApp {
             let mut c = get_config().expect("Failed to read configuration.");
             c.database.database_name = Uuid::new_v4().to_string();
             c.application.port = 0;
+            // Tests send reminders themselves
+            c.application.reminder_interval_minutes = None;
             c
         };
 
@@ -34,6 +39,7 @@ impl TestApp {
             .expect("Failed to build application.");
         let port = app.port();
         let address = format!("http://localhost:{}", port);
+        #[allow(clippy::let_underscore_future)]
         let _ = tokio::spawn(app.run());
 
         let api_client = reqwest::Client::builder()
@@ -51,6 +57,274 @@ impl TestApp {
     }
 }
 
+// SEED DATA
+// -------------------------------------------------------------------------------------
+
+pub struct TestTenant {
+    pub company_id: Uuid,
+    pub admin_id: Uuid,
+    pub warehouse_id: Uuid,
+}
+
+impl TestApp {
+    pub async fn seed_tenant(&self) -> TestTenant {
+        let company_id = sqlx::query_scalar!(
+            r#"INSERT INTO companies (name, state) VALUES ('Looms', 'Maharashtra') RETURNING id"#
+        )
+        .fetch_one(&self.db_pool)
+        .await
+        .expect("Failed to seed company.");
+
+        let admin_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO users (company_id, first_name, last_name, phone_number, role)
+            VALUES ($1, 'Asha', 'Admin', $2, 'admin')
+            RETURNING id
+            "#,
+            company_id,
+            random_phone_number()
+        )
+        .fetch_one(&self.db_pool)
+        .await
+        .expect("Failed to seed admin user.");
+
+        let mut tenant = TestTenant {
+            company_id,
+            admin_id,
+            warehouse_id: Uuid::nil(),
+        };
+        tenant.warehouse_id = self.seed_warehouse(&tenant, "Main Warehouse").await;
+
+        // The admin works out of the main warehouse
+        sqlx::query!(
+            "UPDATE users SET warehouse_id = $2 WHERE id = $1",
+            admin_id,
+            tenant.warehouse_id
+        )
+        .execute(&self.db_pool)
+        .await
+        .expect("Failed to assign admin warehouse.");
+
+        tenant
+    }
+
+    pub async fn seed_staff(&self, tenant: &TestTenant, warehouse_id: Uuid) -> Uuid {
+        sqlx::query_scalar!(
+            r#"
+            INSERT INTO users (company_id, first_name, last_name, phone_number, role, warehouse_id)
+            VALUES ($1, 'Sunil', 'Staff', $2, 'staff', $3)
+            RETURNING id
+            "#,
+            tenant.company_id,
+            random_phone_number(),
+            warehouse_id
+        )
+        .fetch_one(&self.db_pool)
+        .await
+        .expect("Failed to seed staff user.")
+    }
+
+    pub async fn seed_warehouse(&self, tenant: &TestTenant, name: &str) -> Uuid {
+        sqlx::query_scalar!(
+            r#"
+            INSERT INTO warehouses (company_id, name, state, created_by)
+            VALUES ($1, $2, 'Maharashtra', $3)
+            RETURNING id
+            "#,
+            tenant.company_id,
+            name,
+            tenant.admin_id
+        )
+        .fetch_one(&self.db_pool)
+        .await
+        .expect("Failed to seed warehouse.")
+    }
+
+    pub async fn seed_partner(&self, tenant: &TestTenant, partner_type: &str) -> Uuid {
+        sqlx::query_scalar!(
+            r#"
+            INSERT INTO partners (company_id, first_name, last_name, phone_number, partner_type, state, created_by)
+            VALUES ($1, 'Ravi', $2, $3, $2, 'Maharashtra', $4)
+            RETURNING id
+            "#,
+            tenant.company_id,
+            partner_type,
+            random_phone_number(),
+            tenant.admin_id
+        )
+        .fetch_one(&self.db_pool)
+        .await
+        .expect("Failed to seed partner.")
+    }
+
+    pub async fn seed_sales_order(
+        &self,
+        tenant: &TestTenant,
+        customer_id: Uuid,
+        order_date: NaiveDate,
+        total_amount: i64,
+        advance_amount: i64,
+        status: &str,
+    ) -> Uuid {
+        sqlx::query_scalar!(
+            r#"
+            INSERT INTO sales_orders (company_id, customer_id, order_date, total_amount, advance_amount, status, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id
+            "#,
+            tenant.company_id,
+            customer_id,
+            order_date,
+            Decimal::from(total_amount),
+            Decimal::from(advance_amount),
+            status,
+            tenant.admin_id
+        )
+        .fetch_one(&self.db_pool)
+        .await
+        .expect("Failed to seed sales order.")
+    }
+
+    pub async fn seed_product(
+        &self,
+        tenant: &TestTenant,
+        selling_price: i64,
+        tags: &[&str],
+    ) -> Uuid {
+        let tags: Vec<String> = tags.iter().map(|t| t.to_string()).collect();
+        sqlx::query_scalar!(
+            r#"
+            INSERT INTO products (company_id, name, measuring_unit, selling_price_per_unit, tags, created_by)
+            VALUES ($1, 'Cotton Poplin', 'Meters', $2, $3, $4)
+            RETURNING id
+            "#,
+            tenant.company_id,
+            Decimal::from(selling_price),
+            &tags,
+            tenant.admin_id
+        )
+        .fetch_one(&self.db_pool)
+        .await
+        .expect("Failed to seed product.")
+    }
+
+    /// A product under HSN 5208 (woven cotton fabrics) taxed at `gst_rate` percent
+    pub async fn seed_taxed_product(
+        &self,
+        tenant: &TestTenant,
+        selling_price: i64,
+        gst_rate: i64,
+    ) -> Uuid {
+        sqlx::query!(
+            r#"
+            INSERT INTO hsn_gst_rates (company_id, hsn_code, gst_rate, created_by)
+            VALUES ($1, '5208', $2, $3)
+            ON CONFLICT (company_id, hsn_code) DO UPDATE SET gst_rate = EXCLUDED.gst_rate
+            "#,
+            tenant.company_id,
+            Decimal::from(gst_rate),
+            tenant.admin_id
+        )
+        .execute(&self.db_pool)
+        .await
+        .expect("Failed to seed HSN rate.");
+
+        let product_id = self.seed_product(tenant, selling_price, &[]).await;
+        sqlx::query!(
+            "UPDATE products SET hsn_code = '5208' WHERE id = $1",
+            product_id
+        )
+        .execute(&self.db_pool)
+        .await
+        .expect("Failed to set product HSN code.");
+        product_id
+    }
+
+    pub async fn seed_stock_unit(
+        &self,
+        tenant: &TestTenant,
+        product_id: Uuid,
+        size_quantity: i64,
+    ) -> Uuid {
+        sqlx::query_scalar!(
+            r#"
+            INSERT INTO stock_units (company_id, product_id, warehouse_id, size_quantity, status, created_by)
+            VALUES ($1, $2, $3, $4, 'in_stock', $5)
+            RETURNING id
+            "#,
+            tenant.company_id,
+            product_id,
+            tenant.warehouse_id,
+            Decimal::from(size_quantity),
+            tenant.admin_id
+        )
+        .fetch_one(&self.db_pool)
+        .await
+        .expect("Failed to seed stock unit.")
+    }
+
+    pub async fn seed_dispatch(
+        &self,
+        tenant: &TestTenant,
+        partner_id: Uuid,
+        agent_id: Option<Uuid>,
+        sales_order_id: Option<Uuid>,
+        stock_unit_ids: &[Uuid],
+    ) -> Uuid {
+        let dispatch_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO goods_dispatches (company_id, warehouse_id, dispatch_type, dispatch_to_partner_id,
+                                          agent_id, link_type, sales_order_id, created_by)
+            VALUES ($1, $2, 'partner', $3, $4, CASE WHEN $5::UUID IS NULL THEN NULL ELSE 'sales_order' END, $5, $6)
+            RETURNING id
+            "#,
+            tenant.company_id,
+            tenant.warehouse_id,
+            partner_id,
+            agent_id,
+            sales_order_id,
+            tenant.admin_id
+        )
+        .fetch_one(&self.db_pool)
+        .await
+        .expect("Failed to seed dispatch.");
+
+        for stock_unit_id in stock_unit_ids {
+            sqlx::query!(
+                r#"
+                INSERT INTO goods_dispatch_items (company_id, dispatch_id, stock_unit_id)
+                VALUES ($1, $2, $3)
+                "#,
+                tenant.company_id,
+                dispatch_id,
+                stock_unit_id
+            )
+            .execute(&self.db_pool)
+            .await
+            .expect("Failed to seed dispatch item.");
+        }
+        sqlx::query!(
+            "UPDATE stock_units SET status = 'dispatched' WHERE id = ANY($1)",
+            stock_unit_ids
+        )
+        .execute(&self.db_pool)
+        .await
+        .expect("Failed to mark seeded units dispatched.");
+
+        dispatch_id
+    }
+}
+
+fn random_phone_number() -> String {
+    let digits: String = Uuid::new_v4()
+        .as_u128()
+        .to_string()
+        .chars()
+        .take(10)
+        .collect();
+    digits
+}
+
 // DATABASE CONFIGURATION
 // -------------------------------------------------------------------------------------
 